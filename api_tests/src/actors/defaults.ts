@@ -73,9 +73,13 @@ export function defaultAssetDescription(
     }
 }
 
+/**
+ * cnd refuses to fund a swap once beta is about to expire, the beta expiry has
+ * to leave enough time for both parties to fund.
+ */
 export function defaultExpiryTimes() {
-    const alphaExpiry = Math.round(Date.now() / 1000) + 8;
-    const betaExpiry = Math.round(Date.now() / 1000) + 3;
+    const alphaExpiry = Math.round(Date.now() / 1000) + 45;
+    const betaExpiry = Math.round(Date.now() / 1000) + 30;
 
     return {
         alpha_expiry: alphaExpiry,
@@ -273,13 +273,17 @@ function defaultHerc20EthereumErc20RequestParams(
 }
 
 function defaultHalightHanHerc20Expiries() {
-    const alphaAbsoluteExpiry = Math.round(Date.now() / 1000) + 8;
+    const cltvExpiry = 350;
+    // cnd rejects an absolute alpha expiry that is earlier than the beta
+    // cltv expiry, assuming 10 minutes per block
+    const alphaAbsoluteExpiry =
+        Math.round(Date.now() / 1000) + cltvExpiry * 10 * 60 + 3600;
     const betaAbsoluteExpiry = Math.round(Date.now() / 1000) + 3;
 
     return {
         alphaAbsoluteExpiry,
         betaAbsoluteExpiry,
-        alphaCltvExpiry: 350,
-        betaCltvExpiry: 350,
+        alphaCltvExpiry: cltvExpiry,
+        betaCltvExpiry: cltvExpiry,
     };
 }
@@ -14,6 +14,19 @@ export interface CndConfigFile {
     data?: { dir: string };
     network: { listen: string[] };
     logging: { level: string };
+    expiries: Expiries;
+    confirmation_targets: ConfirmationTargets;
+}
+
+export interface Expiries {
+    min_gap_secs: number;
+    min_time_to_beta_expiry_secs: number;
+    max_duration_secs: number;
+}
+
+export interface ConfirmationTargets {
+    bitcoin_blocks: number;
+    ethereum_blocks: number;
 }
 
 export interface HttpApi {
@@ -61,6 +74,18 @@ export class E2ETestActorConfig {
             logging: {
                 level: "Trace",
             },
+            // the tests use expiries only seconds apart to be able to
+            // exercise refunds, so the safety margins are switched off
+            expiries: {
+                min_gap_secs: 0,
+                min_time_to_beta_expiry_secs: 0,
+                max_duration_secs: 60 * 60 * 24 * 30,
+            },
+            // blocks are mined on demand
+            confirmation_targets: {
+                bitcoin_blocks: 0,
+                ethereum_blocks: 0,
+            },
             ...this.createLedgerConnectors(ledgerConfig),
         };
     }
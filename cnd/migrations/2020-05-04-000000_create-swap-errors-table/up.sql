@@ -0,0 +1,12 @@
+-- Your SQL goes here
+
+CREATE TABLE swap_errors
+(
+    id INTEGER      NOT NULL PRIMARY KEY,
+    swap_id         NOT NULL,
+    side            NOT NULL,
+    phase           NOT NULL,
+    retryable       NOT NULL,
+    message         NOT NULL,
+    at              NOT NULL
+);
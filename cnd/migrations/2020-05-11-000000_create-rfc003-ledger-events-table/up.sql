@@ -0,0 +1,15 @@
+-- Your SQL goes here
+
+CREATE TABLE rfc003_ledger_events
+(
+    id INTEGER      NOT NULL PRIMARY KEY,
+    swap_id         NOT NULL,
+    side            NOT NULL,
+    event           NOT NULL,
+    transaction_id  NOT NULL,
+    block_hash,
+    block_height,
+    block_time,
+    data            NOT NULL,
+    at              NOT NULL
+);
@@ -0,0 +1,241 @@
+-- This file should undo anything in `up.sql`
+
+CREATE TABLE rfc003_bitcoin_ethereum_bitcoin_ether_request_messages
+(
+    id INTEGER               NOT NULL PRIMARY KEY,
+    swap_id UNIQUE           NOT NULL,
+    bitcoin_network          NOT NULL,
+    ethereum_chain_id        NOT NULL,
+    bitcoin_amount           NOT NULL,
+    ether_amount             NOT NULL,
+    hash_function            NOT NULL,
+    bitcoin_refund_identity  NOT NULL,
+    ethereum_redeem_identity NOT NULL,
+    bitcoin_expiry           NOT NULL,
+    ethereum_expiry          NOT NULL,
+    secret_hash              NOT NULL
+);
+
+CREATE TABLE rfc003_ethereum_bitcoin_ether_bitcoin_request_messages
+(
+    id INTEGER               NOT NULL PRIMARY KEY,
+    swap_id UNIQUE           NOT NULL,
+    bitcoin_network          NOT NULL,
+    ethereum_chain_id        NOT NULL,
+    bitcoin_amount           NOT NULL,
+    ether_amount             NOT NULL,
+    hash_function            NOT NULL,
+    bitcoin_redeem_identity  NOT NULL,
+    ethereum_refund_identity NOT NULL,
+    bitcoin_expiry           NOT NULL,
+    ethereum_expiry          NOT NULL,
+    secret_hash              NOT NULL
+);
+
+CREATE TABLE rfc003_bitcoin_ethereum_bitcoin_erc20_request_messages
+(
+    id INTEGER               NOT NULL PRIMARY KEY,
+    swap_id UNIQUE           NOT NULL,
+    bitcoin_network          NOT NULL,
+    ethereum_chain_id        NOT NULL,
+    bitcoin_amount           NOT NULL,
+    erc20_amount             NOT NULL,
+    erc20_token_contract     NOT NULL,
+    hash_function            NOT NULL,
+    bitcoin_refund_identity  NOT NULL,
+    ethereum_redeem_identity NOT NULL,
+    bitcoin_expiry           NOT NULL,
+    ethereum_expiry          NOT NULL,
+    secret_hash              NOT NULL
+);
+
+CREATE TABLE rfc003_ethereum_bitcoin_erc20_bitcoin_request_messages
+(
+    id INTEGER               NOT NULL PRIMARY KEY,
+    swap_id UNIQUE           NOT NULL,
+    bitcoin_network          NOT NULL,
+    ethereum_chain_id        NOT NULL,
+    bitcoin_amount           NOT NULL,
+    erc20_amount             NOT NULL,
+    erc20_token_contract     NOT NULL,
+    hash_function            NOT NULL,
+    bitcoin_redeem_identity  NOT NULL,
+    ethereum_refund_identity NOT NULL,
+    bitcoin_expiry           NOT NULL,
+    ethereum_expiry          NOT NULL,
+    secret_hash              NOT NULL
+);
+
+CREATE TABLE rfc003_ethereum_bitcoin_accept_messages
+(
+    id INTEGER               NOT NULL PRIMARY KEY,
+    swap_id UNIQUE           NOT NULL,
+    bitcoin_refund_identity  NOT NULL,
+    ethereum_redeem_identity NOT NULL,
+    at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE rfc003_bitcoin_ethereum_accept_messages
+(
+    id INTEGER               NOT NULL PRIMARY KEY,
+    swap_id UNIQUE           NOT NULL,
+    bitcoin_redeem_identity  NOT NULL,
+    ethereum_refund_identity NOT NULL,
+    at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+INSERT INTO rfc003_bitcoin_ethereum_bitcoin_ether_request_messages
+    (swap_id,
+     bitcoin_network,
+     ethereum_chain_id,
+     bitcoin_amount,
+     ether_amount,
+     hash_function,
+     bitcoin_refund_identity,
+     ethereum_redeem_identity,
+     bitcoin_expiry,
+     ethereum_expiry,
+     secret_hash)
+    SELECT request.swap_id,
+           alpha_ledger.network,
+           CAST(beta_ledger.network AS INTEGER),
+           alpha_asset.quantity,
+           beta_asset.quantity,
+           request.hash_function,
+           alpha_refund.identity,
+           beta_redeem.identity,
+           alpha_ledger.expiry,
+           beta_ledger.expiry,
+           request.secret_hash
+    FROM rfc003_requests request
+    JOIN rfc003_ledgers alpha_ledger ON alpha_ledger.swap_id = request.swap_id AND alpha_ledger.side = 'alpha'
+    JOIN rfc003_ledgers beta_ledger ON beta_ledger.swap_id = request.swap_id AND beta_ledger.side = 'beta'
+    JOIN rfc003_assets alpha_asset ON alpha_asset.swap_id = request.swap_id AND alpha_asset.side = 'alpha'
+    JOIN rfc003_assets beta_asset ON beta_asset.swap_id = request.swap_id AND beta_asset.side = 'beta'
+    JOIN rfc003_identities alpha_refund ON alpha_refund.swap_id = request.swap_id AND alpha_refund.side = 'alpha' AND alpha_refund.kind = 'refund'
+    JOIN rfc003_identities beta_redeem ON beta_redeem.swap_id = request.swap_id AND beta_redeem.side = 'beta' AND beta_redeem.kind = 'redeem'
+    WHERE alpha_asset.asset = 'bitcoin' AND beta_asset.asset = 'ether';
+
+INSERT INTO rfc003_ethereum_bitcoin_ether_bitcoin_request_messages
+    (swap_id,
+     bitcoin_network,
+     ethereum_chain_id,
+     ether_amount,
+     bitcoin_amount,
+     hash_function,
+     ethereum_refund_identity,
+     bitcoin_redeem_identity,
+     ethereum_expiry,
+     bitcoin_expiry,
+     secret_hash)
+    SELECT request.swap_id,
+           beta_ledger.network,
+           CAST(alpha_ledger.network AS INTEGER),
+           alpha_asset.quantity,
+           beta_asset.quantity,
+           request.hash_function,
+           alpha_refund.identity,
+           beta_redeem.identity,
+           alpha_ledger.expiry,
+           beta_ledger.expiry,
+           request.secret_hash
+    FROM rfc003_requests request
+    JOIN rfc003_ledgers alpha_ledger ON alpha_ledger.swap_id = request.swap_id AND alpha_ledger.side = 'alpha'
+    JOIN rfc003_ledgers beta_ledger ON beta_ledger.swap_id = request.swap_id AND beta_ledger.side = 'beta'
+    JOIN rfc003_assets alpha_asset ON alpha_asset.swap_id = request.swap_id AND alpha_asset.side = 'alpha'
+    JOIN rfc003_assets beta_asset ON beta_asset.swap_id = request.swap_id AND beta_asset.side = 'beta'
+    JOIN rfc003_identities alpha_refund ON alpha_refund.swap_id = request.swap_id AND alpha_refund.side = 'alpha' AND alpha_refund.kind = 'refund'
+    JOIN rfc003_identities beta_redeem ON beta_redeem.swap_id = request.swap_id AND beta_redeem.side = 'beta' AND beta_redeem.kind = 'redeem'
+    WHERE alpha_asset.asset = 'ether' AND beta_asset.asset = 'bitcoin';
+
+INSERT INTO rfc003_bitcoin_ethereum_bitcoin_erc20_request_messages
+    (swap_id,
+     bitcoin_network,
+     ethereum_chain_id,
+     bitcoin_amount,
+     erc20_amount,
+     erc20_token_contract,
+     hash_function,
+     bitcoin_refund_identity,
+     ethereum_redeem_identity,
+     bitcoin_expiry,
+     ethereum_expiry,
+     secret_hash)
+    SELECT request.swap_id,
+           alpha_ledger.network,
+           CAST(beta_ledger.network AS INTEGER),
+           alpha_asset.quantity,
+           beta_asset.quantity,
+           beta_asset.token_contract,
+           request.hash_function,
+           alpha_refund.identity,
+           beta_redeem.identity,
+           alpha_ledger.expiry,
+           beta_ledger.expiry,
+           request.secret_hash
+    FROM rfc003_requests request
+    JOIN rfc003_ledgers alpha_ledger ON alpha_ledger.swap_id = request.swap_id AND alpha_ledger.side = 'alpha'
+    JOIN rfc003_ledgers beta_ledger ON beta_ledger.swap_id = request.swap_id AND beta_ledger.side = 'beta'
+    JOIN rfc003_assets alpha_asset ON alpha_asset.swap_id = request.swap_id AND alpha_asset.side = 'alpha'
+    JOIN rfc003_assets beta_asset ON beta_asset.swap_id = request.swap_id AND beta_asset.side = 'beta'
+    JOIN rfc003_identities alpha_refund ON alpha_refund.swap_id = request.swap_id AND alpha_refund.side = 'alpha' AND alpha_refund.kind = 'refund'
+    JOIN rfc003_identities beta_redeem ON beta_redeem.swap_id = request.swap_id AND beta_redeem.side = 'beta' AND beta_redeem.kind = 'redeem'
+    WHERE alpha_asset.asset = 'bitcoin' AND beta_asset.asset = 'erc20';
+
+INSERT INTO rfc003_ethereum_bitcoin_erc20_bitcoin_request_messages
+    (swap_id,
+     bitcoin_network,
+     ethereum_chain_id,
+     erc20_amount,
+     erc20_token_contract,
+     bitcoin_amount,
+     hash_function,
+     ethereum_refund_identity,
+     bitcoin_redeem_identity,
+     ethereum_expiry,
+     bitcoin_expiry,
+     secret_hash)
+    SELECT request.swap_id,
+           beta_ledger.network,
+           CAST(alpha_ledger.network AS INTEGER),
+           alpha_asset.quantity,
+           alpha_asset.token_contract,
+           beta_asset.quantity,
+           request.hash_function,
+           alpha_refund.identity,
+           beta_redeem.identity,
+           alpha_ledger.expiry,
+           beta_ledger.expiry,
+           request.secret_hash
+    FROM rfc003_requests request
+    JOIN rfc003_ledgers alpha_ledger ON alpha_ledger.swap_id = request.swap_id AND alpha_ledger.side = 'alpha'
+    JOIN rfc003_ledgers beta_ledger ON beta_ledger.swap_id = request.swap_id AND beta_ledger.side = 'beta'
+    JOIN rfc003_assets alpha_asset ON alpha_asset.swap_id = request.swap_id AND alpha_asset.side = 'alpha'
+    JOIN rfc003_assets beta_asset ON beta_asset.swap_id = request.swap_id AND beta_asset.side = 'beta'
+    JOIN rfc003_identities alpha_refund ON alpha_refund.swap_id = request.swap_id AND alpha_refund.side = 'alpha' AND alpha_refund.kind = 'refund'
+    JOIN rfc003_identities beta_redeem ON beta_redeem.swap_id = request.swap_id AND beta_redeem.side = 'beta' AND beta_redeem.kind = 'redeem'
+    WHERE alpha_asset.asset = 'erc20' AND beta_asset.asset = 'bitcoin';
+
+INSERT INTO rfc003_bitcoin_ethereum_accept_messages
+    (swap_id, bitcoin_redeem_identity, ethereum_refund_identity, at)
+    SELECT accept.swap_id, alpha_redeem.identity, beta_refund.identity, accept.at
+    FROM rfc003_accepts accept
+    JOIN rfc003_ledgers alpha_ledger ON alpha_ledger.swap_id = accept.swap_id AND alpha_ledger.side = 'alpha'
+    JOIN rfc003_identities alpha_redeem ON alpha_redeem.swap_id = accept.swap_id AND alpha_redeem.side = 'alpha' AND alpha_redeem.kind = 'redeem'
+    JOIN rfc003_identities beta_refund ON beta_refund.swap_id = accept.swap_id AND beta_refund.side = 'beta' AND beta_refund.kind = 'refund'
+    WHERE alpha_ledger.ledger = 'bitcoin';
+
+INSERT INTO rfc003_ethereum_bitcoin_accept_messages
+    (swap_id, ethereum_redeem_identity, bitcoin_refund_identity, at)
+    SELECT accept.swap_id, alpha_redeem.identity, beta_refund.identity, accept.at
+    FROM rfc003_accepts accept
+    JOIN rfc003_ledgers alpha_ledger ON alpha_ledger.swap_id = accept.swap_id AND alpha_ledger.side = 'alpha'
+    JOIN rfc003_identities alpha_redeem ON alpha_redeem.swap_id = accept.swap_id AND alpha_redeem.side = 'alpha' AND alpha_redeem.kind = 'redeem'
+    JOIN rfc003_identities beta_refund ON beta_refund.swap_id = accept.swap_id AND beta_refund.side = 'beta' AND beta_refund.kind = 'refund'
+    WHERE alpha_ledger.ledger = 'ethereum';
+
+DROP TABLE rfc003_requests;
+DROP TABLE rfc003_ledgers;
+DROP TABLE rfc003_assets;
+DROP TABLE rfc003_identities;
+DROP TABLE rfc003_accepts;
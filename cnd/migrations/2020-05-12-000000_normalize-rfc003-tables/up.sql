@@ -0,0 +1,171 @@
+-- Your SQL goes here
+
+CREATE TABLE rfc003_requests
+(
+    id INTEGER          NOT NULL PRIMARY KEY,
+    swap_id UNIQUE      NOT NULL,
+    hash_function       NOT NULL,
+    secret_hash         NOT NULL
+);
+
+CREATE TABLE rfc003_ledgers
+(
+    id INTEGER          NOT NULL PRIMARY KEY,
+    swap_id             NOT NULL,
+    side                NOT NULL,
+    ledger              NOT NULL,
+    network             NOT NULL,
+    expiry              NOT NULL,
+    UNIQUE(swap_id, side)
+);
+
+CREATE TABLE rfc003_assets
+(
+    id INTEGER          NOT NULL PRIMARY KEY,
+    swap_id             NOT NULL,
+    side                NOT NULL,
+    asset               NOT NULL,
+    quantity            NOT NULL,
+    token_contract,
+    UNIQUE(swap_id, side)
+);
+
+CREATE TABLE rfc003_identities
+(
+    id INTEGER          NOT NULL PRIMARY KEY,
+    swap_id             NOT NULL,
+    side                NOT NULL,
+    kind                NOT NULL,
+    identity            NOT NULL,
+    UNIQUE(swap_id, side, kind)
+);
+
+CREATE TABLE rfc003_accepts
+(
+    id INTEGER          NOT NULL PRIMARY KEY,
+    swap_id UNIQUE      NOT NULL,
+    at DATETIME DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Bitcoin on alpha, Ether on beta
+
+INSERT INTO rfc003_requests (swap_id, hash_function, secret_hash)
+    SELECT swap_id, hash_function, secret_hash
+    FROM rfc003_bitcoin_ethereum_bitcoin_ether_request_messages;
+INSERT INTO rfc003_ledgers (swap_id, side, ledger, network, expiry)
+    SELECT swap_id, 'alpha', 'bitcoin', bitcoin_network, bitcoin_expiry
+    FROM rfc003_bitcoin_ethereum_bitcoin_ether_request_messages;
+INSERT INTO rfc003_ledgers (swap_id, side, ledger, network, expiry)
+    SELECT swap_id, 'beta', 'ethereum', CAST(ethereum_chain_id AS TEXT), ethereum_expiry
+    FROM rfc003_bitcoin_ethereum_bitcoin_ether_request_messages;
+INSERT INTO rfc003_assets (swap_id, side, asset, quantity)
+    SELECT swap_id, 'alpha', 'bitcoin', bitcoin_amount
+    FROM rfc003_bitcoin_ethereum_bitcoin_ether_request_messages;
+INSERT INTO rfc003_assets (swap_id, side, asset, quantity)
+    SELECT swap_id, 'beta', 'ether', ether_amount
+    FROM rfc003_bitcoin_ethereum_bitcoin_ether_request_messages;
+INSERT INTO rfc003_identities (swap_id, side, kind, identity)
+    SELECT swap_id, 'alpha', 'refund', bitcoin_refund_identity
+    FROM rfc003_bitcoin_ethereum_bitcoin_ether_request_messages;
+INSERT INTO rfc003_identities (swap_id, side, kind, identity)
+    SELECT swap_id, 'beta', 'redeem', ethereum_redeem_identity
+    FROM rfc003_bitcoin_ethereum_bitcoin_ether_request_messages;
+
+-- Ether on alpha, Bitcoin on beta
+
+INSERT INTO rfc003_requests (swap_id, hash_function, secret_hash)
+    SELECT swap_id, hash_function, secret_hash
+    FROM rfc003_ethereum_bitcoin_ether_bitcoin_request_messages;
+INSERT INTO rfc003_ledgers (swap_id, side, ledger, network, expiry)
+    SELECT swap_id, 'alpha', 'ethereum', CAST(ethereum_chain_id AS TEXT), ethereum_expiry
+    FROM rfc003_ethereum_bitcoin_ether_bitcoin_request_messages;
+INSERT INTO rfc003_ledgers (swap_id, side, ledger, network, expiry)
+    SELECT swap_id, 'beta', 'bitcoin', bitcoin_network, bitcoin_expiry
+    FROM rfc003_ethereum_bitcoin_ether_bitcoin_request_messages;
+INSERT INTO rfc003_assets (swap_id, side, asset, quantity)
+    SELECT swap_id, 'alpha', 'ether', ether_amount
+    FROM rfc003_ethereum_bitcoin_ether_bitcoin_request_messages;
+INSERT INTO rfc003_assets (swap_id, side, asset, quantity)
+    SELECT swap_id, 'beta', 'bitcoin', bitcoin_amount
+    FROM rfc003_ethereum_bitcoin_ether_bitcoin_request_messages;
+INSERT INTO rfc003_identities (swap_id, side, kind, identity)
+    SELECT swap_id, 'alpha', 'refund', ethereum_refund_identity
+    FROM rfc003_ethereum_bitcoin_ether_bitcoin_request_messages;
+INSERT INTO rfc003_identities (swap_id, side, kind, identity)
+    SELECT swap_id, 'beta', 'redeem', bitcoin_redeem_identity
+    FROM rfc003_ethereum_bitcoin_ether_bitcoin_request_messages;
+
+-- Bitcoin on alpha, ERC20 on beta
+
+INSERT INTO rfc003_requests (swap_id, hash_function, secret_hash)
+    SELECT swap_id, hash_function, secret_hash
+    FROM rfc003_bitcoin_ethereum_bitcoin_erc20_request_messages;
+INSERT INTO rfc003_ledgers (swap_id, side, ledger, network, expiry)
+    SELECT swap_id, 'alpha', 'bitcoin', bitcoin_network, bitcoin_expiry
+    FROM rfc003_bitcoin_ethereum_bitcoin_erc20_request_messages;
+INSERT INTO rfc003_ledgers (swap_id, side, ledger, network, expiry)
+    SELECT swap_id, 'beta', 'ethereum', CAST(ethereum_chain_id AS TEXT), ethereum_expiry
+    FROM rfc003_bitcoin_ethereum_bitcoin_erc20_request_messages;
+INSERT INTO rfc003_assets (swap_id, side, asset, quantity)
+    SELECT swap_id, 'alpha', 'bitcoin', bitcoin_amount
+    FROM rfc003_bitcoin_ethereum_bitcoin_erc20_request_messages;
+INSERT INTO rfc003_assets (swap_id, side, asset, quantity, token_contract)
+    SELECT swap_id, 'beta', 'erc20', erc20_amount, erc20_token_contract
+    FROM rfc003_bitcoin_ethereum_bitcoin_erc20_request_messages;
+INSERT INTO rfc003_identities (swap_id, side, kind, identity)
+    SELECT swap_id, 'alpha', 'refund', bitcoin_refund_identity
+    FROM rfc003_bitcoin_ethereum_bitcoin_erc20_request_messages;
+INSERT INTO rfc003_identities (swap_id, side, kind, identity)
+    SELECT swap_id, 'beta', 'redeem', ethereum_redeem_identity
+    FROM rfc003_bitcoin_ethereum_bitcoin_erc20_request_messages;
+
+-- ERC20 on alpha, Bitcoin on beta
+
+INSERT INTO rfc003_requests (swap_id, hash_function, secret_hash)
+    SELECT swap_id, hash_function, secret_hash
+    FROM rfc003_ethereum_bitcoin_erc20_bitcoin_request_messages;
+INSERT INTO rfc003_ledgers (swap_id, side, ledger, network, expiry)
+    SELECT swap_id, 'alpha', 'ethereum', CAST(ethereum_chain_id AS TEXT), ethereum_expiry
+    FROM rfc003_ethereum_bitcoin_erc20_bitcoin_request_messages;
+INSERT INTO rfc003_ledgers (swap_id, side, ledger, network, expiry)
+    SELECT swap_id, 'beta', 'bitcoin', bitcoin_network, bitcoin_expiry
+    FROM rfc003_ethereum_bitcoin_erc20_bitcoin_request_messages;
+INSERT INTO rfc003_assets (swap_id, side, asset, quantity, token_contract)
+    SELECT swap_id, 'alpha', 'erc20', erc20_amount, erc20_token_contract
+    FROM rfc003_ethereum_bitcoin_erc20_bitcoin_request_messages;
+INSERT INTO rfc003_assets (swap_id, side, asset, quantity)
+    SELECT swap_id, 'beta', 'bitcoin', bitcoin_amount
+    FROM rfc003_ethereum_bitcoin_erc20_bitcoin_request_messages;
+INSERT INTO rfc003_identities (swap_id, side, kind, identity)
+    SELECT swap_id, 'alpha', 'refund', ethereum_refund_identity
+    FROM rfc003_ethereum_bitcoin_erc20_bitcoin_request_messages;
+INSERT INTO rfc003_identities (swap_id, side, kind, identity)
+    SELECT swap_id, 'beta', 'redeem', bitcoin_redeem_identity
+    FROM rfc003_ethereum_bitcoin_erc20_bitcoin_request_messages;
+
+-- Accept messages
+
+INSERT INTO rfc003_accepts (swap_id, at)
+    SELECT swap_id, at FROM rfc003_bitcoin_ethereum_accept_messages;
+INSERT INTO rfc003_identities (swap_id, side, kind, identity)
+    SELECT swap_id, 'alpha', 'redeem', bitcoin_redeem_identity
+    FROM rfc003_bitcoin_ethereum_accept_messages;
+INSERT INTO rfc003_identities (swap_id, side, kind, identity)
+    SELECT swap_id, 'beta', 'refund', ethereum_refund_identity
+    FROM rfc003_bitcoin_ethereum_accept_messages;
+
+INSERT INTO rfc003_accepts (swap_id, at)
+    SELECT swap_id, at FROM rfc003_ethereum_bitcoin_accept_messages;
+INSERT INTO rfc003_identities (swap_id, side, kind, identity)
+    SELECT swap_id, 'alpha', 'redeem', ethereum_redeem_identity
+    FROM rfc003_ethereum_bitcoin_accept_messages;
+INSERT INTO rfc003_identities (swap_id, side, kind, identity)
+    SELECT swap_id, 'beta', 'refund', bitcoin_refund_identity
+    FROM rfc003_ethereum_bitcoin_accept_messages;
+
+DROP TABLE rfc003_bitcoin_ethereum_bitcoin_ether_request_messages;
+DROP TABLE rfc003_ethereum_bitcoin_ether_bitcoin_request_messages;
+DROP TABLE rfc003_bitcoin_ethereum_bitcoin_erc20_request_messages;
+DROP TABLE rfc003_ethereum_bitcoin_erc20_bitcoin_request_messages;
+DROP TABLE rfc003_ethereum_bitcoin_accept_messages;
+DROP TABLE rfc003_bitcoin_ethereum_accept_messages;
@@ -0,0 +1,9 @@
+-- Your SQL goes here
+
+CREATE TABLE invoice_cancellations
+(
+    id INTEGER      NOT NULL PRIMARY KEY,
+    local_swap_id   NOT NULL UNIQUE,
+    secret_hash     NOT NULL,
+    expiry          NOT NULL
+);
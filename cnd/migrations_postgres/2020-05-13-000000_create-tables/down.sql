@@ -0,0 +1,11 @@
+-- This file should undo anything in `up.sql`
+
+DROP TABLE rfc003_ledger_events;
+DROP TABLE swap_errors;
+DROP TABLE rfc003_decline_messages;
+DROP TABLE rfc003_accepts;
+DROP TABLE rfc003_identities;
+DROP TABLE rfc003_assets;
+DROP TABLE rfc003_ledgers;
+DROP TABLE rfc003_requests;
+DROP TABLE rfc003_swaps;
@@ -0,0 +1,88 @@
+-- Your SQL goes here
+
+CREATE TABLE rfc003_swaps
+(
+    id              SERIAL PRIMARY KEY,
+    swap_id         TEXT NOT NULL UNIQUE,
+    role            TEXT NOT NULL,
+    counterparty    TEXT NOT NULL
+);
+
+CREATE TABLE rfc003_requests
+(
+    id              SERIAL PRIMARY KEY,
+    swap_id         TEXT NOT NULL UNIQUE,
+    hash_function   TEXT NOT NULL,
+    secret_hash     TEXT NOT NULL
+);
+
+CREATE TABLE rfc003_ledgers
+(
+    id              SERIAL PRIMARY KEY,
+    swap_id         TEXT NOT NULL,
+    side            TEXT NOT NULL,
+    ledger          TEXT NOT NULL,
+    network         TEXT NOT NULL,
+    expiry          BIGINT NOT NULL,
+    UNIQUE (swap_id, side)
+);
+
+CREATE TABLE rfc003_assets
+(
+    id              SERIAL PRIMARY KEY,
+    swap_id         TEXT NOT NULL,
+    side            TEXT NOT NULL,
+    asset           TEXT NOT NULL,
+    quantity        TEXT NOT NULL,
+    token_contract  TEXT,
+    UNIQUE (swap_id, side)
+);
+
+CREATE TABLE rfc003_identities
+(
+    id              SERIAL PRIMARY KEY,
+    swap_id         TEXT NOT NULL,
+    side            TEXT NOT NULL,
+    kind            TEXT NOT NULL,
+    identity        TEXT NOT NULL,
+    UNIQUE (swap_id, side, kind)
+);
+
+CREATE TABLE rfc003_accepts
+(
+    id              SERIAL PRIMARY KEY,
+    swap_id         TEXT NOT NULL UNIQUE,
+    at              TIMESTAMP NOT NULL DEFAULT (NOW() AT TIME ZONE 'utc')
+);
+
+CREATE TABLE rfc003_decline_messages
+(
+    id              SERIAL PRIMARY KEY,
+    swap_id         TEXT NOT NULL UNIQUE,
+    reason          TEXT
+);
+
+CREATE TABLE swap_errors
+(
+    id              SERIAL PRIMARY KEY,
+    swap_id         TEXT NOT NULL,
+    side            TEXT NOT NULL,
+    phase           TEXT NOT NULL,
+    retryable       BOOLEAN NOT NULL,
+    message         TEXT NOT NULL,
+    at              TIMESTAMP NOT NULL
+);
+
+CREATE TABLE rfc003_ledger_events
+(
+    id              SERIAL PRIMARY KEY,
+    swap_id         TEXT NOT NULL,
+    side            TEXT NOT NULL,
+    event           TEXT NOT NULL,
+    transaction_id  TEXT NOT NULL,
+    block_hash      TEXT,
+    block_height    BIGINT,
+    block_time      TIMESTAMP,
+    data            TEXT NOT NULL,
+    at              TIMESTAMP NOT NULL
+);
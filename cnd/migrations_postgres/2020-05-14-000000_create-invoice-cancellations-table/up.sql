@@ -0,0 +1,9 @@
+-- Your SQL goes here
+
+CREATE TABLE invoice_cancellations
+(
+    id              SERIAL PRIMARY KEY,
+    local_swap_id   TEXT NOT NULL UNIQUE,
+    secret_hash     TEXT NOT NULL,
+    expiry          BIGINT NOT NULL
+);
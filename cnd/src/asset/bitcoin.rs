@@ -1,5 +1,6 @@
 use bitcoin::{util::amount::Denomination, Amount};
-use std::fmt;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, str::FromStr};
 
 #[derive(Debug, Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Hash)]
 pub struct Bitcoin(Amount);
@@ -31,6 +32,44 @@ impl fmt::Display for Bitcoin {
     }
 }
 
+/// Serialized as a string of satoshis, the same way the Ethereum assets are
+/// serialized in wei.
+impl Serialize for Bitcoin {
+    fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_sat().to_string().as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for Bitcoin {
+    fn deserialize<D>(deserializer: D) -> Result<Self, <D as Deserializer<'de>>::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct Visitor;
+
+        impl<'vde> de::Visitor<'vde> for Visitor {
+            type Value = Bitcoin;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
+                formatter.write_str("A string representing a satoshi quantity")
+            }
+
+            fn visit_str<E>(self, v: &str) -> Result<Bitcoin, E>
+            where
+                E: de::Error,
+            {
+                let sat = u64::from_str(v).map_err(E::custom)?;
+                Ok(Bitcoin::from_sat(sat))
+            }
+        }
+
+        deserializer.deserialize_str(Visitor)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::asset;
@@ -42,4 +81,15 @@ mod tests {
             "9000.00000000 BTC"
         );
     }
+
+    #[test]
+    fn bitcoin_serializes_as_satoshi_string() {
+        let bitcoin = asset::Bitcoin::from_sat(100_000_000);
+
+        let json = serde_json::to_string(&bitcoin).unwrap();
+        let deserialized = serde_json::from_str::<asset::Bitcoin>(&json).unwrap();
+
+        assert_eq!(json, "\"100000000\"");
+        assert_eq!(deserialized, bitcoin);
+    }
 }
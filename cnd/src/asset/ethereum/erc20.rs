@@ -139,7 +139,7 @@ impl Serialize for Erc20Quantity {
     }
 }
 
-#[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, PartialOrd, Ord, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Erc20 {
     pub token_contract: Address,
     pub quantity: Erc20Quantity,
@@ -0,0 +1,140 @@
+//! Backups of the swaps and the seed of cnd which can be taken while cnd is
+//! running.
+//!
+//! A backup is a directory with a snapshot of the SQLite database and the
+//! seed encrypted with the passphrase of the backup.
+
+use crate::{config, db::Sqlite, seed::RootSeed};
+use chrono::Utc;
+use rand::rngs::OsRng;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const DATABASE_FILE: &str = "cnd.sqlite";
+const ENCRYPTED_SEED_FILE: &str = "seed.encrypted.pem";
+
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+pub enum Error {
+    #[error("backups are only supported for SQLite, back up PostgreSQL with pg_dump")]
+    Unsupported,
+    #[error("the passphrase of a backup must not be empty")]
+    EmptyPassphrase,
+}
+
+/// A new directory for a backup within the `backups` directory of the data
+/// directory, named after the current time.
+pub fn new_backup_dir(data_dir: &Path) -> PathBuf {
+    data_dir
+        .join("backups")
+        .join(Utc::now().format("%Y%m%dT%H%M%SZ").to_string())
+}
+
+/// Write a snapshot of the database in the data directory and the seed
+/// encrypted with 'passphrase' to 'dir'.
+///
+/// cnd may keep running, neither the database nor the seed is modified.
+pub fn create(data: &config::Data, dir: &Path, passphrase: &str) -> anyhow::Result<()> {
+    if passphrase.is_empty() {
+        return Err(Error::EmptyPassphrase.into());
+    }
+    if data.postgres.is_some() {
+        return Err(Error::Unsupported.into());
+    }
+
+    let seed = RootSeed::from_dir(&data.dir)?;
+
+    let database_file = dir.join(DATABASE_FILE);
+    if database_file.exists() {
+        anyhow::bail!("{} already contains a backup", dir.display())
+    }
+
+    fs::create_dir_all(dir)?;
+    Sqlite::snapshot_in_dir(&data.dir, &database_file)?;
+    seed.write_encrypted_to(&dir.join(ENCRYPTED_SEED_FILE), passphrase, OsRng)?;
+
+    Ok(())
+}
+
+/// Restore the backup in 'dir' into the data directory.
+///
+/// cnd must not be running. Nothing in the data directory is replaced unless
+/// the seed decrypts with 'passphrase' and the snapshot validates.
+pub fn restore(dir: &Path, data: &config::Data, passphrase: &str) -> anyhow::Result<()> {
+    if data.postgres.is_some() {
+        return Err(Error::Unsupported.into());
+    }
+
+    let seed = RootSeed::from_encrypted_file(&dir.join(ENCRYPTED_SEED_FILE), passphrase)?;
+    let database_file = dir.join(DATABASE_FILE);
+    Sqlite::validate_snapshot(&database_file)?;
+
+    Sqlite::restore_snapshot_in_dir(&database_file, &data.dir)?;
+    seed.restore_to_dir(&data.dir)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{Database, Retrieve, Save, Swap},
+        swap_protocols::{rfc003::SwapId, Role},
+    };
+    use libp2p::PeerId;
+    use std::str::FromStr;
+
+    #[tokio::test]
+    async fn restored_backup_contains_swaps_and_seed() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let backup_dir = new_backup_dir(data_dir.path());
+        let restore_dir = tempfile::tempdir().unwrap();
+
+        let data = config::Data {
+            dir: data_dir.path().to_path_buf(),
+            postgres: None,
+        };
+        let database = Database::new(&data).unwrap();
+        let seed = RootSeed::from_dir_or_generate(data_dir.path(), OsRng).unwrap();
+        let swap_id = SwapId::from_str("ad2652ca-ecf2-4cc6-b35c-b4351ac28a34").unwrap();
+        let swap = Swap::new(swap_id, Role::Bob, PeerId::random());
+        database.save(swap.clone()).await.unwrap();
+
+        create(&data, &backup_dir, "passphrase").unwrap();
+        let restore_data = config::Data {
+            dir: restore_dir.path().to_path_buf(),
+            postgres: None,
+        };
+        restore(&backup_dir, &restore_data, "passphrase").unwrap();
+
+        let restored = Database::new(&restore_data).unwrap();
+        assert_eq!(restored.get(&swap_id).await.unwrap(), swap);
+        assert_eq!(RootSeed::from_dir(restore_dir.path()).unwrap(), seed);
+    }
+
+    #[test]
+    fn nothing_is_restored_with_a_wrong_passphrase() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let backup_dir = new_backup_dir(data_dir.path());
+        let restore_dir = tempfile::tempdir().unwrap();
+
+        let data = config::Data {
+            dir: data_dir.path().to_path_buf(),
+            postgres: None,
+        };
+        Database::new(&data).unwrap();
+        RootSeed::from_dir_or_generate(data_dir.path(), OsRng).unwrap();
+        create(&data, &backup_dir, "passphrase").unwrap();
+
+        let restore_data = config::Data {
+            dir: restore_dir.path().to_path_buf(),
+            postgres: None,
+        };
+        let result = restore(&backup_dir, &restore_data, "another passphrase");
+
+        assert!(result.is_err());
+        assert_eq!(fs::read_dir(restore_dir.path()).unwrap().count(), 0);
+    }
+}
@@ -6,6 +6,8 @@
 //!       libraries
 //!     - Common functionality that is not (yet) available upstream
 
+pub mod fee_estimation;
+
 use bitcoin::secp256k1;
 use serde::{
     de::{self, Visitor},
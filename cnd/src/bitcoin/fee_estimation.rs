@@ -0,0 +1,404 @@
+use crate::{config::BitcoinFees, jsonrpc};
+use anyhow::Context;
+use bitcoin::{Address, Amount, OutPoint, Script, Transaction, Txid};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    sync::Mutex,
+};
+
+/// bitcoind reports fee rates in BTC per 1000 virtual bytes, one virtual
+/// byte being four weight units.
+const WU_PER_KVB: u64 = 4000;
+
+/// The weight of a transaction spending one P2WPKH output to one P2WPKH
+/// output, which is what we expect a child transaction to look like.
+pub const CHILD_WEIGHT: u64 = 440;
+
+/// The error bitcoind answers with if a transaction is not in its mempool.
+const RPC_INVALID_ADDRESS_OR_KEY: i64 = -5;
+
+/// Asks bitcoind for the fee rate a transaction should pay to be included
+/// within a given number of blocks.
+///
+/// Also keeps track of the transactions we signed for spending an HTLC so
+/// that we can tell whether one of them is stuck in the mempool. They are
+/// only kept in memory until the HTLC is spent in a block, hence after a
+/// restart we cannot bump the fee of a transaction signed before.
+#[derive(Debug)]
+pub struct FeeEstimator {
+    client: jsonrpc::Client,
+    fees: BitcoinFees,
+    spends: Mutex<HashMap<OutPoint, Vec<Txid>>>,
+    /// The scripts paid to by the transactions in the mempool of bitcoind,
+    /// so that we only fetch each of them once.
+    mempool: Mutex<HashMap<Txid, Vec<Script>>>,
+}
+
+/// A transaction that has been broadcast but is not yet confirmed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PendingTransaction {
+    pub txid: Txid,
+    pub weight: u64,
+    /// In satoshi.
+    pub fee: u64,
+}
+
+impl PendingTransaction {
+    /// Rounds up, a replacement has to pay more than this.
+    pub fn fee_per_wu(&self) -> u64 {
+        (self.fee + self.weight - 1) / self.weight
+    }
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct SmartFeeEstimate {
+    /// Absent if bitcoind has not seen enough transactions to come up with
+    /// an estimate, which is always the case on a fresh regtest chain.
+    feerate: Option<f64>,
+    #[serde(default)]
+    errors: Vec<String>,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct MempoolEntry {
+    weight: u64,
+    fees: MempoolEntryFees,
+}
+
+#[derive(Debug, serde::Deserialize)]
+struct MempoolEntryFees {
+    /// In BTC.
+    base: f64,
+}
+
+impl FeeEstimator {
+    pub fn new(node_url: reqwest::Url, fees: BitcoinFees) -> Self {
+        Self {
+            client: jsonrpc::Client::new(node_url),
+            fees,
+            spends: Mutex::new(HashMap::new()),
+            mempool: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Remembers a transaction we handed out for spending an HTLC.
+    pub fn record_spend(&self, transaction: &Transaction) {
+        let mut spends = self.spends.lock().expect("lock is not poisoned");
+
+        for input in transaction.input.iter() {
+            spends
+                .entry(input.previous_output)
+                .or_default()
+                .push(transaction.txid());
+        }
+    }
+
+    /// Returns the latest of our transactions spending `outpoint` that is
+    /// still in the mempool, `None` if none was broadcast or it is already
+    /// confirmed.
+    ///
+    /// Forgets our transactions spending `outpoint` once it is spent in a
+    /// block.
+    pub async fn pending_spend(
+        &self,
+        outpoint: OutPoint,
+    ) -> anyhow::Result<Option<PendingTransaction>> {
+        let txids = self
+            .spends
+            .lock()
+            .expect("lock is not poisoned")
+            .get(&outpoint)
+            .cloned()
+            .unwrap_or_default();
+
+        if txids.is_empty() {
+            return Ok(None);
+        }
+
+        for txid in txids.into_iter().rev() {
+            if let Some(pending) = self.pending_transaction(txid).await? {
+                return Ok(Some(pending));
+            }
+        }
+
+        if self.is_spent_in_block(outpoint).await? {
+            self.spends
+                .lock()
+                .expect("lock is not poisoned")
+                .remove(&outpoint);
+        }
+
+        Ok(None)
+    }
+
+    /// We only record spends of HTLCs funded in a block, hence an outpoint
+    /// missing from the UTXO set has been spent in a block.
+    async fn is_spent_in_block(&self, outpoint: OutPoint) -> anyhow::Result<bool> {
+        let output: Option<serde_json::Value> = self
+            .client
+            .send(jsonrpc::Request::new("gettxout", vec![
+                jsonrpc::serialize(outpoint.txid)?,
+                jsonrpc::serialize(outpoint.vout)?,
+                jsonrpc::serialize(false)?,
+            ]))
+            .await
+            .with_context(|| format!("failed to fetch transaction output {}", outpoint))?;
+
+        Ok(output.is_none())
+    }
+
+    /// Returns a transaction in the mempool paying to `address`, `None` if
+    /// there is none.
+    ///
+    /// The user's wallet builds the transactions funding an HTLC, hence we
+    /// only find out about them by looking through the mempool.
+    pub async fn pending_payment(
+        &self,
+        address: &Address,
+    ) -> anyhow::Result<Option<PendingTransaction>> {
+        let txids = self
+            .client
+            .send::<Vec<()>, HashSet<Txid>>(jsonrpc::Request::new("getrawmempool", vec![]))
+            .await
+            .context("failed to fetch the mempool of bitcoind")?;
+
+        let unknown = {
+            let mut mempool = self.mempool.lock().expect("lock is not poisoned");
+            mempool.retain(|txid, _| txids.contains(txid));
+
+            txids
+                .into_iter()
+                .filter(|txid| !mempool.contains_key(txid))
+                .collect::<Vec<_>>()
+        };
+
+        for txid in unknown {
+            // The transaction may have left the mempool since we listed it.
+            let scripts = match self.mempool_transaction(txid).await? {
+                Some(transaction) => transaction
+                    .output
+                    .into_iter()
+                    .map(|output| output.script_pubkey)
+                    .collect(),
+                None => continue,
+            };
+
+            self.mempool
+                .lock()
+                .expect("lock is not poisoned")
+                .insert(txid, scripts);
+        }
+
+        let script = address.script_pubkey();
+        let payment = self
+            .mempool
+            .lock()
+            .expect("lock is not poisoned")
+            .iter()
+            .find(|(_, scripts)| scripts.contains(&script))
+            .map(|(txid, _)| *txid);
+
+        match payment {
+            Some(txid) => self.pending_transaction(txid).await,
+            None => Ok(None),
+        }
+    }
+
+    /// Returns `None` if the transaction is not in the mempool of bitcoind.
+    pub async fn pending_transaction(
+        &self,
+        txid: Txid,
+    ) -> anyhow::Result<Option<PendingTransaction>> {
+        let entry = self
+            .client
+            .send::<_, MempoolEntry>(jsonrpc::Request::new("getmempoolentry", vec![
+                jsonrpc::serialize(txid)?,
+            ]))
+            .await;
+
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(jsonrpc::Error::JsonRpc { code, .. }) if code == RPC_INVALID_ADDRESS_OR_KEY => {
+                return Ok(None)
+            }
+            Err(e) => {
+                return Err(anyhow::Error::from(e)
+                    .context(format!("failed to fetch mempool entry of {}", txid)))
+            }
+        };
+
+        let fee = Amount::from_btc(entry.fees.base)
+            .with_context(|| format!("bitcoind returned an invalid fee: {}", entry.fees.base))?
+            .as_sat();
+
+        Ok(Some(PendingTransaction {
+            txid,
+            weight: entry.weight,
+            fee,
+        }))
+    }
+
+    /// Works for mempool transactions without bitcoind indexing all
+    /// transactions.
+    pub async fn transaction(&self, txid: Txid) -> anyhow::Result<Transaction> {
+        self.mempool_transaction(txid)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("transaction {} is not in the mempool", txid))
+    }
+
+    /// Returns `None` if the transaction is not in the mempool of bitcoind.
+    async fn mempool_transaction(&self, txid: Txid) -> anyhow::Result<Option<Transaction>> {
+        let hex = self
+            .client
+            .send::<_, String>(jsonrpc::Request::new("getrawtransaction", vec![
+                jsonrpc::serialize(txid)?,
+            ]))
+            .await;
+
+        let hex = match hex {
+            Ok(hex) => hex,
+            Err(jsonrpc::Error::JsonRpc { code, .. }) if code == RPC_INVALID_ADDRESS_OR_KEY => {
+                return Ok(None)
+            }
+            Err(e) => {
+                return Err(
+                    anyhow::Error::from(e).context(format!("failed to fetch transaction {}", txid))
+                )
+            }
+        };
+        let bytes = hex::decode(&hex).context("bitcoind returned invalid hex")?;
+        let transaction = bitcoin::consensus::deserialize(&bytes)
+            .context("bitcoind returned an invalid transaction")?;
+
+        Ok(Some(transaction))
+    }
+
+    /// Returns the estimated fee in satoshi per weight unit, bounded by the
+    /// configured floor and ceiling.
+    ///
+    /// Uses the configured confirmation target unless `target_blocks` is
+    /// given.
+    pub async fn estimate_fee_per_wu(&self, target_blocks: Option<u16>) -> anyhow::Result<usize> {
+        let target_blocks = target_blocks.unwrap_or(self.fees.target_blocks);
+
+        let estimate: SmartFeeEstimate = self
+            .client
+            .send(jsonrpc::Request::new("estimatesmartfee", vec![
+                jsonrpc::serialize(target_blocks)?,
+            ]))
+            .await
+            .context("failed to estimate fee with bitcoind")?;
+
+        let fee_per_wu = match estimate.feerate {
+            Some(feerate) => btc_per_kvb_to_sat_per_wu(feerate)?,
+            None => {
+                tracing::warn!(
+                    "bitcoind could not estimate a fee for {} blocks, using the floor of {} sat/WU: {:?}",
+                    target_blocks,
+                    self.fees.floor_sat_per_wu,
+                    estimate.errors
+                );
+                self.fees.floor_sat_per_wu
+            }
+        };
+
+        Ok(bound(fee_per_wu, &self.fees))
+    }
+}
+
+/// Returns the fee per weight unit a child of the given mempool
+/// transaction has to pay for both to be mined at `package_fee_per_wu`.
+///
+/// The child pays at least `package_fee_per_wu` for itself, even if the
+/// parent already pays enough.
+pub fn child_pays_for_parent_fee_per_wu(
+    parent: &PendingTransaction,
+    package_fee_per_wu: usize,
+) -> anyhow::Result<usize> {
+    let child_fee_per_wu = child_fee_per_wu(
+        parent.weight,
+        parent.fee,
+        u64::try_from(package_fee_per_wu)?,
+    );
+
+    Ok(usize::try_from(child_fee_per_wu)?)
+}
+
+fn child_fee_per_wu(parent_weight: u64, parent_fee: u64, package_fee_per_wu: u64) -> u64 {
+    let package_fee = package_fee_per_wu * (parent_weight + CHILD_WEIGHT);
+    let child_fee = package_fee
+        .saturating_sub(parent_fee)
+        .max(package_fee_per_wu * CHILD_WEIGHT);
+
+    (child_fee + CHILD_WEIGHT - 1) / CHILD_WEIGHT
+}
+
+/// Rounds up so that we never end up paying less than the estimate.
+fn btc_per_kvb_to_sat_per_wu(feerate: f64) -> anyhow::Result<usize> {
+    let sat_per_kvb = Amount::from_btc(feerate)
+        .with_context(|| format!("bitcoind returned an invalid fee rate: {}", feerate))?
+        .as_sat();
+    let sat_per_wu = (sat_per_kvb + WU_PER_KVB - 1) / WU_PER_KVB;
+
+    Ok(usize::try_from(sat_per_wu)?)
+}
+
+fn bound(fee_per_wu: usize, fees: &BitcoinFees) -> usize {
+    fee_per_wu
+        .max(fees.floor_sat_per_wu)
+        .min(fees.ceiling_sat_per_wu)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_fee_rate_rounding_up() {
+        // 1 sat/vB, the default minimum relay fee
+        assert_eq!(btc_per_kvb_to_sat_per_wu(0.000_01).unwrap(), 1);
+        // 20 sat/vB
+        assert_eq!(btc_per_kvb_to_sat_per_wu(0.000_2).unwrap(), 5);
+        assert_eq!(btc_per_kvb_to_sat_per_wu(0.000_200_01).unwrap(), 6);
+    }
+
+    #[test]
+    fn estimate_is_bounded_by_floor_and_ceiling() {
+        let fees = BitcoinFees {
+            target_blocks: 6,
+            floor_sat_per_wu: 2,
+            ceiling_sat_per_wu: 100,
+        };
+
+        assert_eq!(bound(1, &fees), 2);
+        assert_eq!(bound(42, &fees), 42);
+        assert_eq!(bound(1000, &fees), 100);
+    }
+
+    #[test]
+    fn child_makes_up_for_the_fee_missing_from_the_parent() {
+        // parent of 560 WU paying 0 sat, package at 10 sat/WU needs 10_000 sat
+        assert_eq!(child_fee_per_wu(560, 0, 10), 23);
+        // parent already pays enough, child still pays for itself
+        assert_eq!(child_fee_per_wu(560, 100_000, 10), 10);
+    }
+
+    #[test]
+    fn pending_fee_rate_rounds_up() {
+        let pending = PendingTransaction {
+            txid: "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"
+                .parse()
+                .unwrap(),
+            weight: 561,
+            fee: 5610,
+        };
+        assert_eq!(pending.fee_per_wu(), 10);
+
+        let pending = PendingTransaction {
+            fee: 5611,
+            ..pending
+        };
+        assert_eq!(pending.fee_per_wu(), 11);
+    }
+}
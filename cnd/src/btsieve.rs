@@ -25,9 +25,40 @@ pub trait BlockByHash: Send + Sync + 'static {
     async fn block_by_hash(&self, block_hash: Self::BlockHash) -> anyhow::Result<Self::Block>;
 }
 
-/// Checks if a given block predates a certain timestamp.
+/// The block a transaction was included in.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockInclusion {
+    pub block_hash: String,
+    pub block_height: u32,
+    pub block_time: NaiveDateTime,
+}
+
+/// Abstracts over the ability of finding the block a transaction was included
+/// in, `None` if the block cannot be determined.
+#[async_trait]
+pub trait BlockOfTransaction<T>: Send + Sync + 'static {
+    async fn block_of_transaction(&self, transaction: &T)
+        -> anyhow::Result<Option<BlockInclusion>>;
+}
+
+/// Where looking for the events of a swap in the past stops.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StartOfSwap {
+    /// The swap started at this time, its events are in blocks mined later.
+    Time(NaiveDateTime),
+    /// The events of the swap up to the block at this height were seen before.
+    BlockHeight(u32),
+}
+
+impl From<NaiveDateTime> for StartOfSwap {
+    fn from(time: NaiveDateTime) -> Self {
+        StartOfSwap::Time(time)
+    }
+}
+
+/// Checks if a given block predates the start of a swap.
 pub trait Predates {
-    fn predates(&self, timestamp: NaiveDateTime) -> bool;
+    fn predates(&self, start_of_swap: StartOfSwap) -> bool;
 }
 
 /// Abstracts over the ability of getting the hash of the current block.
@@ -56,7 +87,7 @@ pub trait PreviousBlockHash {
 pub async fn find_relevant_blocks<C, B, H>(
     connector: &C,
     co: Co<B>,
-    start_of_swap: NaiveDateTime,
+    start_of_swap: StartOfSwap,
 ) -> anyhow::Result<Never>
 where
     C: LatestBlock<Block = B> + BlockByHash<Block = B, BlockHash = H>,
@@ -130,8 +161,8 @@ where
 }
 
 /// Constructs a predicate that returns `true` if the given block predates the
-/// start_of_swap timestamp.
-fn predates_start_of_swap<B>(start_of_swap: NaiveDateTime) -> impl Fn(&B) -> bool
+/// start of the swap.
+fn predates_start_of_swap<B>(start_of_swap: StartOfSwap) -> impl Fn(&B) -> bool
 where
     B: Predates,
 {
@@ -139,10 +170,10 @@ where
 }
 
 /// Constructs a predicate that returns `true` if we have seen the given block
-/// or the block predates the start_of_swap timestamp.
+/// or the block predates the start of the swap.
 fn seen_block_or_predates_start_of_swap<'sb, B, H>(
     seen_blocks: &'sb HashSet<H>,
-    start_of_swap: NaiveDateTime,
+    start_of_swap: StartOfSwap,
 ) -> impl Fn(&B) -> bool + 'sb
 where
     B: Predates + BlockHash<BlockHash = H>,
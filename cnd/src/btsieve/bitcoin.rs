@@ -8,6 +8,7 @@ pub use self::{
 use crate::{
     btsieve::{
         find_relevant_blocks, BlockByHash, BlockHash, LatestBlock, Predates, PreviousBlockHash,
+        StartOfSwap,
     },
     identity,
 };
@@ -15,7 +16,6 @@ use bitcoin::{
     consensus::{encode::deserialize, Decodable},
     BitcoinHash, OutPoint,
 };
-use chrono::NaiveDateTime;
 use genawaiter::{sync::Gen, GeneratorState};
 use reqwest::{Client, Url};
 
@@ -40,7 +40,7 @@ impl PreviousBlockHash for Block {
 
 pub async fn watch_for_spent_outpoint<C>(
     blockchain_connector: &C,
-    start_of_swap: NaiveDateTime,
+    start_of_swap: StartOfSwap,
     from_outpoint: OutPoint,
     identity: identity::Bitcoin,
 ) -> anyhow::Result<(bitcoin::Transaction, bitcoin::TxIn)>
@@ -62,7 +62,7 @@ where
 
 pub async fn watch_for_created_outpoint<C>(
     blockchain_connector: &C,
-    start_of_swap: NaiveDateTime,
+    start_of_swap: StartOfSwap,
     compute_address: bitcoin::Address,
 ) -> anyhow::Result<(bitcoin::Transaction, bitcoin::OutPoint)>
 where
@@ -91,7 +91,7 @@ where
 
 async fn watch<C, S, M>(
     connector: &C,
-    start_of_swap: NaiveDateTime,
+    start_of_swap: StartOfSwap,
     sieve: S,
 ) -> anyhow::Result<(bitcoin::Transaction, M)>
 where
@@ -121,11 +121,42 @@ where
 }
 
 impl Predates for Block {
-    fn predates(&self, timestamp: NaiveDateTime) -> bool {
-        let unix_timestamp = timestamp.timestamp();
-        let block_time = self.header.time as i64;
+    fn predates(&self, start_of_swap: StartOfSwap) -> bool {
+        match start_of_swap {
+            StartOfSwap::Time(timestamp) => {
+                let unix_timestamp = timestamp.timestamp();
+                let block_time = self.header.time as i64;
 
-        block_time < unix_timestamp
+                block_time < unix_timestamp
+            }
+            // Blocks without their height in the coinbase predate BIP34.
+            StartOfSwap::BlockHeight(height) => {
+                block_height(self).map_or(true, |block_height| block_height < height)
+            }
+        }
+    }
+}
+
+/// Decodes the height BIP34 requires to be the first push of the coinbase
+/// input, `None` if the block does not follow BIP34.
+pub fn block_height(block: &Block) -> Option<u32> {
+    let script_sig = block.txdata.first()?.input.first()?.script_sig.as_bytes();
+    let (&opcode, rest) = script_sig.split_first()?;
+
+    match opcode {
+        // OP_1 to OP_16
+        0x51..=0x60 => Some(u32::from(opcode - 0x50)),
+        // a push of up to 4 little-endian bytes, the heights are positive
+        0x01..=0x04 => {
+            let bytes = rest.get(..usize::from(opcode))?;
+            let height = bytes
+                .iter()
+                .rev()
+                .fold(0u32, |height, byte| (height << 8) | u32::from(*byte));
+
+            Some(height)
+        }
+        _ => None,
     }
 }
 
@@ -189,4 +220,23 @@ mod tests {
 
         assert_that(&bytes).is_ok();
     }
+
+    #[test]
+    fn decodes_block_height_from_coinbase() {
+        let block = "00000020837603de6069115e22e7fbf063c2a6e3bc3b3206f0b7e08d6ab6c168c2e50d4a9b48676dedc93d05f677778c1d83df28fd38d377548340052823616837666fb8be1b795dffff7f200000000001020000000001010000000000000000000000000000000000000000000000000000000000000000ffffffff0401650101ffffffff0200f2052a0100000023210205980e76eee77386241a3a7a5af65e910fb7be411b98e609f7c0d97c50ab8ebeac0000000000000000266a24aa21a9ede2f61c3f71d1defd3fa999dfa36953755c690689799962b48bebd836974e8cf90120000000000000000000000000000000000000000000000000000000000000000000000000".to_owned();
+
+        let block = decode_response::<Block>(block).unwrap();
+
+        assert_that(&block_height(&block)).is_equal_to(Some(101));
+    }
+
+    #[test]
+    fn block_predates_the_heights_above_its_own() {
+        let block = "00000020837603de6069115e22e7fbf063c2a6e3bc3b3206f0b7e08d6ab6c168c2e50d4a9b48676dedc93d05f677778c1d83df28fd38d377548340052823616837666fb8be1b795dffff7f200000000001020000000001010000000000000000000000000000000000000000000000000000000000000000ffffffff0401650101ffffffff0200f2052a0100000023210205980e76eee77386241a3a7a5af65e910fb7be411b98e609f7c0d97c50ab8ebeac0000000000000000266a24aa21a9ede2f61c3f71d1defd3fa999dfa36953755c690689799962b48bebd836974e8cf90120000000000000000000000000000000000000000000000000000000000000000000000000".to_owned();
+
+        let block = decode_response::<Block>(block).unwrap();
+
+        assert_that(&block.predates(StartOfSwap::BlockHeight(102))).is_true();
+        assert_that(&block.predates(StartOfSwap::BlockHeight(101))).is_false();
+    }
 }
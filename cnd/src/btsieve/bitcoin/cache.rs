@@ -1,6 +1,9 @@
-use crate::btsieve::{BlockByHash, LatestBlock};
+use crate::btsieve::{
+    bitcoin::block_height, BlockByHash, BlockInclusion, BlockOfTransaction, LatestBlock,
+};
 use async_trait::async_trait;
-use bitcoin::{util::hash::BitcoinHash, Block, BlockHash as Hash, BlockHash};
+use bitcoin::{util::hash::BitcoinHash, Block, BlockHash as Hash, BlockHash, Transaction};
+use chrono::NaiveDateTime;
 use derivative::Derivative;
 use lru::LruCache;
 use std::sync::Arc;
@@ -69,3 +72,32 @@ where
         Ok(block)
     }
 }
+
+#[async_trait]
+impl<C> BlockOfTransaction<Transaction> for Cache<C>
+where
+    C: Send + Sync + 'static,
+{
+    /// Only looks at the cached blocks: a transaction we just watched for was
+    /// found in one of them.
+    async fn block_of_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> anyhow::Result<Option<BlockInclusion>> {
+        let txid = transaction.txid();
+        let guard = self.block_cache.lock().await;
+
+        let inclusion = guard
+            .iter()
+            .find(|(_, block)| block.txdata.iter().any(|tx| tx.txid() == txid))
+            .and_then(|(block_hash, block)| {
+                Some(BlockInclusion {
+                    block_hash: block_hash.to_string(),
+                    block_height: block_height(block)?,
+                    block_time: NaiveDateTime::from_timestamp(i64::from(block.header.time), 0),
+                })
+            });
+
+        Ok(inclusion)
+    }
+}
@@ -5,11 +5,11 @@ pub use self::{cache::Cache, web3_connector::Web3Connector};
 use crate::{
     btsieve::{
         find_relevant_blocks, BlockByHash, BlockHash, LatestBlock, Predates, PreviousBlockHash,
+        StartOfSwap,
     },
     ethereum::{Address, Block, Bytes, Hash, Input, Log, Transaction, TransactionReceipt, U256},
 };
 use async_trait::async_trait;
-use chrono::NaiveDateTime;
 use genawaiter::{sync::Gen, GeneratorState};
 
 #[async_trait]
@@ -17,6 +17,19 @@ pub trait ReceiptByHash: Send + Sync + 'static {
     async fn receipt_by_hash(&self, transaction_hash: Hash) -> anyhow::Result<TransactionReceipt>;
 }
 
+#[async_trait]
+pub trait CallContract: Send + Sync + 'static {
+    /// Executes a read-only call of the contract at `to` against the latest
+    /// block and returns the output of the call.
+    async fn call_contract(&self, to: Address, data: Bytes) -> anyhow::Result<Bytes>;
+}
+
+#[async_trait]
+pub trait ContractCode: Send + Sync + 'static {
+    /// Returns the code of the contract at `address` in the latest block.
+    async fn contract_code(&self, address: Address) -> anyhow::Result<Bytes>;
+}
+
 impl BlockHash for Block {
     type BlockHash = Hash;
 
@@ -35,7 +48,7 @@ impl PreviousBlockHash for Block {
 
 pub async fn watch_for_contract_creation<C>(
     blockchain_connector: &C,
-    start_of_swap: NaiveDateTime,
+    start_of_swap: StartOfSwap,
     bytecode: &Bytes,
 ) -> anyhow::Result<(Transaction, Address)>
 where
@@ -82,7 +95,7 @@ where
 
 pub async fn watch_for_event<C>(
     blockchain_connector: &C,
-    start_of_swap: NaiveDateTime,
+    start_of_swap: StartOfSwap,
     event: Event,
 ) -> anyhow::Result<(Transaction, Log)>
 where
@@ -131,7 +144,7 @@ fn find_log_for_event_in_receipt(event: &Event, receipt: TransactionReceipt) ->
 
 pub async fn matching_transaction_and_receipt<C, F>(
     connector: &C,
-    start_of_swap: NaiveDateTime,
+    start_of_swap: StartOfSwap,
     matcher: F,
 ) -> anyhow::Result<(Transaction, TransactionReceipt)>
 where
@@ -182,7 +195,7 @@ where
 
 async fn matching_transaction_and_log<C, F>(
     connector: &C,
-    start_of_swap: NaiveDateTime,
+    start_of_swap: StartOfSwap,
     topics: Vec<Option<Topic>>,
     matcher: F,
 ) -> anyhow::Result<(Transaction, Log)>
@@ -254,10 +267,18 @@ where
 }
 
 impl Predates for Block {
-    fn predates(&self, timestamp: NaiveDateTime) -> bool {
-        let unix_timestamp = timestamp.timestamp();
+    fn predates(&self, start_of_swap: StartOfSwap) -> bool {
+        match start_of_swap {
+            StartOfSwap::Time(timestamp) => {
+                let unix_timestamp = timestamp.timestamp();
 
-        self.timestamp < U256::from(unix_timestamp)
+                self.timestamp < U256::from(unix_timestamp)
+            }
+            // Only pending blocks have no number, they are the newest.
+            StartOfSwap::BlockHeight(height) => self
+                .number
+                .map_or(false, |number| number < U256::from(height)),
+        }
     }
 }
 
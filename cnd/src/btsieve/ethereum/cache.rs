@@ -1,11 +1,13 @@
 use crate::{
     btsieve::{
-        ethereum::{self, Hash, ReceiptByHash},
-        BlockByHash, LatestBlock,
+        ethereum::{self, CallContract, ContractCode, Hash, ReceiptByHash},
+        BlockByHash, BlockInclusion, BlockOfTransaction, LatestBlock,
     },
-    ethereum::TransactionReceipt,
+    ethereum::{Address, Bytes, Transaction, TransactionReceipt, U256},
 };
+use anyhow::Context;
 use async_trait::async_trait;
+use chrono::NaiveDateTime;
 use derivative::Derivative;
 use lru::LruCache;
 use std::sync::Arc;
@@ -112,3 +114,61 @@ where
         Ok(receipt)
     }
 }
+
+#[async_trait]
+impl<C> BlockOfTransaction<Transaction> for Cache<C>
+where
+    C: ReceiptByHash + BlockByHash<Block = Block, BlockHash = Hash>,
+{
+    async fn block_of_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> anyhow::Result<Option<BlockInclusion>> {
+        let receipt = self.receipt_by_hash(transaction.hash).await?;
+        let (block_hash, block_number) = match (receipt.block_hash, receipt.block_number) {
+            (Some(block_hash), Some(block_number)) => (block_hash, block_number),
+            _ => return Ok(None),
+        };
+        let block = self.block_by_hash(block_hash).await?;
+
+        let block_height = to_u32(block_number).context("block number does not fit into u32")?;
+        let block_time =
+            to_u32(block.timestamp).context("block timestamp does not fit into u32")?;
+
+        Ok(Some(BlockInclusion {
+            block_hash: format!("{:#x}", block_hash),
+            block_height,
+            block_time: NaiveDateTime::from_timestamp(i64::from(block_time), 0),
+        }))
+    }
+}
+
+fn to_u32(value: U256) -> Option<u32> {
+    if value > U256::from(u32::max_value()) {
+        return None;
+    }
+
+    Some(value.low_u32())
+}
+
+#[async_trait]
+impl<C> CallContract for Cache<C>
+where
+    C: CallContract,
+{
+    async fn call_contract(&self, to: Address, data: Bytes) -> anyhow::Result<Bytes> {
+        // The output depends on the state of the latest block, hence no caching.
+        self.connector.call_contract(to, data).await
+    }
+}
+
+#[async_trait]
+impl<C> ContractCode for Cache<C>
+where
+    C: ContractCode,
+{
+    async fn contract_code(&self, address: Address) -> anyhow::Result<Bytes> {
+        // Only fetched once per funding, not worth caching.
+        self.connector.contract_code(address).await
+    }
+}
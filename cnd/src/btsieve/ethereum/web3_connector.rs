@@ -1,7 +1,10 @@
 use crate::{
-    btsieve::{ethereum::ReceiptByHash, BlockByHash, LatestBlock},
+    btsieve::{
+        ethereum::{CallContract, ContractCode, ReceiptByHash},
+        BlockByHash, LatestBlock,
+    },
     config::validation::FetchNetworkId,
-    ethereum::{Hash, TransactionReceipt},
+    ethereum::{Address, Bytes, Hash, TransactionReceipt},
     jsonrpc,
     swap_protocols::ledger::ethereum::ChainId,
 };
@@ -75,6 +78,40 @@ impl ReceiptByHash for Web3Connector {
     }
 }
 
+#[async_trait]
+impl CallContract for Web3Connector {
+    async fn call_contract(&self, to: Address, data: Bytes) -> anyhow::Result<Bytes> {
+        let output = self
+            .client
+            .send(jsonrpc::Request::new("eth_call", vec![
+                serde_json::json!({ "to": to, "data": data }),
+                jsonrpc::serialize("latest")?,
+            ]))
+            .await?;
+
+        tracing::trace!("Called contract {:x} with web3", to);
+
+        Ok(output)
+    }
+}
+
+#[async_trait]
+impl ContractCode for Web3Connector {
+    async fn contract_code(&self, address: Address) -> anyhow::Result<Bytes> {
+        let code = self
+            .client
+            .send(jsonrpc::Request::new("eth_getCode", vec![
+                jsonrpc::serialize(address)?,
+                jsonrpc::serialize("latest")?,
+            ]))
+            .await?;
+
+        tracing::trace!("Fetched code of contract {:x} from web3", address);
+
+        Ok(code)
+    }
+}
+
 #[async_trait]
 impl FetchNetworkId<ChainId> for Web3Connector {
     async fn network_id(&self) -> anyhow::Result<ChainId> {
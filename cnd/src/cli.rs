@@ -13,4 +13,40 @@ pub struct Options {
     /// Display the current version
     #[structopt(short = "V", long = "version")]
     pub version: bool,
+
+    #[structopt(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(structopt::StructOpt, Debug)]
+pub enum Command {
+    /// Back up the swaps and the seed, cnd may keep running meanwhile
+    Backup {
+        /// Directory to write the backup to, defaults to a new directory in
+        /// the `backups` directory of the data directory
+        #[structopt(long = "to", parse(from_os_str))]
+        to: Option<PathBuf>,
+
+        /// Passphrase to encrypt the seed with
+        #[structopt(
+            long = "passphrase",
+            env = "CND_BACKUP_PASSPHRASE",
+            hide_env_values = true
+        )]
+        passphrase: String,
+    },
+    /// Restore a backup into the data directory, cnd must not be running
+    Restore {
+        /// Directory of the backup to restore
+        #[structopt(long = "from", parse(from_os_str))]
+        from: PathBuf,
+
+        /// Passphrase the seed was encrypted with
+        #[structopt(
+            long = "passphrase",
+            env = "CND_BACKUP_PASSPHRASE",
+            hide_env_values = true
+        )]
+        passphrase: String,
+    },
 }
@@ -3,7 +3,7 @@ mod serde_bitcoin_network;
 pub mod settings;
 pub mod validation;
 
-use crate::swap_protocols::ledger::ethereum;
+use crate::{identity, swap_protocols::ledger::ethereum};
 use libp2p::Multiaddr;
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
@@ -18,11 +18,30 @@ lazy_static::lazy_static! {
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Data {
     pub dir: PathBuf,
+    /// Store swaps in this PostgreSQL database instead of a SQLite file in
+    /// `dir`, requires cnd to be built with the `postgres` feature.
+    #[serde(default)]
+    pub postgres: Option<Postgres>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Postgres {
+    pub url: Url,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
 pub struct Network {
     pub listen: Vec<Multiaddr>,
+    /// For how long we wait for a peer to respond to our swap request, the
+    /// peer may have to decide manually whether to accept it.
+    #[serde(default = "Network::default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+}
+
+impl Network {
+    fn default_request_timeout_secs() -> u64 {
+        libp2p_comit::DEFAULT_REQUEST_TIMEOUT.as_secs()
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -30,6 +49,7 @@ pub struct Bitcoin {
     #[serde(with = "crate::config::serde_bitcoin_network")]
     pub network: bitcoin::Network,
     pub bitcoind: Bitcoind,
+    pub fees: BitcoinFees,
 }
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
@@ -37,6 +57,26 @@ pub struct Bitcoind {
     pub node_url: Url,
 }
 
+/// Bounds for the fees cnd estimates when the user does not provide a fee
+/// for spending a bitcoin HTLC.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct BitcoinFees {
+    /// The number of blocks within which the transaction should be confirmed.
+    pub target_blocks: u16,
+    pub floor_sat_per_wu: usize,
+    pub ceiling_sat_per_wu: usize,
+}
+
+impl Default for BitcoinFees {
+    fn default() -> Self {
+        Self {
+            target_blocks: 6,
+            floor_sat_per_wu: 1,
+            ceiling_sat_per_wu: 250,
+        }
+    }
+}
+
 impl Default for Bitcoin {
     fn default() -> Self {
         Self {
@@ -45,6 +85,7 @@ impl Default for Bitcoin {
                 node_url: Url::parse("http://localhost:18443")
                     .expect("static string to be a valid url"),
             },
+            fees: BitcoinFees::default(),
         }
     }
 }
@@ -54,6 +95,48 @@ impl From<Bitcoin> for file::Bitcoin {
         file::Bitcoin {
             network: bitcoin.network,
             bitcoind: Some(bitcoin.bitcoind),
+            fees: Some(bitcoin.fees),
+        }
+    }
+}
+
+/// Limits on the expiries of the swaps we are willing to take part in, all
+/// in seconds.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Expiries {
+    /// How much later than beta the alpha HTLC has to expire, this is the
+    /// time the beta redeemer has to redeem alpha once the secret is known.
+    pub min_gap_secs: u32,
+    /// How much time there has to be left until the beta HTLC expires.
+    pub min_time_to_beta_expiry_secs: u32,
+    /// For how long funds may be locked up in the alpha HTLC at most.
+    pub max_duration_secs: u32,
+}
+
+impl Default for Expiries {
+    fn default() -> Self {
+        Self {
+            min_gap_secs: 60 * 60,
+            min_time_to_beta_expiry_secs: 60 * 60,
+            max_duration_secs: 60 * 60 * 24 * 7,
+        }
+    }
+}
+
+/// The number of blocks after which we expect a transaction to be confirmed.
+/// Together with the expiries of a swap, this determines until when it is
+/// still safe to fund it.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct ConfirmationTargets {
+    pub bitcoin_blocks: u32,
+    pub ethereum_blocks: u32,
+}
+
+impl Default for ConfirmationTargets {
+    fn default() -> Self {
+        Self {
+            bitcoin_blocks: 6,
+            ethereum_blocks: 12,
         }
     }
 }
@@ -62,13 +145,60 @@ impl From<Bitcoin> for file::Bitcoin {
 pub struct Ethereum {
     pub chain_id: ethereum::ChainId,
     pub parity: Parity,
+    pub gas: EthereumGas,
+    /// The swap registry to use for the HTLCs on `chain_id`, if any.
+    pub swap_registry: Option<identity::Ethereum>,
+}
+
+/// A swap registry contract deployed on the Ethereum chain with the given
+/// id. Ethereum HTLCs of swaps live in the registry instead of being
+/// deployed one by one.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SwapRegistry {
+    pub chain_id: ethereum::ChainId,
+    pub address: identity::Ethereum,
+}
+
+/// How cnd turns the gas limit and gas price estimated by the Ethereum node
+/// into the values it recommends in Ethereum actions.
+///
+/// Multipliers are given in percent of the node's estimate.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+pub struct EthereumGas {
+    /// Leaves room for the state of the chain to change until the
+    /// transaction is mined.
+    pub limit_multiplier_percent: u64,
+    pub max_limit: u64,
+    pub slow_price_multiplier_percent: u64,
+    pub standard_price_multiplier_percent: u64,
+    pub fast_price_multiplier_percent: u64,
+    pub max_price_gwei: u64,
+}
+
+impl Default for EthereumGas {
+    fn default() -> Self {
+        Self {
+            limit_multiplier_percent: 120,
+            max_limit: 500_000,
+            slow_price_multiplier_percent: 100,
+            standard_price_multiplier_percent: 120,
+            fast_price_multiplier_percent: 150,
+            max_price_gwei: 500,
+        }
+    }
 }
 
 impl From<Ethereum> for file::Ethereum {
     fn from(ethereum: Ethereum) -> Self {
+        let chain_id = ethereum.chain_id;
+
         file::Ethereum {
-            chain_id: ethereum.chain_id,
+            chain_id,
             parity: Some(ethereum.parity),
+            gas: Some(ethereum.gas),
+            swap_registries: ethereum
+                .swap_registry
+                .map(|address| vec![SwapRegistry { chain_id, address }]),
         }
     }
 }
@@ -81,6 +211,8 @@ impl Default for Ethereum {
                 node_url: Url::parse("http://localhost:8545")
                     .expect("static string to be a valid url"),
             },
+            gas: EthereumGas::default(),
+            swap_registry: None,
         }
     }
 }
@@ -93,14 +225,18 @@ pub struct Parity {
 #[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct Lightning {
     pub network: bitcoin::Network,
+    pub backend: LightningBackend,
     pub lnd: Lnd,
+    pub c_lightning: CLightning,
 }
 
 impl Default for Lightning {
     fn default() -> Self {
         Self {
             network: bitcoin::Network::Regtest,
+            backend: LightningBackend::default(),
             lnd: Lnd::default(),
+            c_lightning: CLightning::new(bitcoin::Network::Regtest),
         }
     }
 }
@@ -108,21 +244,69 @@ impl Default for Lightning {
 impl From<Lightning> for file::Lightning {
     fn from(lightning: Lightning) -> Self {
         file::Lightning {
+            backend: Some(lightning.backend),
             lnd: Some(file::Lnd {
                 rest_api_url: lightning.lnd.rest_api_url,
                 dir: lightning.lnd.dir,
+                execute_actions: Some(lightning.lnd.execute_actions),
             }),
+            c_lightning: Some(lightning.c_lightning),
             network: lightning.network,
         }
     }
 }
 
+/// The Lightning node cnd uses for halight swaps.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum LightningBackend {
+    Lnd,
+    CLightning,
+}
+
+impl Default for LightningBackend {
+    fn default() -> Self {
+        LightningBackend::Lnd
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct CLightning {
+    /// The unix socket of c-lightning's JSON-RPC interface.
+    pub rpc_socket: PathBuf,
+}
+
+impl CLightning {
+    pub fn new(network: bitcoin::Network) -> Self {
+        let network_dir = match network {
+            bitcoin::Network::Bitcoin => "bitcoin",
+            bitcoin::Network::Testnet => "testnet",
+            bitcoin::Network::Regtest => "regtest",
+        };
+
+        Self {
+            rpc_socket: default_c_lightning_dir()
+                .join(network_dir)
+                .join("lightning-rpc"),
+        }
+    }
+}
+
+fn default_c_lightning_dir() -> PathBuf {
+    crate::c_lightning_dir().expect("no home directory")
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct Lnd {
     pub rest_api_url: Url,
     pub dir: PathBuf,
     pub cert_path: PathBuf,
     pub readonly_macaroon_path: PathBuf,
+    pub admin_macaroon_path: PathBuf,
+    /// Whether cnd adds, settles and cancels invoices and sends payments
+    /// itself instead of returning these actions to the client. Requires the
+    /// admin macaroon.
+    pub execute_actions: bool,
 }
 
 impl Default for Lnd {
@@ -138,6 +322,8 @@ impl Lnd {
             dir: default_lnd_dir(),
             cert_path: default_lnd_cert_path(default_lnd_dir()),
             readonly_macaroon_path: default_lnd_readonly_macaroon_path(default_lnd_dir(), network),
+            admin_macaroon_path: default_lnd_admin_macaroon_path(default_lnd_dir(), network),
+            execute_actions: false,
         }
     }
 }
@@ -151,6 +337,14 @@ fn default_lnd_cert_path(lnd_dir: PathBuf) -> PathBuf {
 }
 
 fn default_lnd_readonly_macaroon_path(lnd_dir: PathBuf, network: bitcoin::Network) -> PathBuf {
+    lnd_macaroon_dir(lnd_dir, network).join("readonly.macaroon")
+}
+
+fn default_lnd_admin_macaroon_path(lnd_dir: PathBuf, network: bitcoin::Network) -> PathBuf {
+    lnd_macaroon_dir(lnd_dir, network).join("admin.macaroon")
+}
+
+fn lnd_macaroon_dir(lnd_dir: PathBuf, network: bitcoin::Network) -> PathBuf {
     let network_dir = match network {
         bitcoin::Network::Bitcoin => "mainnet",
         bitcoin::Network::Testnet => "testnet",
@@ -161,7 +355,6 @@ fn default_lnd_readonly_macaroon_path(lnd_dir: PathBuf, network: bitcoin::Networ
         .join("chain")
         .join("bitcoin")
         .join(network_dir)
-        .join("readonly.macaroon")
 }
 
 #[cfg(test)]
@@ -177,17 +370,27 @@ mod tests {
             r#"
             listen = ["/ip4/0.0.0.0/tcp/9939", "/ip4/127.0.0.1/tcp/9939"]
             "#,
+            r#"
+            listen = ["/ip4/0.0.0.0/tcp/9939"]
+            request_timeout_secs = 60
+            "#,
         ];
 
         let expected = vec![
             Network {
                 listen: vec!["/ip4/0.0.0.0/tcp/9939".parse().unwrap()],
+                request_timeout_secs: 3600,
             },
             Network {
                 listen: (vec![
                     "/ip4/0.0.0.0/tcp/9939".parse().unwrap(),
                     "/ip4/127.0.0.1/tcp/9939".parse().unwrap(),
                 ]),
+                request_timeout_secs: 3600,
+            },
+            Network {
+                listen: vec!["/ip4/0.0.0.0/tcp/9939".parse().unwrap()],
+                request_timeout_secs: 60,
             },
         ];
 
@@ -212,6 +415,7 @@ mod tests {
         let expected = file::Lnd {
             rest_api_url: LND_URL.clone(),
             dir: PathBuf::from("~/.local/share/comit/lnd"),
+            execute_actions: None,
         };
 
         assert_eq!(actual, Ok(expected));
@@ -230,9 +434,35 @@ mod tests {
 
         let expected = file::Lightning {
             network: bitcoin::Network::Regtest,
+            backend: None,
             lnd: Some(file::Lnd {
                 rest_api_url: LND_URL.clone(),
                 dir: PathBuf::from("/path/to/lnd"),
+                execute_actions: None,
+            }),
+            c_lightning: None,
+        };
+
+        assert_eq!(actual, Ok(expected));
+    }
+
+    #[test]
+    fn c_lightning_backend_deserializes_correctly() {
+        let actual = toml::from_str(
+            r#"
+            network = "testnet"
+            backend = "c-lightning"
+            [c_lightning]
+            rpc_socket = "/path/to/lightning-rpc"
+            "#,
+        );
+
+        let expected = file::Lightning {
+            network: bitcoin::Network::Testnet,
+            backend: Some(LightningBackend::CLightning),
+            lnd: None,
+            c_lightning: Some(CLightning {
+                rpc_socket: PathBuf::from("/path/to/lightning-rpc"),
             }),
         };
 
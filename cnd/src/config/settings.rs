@@ -1,6 +1,11 @@
-use crate::config::{
-    default_lnd_cert_path, default_lnd_readonly_macaroon_path, file, Bitcoin, Bitcoind, Data,
-    Ethereum, File, Lightning, Lnd, Network, Parity,
+use crate::{
+    config::{
+        default_lnd_admin_macaroon_path, default_lnd_cert_path, default_lnd_readonly_macaroon_path,
+        file, Bitcoin, Bitcoind, CLightning, ConfirmationTargets, Data, Ethereum, EthereumGas,
+        Expiries, File, Lightning, Lnd, Network, Parity, SwapRegistry,
+    },
+    identity,
+    swap_protocols::ledger::ethereum,
 };
 use anyhow::Context;
 use log::LevelFilter;
@@ -22,10 +27,12 @@ pub struct Settings {
     pub bitcoin: Bitcoin,
     pub ethereum: Ethereum,
     pub lightning: Lightning,
+    pub expiries: Expiries,
+    pub confirmation_targets: ConfirmationTargets,
 }
 
-fn derive_url_bitcoin(bitcoin: Option<file::Bitcoin>) -> Bitcoin {
-    match bitcoin {
+fn derive_url_bitcoin(bitcoin: Option<file::Bitcoin>) -> anyhow::Result<Bitcoin> {
+    let bitcoin = match bitcoin {
         None => Bitcoin::default(),
         Some(bitcoin) => {
             let node_url = match bitcoin.bitcoind {
@@ -45,13 +52,20 @@ fn derive_url_bitcoin(bitcoin: Option<file::Bitcoin>) -> Bitcoin {
             Bitcoin {
                 network: bitcoin.network,
                 bitcoind: Bitcoind { node_url },
+                fees: bitcoin.fees.unwrap_or_default(),
             }
         }
+    };
+
+    if bitcoin.fees.floor_sat_per_wu > bitcoin.fees.ceiling_sat_per_wu {
+        anyhow::bail!("bitcoin fee floor must not be higher than the fee ceiling")
     }
+
+    Ok(bitcoin)
 }
 
-fn derive_url_ethereum(ethereum: Option<file::Ethereum>) -> Ethereum {
-    match ethereum {
+fn derive_url_ethereum(ethereum: Option<file::Ethereum>) -> anyhow::Result<Ethereum> {
+    let ethereum = match ethereum {
         None => Ethereum::default(),
         Some(ethereum) => {
             let node_url = match ethereum.parity {
@@ -63,12 +77,46 @@ fn derive_url_ethereum(ethereum: Option<file::Ethereum>) -> Ethereum {
                 }
                 Some(parity) => parity.node_url,
             };
+            let swap_registry = derive_swap_registry(
+                ethereum.chain_id,
+                ethereum.swap_registries.unwrap_or_default(),
+            )?;
+
             Ethereum {
                 chain_id: ethereum.chain_id,
                 parity: Parity { node_url },
+                gas: ethereum.gas.unwrap_or_default(),
+                swap_registry,
             }
         }
+    };
+
+    if ethereum.gas.limit_multiplier_percent < 100 {
+        anyhow::bail!("ethereum gas limit multiplier must not be lower than 100 percent")
+    }
+
+    Ok(ethereum)
+}
+
+/// Picks the swap registry configured for the chain cnd is connected to.
+fn derive_swap_registry(
+    chain_id: ethereum::ChainId,
+    swap_registries: Vec<SwapRegistry>,
+) -> anyhow::Result<Option<identity::Ethereum>> {
+    let mut addresses = swap_registries
+        .into_iter()
+        .filter(|registry| registry.chain_id == chain_id)
+        .map(|registry| registry.address);
+
+    let address = addresses.next();
+    if addresses.next().is_some() {
+        anyhow::bail!(
+            "more than one swap registry configured for ethereum chain id {}",
+            u32::from(chain_id)
+        )
     }
+
+    Ok(address)
 }
 
 fn check_url_lnd(lnd_url: Url) -> anyhow::Result<Url> {
@@ -83,25 +131,28 @@ impl From<Settings> for File {
     fn from(settings: Settings) -> Self {
         let Settings {
             network,
-            http_api: HttpApi { socket, cors },
+            http_api,
             data,
             logging: Logging { level },
             bitcoin,
             ethereum,
             lightning,
+            expiries,
+            confirmation_targets,
         } = settings;
 
         File {
             network: Some(network),
             http_api: Some(file::HttpApi {
-                socket,
+                socket: http_api.socket,
                 cors: Some(file::Cors {
-                    allowed_origins: match cors.allowed_origins {
+                    allowed_origins: match http_api.cors.allowed_origins {
                         AllowedOrigins::All => file::AllowedOrigins::All(file::All::All),
                         AllowedOrigins::None => file::AllowedOrigins::None(file::None::None),
                         AllowedOrigins::Some(origins) => file::AllowedOrigins::Some(origins),
                     },
                 }),
+                admin_socket: http_api.admin_socket,
             }),
             data: Some(data),
             logging: Some(file::Logging {
@@ -110,6 +161,8 @@ impl From<Settings> for File {
             bitcoin: Some(bitcoin.into()),
             ethereum: Some(ethereum.into()),
             lightning: Some(lightning.into()),
+            expiries: Some(expiries),
+            confirmation_targets: Some(confirmation_targets),
         }
     }
 }
@@ -118,6 +171,9 @@ impl From<Settings> for File {
 pub struct HttpApi {
     pub socket: SocketAddr,
     pub cors: Cors,
+    /// The admin API is only served if this is configured, it has no CORS
+    /// headers and should only be reachable by the operator of cnd.
+    pub admin_socket: Option<SocketAddr>,
 }
 
 impl Default for HttpApi {
@@ -125,6 +181,7 @@ impl Default for HttpApi {
         Self {
             socket: SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 8000),
             cors: Cors::default(),
+            admin_socket: None,
         }
     }
 }
@@ -166,6 +223,8 @@ impl Settings {
             bitcoin,
             ethereum,
             lightning,
+            expiries,
+            confirmation_targets,
         } = config_file;
 
         Ok(Self {
@@ -176,11 +235,13 @@ impl Settings {
 
                 Network {
                     listen: vec![default_socket],
+                    request_timeout_secs: Network::default_request_timeout_secs(),
                 }
             }),
             http_api: http_api
-                .map(|file::HttpApi { socket, cors }| {
-                    let cors = cors
+                .map(|http_api| {
+                    let cors = http_api
+                        .cors
                         .map(|cors| {
                             let allowed_origins = match cors.allowed_origins {
                                 file::AllowedOrigins::All(_) => AllowedOrigins::All,
@@ -194,7 +255,11 @@ impl Settings {
                         })
                         .unwrap_or_default();
 
-                    HttpApi { socket, cors }
+                    HttpApi {
+                        socket: http_api.socket,
+                        cors,
+                        admin_socket: http_api.admin_socket,
+                    }
                 })
                 .unwrap_or_default(),
             data: {
@@ -202,6 +267,7 @@ impl Settings {
                     crate::data_dir().context("unable to determine default data path")?;
                 data.unwrap_or(Data {
                     dir: default_data_dir,
+                    postgres: None,
                 })
             },
 
@@ -216,12 +282,13 @@ impl Settings {
                     },
                 }
             },
-            bitcoin: derive_url_bitcoin(bitcoin),
-            ethereum: derive_url_ethereum(ethereum),
+            bitcoin: derive_url_bitcoin(bitcoin)?,
+            ethereum: derive_url_ethereum(ethereum)?,
             lightning: match lightning {
                 None => Lightning::default(),
                 Some(lightning) => Lightning {
                     network: lightning.network,
+                    backend: lightning.backend.unwrap_or_default(),
                     lnd: match lightning.lnd {
                         None => Lnd::default(),
                         Some(lnd) => Lnd {
@@ -229,13 +296,23 @@ impl Settings {
                             dir: lnd.dir.clone(),
                             cert_path: default_lnd_cert_path(lnd.dir.clone()),
                             readonly_macaroon_path: default_lnd_readonly_macaroon_path(
+                                lnd.dir.clone(),
+                                lightning.network,
+                            ),
+                            admin_macaroon_path: default_lnd_admin_macaroon_path(
                                 lnd.dir,
                                 lightning.network,
                             ),
+                            execute_actions: lnd.execute_actions.unwrap_or(false),
                         },
                     },
+                    c_lightning: lightning
+                        .c_lightning
+                        .unwrap_or_else(|| CLightning::new(lightning.network)),
                 },
             },
+            expiries: expiries.unwrap_or_default(),
+            confirmation_targets: confirmation_targets.unwrap_or_default(),
         })
     }
 }
@@ -244,9 +321,12 @@ impl Settings {
 mod tests {
 
     use super::*;
-    use crate::{config::file, swap_protocols::ledger::ethereum};
+    use crate::{
+        config::{file, BitcoinFees, LightningBackend},
+        swap_protocols::ledger::ethereum,
+    };
     use spectral::prelude::*;
-    use std::net::IpAddr;
+    use std::{net::IpAddr, path::PathBuf};
 
     #[test]
     fn logging_section_defaults_to_info() {
@@ -271,6 +351,7 @@ mod tests {
             http_api: Some(file::HttpApi {
                 socket: SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), 8000),
                 cors: None,
+                admin_socket: None,
             }),
             ..File::default()
         };
@@ -302,6 +383,7 @@ mod tests {
                 cors: Cors {
                     allowed_origins: AllowedOrigins::None,
                 },
+                admin_socket: None,
             })
     }
 
@@ -319,6 +401,7 @@ mod tests {
             .map(|settings| &settings.network)
             .is_equal_to(Network {
                 listen: vec!["/ip4/0.0.0.0/tcp/9939".parse().unwrap()],
+                request_timeout_secs: 3600,
             })
     }
 
@@ -336,6 +419,11 @@ mod tests {
                 bitcoind: Bitcoind {
                     node_url: "http://localhost:18443".parse().unwrap(),
                 },
+                fees: BitcoinFees {
+                    target_blocks: 6,
+                    floor_sat_per_wu: 1,
+                    ceiling_sat_per_wu: 250,
+                },
             })
     }
 
@@ -352,6 +440,7 @@ mod tests {
                 bitcoin: Some(file::Bitcoin {
                     network,
                     bitcoind: None,
+                    fees: None,
                 }),
                 ..File::default()
             };
@@ -366,10 +455,31 @@ mod tests {
                     bitcoind: Bitcoind {
                         node_url: url.parse().unwrap(),
                     },
+                    fees: BitcoinFees::default(),
                 })
         }
     }
 
+    #[test]
+    fn bitcoin_fee_floor_above_ceiling_is_rejected() {
+        let config_file = File {
+            bitcoin: Some(file::Bitcoin {
+                network: bitcoin::Network::Regtest,
+                bitcoind: None,
+                fees: Some(BitcoinFees {
+                    target_blocks: 6,
+                    floor_sat_per_wu: 10,
+                    ceiling_sat_per_wu: 5,
+                }),
+            }),
+            ..File::default()
+        };
+
+        let settings = Settings::from_config_file_and_defaults(config_file);
+
+        assert_that(&settings).is_err();
+    }
+
     #[test]
     fn ethereum_defaults() {
         let config_file = File { ..File::default() };
@@ -384,6 +494,8 @@ mod tests {
                 parity: Parity {
                     node_url: "http://localhost:8545".parse().unwrap(),
                 },
+                gas: EthereumGas::default(),
+                swap_registry: None,
             })
     }
 
@@ -399,6 +511,8 @@ mod tests {
             let ethereum = Some(file::Ethereum {
                 chain_id,
                 parity: None,
+                gas: None,
+                swap_registries: None,
             });
             let config_file = File {
                 ethereum,
@@ -415,10 +529,83 @@ mod tests {
                     parity: Parity {
                         node_url: url.parse().unwrap(),
                     },
+                    gas: EthereumGas::default(),
+                    swap_registry: None,
                 })
         }
     }
 
+    #[test]
+    fn ethereum_gas_limit_multiplier_below_one_is_rejected() {
+        let config_file = File {
+            ethereum: Some(file::Ethereum {
+                chain_id: ethereum::ChainId::regtest(),
+                parity: None,
+                gas: Some(EthereumGas {
+                    limit_multiplier_percent: 90,
+                    ..EthereumGas::default()
+                }),
+                swap_registries: None,
+            }),
+            ..File::default()
+        };
+
+        let settings = Settings::from_config_file_and_defaults(config_file);
+
+        assert_that(&settings).is_err();
+    }
+
+    #[test]
+    fn ethereum_swap_registry_is_picked_by_chain_id() {
+        let regtest_registry = identity::Ethereum::random();
+        let config_file = File {
+            ethereum: Some(file::Ethereum {
+                chain_id: ethereum::ChainId::regtest(),
+                parity: None,
+                gas: None,
+                swap_registries: Some(vec![
+                    SwapRegistry {
+                        chain_id: ethereum::ChainId::mainnet(),
+                        address: identity::Ethereum::random(),
+                    },
+                    SwapRegistry {
+                        chain_id: ethereum::ChainId::regtest(),
+                        address: regtest_registry,
+                    },
+                ]),
+            }),
+            ..File::default()
+        };
+
+        let settings = Settings::from_config_file_and_defaults(config_file);
+
+        assert_that(&settings)
+            .is_ok()
+            .map(|settings| &settings.ethereum.swap_registry)
+            .is_equal_to(Some(regtest_registry))
+    }
+
+    #[test]
+    fn ethereum_swap_registries_for_the_same_chain_are_rejected() {
+        let registry = SwapRegistry {
+            chain_id: ethereum::ChainId::regtest(),
+            address: identity::Ethereum::random(),
+        };
+        let config_file = File {
+            ethereum: Some(file::Ethereum {
+                chain_id: ethereum::ChainId::regtest(),
+                parity: None,
+                gas: None,
+                swap_registries: Some(vec![registry, registry]),
+            }),
+            ..File::default()
+        };
+
+        let settings = Settings::from_config_file_and_defaults(config_file);
+
+        assert_that(&settings).is_err();
+    }
+
     #[test]
     fn lightning_section_defaults() {
         let config_file = File {
@@ -439,7 +626,9 @@ mod tests {
         let config_file = File {
             lightning: Some(file::Lightning {
                 network: bitcoin::Network::Regtest,
+                backend: None,
                 lnd: None,
+                c_lightning: None,
             }),
             ..File::default()
         };
@@ -451,7 +640,94 @@ mod tests {
             .map(|settings| &settings.lightning)
             .is_equal_to(Lightning {
                 network: bitcoin::Network::Regtest,
+                backend: LightningBackend::Lnd,
                 lnd: Lnd::default(),
+                c_lightning: CLightning::new(bitcoin::Network::Regtest),
+            })
+    }
+
+    #[test]
+    fn c_lightning_rpc_socket_defaults_to_network_dir() {
+        let config_file = File {
+            lightning: Some(file::Lightning {
+                network: bitcoin::Network::Testnet,
+                backend: Some(LightningBackend::CLightning),
+                lnd: None,
+                c_lightning: None,
+            }),
+            ..File::default()
+        };
+
+        let settings = Settings::from_config_file_and_defaults(config_file);
+        let expected = crate::c_lightning_dir()
+            .unwrap()
+            .join("testnet")
+            .join("lightning-rpc");
+
+        assert_that(&settings)
+            .is_ok()
+            .map(|settings| &settings.lightning.c_lightning.rpc_socket)
+            .is_equal_to(&expected)
+    }
+
+    #[test]
+    fn lnd_does_not_execute_actions_by_default() {
+        let config_file = File {
+            lightning: Some(file::Lightning {
+                network: bitcoin::Network::Regtest,
+                backend: None,
+                c_lightning: None,
+                lnd: Some(file::Lnd {
+                    rest_api_url: "https://localhost:8080/".parse().unwrap(),
+                    dir: PathBuf::from("/foo/bar"),
+                    execute_actions: None,
+                }),
+            }),
+            ..File::default()
+        };
+
+        let settings = Settings::from_config_file_and_defaults(config_file);
+
+        assert_that(&settings)
+            .is_ok()
+            .map(|settings| &settings.lightning.lnd.execute_actions)
+            .is_equal_to(&false)
+    }
+
+    #[test]
+    fn expiries_section_defaults() {
+        let config_file = File {
+            expiries: None,
+            ..File::default()
+        };
+
+        let settings = Settings::from_config_file_and_defaults(config_file);
+
+        assert_that(&settings)
+            .is_ok()
+            .map(|settings| &settings.expiries)
+            .is_equal_to(Expiries {
+                min_gap_secs: 3600,
+                min_time_to_beta_expiry_secs: 3600,
+                max_duration_secs: 604_800,
+            })
+    }
+
+    #[test]
+    fn confirmation_targets_section_defaults() {
+        let config_file = File {
+            confirmation_targets: None,
+            ..File::default()
+        };
+
+        let settings = Settings::from_config_file_and_defaults(config_file);
+
+        assert_that(&settings)
+            .is_ok()
+            .map(|settings| &settings.confirmation_targets)
+            .is_equal_to(ConfirmationTargets {
+                bitcoin_blocks: 6,
+                ethereum_blocks: 12,
             })
     }
 
@@ -460,9 +736,12 @@ mod tests {
         let config_file = File {
             lightning: Some(file::Lightning {
                 network: bitcoin::Network::Regtest,
+                backend: None,
+                c_lightning: None,
                 lnd: Some(file::Lnd {
                     rest_api_url: "http://localhost:8000/".parse().unwrap(),
                     dir: Default::default(),
+                    execute_actions: None,
                 }),
             }),
             ..File::default()
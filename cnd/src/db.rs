@@ -1,28 +1,63 @@
+/// Implements the given storage traits once for every backend: within the
+/// items, `Backend` names the backend and `BackendConnection` its diesel
+/// connection.
+macro_rules! for_each_backend {
+    ($($item:item)*) => {
+        const _: () = {
+            #[allow(dead_code)]
+            type Backend = crate::db::Sqlite;
+            #[allow(dead_code)]
+            type BackendConnection = diesel::sqlite::SqliteConnection;
+
+            $($item)*
+        };
+        #[cfg(feature = "postgres")]
+        const _: () = {
+            #[allow(dead_code)]
+            type Backend = crate::db::Postgres;
+            #[allow(dead_code)]
+            type BackendConnection = diesel::pg::PgConnection;
+
+            $($item)*
+        };
+    };
+}
+
+mod columns;
+mod database;
 #[cfg(test)]
 mod integration_tests;
+mod invoice_cancellations;
+mod ledger_events;
 mod load_swaps;
+#[cfg(feature = "postgres")]
+mod postgres;
 mod save;
 mod schema;
+mod snapshot;
+mod swap_errors;
 mod wrapper_types;
 #[macro_use]
 mod swap;
-#[macro_use]
 mod swap_types;
 #[macro_use]
 pub mod with_swap_types;
 embed_migrations!("./migrations");
 
+#[cfg(feature = "postgres")]
+pub use self::postgres::Postgres;
 pub use self::{
+    database::Database,
+    invoice_cancellations::{InvoiceCancellation, InvoiceCancellationStore},
+    ledger_events::{LedgerEvent, LedgerEventStore},
     load_swaps::{AcceptedSwap, LoadAcceptedSwap},
     save::*,
     swap::*,
+    swap_errors::SwapErrorStore,
     swap_types::*,
 };
 
-use crate::{
-    db::wrapper_types::custom_sql_types::Text,
-    swap_protocols::{rfc003::SwapId, LocalSwapId, Role},
-};
+use crate::swap_protocols::{LocalSwapId, Role};
 use diesel::{self, prelude::*, sqlite::SqliteConnection};
 use libp2p::PeerId;
 use std::{
@@ -86,25 +121,6 @@ impl Sqlite {
 
         Ok(result)
     }
-
-    async fn role(&self, key: &SwapId) -> anyhow::Result<Role> {
-        use self::schema::rfc003_swaps as swaps;
-
-        let record: QueryableSwapRole = self
-            .do_in_transaction(|connection| {
-                let key = Text(key);
-
-                swaps::table
-                    .filter(swaps::swap_id.eq(key))
-                    .select((swaps::swap_id, swaps::role))
-                    .first(connection)
-                    .optional()
-            })
-            .await?
-            .ok_or(Error::SwapNotFound)?;
-
-        Ok(*record.role)
-    }
 }
 
 // Construct an absolute path to the database file using 'dir' as the base.
@@ -121,12 +137,6 @@ fn ensure_folder_tree_exists(path: &Path) -> anyhow::Result<()> {
     Ok(())
 }
 
-#[derive(Queryable, Debug, Clone, PartialEq)]
-struct QueryableSwapRole {
-    pub swap_id: Text<SwapId>,
-    pub role: Text<Role>,
-}
-
 #[derive(Debug, Clone, Copy, thiserror::Error)]
 pub enum Error {
     #[error("swap not found")]
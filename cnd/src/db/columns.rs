@@ -0,0 +1,204 @@
+//! Mapping of the ledgers, assets and identities of an rfc003 swap to the
+//! columns of the `rfc003_ledgers`, `rfc003_assets` and `rfc003_identities`
+//! tables.
+//!
+//! Supporting a new ledger or asset only requires implementing these traits,
+//! the tables themselves are shared by all combinations.
+
+use crate::{
+    asset,
+    db::{
+        wrapper_types::{BitcoinNetwork, Erc20Amount, Ether, EthereumAddress, Satoshis},
+        AssetKind,
+    },
+    identity,
+    swap_protocols::{
+        ledger::{self, ethereum::ChainId, Ethereum},
+        Side,
+    },
+};
+use std::str::FromStr;
+use strum_macros::{Display, EnumIter, EnumString};
+
+/// The `ledger` column of the `rfc003_ledgers` table.
+#[derive(Clone, Copy, Debug, Display, EnumString, EnumIter, PartialEq)]
+#[strum(serialize_all = "lowercase")]
+pub enum LedgerName {
+    Bitcoin,
+    Ethereum,
+}
+
+/// The `kind` column of the `rfc003_identities` table.
+#[derive(Clone, Copy, Debug, Display, EnumString, EnumIter, PartialEq)]
+#[strum(serialize_all = "lowercase")]
+pub enum IdentityKind {
+    Redeem,
+    Refund,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("stored {column} '{value}' does not match the types of the swap")]
+pub struct ColumnMismatch {
+    column: &'static str,
+    value: String,
+}
+
+impl ColumnMismatch {
+    pub fn new(column: &'static str, value: impl ToString) -> Self {
+        Self {
+            column,
+            value: value.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("no {what} stored for the {side} side of the swap")]
+pub struct MissingRow {
+    what: String,
+    side: Side,
+}
+
+impl MissingRow {
+    pub fn new(what: impl ToString, side: Side) -> Self {
+        Self {
+            what: what.to_string(),
+            side,
+        }
+    }
+}
+
+/// A ledger stored as its name and network.
+pub trait LedgerColumns: Sized {
+    const NAME: LedgerName;
+
+    fn network(&self) -> String;
+    fn from_network(network: &str) -> anyhow::Result<Self>;
+}
+
+macro_rules! impl_ledger_columns_for_bitcoin {
+    ($ledger:ident) => {
+        impl LedgerColumns for ledger::bitcoin::$ledger {
+            const NAME: LedgerName = LedgerName::Bitcoin;
+
+            fn network(&self) -> String {
+                BitcoinNetwork::from(*self).to_string()
+            }
+
+            fn from_network(network: &str) -> anyhow::Result<Self> {
+                match BitcoinNetwork::from_str(network)? {
+                    BitcoinNetwork::$ledger => Ok(ledger::bitcoin::$ledger),
+                    _ => Err(ColumnMismatch::new("network", network).into()),
+                }
+            }
+        }
+    };
+}
+
+impl_ledger_columns_for_bitcoin!(Mainnet);
+impl_ledger_columns_for_bitcoin!(Testnet);
+impl_ledger_columns_for_bitcoin!(Regtest);
+
+/// The network of an Ethereum ledger is its chain id in decimal.
+impl LedgerColumns for Ethereum {
+    const NAME: LedgerName = LedgerName::Ethereum;
+
+    fn network(&self) -> String {
+        u32::from(self.chain_id).to_string()
+    }
+
+    fn from_network(network: &str) -> anyhow::Result<Self> {
+        let chain_id = u32::from_str(network)?;
+
+        Ok(Ethereum::new(ChainId::from(chain_id)))
+    }
+}
+
+/// An asset stored as its kind, quantity and, for tokens, its contract.
+pub trait AssetColumns: Sized {
+    const KIND: AssetKind;
+
+    fn quantity(&self) -> String;
+    fn token_contract(&self) -> Option<String>;
+    fn from_columns(quantity: &str, token_contract: Option<&str>) -> anyhow::Result<Self>;
+}
+
+impl AssetColumns for asset::Bitcoin {
+    const KIND: AssetKind = AssetKind::Bitcoin;
+
+    fn quantity(&self) -> String {
+        Satoshis::from(*self).to_string()
+    }
+
+    fn token_contract(&self) -> Option<String> {
+        None
+    }
+
+    fn from_columns(quantity: &str, _: Option<&str>) -> anyhow::Result<Self> {
+        Ok(Satoshis::from_str(quantity)?.into())
+    }
+}
+
+impl AssetColumns for asset::Ether {
+    const KIND: AssetKind = AssetKind::Ether;
+
+    fn quantity(&self) -> String {
+        Ether::from(self.clone()).to_string()
+    }
+
+    fn token_contract(&self) -> Option<String> {
+        None
+    }
+
+    fn from_columns(quantity: &str, _: Option<&str>) -> anyhow::Result<Self> {
+        Ok(Ether::from_str(quantity)?.into())
+    }
+}
+
+impl AssetColumns for asset::Erc20 {
+    const KIND: AssetKind = AssetKind::Erc20;
+
+    fn quantity(&self) -> String {
+        Erc20Amount::from(self.quantity.clone()).to_string()
+    }
+
+    fn token_contract(&self) -> Option<String> {
+        Some(EthereumAddress::from(self.token_contract).to_string())
+    }
+
+    fn from_columns(quantity: &str, token_contract: Option<&str>) -> anyhow::Result<Self> {
+        let token_contract =
+            token_contract.ok_or_else(|| ColumnMismatch::new("token_contract", "NULL"))?;
+
+        Ok(asset::Erc20::new(
+            EthereumAddress::from_str(token_contract)?.into(),
+            Erc20Amount::from_str(quantity)?.into(),
+        ))
+    }
+}
+
+/// An identity stored in a single text column.
+pub trait IdentityColumn: Sized {
+    fn to_column(&self) -> String;
+    fn from_column(identity: &str) -> anyhow::Result<Self>;
+}
+
+impl IdentityColumn for identity::Bitcoin {
+    fn to_column(&self) -> String {
+        ::bitcoin::PublicKey::from(*self).to_string()
+    }
+
+    fn from_column(identity: &str) -> anyhow::Result<Self> {
+        Ok(::bitcoin::PublicKey::from_str(identity)?.into())
+    }
+}
+
+impl IdentityColumn for identity::Ethereum {
+    fn to_column(&self) -> String {
+        EthereumAddress::from(*self).to_string()
+    }
+
+    fn from_column(identity: &str) -> anyhow::Result<Self> {
+        Ok(EthereumAddress::from_str(identity)?.into())
+    }
+}
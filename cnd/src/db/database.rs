@@ -0,0 +1,158 @@
+#[cfg(feature = "postgres")]
+use crate::db::Postgres;
+use crate::{
+    config,
+    db::{
+        columns::{AssetColumns, IdentityColumn, LedgerColumns},
+        AcceptedSwap, DetermineTypes, InvoiceCancellation, InvoiceCancellationStore, LedgerEvent,
+        LedgerEventStore, LoadAcceptedSwap, Retrieve, Save, Sqlite, Swap, SwapErrorStore,
+        SwapTypes,
+    },
+    swap_protocols::{rfc003::SwapId, FailedSwap, LocalSwapId, Side},
+};
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+/// The storage backend selected in the configuration of cnd.
+///
+/// All storage traits are forwarded to the selected backend, hence the rest of
+/// cnd does not need to know where the swaps are stored.
+#[derive(Clone, Debug)]
+pub enum Database {
+    Sqlite(Sqlite),
+    #[cfg(feature = "postgres")]
+    Postgres(Postgres),
+}
+
+impl Database {
+    /// Return a handle to the database configured in 'data'.
+    pub fn new(data: &config::Data) -> anyhow::Result<Self> {
+        match &data.postgres {
+            None => Ok(Database::Sqlite(Sqlite::new_in_dir(&data.dir)?)),
+            #[cfg(feature = "postgres")]
+            Some(config::Postgres { url }) => Ok(Database::Postgres(Postgres::new(url)?)),
+            #[cfg(not(feature = "postgres"))]
+            Some(_) => anyhow::bail!("cnd was built without the postgres feature"),
+        }
+    }
+}
+
+impl From<Sqlite> for Database {
+    fn from(sqlite: Sqlite) -> Self {
+        Database::Sqlite(sqlite)
+    }
+}
+
+#[cfg(feature = "postgres")]
+impl From<Postgres> for Database {
+    fn from(postgres: Postgres) -> Self {
+        Database::Postgres(postgres)
+    }
+}
+
+/// Evaluates the given expression with `$db` bound to the selected backend.
+macro_rules! with_backend {
+    ($database:expr, |$db:ident| $body:expr) => {
+        match $database {
+            Database::Sqlite($db) => $body,
+            #[cfg(feature = "postgres")]
+            Database::Postgres($db) => $body,
+        }
+    };
+}
+
+#[async_trait]
+impl Retrieve for Database {
+    async fn get(&self, key: &SwapId) -> anyhow::Result<Swap> {
+        with_backend!(self, |db| db.get(key).await)
+    }
+
+    async fn all(&self) -> anyhow::Result<Vec<Swap>> {
+        with_backend!(self, |db| db.all().await)
+    }
+}
+
+#[async_trait]
+impl DetermineTypes for Database {
+    async fn determine_types(&self, key: &SwapId) -> anyhow::Result<SwapTypes> {
+        with_backend!(self, |db| db.determine_types(key).await)
+    }
+}
+
+#[async_trait]
+impl<AL, BL, AA, BA, AI, BI> LoadAcceptedSwap<AL, BL, AA, BA, AI, BI> for Database
+where
+    AL: LedgerColumns + Send + 'static,
+    BL: LedgerColumns + Send + 'static,
+    AA: AssetColumns + Send + 'static,
+    BA: AssetColumns + Send + 'static,
+    AI: IdentityColumn + Send + 'static,
+    BI: IdentityColumn + Send + 'static,
+{
+    async fn load_accepted_swap(
+        &self,
+        key: &SwapId,
+    ) -> anyhow::Result<AcceptedSwap<AL, BL, AA, BA, AI, BI>> {
+        with_backend!(self, |db| db.load_accepted_swap(key).await)
+    }
+}
+
+#[cfg(not(feature = "postgres"))]
+#[async_trait]
+impl<T> Save<T> for Database
+where
+    T: Send + 'static,
+    Sqlite: Save<T>,
+{
+    async fn save(&self, data: T) -> anyhow::Result<()> {
+        with_backend!(self, |db| db.save(data).await)
+    }
+}
+
+#[cfg(feature = "postgres")]
+#[async_trait]
+impl<T> Save<T> for Database
+where
+    T: Send + 'static,
+    Sqlite: Save<T>,
+    Postgres: Save<T>,
+{
+    async fn save(&self, data: T) -> anyhow::Result<()> {
+        with_backend!(self, |db| db.save(data).await)
+    }
+}
+
+#[async_trait]
+impl SwapErrorStore for Database {
+    async fn all_swap_errors(&self) -> anyhow::Result<Vec<FailedSwap>> {
+        with_backend!(self, |db| db.all_swap_errors().await)
+    }
+
+    async fn delete_swap_errors(
+        &self,
+        swap_id: Uuid,
+        side: Side,
+        until: NaiveDateTime,
+    ) -> anyhow::Result<()> {
+        with_backend!(self, |db| db.delete_swap_errors(swap_id, side, until).await)
+    }
+}
+
+#[async_trait]
+impl LedgerEventStore for Database {
+    async fn ledger_events(&self, swap_id: SwapId) -> anyhow::Result<Vec<LedgerEvent>> {
+        with_backend!(self, |db| db.ledger_events(swap_id).await)
+    }
+}
+
+#[async_trait]
+impl InvoiceCancellationStore for Database {
+    async fn all_invoice_cancellations(&self) -> anyhow::Result<Vec<InvoiceCancellation>> {
+        with_backend!(self, |db| db.all_invoice_cancellations().await)
+    }
+
+    async fn delete_invoice_cancellation(&self, swap_id: LocalSwapId) -> anyhow::Result<()> {
+        with_backend!(self, |db| db.delete_invoice_cancellation(swap_id).await)
+    }
+}
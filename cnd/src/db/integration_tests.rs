@@ -1,2 +1,3 @@
 mod db_roundtrips;
+mod migrations;
 mod serialization_format_stability;
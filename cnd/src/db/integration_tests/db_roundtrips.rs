@@ -3,7 +3,7 @@ use crate::{
     db::{
         load_swaps::LoadAcceptedSwap,
         swap_types::{DetermineTypes, SwapTypes},
-        AssetKind, BitcoinLedgerKind, LedgerKind, Retrieve, Save, Sqlite, Swap,
+        AssetKind, BitcoinLedgerKind, Database, LedgerKind, Retrieve, Save, Sqlite, Swap,
     },
     identity,
     quickcheck::Quickcheck,
@@ -15,12 +15,41 @@ use crate::{
 use std::path::Path;
 
 use crate::swap_protocols::ledger::bitcoin::{Mainnet, Regtest, Testnet};
+
+/// Connects to the PostgreSQL database in `CND_TEST_POSTGRES_URL`. Tests
+/// connect one at a time because concurrent migration runs would race on
+/// creating the tables.
+#[cfg(feature = "postgres")]
+fn test_postgres() -> anyhow::Result<crate::db::Postgres> {
+    lazy_static::lazy_static! {
+        static ref MIGRATIONS: std::sync::Mutex<()> = std::sync::Mutex::new(());
+    }
+
+    let url = std::env::var("CND_TEST_POSTGRES_URL")
+        .expect("CND_TEST_POSTGRES_URL must be set to run the tests of the postgres feature");
+    let _guard = MIGRATIONS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    crate::db::Postgres::new(&url.parse()?)
+}
 macro_rules! db_roundtrip_test {
     ($alpha_ledger:ident, $beta_ledger:ident, $alpha_asset:ident, $beta_asset:ident, $alpha_identity:ident, $beta_identity:ident, $expected_swap_types_fn:expr) => {
+        db_roundtrip_test!(
+            @test roundtrip_test, Database::from(Sqlite::new(&Path::new(":memory:"))?),
+            $alpha_ledger, $beta_ledger, $alpha_asset, $beta_asset, $alpha_identity, $beta_identity, $expected_swap_types_fn
+        );
+        db_roundtrip_test!(
+            @test #[cfg(feature = "postgres")] postgres_roundtrip_test, Database::from(test_postgres()?),
+            $alpha_ledger, $beta_ledger, $alpha_asset, $beta_asset, $alpha_identity, $beta_identity, $expected_swap_types_fn
+        );
+    };
+    (@test $(#[$meta:meta])* $prefix:ident, $db:expr, $alpha_ledger:ident, $beta_ledger:ident, $alpha_asset:ident, $beta_asset:ident, $alpha_identity:ident, $beta_identity:ident, $expected_swap_types_fn:expr) => {
         paste::item! {
+            $(#[$meta])*
             #[test]
             #[allow(non_snake_case, clippy::redundant_closure_call)]
-            fn [<roundtrip_test_ $alpha_ledger _ $beta_ledger _ $alpha_asset _ $beta_asset>]() {
+            fn [<$prefix _ $alpha_ledger _ $beta_ledger _ $alpha_asset _ $beta_asset>]() {
                 fn prop(swap: Quickcheck<Swap>,
                         request: Quickcheck<Request<$alpha_ledger, $beta_ledger, $alpha_asset, $beta_asset, $alpha_identity, $beta_identity>>,
                         accept: Quickcheck<Accept<$alpha_identity, $beta_identity>>,
@@ -32,7 +61,7 @@ macro_rules! db_roundtrip_test {
                     // construct the expected swap types from the function we get passed in order to enrich it with the role
                     let expected_swap_types = ($expected_swap_types_fn)(role);
 
-                    let db = Sqlite::new(&Path::new(":memory:"))?;
+                    let db = $db;
 
                     let saved_swap = Swap {
                         swap_id,
@@ -0,0 +1,123 @@
+use crate::{
+    asset::{self, ethereum::FromWei, Erc20Quantity},
+    db::{
+        load_swaps::LoadAcceptedSwap,
+        swap_types::{DetermineTypes, SwapTypes},
+        AssetKind, BitcoinLedgerKind, LedgerKind, Sqlite,
+    },
+    ethereum::U256,
+    identity,
+    swap_protocols::{
+        ledger::{bitcoin::Regtest, ethereum::ChainId, Ethereum},
+        rfc003::{Accept, Request, SecretHash, SwapId},
+        HashFunction, Role,
+    },
+    timestamp::Timestamp,
+};
+use diesel::{connection::SimpleConnection, Connection, SqliteConnection};
+use std::str::FromStr;
+
+/// Creates a database as it looked before the rfc003 tables were normalized
+/// and marks the first migration as run.
+fn database_with_per_pair_tables(path: &std::path::Path) {
+    let connection = SqliteConnection::establish(&format!("file:{}", path.display())).unwrap();
+
+    connection
+        .batch_execute(include_str!(
+            "../../../migrations/2019-11-11-034058_create-message-tables/up.sql"
+        ))
+        .unwrap();
+    connection
+        .batch_execute(
+            r#"
+CREATE TABLE __diesel_schema_migrations (
+    version VARCHAR(50) PRIMARY KEY NOT NULL,
+    run_on TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+INSERT INTO __diesel_schema_migrations (version) VALUES ('20191111034058');
+
+INSERT INTO rfc003_swaps (swap_id, role, counterparty)
+    VALUES ('ad2652ca-ecf2-4cc6-b35c-b4351ac28a34', 'Alice', 'QmfUfpC2frwFvcDzpspnfZitHt5wct6n4kpG5jzgRdsxkY');
+INSERT INTO rfc003_bitcoin_ethereum_bitcoin_erc20_request_messages
+    (swap_id, bitcoin_network, ethereum_chain_id, bitcoin_amount, erc20_amount, erc20_token_contract,
+     hash_function, bitcoin_refund_identity, ethereum_redeem_identity, bitcoin_expiry, ethereum_expiry,
+     secret_hash)
+    VALUES ('ad2652ca-ecf2-4cc6-b35c-b4351ac28a34', 'regtest', 1337, '100000000', '5000000000000000000',
+            'b97048628db6b661d4c2aa833e95dbe1a905b280', 'SHA-256',
+            '0216867374f539badfd90d7b2269008d893ae7bd4f9ee7c695c967d01d6953c401',
+            '68917b35bacf71dbadf37628b3b7f290f6d88877', 1590000000, 1589990000,
+            '68d627971643a6f97f27c58957826fcba853ec2077fd10ec6b93d8e61deb4cec');
+INSERT INTO rfc003_bitcoin_ethereum_accept_messages
+    (swap_id, bitcoin_redeem_identity, ethereum_refund_identity, at)
+    VALUES ('ad2652ca-ecf2-4cc6-b35c-b4351ac28a34',
+            '02c2a8efce029526d364c2cf39d89e3cdda05e5df7b2cbfc098b4e3d02b70b5275',
+            'd51ecee7414c4445534f74208538683702cbb3e4', '2020-05-10 12:00:00');
+"#,
+        )
+        .unwrap();
+}
+
+#[tokio::test]
+async fn swaps_stored_in_per_pair_tables_survive_the_migration() {
+    let temp_file = tempfile::Builder::new()
+        .suffix(".sqlite")
+        .tempfile()
+        .unwrap();
+    database_with_per_pair_tables(temp_file.path());
+    let swap_id = SwapId::from_str("ad2652ca-ecf2-4cc6-b35c-b4351ac28a34").unwrap();
+
+    let db = Sqlite::new(temp_file.path()).unwrap();
+    let swap_types = db.determine_types(&swap_id).await.unwrap();
+    let (request, accept, at): (
+        Request<
+            Regtest,
+            Ethereum,
+            asset::Bitcoin,
+            asset::Erc20,
+            identity::Bitcoin,
+            identity::Ethereum,
+        >,
+        Accept<identity::Bitcoin, identity::Ethereum>,
+        _,
+    ) = db.load_accepted_swap(&swap_id).await.unwrap();
+
+    assert_eq!(swap_types, SwapTypes {
+        alpha_ledger: LedgerKind::Bitcoin(BitcoinLedgerKind::Regtest),
+        beta_ledger: LedgerKind::Ethereum,
+        alpha_asset: AssetKind::Bitcoin,
+        beta_asset: AssetKind::Erc20,
+        role: Role::Alice,
+    });
+    assert_eq!(request, Request {
+        swap_id,
+        alpha_ledger: Regtest,
+        beta_ledger: Ethereum::new(ChainId::regtest()),
+        alpha_asset: asset::Bitcoin::from_sat(100_000_000),
+        beta_asset: asset::Erc20::new(
+            identity::Ethereum::from_str("b97048628db6b661d4c2aa833e95dbe1a905b280").unwrap(),
+            Erc20Quantity::from_wei(U256::from(5_000_000_000_000_000_000u64)),
+        ),
+        hash_function: HashFunction::Sha256,
+        alpha_ledger_refund_identity: identity::Bitcoin::from(
+            ::bitcoin::PublicKey::from_str(
+                "0216867374f539badfd90d7b2269008d893ae7bd4f9ee7c695c967d01d6953c401"
+            )
+            .unwrap()
+        ),
+        beta_ledger_redeem_identity: identity::Ethereum::from_str(
+            "68917b35bacf71dbadf37628b3b7f290f6d88877"
+        )
+        .unwrap(),
+        alpha_expiry: Timestamp::from(1_590_000_000),
+        beta_expiry: Timestamp::from(1_589_990_000),
+        secret_hash: SecretHash::from_str(
+            "68d627971643a6f97f27c58957826fcba853ec2077fd10ec6b93d8e61deb4cec"
+        )
+        .unwrap(),
+    });
+    assert_eq!(
+        accept.beta_ledger_refund_identity,
+        identity::Ethereum::from_str("d51ecee7414c4445534f74208538683702cbb3e4").unwrap()
+    );
+    assert_eq!(at.to_string(), "2020-05-10 12:00:00");
+}
@@ -3,8 +3,12 @@
 /// activity that involves migration scripts to migrate old data. These tests
 /// make sure we don't change the format accidentally!
 use crate::{
-    db::wrapper_types::{Erc20Amount, Ether, EthereumAddress, Satoshis},
-    swap_protocols::{rfc003::SecretHash, rfc003::SwapId, HashFunction},
+    db::{
+        columns::{IdentityKind, LedgerColumns, LedgerName},
+        wrapper_types::{Erc20Amount, Ether, EthereumAddress, Satoshis},
+        AssetKind,
+    },
+    swap_protocols::{ledger::Ethereum, rfc003::SecretHash, rfc003::SwapId, HashFunction, Side},
 };
 use std::{fmt, str::FromStr};
 
@@ -38,7 +42,9 @@ fn bitcoin_amount() {
 #[test]
 fn hash_function() {
     roundtrip_test::<HashFunction>("SHA-256");
-    assert_num_variants::<HashFunction>(1)
+    roundtrip_test::<HashFunction>("HASH160");
+    roundtrip_test::<HashFunction>("KECCAK-256");
+    assert_num_variants::<HashFunction>(3)
 }
 
 #[test]
@@ -58,6 +64,42 @@ fn secrethash() {
     roundtrip_test::<SecretHash>(
         "68917b35bacf71dbadf37628b3b7f290f6d88877d7b2269008d893ae7bd4f9ee",
     );
+    roundtrip_test::<SecretHash>("68917b35bacf71dbadf37628b3b7f290f6d88877");
+}
+
+#[test]
+fn side() {
+    roundtrip_test::<Side>("alpha");
+    roundtrip_test::<Side>("beta");
+}
+
+#[test]
+fn ledger_name() {
+    roundtrip_test::<LedgerName>("bitcoin");
+    roundtrip_test::<LedgerName>("ethereum");
+    assert_num_variants::<LedgerName>(2)
+}
+
+#[test]
+fn asset_kind() {
+    roundtrip_test::<AssetKind>("bitcoin");
+    roundtrip_test::<AssetKind>("ether");
+    roundtrip_test::<AssetKind>("erc20");
+    assert_num_variants::<AssetKind>(3)
+}
+
+#[test]
+fn identity_kind() {
+    roundtrip_test::<IdentityKind>("redeem");
+    roundtrip_test::<IdentityKind>("refund");
+    assert_num_variants::<IdentityKind>(2)
+}
+
+#[test]
+fn ethereum_network() {
+    let ledger = Ethereum::from_network("1337").unwrap();
+
+    assert_eq!(ledger.network(), "1337");
 }
 
 /// Given a string representation of a value T, this function will assert
@@ -0,0 +1,111 @@
+use crate::{
+    db::{
+        schema::{self, invoice_cancellations},
+        wrapper_types::custom_sql_types::{Text, U32},
+        Save,
+    },
+    diesel::{ExpressionMethods, QueryDsl},
+    swap_protocols::{rfc003::SecretHash, LocalSwapId},
+    timestamp::Timestamp,
+};
+use async_trait::async_trait;
+use diesel::RunQueryDsl;
+
+/// A hold invoice cnd added on behalf of Alice that has to be cancelled once
+/// the alpha HTLC expired unless it was settled before.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InvoiceCancellation {
+    pub swap_id: LocalSwapId,
+    pub secret_hash: SecretHash,
+    pub expiry: Timestamp,
+}
+
+/// Retrieve and remove the invoice cancellations still to be done.
+#[async_trait]
+pub trait InvoiceCancellationStore: Send + Sync + 'static {
+    async fn all_invoice_cancellations(&self) -> anyhow::Result<Vec<InvoiceCancellation>>;
+    async fn delete_invoice_cancellation(&self, swap_id: LocalSwapId) -> anyhow::Result<()>;
+}
+
+for_each_backend! {
+    #[async_trait]
+    impl Save<InvoiceCancellation> for Backend {
+        async fn save(&self, cancellation: InvoiceCancellation) -> anyhow::Result<()> {
+            let insertable = InsertableInvoiceCancellation::from(cancellation);
+
+            self.do_in_transaction(|connection| {
+                diesel::insert_into(schema::invoice_cancellations::dsl::invoice_cancellations)
+                    .values(&insertable)
+                    .execute(&*connection)
+            })
+            .await?;
+
+            Ok(())
+        }
+    }
+}
+
+for_each_backend! {
+    #[async_trait]
+    impl InvoiceCancellationStore for Backend {
+        async fn all_invoice_cancellations(&self) -> anyhow::Result<Vec<InvoiceCancellation>> {
+            use self::schema::invoice_cancellations::dsl::*;
+
+            let records: Vec<QueryableInvoiceCancellation> = self
+                .do_in_transaction(|connection| {
+                    invoice_cancellations.order(id.asc()).load(&*connection)
+                })
+                .await?;
+
+            Ok(records.into_iter().map(InvoiceCancellation::from).collect())
+        }
+
+        async fn delete_invoice_cancellation(&self, key: LocalSwapId) -> anyhow::Result<()> {
+            use self::schema::invoice_cancellations::dsl::*;
+
+            self.do_in_transaction(|connection| {
+                diesel::delete(invoice_cancellations.filter(local_swap_id.eq(Text(key))))
+                    .execute(&*connection)
+            })
+            .await?;
+
+            Ok(())
+        }
+    }
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[table_name = "invoice_cancellations"]
+struct InsertableInvoiceCancellation {
+    pub local_swap_id: Text<LocalSwapId>,
+    pub secret_hash: Text<SecretHash>,
+    pub expiry: U32,
+}
+
+impl From<InvoiceCancellation> for InsertableInvoiceCancellation {
+    fn from(cancellation: InvoiceCancellation) -> Self {
+        InsertableInvoiceCancellation {
+            local_swap_id: Text(cancellation.swap_id),
+            secret_hash: Text(cancellation.secret_hash),
+            expiry: U32(cancellation.expiry.into()),
+        }
+    }
+}
+
+#[derive(Queryable, Debug, Clone, PartialEq)]
+struct QueryableInvoiceCancellation {
+    pub id: i32,
+    pub local_swap_id: Text<LocalSwapId>,
+    pub secret_hash: Text<SecretHash>,
+    pub expiry: U32,
+}
+
+impl From<QueryableInvoiceCancellation> for InvoiceCancellation {
+    fn from(record: QueryableInvoiceCancellation) -> Self {
+        InvoiceCancellation {
+            swap_id: *record.local_swap_id,
+            secret_hash: *record.secret_hash,
+            expiry: record.expiry.into(),
+        }
+    }
+}
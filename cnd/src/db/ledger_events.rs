@@ -0,0 +1,222 @@
+use crate::{
+    btsieve::BlockInclusion,
+    db::{
+        schema::{self, rfc003_ledger_events},
+        wrapper_types::custom_sql_types::{Text, U32},
+        Save,
+    },
+    diesel::{ExpressionMethods, QueryDsl},
+    swap_protocols::{rfc003::SwapId, Side},
+};
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, Utc};
+use diesel::RunQueryDsl;
+use serde::Serialize;
+use std::fmt;
+
+/// An event observed on the ledger of one side of an rfc003 swap together with
+/// the transaction that caused it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LedgerEvent {
+    pub swap_id: SwapId,
+    pub side: Side,
+    pub event: String,
+    pub transaction_id: String,
+    /// `None` if the block of the transaction could not be determined.
+    pub block: Option<BlockInclusion>,
+    /// The event as it was yielded by the watcher, serialized to JSON.
+    pub data: serde_json::Value,
+    pub at: NaiveDateTime,
+}
+
+impl LedgerEvent {
+    pub fn new<E>(
+        swap_id: SwapId,
+        side: Side,
+        event: &E,
+        transaction_id: String,
+        block: Option<BlockInclusion>,
+    ) -> anyhow::Result<Self>
+    where
+        E: Serialize + fmt::Display,
+    {
+        Ok(LedgerEvent {
+            swap_id,
+            side,
+            event: event.to_string(),
+            transaction_id,
+            block,
+            data: serde_json::to_value(event)?,
+            at: Utc::now().naive_local(),
+        })
+    }
+}
+
+/// Retrieve the ledger events of rfc003 swaps.
+#[async_trait]
+pub trait LedgerEventStore: Send + Sync + 'static {
+    /// Returns the events of both sides of the swap in the order they were
+    /// observed.
+    async fn ledger_events(&self, swap_id: SwapId) -> anyhow::Result<Vec<LedgerEvent>>;
+}
+
+for_each_backend! {
+    #[async_trait]
+    impl Save<LedgerEvent> for Backend {
+        async fn save(&self, ledger_event: LedgerEvent) -> anyhow::Result<()> {
+            let insertable = InsertableLedgerEvent::from(ledger_event);
+
+            self.do_in_transaction(|connection| {
+                diesel::insert_into(schema::rfc003_ledger_events::dsl::rfc003_ledger_events)
+                    .values(&insertable)
+                    .execute(&*connection)
+            })
+            .await?;
+
+            Ok(())
+        }
+    }
+}
+
+for_each_backend! {
+    #[async_trait]
+    impl LedgerEventStore for Backend {
+        async fn ledger_events(&self, key: SwapId) -> anyhow::Result<Vec<LedgerEvent>> {
+            use self::schema::rfc003_ledger_events::dsl::*;
+
+            let records: Vec<QueryableLedgerEvent> = self
+                .do_in_transaction(|connection| {
+                    rfc003_ledger_events
+                        .filter(swap_id.eq(Text(key)))
+                        .order(id.asc())
+                        .load(&*connection)
+                })
+                .await?;
+
+            Ok(records.into_iter().map(LedgerEvent::from).collect())
+        }
+    }
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[table_name = "rfc003_ledger_events"]
+struct InsertableLedgerEvent {
+    pub swap_id: Text<SwapId>,
+    pub side: Text<Side>,
+    pub event: String,
+    pub transaction_id: String,
+    pub block_hash: Option<String>,
+    pub block_height: Option<U32>,
+    pub block_time: Option<NaiveDateTime>,
+    pub data: Text<serde_json::Value>,
+    pub at: NaiveDateTime,
+}
+
+impl From<LedgerEvent> for InsertableLedgerEvent {
+    fn from(ledger_event: LedgerEvent) -> Self {
+        let LedgerEvent {
+            swap_id,
+            side,
+            event,
+            transaction_id,
+            block,
+            data,
+            at,
+        } = ledger_event;
+
+        InsertableLedgerEvent {
+            swap_id: Text(swap_id),
+            side: Text(side),
+            event,
+            transaction_id,
+            block_hash: block.as_ref().map(|block| block.block_hash.clone()),
+            block_height: block.as_ref().map(|block| U32(block.block_height)),
+            block_time: block.map(|block| block.block_time),
+            data: Text(data),
+            at,
+        }
+    }
+}
+
+#[derive(Queryable, Debug, Clone, PartialEq)]
+struct QueryableLedgerEvent {
+    pub id: i32,
+    pub swap_id: Text<SwapId>,
+    pub side: Text<Side>,
+    pub event: String,
+    pub transaction_id: String,
+    pub block_hash: Option<String>,
+    pub block_height: Option<U32>,
+    pub block_time: Option<NaiveDateTime>,
+    pub data: Text<serde_json::Value>,
+    pub at: NaiveDateTime,
+}
+
+impl From<QueryableLedgerEvent> for LedgerEvent {
+    fn from(record: QueryableLedgerEvent) -> Self {
+        let block = match (record.block_hash, record.block_height, record.block_time) {
+            (Some(block_hash), Some(block_height), Some(block_time)) => Some(BlockInclusion {
+                block_hash,
+                block_height: block_height.into(),
+                block_time,
+            }),
+            _ => None,
+        };
+
+        LedgerEvent {
+            swap_id: *record.swap_id,
+            side: *record.side,
+            event: record.event,
+            transaction_id: record.transaction_id,
+            block,
+            data: record.data.0,
+            at: record.at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::Sqlite;
+    use std::{path::Path, str::FromStr};
+
+    fn ledger_event(swap_id: SwapId, side: Side, event: &str) -> LedgerEvent {
+        LedgerEvent {
+            swap_id,
+            side,
+            event: event.to_owned(),
+            transaction_id: "8c6f8f7cd13ad87be14ba93e7b2cbcf0b5b6a4c2ce8a6a5a8e0bfde1c4cd2d3a"
+                .to_owned(),
+            block: Some(BlockInclusion {
+                block_hash: "0f9188f13cb7b2c71f2a335e3a4fc328bf5beb436012afca590b1a11466e2206"
+                    .to_owned(),
+                block_height: 101,
+                block_time: NaiveDateTime::from_timestamp(1_589_000_000, 0),
+            }),
+            data: serde_json::json!({ event: { "transaction": "0x00" } }),
+            at: NaiveDateTime::from_timestamp(1_589_000_060, 0),
+        }
+    }
+
+    #[tokio::test]
+    async fn ledger_events_roundtrip_in_order() {
+        let db = Sqlite::new(&Path::new(":memory:")).unwrap();
+        let swap_id = SwapId::from_str("ad2652ca-ecf2-4cc6-b35c-b4351ac28a34").unwrap();
+        let other_swap_id = SwapId::from_str("7f9a3f2b-4b3a-4e2f-8d3c-1c2b3a4d5e6f").unwrap();
+
+        let deployed = ledger_event(swap_id, Side::Alpha, "Deployed");
+        let funded = LedgerEvent {
+            block: None,
+            ..ledger_event(swap_id, Side::Alpha, "Funded")
+        };
+        let other = ledger_event(other_swap_id, Side::Beta, "Deployed");
+        db.save(deployed.clone()).await.unwrap();
+        db.save(other).await.unwrap();
+        db.save(funded.clone()).await.unwrap();
+
+        let loaded = db.ledger_events(swap_id).await.unwrap();
+
+        assert_eq!(loaded, vec![deployed, funded]);
+    }
+}
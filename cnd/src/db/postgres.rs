@@ -0,0 +1,51 @@
+use diesel::{pg::PgConnection, Connection};
+use reqwest::Url;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+
+embed_migrations!("./migrations_postgres");
+
+/// Storage in a PostgreSQL database.
+///
+/// Unlike with `Sqlite`, the data does not live in the data directory of cnd,
+/// which allows to run a hot standby and to inspect it with the usual
+/// PostgreSQL tooling.
+#[derive(Clone, derivative::Derivative)]
+#[derivative(Debug)]
+pub struct Postgres {
+    #[derivative(Debug = "ignore")]
+    connection: Arc<Mutex<PgConnection>>,
+}
+
+impl Postgres {
+    /// Return a handle that can be used to access the database.
+    ///
+    /// When this returns, a successful connection to the database at 'url'
+    /// has been made and the database migrations have been run.
+    pub fn new(url: &Url) -> anyhow::Result<Self> {
+        let connection = PgConnection::establish(url.as_str())?;
+        embedded_migrations::run(&connection)?;
+
+        tracing::info!(
+            "PostgreSQL database: {}",
+            url.host_str().unwrap_or_default()
+        );
+
+        Ok(Postgres {
+            connection: Arc::new(Mutex::new(connection)),
+        })
+    }
+
+    pub(super) async fn do_in_transaction<F, T, E>(&self, f: F) -> Result<T, E>
+    where
+        F: Fn(&PgConnection) -> Result<T, E>,
+        E: From<diesel::result::Error>,
+    {
+        let guard = self.connection.lock().await;
+        let connection = &*guard;
+
+        let result = connection.transaction(|| f(&connection))?;
+
+        Ok(result)
+    }
+}
@@ -1,24 +1,19 @@
 use crate::{
-    asset,
     db::{
+        columns::{AssetColumns, IdentityColumn, IdentityKind, LedgerColumns, LedgerName},
         schema::{self, *},
-        wrapper_types::{
-            custom_sql_types::{Text, U32},
-            BitcoinNetwork, Erc20Amount, Ether, EthereumAddress, Satoshis,
-        },
-        CreatedSwap, Sqlite, Swap,
+        wrapper_types::custom_sql_types::{Text, U32},
+        AssetKind, CreatedSwap, Swap,
     },
-    identity,
     swap_protocols::{
         halight, han,
-        ledger::{self, Ethereum},
         rfc003::{Accept, Decline, Request, SecretHash, SwapId},
-        HashFunction, Role,
+        HashFunction, Role, Side,
     },
+    timestamp::Timestamp,
 };
 use async_trait::async_trait;
 use diesel::RunQueryDsl;
-use impl_template::impl_template;
 use libp2p::{self, PeerId};
 
 /// Save swap to database.
@@ -27,19 +22,21 @@ pub trait Save<T>: Send + Sync + 'static {
     async fn save(&self, swap: T) -> anyhow::Result<()>;
 }
 
-#[async_trait]
-impl Save<Swap> for Sqlite {
-    async fn save(&self, swap: Swap) -> anyhow::Result<()> {
-        let insertable = InsertableSwap::from(swap);
+for_each_backend! {
+    #[async_trait]
+    impl Save<Swap> for Backend {
+        async fn save(&self, swap: Swap) -> anyhow::Result<()> {
+            let insertable = InsertableSwap::from(swap);
 
-        self.do_in_transaction(|connection| {
-            diesel::insert_into(schema::rfc003_swaps::dsl::rfc003_swaps)
-                .values(&insertable)
-                .execute(&*connection)
-        })
-        .await?;
+            self.do_in_transaction(|connection| {
+                diesel::insert_into(schema::rfc003_swaps::dsl::rfc003_swaps)
+                    .values(&insertable)
+                    .execute(&*connection)
+            })
+            .await?;
 
-        Ok(())
+            Ok(())
+        }
     }
 }
 
@@ -62,416 +59,211 @@ impl From<Swap> for InsertableSwap {
 }
 
 #[derive(Insertable, Debug, Clone)]
-#[table_name = "rfc003_bitcoin_ethereum_bitcoin_ether_request_messages"]
-struct InsertableBitcoinEthereumBitcoinEtherRequestMessage {
+#[table_name = "rfc003_requests"]
+struct InsertableRequest {
     swap_id: Text<SwapId>,
-    bitcoin_network: Text<BitcoinNetwork>,
-    ethereum_chain_id: U32,
-    bitcoin_amount: Text<Satoshis>,
-    ether_amount: Text<Ether>,
     hash_function: Text<HashFunction>,
-    bitcoin_refund_identity: Text<::bitcoin::PublicKey>,
-    ethereum_redeem_identity: Text<EthereumAddress>,
-    bitcoin_expiry: U32,
-    ethereum_expiry: U32,
     secret_hash: Text<SecretHash>,
 }
 
-#[impl_template]
-#[async_trait]
-impl
-    Save<
-        Request<
-            ((
-                ledger::bitcoin::Mainnet,
-                ledger::bitcoin::Testnet,
-                ledger::bitcoin::Regtest,
-            )),
-            Ethereum,
-            asset::Bitcoin,
-            asset::Ether,
-            identity::Bitcoin,
-            identity::Ethereum,
-        >,
-    > for Sqlite
-{
-    async fn save(
-        &self,
-        message: Request<
-            __TYPE0__,
-            Ethereum,
-            asset::Bitcoin,
-            asset::Ether,
-            identity::Bitcoin,
-            identity::Ethereum,
-        >,
-    ) -> anyhow::Result<()> {
-        let Request {
-            swap_id,
-            alpha_asset,
-            beta_ledger,
-            beta_asset,
-            hash_function,
-            alpha_ledger_refund_identity,
-            beta_ledger_redeem_identity,
-            alpha_expiry,
-            beta_expiry,
-            secret_hash,
-            ..
-        } = message;
-
-        let insertable = InsertableBitcoinEthereumBitcoinEtherRequestMessage {
-            swap_id: Text(swap_id),
-            bitcoin_network: Text(BitcoinNetwork::from(__TYPE0__)),
-            ethereum_chain_id: U32(beta_ledger.chain_id.into()),
-            bitcoin_amount: Text(alpha_asset.into()),
-            ether_amount: Text(beta_asset.into()),
-            hash_function: Text(hash_function),
-            bitcoin_refund_identity: Text(alpha_ledger_refund_identity.into()),
-            ethereum_redeem_identity: Text(beta_ledger_redeem_identity.into()),
-            bitcoin_expiry: U32(alpha_expiry.into()),
-            ethereum_expiry: U32(beta_expiry.into()),
-            secret_hash: Text(secret_hash),
-        };
-
-        self.do_in_transaction(|connection| {
-            diesel::insert_into(rfc003_bitcoin_ethereum_bitcoin_ether_request_messages::table)
-                .values(&insertable)
-                .execute(connection)
-        })
-        .await?;
-
-        Ok(())
-    }
-}
-
 #[derive(Insertable, Debug, Clone)]
-#[table_name = "rfc003_bitcoin_ethereum_bitcoin_erc20_request_messages"]
-struct InsertableBitcoinEthereumBitcoinErc20RequestMessage {
+#[table_name = "rfc003_ledgers"]
+struct InsertableLedger {
     swap_id: Text<SwapId>,
-    bitcoin_network: Text<BitcoinNetwork>,
-    ethereum_chain_id: U32,
-    bitcoin_amount: Text<Satoshis>,
-    erc20_amount: Text<Erc20Amount>,
-    erc20_token_contract: Text<EthereumAddress>,
-    hash_function: Text<HashFunction>,
-    bitcoin_refund_identity: Text<::bitcoin::PublicKey>,
-    ethereum_redeem_identity: Text<EthereumAddress>,
-    bitcoin_expiry: U32,
-    ethereum_expiry: U32,
-    secret_hash: Text<SecretHash>,
+    side: Text<Side>,
+    ledger: Text<LedgerName>,
+    network: String,
+    expiry: U32,
 }
 
-#[impl_template]
-#[async_trait]
-impl
-    Save<
-        Request<
-            ((
-                ledger::bitcoin::Mainnet,
-                ledger::bitcoin::Testnet,
-                ledger::bitcoin::Regtest,
-            )),
-            Ethereum,
-            asset::Bitcoin,
-            asset::Erc20,
-            identity::Bitcoin,
-            identity::Ethereum,
-        >,
-    > for Sqlite
-{
-    async fn save(
-        &self,
-        message: Request<
-            __TYPE0__,
-            Ethereum,
-            asset::Bitcoin,
-            asset::Erc20,
-            identity::Bitcoin,
-            identity::Ethereum,
-        >,
-    ) -> anyhow::Result<()> {
-        let Request {
-            swap_id,
-            alpha_asset,
-            beta_ledger,
-            beta_asset,
-            hash_function,
-            alpha_ledger_refund_identity,
-            beta_ledger_redeem_identity,
-            alpha_expiry,
-            beta_expiry,
-            secret_hash,
-            ..
-        } = message;
-
-        let insertable = InsertableBitcoinEthereumBitcoinErc20RequestMessage {
+impl InsertableLedger {
+    fn new<L>(swap_id: SwapId, side: Side, ledger: &L, expiry: Timestamp) -> Self
+    where
+        L: LedgerColumns,
+    {
+        InsertableLedger {
             swap_id: Text(swap_id),
-            bitcoin_network: Text(BitcoinNetwork::from(__TYPE0__)),
-            ethereum_chain_id: U32(beta_ledger.chain_id.into()),
-            bitcoin_amount: Text(alpha_asset.into()),
-            erc20_amount: Text(beta_asset.quantity.into()),
-            erc20_token_contract: Text(beta_asset.token_contract.into()),
-            hash_function: Text(hash_function),
-            bitcoin_refund_identity: Text(alpha_ledger_refund_identity.into()),
-            ethereum_redeem_identity: Text(beta_ledger_redeem_identity.into()),
-            bitcoin_expiry: U32(alpha_expiry.into()),
-            ethereum_expiry: U32(beta_expiry.into()),
-            secret_hash: Text(secret_hash),
-        };
-
-        self.do_in_transaction(|connection| {
-            diesel::insert_into(rfc003_bitcoin_ethereum_bitcoin_erc20_request_messages::table)
-                .values(&insertable)
-                .execute(connection)
-        })
-        .await?;
-
-        Ok(())
+            side: Text(side),
+            ledger: Text(L::NAME),
+            network: ledger.network(),
+            expiry: U32(expiry.into()),
+        }
     }
 }
 
 #[derive(Insertable, Debug, Clone)]
-#[table_name = "rfc003_ethereum_bitcoin_ether_bitcoin_request_messages"]
-struct InsertableEthereumBitcoinEtherBitcoinRequestMessage {
+#[table_name = "rfc003_assets"]
+struct InsertableAsset {
     swap_id: Text<SwapId>,
-    ethereum_chain_id: U32,
-    bitcoin_network: Text<BitcoinNetwork>,
-    ether_amount: Text<Ether>,
-    bitcoin_amount: Text<Satoshis>,
-    hash_function: Text<HashFunction>,
-    ethereum_refund_identity: Text<EthereumAddress>,
-    bitcoin_redeem_identity: Text<::bitcoin::PublicKey>,
-    ethereum_expiry: U32,
-    bitcoin_expiry: U32,
-    secret_hash: Text<SecretHash>,
+    side: Text<Side>,
+    asset: Text<AssetKind>,
+    quantity: String,
+    token_contract: Option<String>,
 }
 
-#[impl_template]
-#[async_trait]
-impl
-    Save<
-        Request<
-            Ethereum,
-            ((
-                ledger::bitcoin::Mainnet,
-                ledger::bitcoin::Testnet,
-                ledger::bitcoin::Regtest,
-            )),
-            asset::Ether,
-            asset::Bitcoin,
-            identity::Ethereum,
-            identity::Bitcoin,
-        >,
-    > for Sqlite
-{
-    async fn save(
-        &self,
-        message: Request<
-            Ethereum,
-            __TYPE0__,
-            asset::Ether,
-            asset::Bitcoin,
-            identity::Ethereum,
-            identity::Bitcoin,
-        >,
-    ) -> anyhow::Result<()> {
-        let Request {
-            swap_id,
-            alpha_ledger,
-            alpha_asset,
-            beta_asset,
-            hash_function,
-            alpha_ledger_refund_identity,
-            beta_ledger_redeem_identity,
-            alpha_expiry,
-            beta_expiry,
-            secret_hash,
-            ..
-        } = message;
-
-        let insertable = InsertableEthereumBitcoinEtherBitcoinRequestMessage {
+impl InsertableAsset {
+    fn new<A>(swap_id: SwapId, side: Side, asset: &A) -> Self
+    where
+        A: AssetColumns,
+    {
+        InsertableAsset {
             swap_id: Text(swap_id),
-            bitcoin_network: Text(BitcoinNetwork::from(__TYPE0__)),
-            ethereum_chain_id: U32(alpha_ledger.chain_id.into()),
-            ether_amount: Text(alpha_asset.into()),
-            bitcoin_amount: Text(beta_asset.into()),
-            hash_function: Text(hash_function),
-            ethereum_refund_identity: Text(alpha_ledger_refund_identity.into()),
-            bitcoin_redeem_identity: Text(beta_ledger_redeem_identity.into()),
-            ethereum_expiry: U32(alpha_expiry.into()),
-            bitcoin_expiry: U32(beta_expiry.into()),
-            secret_hash: Text(secret_hash),
-        };
-
-        self.do_in_transaction(|connection| {
-            diesel::insert_into(rfc003_ethereum_bitcoin_ether_bitcoin_request_messages::table)
-                .values(&insertable)
-                .execute(connection)
-        })
-        .await?;
-
-        Ok(())
+            side: Text(side),
+            asset: Text(A::KIND),
+            quantity: asset.quantity(),
+            token_contract: asset.token_contract(),
+        }
     }
 }
 
 #[derive(Insertable, Debug, Clone)]
-#[table_name = "rfc003_ethereum_bitcoin_erc20_bitcoin_request_messages"]
-struct InsertableEthereumBitcoinErc20BitcoinRequestMessage {
+#[table_name = "rfc003_identities"]
+struct InsertableIdentity {
     swap_id: Text<SwapId>,
-    ethereum_chain_id: U32,
-    bitcoin_network: Text<BitcoinNetwork>,
-    erc20_amount: Text<Erc20Amount>,
-    erc20_token_contract: Text<EthereumAddress>,
-    bitcoin_amount: Text<Satoshis>,
-    hash_function: Text<HashFunction>,
-    ethereum_refund_identity: Text<EthereumAddress>,
-    bitcoin_redeem_identity: Text<::bitcoin::PublicKey>,
-    ethereum_expiry: U32,
-    bitcoin_expiry: U32,
-    secret_hash: Text<SecretHash>,
+    side: Text<Side>,
+    kind: Text<IdentityKind>,
+    identity: String,
 }
 
-#[impl_template]
-#[async_trait]
-impl
-    Save<
-        Request<
-            Ethereum,
-            ((
-                ledger::bitcoin::Mainnet,
-                ledger::bitcoin::Testnet,
-                ledger::bitcoin::Regtest,
-            )),
-            asset::Erc20,
-            asset::Bitcoin,
-            identity::Ethereum,
-            identity::Bitcoin,
-        >,
-    > for Sqlite
-{
-    async fn save(
-        &self,
-        message: Request<
-            Ethereum,
-            __TYPE0__,
-            asset::Erc20,
-            asset::Bitcoin,
-            identity::Ethereum,
-            identity::Bitcoin,
-        >,
-    ) -> anyhow::Result<()> {
-        let Request {
-            swap_id,
-            alpha_ledger,
-            alpha_asset,
-            beta_asset,
-            hash_function,
-            alpha_ledger_refund_identity,
-            beta_ledger_redeem_identity,
-            alpha_expiry,
-            beta_expiry,
-            secret_hash,
-            ..
-        } = message;
-
-        let insertable = InsertableEthereumBitcoinErc20BitcoinRequestMessage {
+impl InsertableIdentity {
+    fn new<I>(swap_id: SwapId, side: Side, kind: IdentityKind, identity: &I) -> Self
+    where
+        I: IdentityColumn,
+    {
+        InsertableIdentity {
             swap_id: Text(swap_id),
-            ethereum_chain_id: U32(alpha_ledger.chain_id.into()),
-            bitcoin_network: Text(BitcoinNetwork::from(__TYPE0__)),
-            erc20_amount: Text(alpha_asset.quantity.into()),
-            erc20_token_contract: Text(alpha_asset.token_contract.into()),
-            bitcoin_amount: Text(beta_asset.into()),
-            hash_function: Text(hash_function),
-            ethereum_refund_identity: Text(alpha_ledger_refund_identity.into()),
-            bitcoin_redeem_identity: Text(beta_ledger_redeem_identity.into()),
-            ethereum_expiry: U32(alpha_expiry.into()),
-            bitcoin_expiry: U32(beta_expiry.into()),
-            secret_hash: Text(secret_hash),
-        };
-
-        self.do_in_transaction(|connection| {
-            diesel::insert_into(rfc003_ethereum_bitcoin_erc20_bitcoin_request_messages::table)
-                .values(&insertable)
-                .execute(connection)
-        })
-        .await?;
-
-        Ok(())
+            side: Text(side),
+            kind: Text(kind),
+            identity: identity.to_column(),
+        }
     }
 }
 
-#[derive(Insertable, Debug, Copy, Clone)]
-#[table_name = "rfc003_ethereum_bitcoin_accept_messages"]
-struct InsertableEthereumBitcoinAcceptMessage {
-    swap_id: Text<SwapId>,
-    ethereum_redeem_identity: Text<EthereumAddress>,
-    bitcoin_refund_identity: Text<bitcoin::PublicKey>,
-}
-
-#[async_trait]
-impl Save<Accept<identity::Ethereum, identity::Bitcoin>> for Sqlite {
-    async fn save(
-        &self,
-        message: Accept<identity::Ethereum, identity::Bitcoin>,
-    ) -> anyhow::Result<()> {
-        let Accept {
-            swap_id,
-            alpha_ledger_redeem_identity,
-            beta_ledger_refund_identity,
-        } = message;
-
-        let insertable = InsertableEthereumBitcoinAcceptMessage {
-            swap_id: Text(swap_id),
-            ethereum_redeem_identity: Text(alpha_ledger_redeem_identity.into()),
-            bitcoin_refund_identity: Text(beta_ledger_refund_identity.into()),
-        };
-
-        self.do_in_transaction(|connection| {
-            diesel::insert_into(rfc003_ethereum_bitcoin_accept_messages::table)
-                .values(&insertable)
-                .execute(&*connection)
-        })
-        .await?;
-
-        Ok(())
+for_each_backend! {
+    #[async_trait]
+    impl<AL, BL, AA, BA, AI, BI> Save<Request<AL, BL, AA, BA, AI, BI>> for Backend
+    where
+        AL: LedgerColumns + Send + 'static,
+        BL: LedgerColumns + Send + 'static,
+        AA: AssetColumns + Send + 'static,
+        BA: AssetColumns + Send + 'static,
+        AI: IdentityColumn + Send + 'static,
+        BI: IdentityColumn + Send + 'static,
+    {
+        async fn save(&self, message: Request<AL, BL, AA, BA, AI, BI>) -> anyhow::Result<()> {
+            let Request {
+                swap_id,
+                alpha_ledger,
+                beta_ledger,
+                alpha_asset,
+                beta_asset,
+                hash_function,
+                alpha_ledger_refund_identity,
+                beta_ledger_redeem_identity,
+                alpha_expiry,
+                beta_expiry,
+                secret_hash,
+            } = message;
+
+            let request = InsertableRequest {
+                swap_id: Text(swap_id),
+                hash_function: Text(hash_function),
+                secret_hash: Text(secret_hash),
+            };
+            let ledgers = vec![
+                InsertableLedger::new(swap_id, Side::Alpha, &alpha_ledger, alpha_expiry),
+                InsertableLedger::new(swap_id, Side::Beta, &beta_ledger, beta_expiry),
+            ];
+            let assets = vec![
+                InsertableAsset::new(swap_id, Side::Alpha, &alpha_asset),
+                InsertableAsset::new(swap_id, Side::Beta, &beta_asset),
+            ];
+            let identities = vec![
+                InsertableIdentity::new(
+                    swap_id,
+                    Side::Alpha,
+                    IdentityKind::Refund,
+                    &alpha_ledger_refund_identity,
+                ),
+                InsertableIdentity::new(
+                    swap_id,
+                    Side::Beta,
+                    IdentityKind::Redeem,
+                    &beta_ledger_redeem_identity,
+                ),
+            ];
+
+            self.do_in_transaction(|connection| {
+                diesel::insert_into(rfc003_requests::table)
+                    .values(&request)
+                    .execute(connection)?;
+                diesel::insert_into(rfc003_ledgers::table)
+                    .values(&ledgers)
+                    .execute(connection)?;
+                diesel::insert_into(rfc003_assets::table)
+                    .values(&assets)
+                    .execute(connection)?;
+                diesel::insert_into(rfc003_identities::table)
+                    .values(&identities)
+                    .execute(connection)
+            })
+            .await?;
+
+            Ok(())
+        }
     }
 }
 
 #[derive(Insertable, Debug, Copy, Clone)]
-#[table_name = "rfc003_bitcoin_ethereum_accept_messages"]
-struct InsertableBitcoinEthereumAcceptMessage {
+#[table_name = "rfc003_accepts"]
+struct InsertableAccept {
     swap_id: Text<SwapId>,
-    bitcoin_redeem_identity: Text<bitcoin::PublicKey>,
-    ethereum_refund_identity: Text<EthereumAddress>,
 }
 
-#[async_trait]
-impl Save<Accept<identity::Bitcoin, identity::Ethereum>> for Sqlite {
-    async fn save(
-        &self,
-        message: Accept<identity::Bitcoin, identity::Ethereum>,
-    ) -> anyhow::Result<()> {
-        let Accept {
-            swap_id,
-            alpha_ledger_redeem_identity,
-            beta_ledger_refund_identity,
-        } = message;
-
-        let insertable = InsertableBitcoinEthereumAcceptMessage {
-            swap_id: Text(swap_id),
-            bitcoin_redeem_identity: Text(alpha_ledger_redeem_identity.into()),
-            ethereum_refund_identity: Text(beta_ledger_refund_identity.into()),
-        };
-
-        self.do_in_transaction(|connection| {
-            diesel::insert_into(rfc003_bitcoin_ethereum_accept_messages::table)
-                .values(&insertable)
-                .execute(&*connection)
-        })
-        .await?;
-
-        Ok(())
+for_each_backend! {
+    #[async_trait]
+    impl<AI, BI> Save<Accept<AI, BI>> for Backend
+    where
+        AI: IdentityColumn + Send + 'static,
+        BI: IdentityColumn + Send + 'static,
+    {
+        async fn save(&self, message: Accept<AI, BI>) -> anyhow::Result<()> {
+            let Accept {
+                swap_id,
+                alpha_ledger_redeem_identity,
+                beta_ledger_refund_identity,
+            } = message;
+
+            let accept = InsertableAccept {
+                swap_id: Text(swap_id),
+            };
+            let identities = vec![
+                InsertableIdentity::new(
+                    swap_id,
+                    Side::Alpha,
+                    IdentityKind::Redeem,
+                    &alpha_ledger_redeem_identity,
+                ),
+                InsertableIdentity::new(
+                    swap_id,
+                    Side::Beta,
+                    IdentityKind::Refund,
+                    &beta_ledger_refund_identity,
+                ),
+            ];
+
+            self.do_in_transaction(|connection| {
+                diesel::insert_into(rfc003_accepts::table)
+                    .values(&accept)
+                    .execute(connection)?;
+                diesel::insert_into(rfc003_identities::table)
+                    .values(&identities)
+                    .execute(connection)
+            })
+            .await?;
+
+            Ok(())
+        }
     }
 }
 
@@ -482,37 +274,41 @@ struct InsertableDeclineMessage {
     reason: Option<String>,
 }
 
-#[async_trait]
-impl Save<Decline> for Sqlite {
-    async fn save(&self, message: Decline) -> anyhow::Result<()> {
-        let Decline {
-            swap_id,
-            reason: _reason, /* we don't map reason to a DB type because will be gone soon
-                              * (hopefully) */
-        } = message;
-
-        let insertable = InsertableDeclineMessage {
-            swap_id: Text(swap_id),
-            reason: None,
-        };
-
-        self.do_in_transaction(|connection| {
-            diesel::insert_into(rfc003_decline_messages::table)
-                .values(&insertable)
-                .execute(&*connection)
-        })
-        .await?;
-
-        Ok(())
+for_each_backend! {
+    #[async_trait]
+    impl Save<Decline> for Backend {
+        async fn save(&self, message: Decline) -> anyhow::Result<()> {
+            let Decline {
+                swap_id,
+                reason: _reason, /* we don't map reason to a DB type because will be gone soon
+                                  * (hopefully) */
+            } = message;
+
+            let insertable = InsertableDeclineMessage {
+                swap_id: Text(swap_id),
+                reason: None,
+            };
+
+            self.do_in_transaction(|connection| {
+                diesel::insert_into(rfc003_decline_messages::table)
+                    .values(&insertable)
+                    .execute(&*connection)
+            })
+            .await?;
+
+            Ok(())
+        }
     }
 }
 
-#[async_trait]
-impl Save<CreatedSwap<han::CreatedSwap, halight::CreatedSwap>> for Sqlite {
-    async fn save(
-        &self,
-        _: CreatedSwap<han::CreatedSwap, halight::CreatedSwap>,
-    ) -> anyhow::Result<()> {
-        unimplemented!()
+for_each_backend! {
+    #[async_trait]
+    impl Save<CreatedSwap<han::CreatedSwap, halight::CreatedSwap>> for Backend {
+        async fn save(
+            &self,
+            _: CreatedSwap<han::CreatedSwap, halight::CreatedSwap>,
+        ) -> anyhow::Result<()> {
+            anyhow::bail!("saving han-halight swaps is not yet supported")
+        }
     }
 }
@@ -1,106 +1,101 @@
 table! {
-   rfc003_bitcoin_ethereum_bitcoin_ether_request_messages {
+   rfc003_requests {
        id -> Integer,
        swap_id -> Text,
-       bitcoin_network -> Text,
-       ethereum_chain_id -> BigInt,
-       bitcoin_amount -> Text,
-       ether_amount -> Text,
        hash_function -> Text,
-       bitcoin_refund_identity -> Text,
-       ethereum_redeem_identity -> Text,
-       bitcoin_expiry -> BigInt,
-       ethereum_expiry -> BigInt,
        secret_hash -> Text,
    }
 }
 
 table! {
-   rfc003_ethereum_bitcoin_ether_bitcoin_request_messages {
+   rfc003_ledgers {
        id -> Integer,
        swap_id -> Text,
-       ethereum_chain_id -> BigInt,
-       bitcoin_network -> Text,
-       ether_amount -> Text,
-       bitcoin_amount -> Text,
-       hash_function -> Text,
-       ethereum_refund_identity -> Text,
-       bitcoin_redeem_identity -> Text,
-       ethereum_expiry -> BigInt,
-       bitcoin_expiry -> BigInt,
-       secret_hash -> Text,
+       side -> Text,
+       ledger -> Text,
+       network -> Text,
+       expiry -> BigInt,
    }
 }
 
 table! {
-   rfc003_bitcoin_ethereum_bitcoin_erc20_request_messages {
+   rfc003_assets {
        id -> Integer,
        swap_id -> Text,
-       bitcoin_network -> Text,
-       ethereum_chain_id -> BigInt,
-       bitcoin_amount -> Text,
-       erc20_amount -> Text,
-       erc20_token_contract -> Text,
-       hash_function -> Text,
-       bitcoin_refund_identity -> Text,
-       ethereum_redeem_identity -> Text,
-       bitcoin_expiry -> BigInt,
-       ethereum_expiry -> BigInt,
-       secret_hash -> Text,
+       side -> Text,
+       asset -> Text,
+       quantity -> Text,
+       token_contract -> Nullable<Text>,
    }
 }
 
 table! {
-   rfc003_ethereum_bitcoin_erc20_bitcoin_request_messages {
+   rfc003_identities {
        id -> Integer,
        swap_id -> Text,
-       ethereum_chain_id -> BigInt,
-       bitcoin_network -> Text,
-       erc20_amount -> Text,
-       erc20_token_contract -> Text,
-       bitcoin_amount -> Text,
-       hash_function -> Text,
-       ethereum_refund_identity -> Text,
-       bitcoin_redeem_identity -> Text,
-       ethereum_expiry -> BigInt,
-       bitcoin_expiry -> BigInt,
-       secret_hash -> Text,
+       side -> Text,
+       kind -> Text,
+       identity -> Text,
    }
 }
 
 table! {
-   rfc003_ethereum_bitcoin_accept_messages {
+   rfc003_accepts {
        id -> Integer,
        swap_id -> Text,
-       ethereum_redeem_identity -> Text,
-       bitcoin_refund_identity -> Text,
        at -> Timestamp,
    }
 }
 
 table! {
-   rfc003_bitcoin_ethereum_accept_messages {
+   rfc003_decline_messages {
        id -> Integer,
        swap_id -> Text,
-       bitcoin_redeem_identity -> Text,
-       ethereum_refund_identity -> Text,
-       at -> Timestamp,
+       reason -> Nullable<Text>,
    }
 }
 
 table! {
-   rfc003_decline_messages {
+   rfc003_swaps {
        id -> Integer,
        swap_id -> Text,
-       reason -> Nullable<Text>,
+       role -> Text,
+       counterparty -> Text,
    }
 }
 
 table! {
-   rfc003_swaps {
+   swap_errors {
        id -> Integer,
        swap_id -> Text,
-       role -> Text,
-       counterparty -> Text,
+       side -> Text,
+       phase -> Text,
+       retryable -> Bool,
+       message -> Text,
+       at -> Timestamp,
+   }
+}
+
+table! {
+   rfc003_ledger_events {
+       id -> Integer,
+       swap_id -> Text,
+       side -> Text,
+       event -> Text,
+       transaction_id -> Text,
+       block_hash -> Nullable<Text>,
+       block_height -> Nullable<BigInt>,
+       block_time -> Nullable<Timestamp>,
+       data -> Text,
+       at -> Timestamp,
+   }
+}
+
+table! {
+   invoice_cancellations {
+       id -> Integer,
+       local_swap_id -> Text,
+       secret_hash -> Text,
+       expiry -> BigInt,
    }
 }
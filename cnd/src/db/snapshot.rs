@@ -0,0 +1,144 @@
+use crate::db::{db_path_from_dir, embedded_migrations, Sqlite};
+use anyhow::Context;
+use diesel::{sqlite::SqliteConnection, Connection};
+use diesel_migrations::MigrationConnection;
+use rusqlite::{DatabaseName, OpenFlags};
+use std::{fs, path::Path};
+
+#[derive(Debug, thiserror::Error)]
+#[error("snapshot contains migrations unknown to this version of cnd: {}", versions.join(", "))]
+pub struct UnknownMigrations {
+    versions: Vec<String>,
+}
+
+impl Sqlite {
+    /// Write a consistent snapshot of the database in 'dir' to 'file'.
+    ///
+    /// The snapshot is taken with SQLite's online backup API on a read-only
+    /// connection of its own which does not run migrations, cnd does not have
+    /// to stop reading and writing swaps.
+    pub fn snapshot_in_dir(dir: &Path, file: &Path) -> anyhow::Result<()> {
+        let path = db_path_from_dir(dir);
+        if !path.exists() {
+            anyhow::bail!("database {} does not exist", path.display())
+        }
+
+        let source =
+            rusqlite::Connection::open_with_flags(&path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        source
+            .backup(DatabaseName::Main, file, None)
+            .with_context(|| format!("failed to write snapshot to {}", file.display()))?;
+
+        tracing::info!("Wrote database snapshot to {}", file.display());
+
+        Ok(())
+    }
+
+    /// Check that this version of cnd knows all migrations that were applied
+    /// to the database in 'file'.
+    ///
+    /// Migrations missing in the snapshot are run once cnd opens it.
+    pub fn validate_snapshot(file: &Path) -> anyhow::Result<()> {
+        if !file.exists() {
+            anyhow::bail!("snapshot {} does not exist", file.display())
+        }
+
+        let snapshot = SqliteConnection::establish(&format!("file:{}?mode=ro", file.display()))?;
+        let applied = snapshot
+            .previously_run_migration_versions()
+            .with_context(|| format!("{} is not a database of cnd", file.display()))?;
+
+        let known = {
+            let connection = SqliteConnection::establish(":memory:")?;
+            embedded_migrations::run(&connection)?;
+
+            connection.previously_run_migration_versions()?
+        };
+
+        let mut unknown = applied.difference(&known).cloned().collect::<Vec<_>>();
+        if !unknown.is_empty() {
+            unknown.sort();
+            return Err(UnknownMigrations { versions: unknown }.into());
+        }
+
+        Ok(())
+    }
+
+    /// Replace the database in 'dir' with the snapshot in 'file'.
+    ///
+    /// Fails if the snapshot does not validate, otherwise the replaced database
+    /// is kept next to it with the extension `.sqlite.before-restore`.
+    pub fn restore_snapshot_in_dir(file: &Path, dir: &Path) -> anyhow::Result<()> {
+        Self::validate_snapshot(file)?;
+
+        let path = db_path_from_dir(dir);
+        let restoring = path.with_extension("sqlite.restoring");
+        fs::create_dir_all(dir)?;
+        fs::copy(file, &restoring)?;
+
+        if path.exists() {
+            fs::rename(&path, path.with_extension("sqlite.before-restore"))?;
+        }
+        fs::rename(&restoring, &path)?;
+
+        tracing::info!(
+            "Restored database {} from {}",
+            path.display(),
+            file.display()
+        );
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        db::{Retrieve, Save, Swap},
+        swap_protocols::{rfc003::SwapId, Role},
+    };
+    use diesel::connection::SimpleConnection;
+    use libp2p::PeerId;
+    use std::str::FromStr;
+
+    #[tokio::test]
+    async fn restored_snapshot_contains_saved_swaps() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let snapshot_dir = tempfile::tempdir().unwrap();
+        let restore_dir = tempfile::tempdir().unwrap();
+        let snapshot = snapshot_dir.path().join("cnd.sqlite");
+
+        let db = Sqlite::new_in_dir(data_dir.path()).unwrap();
+        let swap_id = SwapId::from_str("ad2652ca-ecf2-4cc6-b35c-b4351ac28a34").unwrap();
+        let swap = Swap::new(swap_id, Role::Alice, PeerId::random());
+        db.save(swap.clone()).await.unwrap();
+
+        Sqlite::snapshot_in_dir(data_dir.path(), &snapshot).unwrap();
+        Sqlite::restore_snapshot_in_dir(&snapshot, restore_dir.path()).unwrap();
+
+        let restored = Sqlite::new_in_dir(restore_dir.path()).unwrap();
+        assert_eq!(restored.get(&swap_id).await.unwrap(), swap);
+    }
+
+    #[test]
+    fn snapshot_with_unknown_migration_is_rejected() {
+        let snapshot = tempfile::Builder::new()
+            .suffix(".sqlite")
+            .tempfile()
+            .unwrap();
+        Sqlite::new(snapshot.path()).unwrap();
+
+        let connection =
+            SqliteConnection::establish(&format!("file:{}", snapshot.path().display())).unwrap();
+        connection
+            .batch_execute(
+                "INSERT INTO __diesel_schema_migrations (version) VALUES ('99991231000000');",
+            )
+            .unwrap();
+
+        let error = Sqlite::validate_snapshot(snapshot.path()).unwrap_err();
+
+        assert!(error.is::<UnknownMigrations>());
+    }
+}
@@ -1,5 +1,5 @@
 use crate::{
-    db::{schema, wrapper_types::custom_sql_types::Text, Error, Sqlite},
+    db::{schema, wrapper_types::custom_sql_types::Text, Error},
     diesel::{ExpressionMethods, OptionalExtension, QueryDsl},
     swap_protocols::{rfc003::SwapId, Role},
 };
@@ -32,34 +32,57 @@ impl Swap {
     }
 }
 
-#[async_trait]
-impl Retrieve for Sqlite {
-    async fn get(&self, key: &SwapId) -> anyhow::Result<Swap> {
-        use self::schema::rfc003_swaps::dsl::*;
-
-        let record: QueryableSwap = self
-            .do_in_transaction(|connection| {
-                let key = Text(key);
-
-                rfc003_swaps
-                    .filter(swap_id.eq(key))
-                    .first(&*connection)
-                    .optional()
-            })
-            .await?
-            .ok_or(Error::SwapNotFound)?;
-
-        Ok(Swap::from(record))
+for_each_backend! {
+    impl Backend {
+        pub(in crate::db) async fn role(&self, key: &SwapId) -> anyhow::Result<Role> {
+            use self::schema::rfc003_swaps as swaps;
+
+            let record: QueryableSwapRole = self
+                .do_in_transaction(|connection| {
+                    let key = Text(key);
+
+                    swaps::table
+                        .filter(swaps::swap_id.eq(key))
+                        .select((swaps::swap_id, swaps::role))
+                        .first(connection)
+                        .optional()
+                })
+                .await?
+                .ok_or(Error::SwapNotFound)?;
+
+            Ok(*record.role)
+        }
     }
 
-    async fn all(&self) -> anyhow::Result<Vec<Swap>> {
-        use self::schema::rfc003_swaps::dsl::*;
+    #[async_trait]
+    impl Retrieve for Backend {
+        async fn get(&self, key: &SwapId) -> anyhow::Result<Swap> {
+            use self::schema::rfc003_swaps::dsl::*;
+
+            let record: QueryableSwap = self
+                .do_in_transaction(|connection| {
+                    let key = Text(key);
+
+                    rfc003_swaps
+                        .filter(swap_id.eq(key))
+                        .first(&*connection)
+                        .optional()
+                })
+                .await?
+                .ok_or(Error::SwapNotFound)?;
+
+            Ok(Swap::from(record))
+        }
+
+        async fn all(&self) -> anyhow::Result<Vec<Swap>> {
+            use self::schema::rfc003_swaps::dsl::*;
 
-        let records: Vec<QueryableSwap> = self
-            .do_in_transaction(|connection| rfc003_swaps.load(&*connection))
-            .await?;
+            let records: Vec<QueryableSwap> = self
+                .do_in_transaction(|connection| rfc003_swaps.load(&*connection))
+                .await?;
 
-        Ok(records.into_iter().map(|q| q.into()).collect())
+            Ok(records.into_iter().map(|q| q.into()).collect())
+        }
     }
 }
 
@@ -71,6 +94,12 @@ struct QueryableSwap {
     pub counterparty: Text<PeerId>,
 }
 
+#[derive(Queryable, Debug, Clone, PartialEq)]
+struct QueryableSwapRole {
+    pub swap_id: Text<SwapId>,
+    pub role: Text<Role>,
+}
+
 impl From<QueryableSwap> for Swap {
     fn from(swap: QueryableSwap) -> Swap {
         Swap {
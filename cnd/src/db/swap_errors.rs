@@ -0,0 +1,133 @@
+use crate::{
+    db::{
+        schema::{self, swap_errors},
+        wrapper_types::custom_sql_types::Text,
+        Save,
+    },
+    diesel::{ExpressionMethods, QueryDsl},
+    swap_protocols::{FailedSwap, Phase, Side, SwapError},
+};
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use diesel::RunQueryDsl;
+use uuid::Uuid;
+
+/// Retrieve and remove errors of failed swaps.
+#[async_trait]
+pub trait SwapErrorStore: Send + Sync + 'static {
+    async fn all_swap_errors(&self) -> anyhow::Result<Vec<FailedSwap>>;
+    /// Deletes the errors of one side of a swap recorded until 'until'.
+    async fn delete_swap_errors(
+        &self,
+        swap_id: Uuid,
+        side: Side,
+        until: NaiveDateTime,
+    ) -> anyhow::Result<()>;
+}
+
+for_each_backend! {
+    #[async_trait]
+    impl Save<FailedSwap> for Backend {
+        async fn save(&self, failed_swap: FailedSwap) -> anyhow::Result<()> {
+            let insertable = InsertableSwapError::from(failed_swap);
+
+            self.do_in_transaction(|connection| {
+                diesel::insert_into(schema::swap_errors::dsl::swap_errors)
+                    .values(&insertable)
+                    .execute(&*connection)
+            })
+            .await?;
+
+            Ok(())
+        }
+    }
+}
+
+for_each_backend! {
+    #[async_trait]
+    impl SwapErrorStore for Backend {
+        async fn all_swap_errors(&self) -> anyhow::Result<Vec<FailedSwap>> {
+            use self::schema::swap_errors::dsl::*;
+
+            let records: Vec<QueryableSwapError> = self
+                .do_in_transaction(|connection| swap_errors.order(id.asc()).load(&*connection))
+                .await?;
+
+            Ok(records.into_iter().map(FailedSwap::from).collect())
+        }
+
+        async fn delete_swap_errors(
+            &self,
+            key: Uuid,
+            ledger_side: Side,
+            until: NaiveDateTime,
+        ) -> anyhow::Result<()> {
+            use self::schema::swap_errors::dsl::*;
+
+            self.do_in_transaction(|connection| {
+                diesel::delete(
+                    swap_errors
+                        .filter(swap_id.eq(Text(key)))
+                        .filter(side.eq(Text(ledger_side)))
+                        .filter(at.le(until)),
+                )
+                .execute(&*connection)
+            })
+            .await?;
+
+            Ok(())
+        }
+    }
+}
+
+#[derive(Insertable, Debug, Clone)]
+#[table_name = "swap_errors"]
+struct InsertableSwapError {
+    pub swap_id: Text<Uuid>,
+    pub side: Text<Side>,
+    pub phase: Text<Phase>,
+    pub retryable: bool,
+    pub message: String,
+    pub at: NaiveDateTime,
+}
+
+impl From<FailedSwap> for InsertableSwapError {
+    fn from(failed_swap: FailedSwap) -> Self {
+        let FailedSwap { swap_id, error } = failed_swap;
+
+        InsertableSwapError {
+            swap_id: Text(swap_id),
+            side: Text(error.side),
+            phase: Text(error.phase),
+            retryable: error.retryable,
+            message: error.message,
+            at: error.at,
+        }
+    }
+}
+
+#[derive(Queryable, Debug, Clone, PartialEq)]
+struct QueryableSwapError {
+    pub id: i32,
+    pub swap_id: Text<Uuid>,
+    pub side: Text<Side>,
+    pub phase: Text<Phase>,
+    pub retryable: bool,
+    pub message: String,
+    pub at: NaiveDateTime,
+}
+
+impl From<QueryableSwapError> for FailedSwap {
+    fn from(record: QueryableSwapError) -> Self {
+        FailedSwap {
+            swap_id: *record.swap_id,
+            error: SwapError {
+                side: *record.side,
+                phase: *record.phase,
+                retryable: record.retryable,
+                message: record.message,
+                at: record.at,
+            },
+        }
+    }
+}
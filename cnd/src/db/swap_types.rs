@@ -1,15 +1,15 @@
 use crate::{
     asset, comit_api,
     db::{
-        schema,
+        columns::{LedgerName, MissingRow},
+        load_swaps::{QueryableAsset, QueryableLedger},
         wrapper_types::{custom_sql_types::Text, BitcoinNetwork},
-        Sqlite,
     },
-    diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl},
-    swap_protocols::{rfc003::SwapId, Role},
+    swap_protocols::{rfc003::SwapId, Role, Side},
 };
 use async_trait::async_trait;
-use strum_macros::{Display, EnumString};
+use std::str::FromStr;
+use strum_macros::{Display, EnumIter, EnumString};
 
 /// Determine swap types for swaps currently stored in the database.
 ///
@@ -21,106 +21,53 @@ pub trait DetermineTypes: Send + Sync + 'static {
     async fn determine_types(&self, key: &SwapId) -> anyhow::Result<SwapTypes>;
 }
 
-#[async_trait]
-impl DetermineTypes for Sqlite {
-    async fn determine_types(&self, key: &SwapId) -> anyhow::Result<SwapTypes> {
-        let role = self.role(key).await?;
-
-        if let Some(bitcoin_network) = self
-            .rfc003_bitcoin_ethereum_bitcoin_ether_request_messages_has_swap(key)
-            .await?
-        {
-            return Ok(SwapTypes {
-                alpha_ledger: bitcoin_network.into(),
-                beta_ledger: LedgerKind::Ethereum,
-                alpha_asset: AssetKind::Bitcoin,
-                beta_asset: AssetKind::Ether,
-                role,
-            });
-        }
+for_each_backend! {
+    #[async_trait]
+    impl DetermineTypes for Backend {
+        async fn determine_types(&self, key: &SwapId) -> anyhow::Result<SwapTypes> {
+            let role = self.role(key).await?;
 
-        if let Some(bitcoin_network) = self
-            .rfc003_ethereum_bitcoin_ether_bitcoin_request_messages_has_swap(key)
-            .await?
-        {
-            return Ok(SwapTypes {
-                alpha_ledger: LedgerKind::Ethereum,
-                beta_ledger: bitcoin_network.into(),
-                alpha_asset: AssetKind::Ether,
-                beta_asset: AssetKind::Bitcoin,
-                role,
-            });
-        }
+            let (ledgers, assets) = self
+                .do_in_transaction(|connection| {
+                    let key = Text(key);
 
-        if let Some(bitcoin_network) = self
-            .rfc003_bitcoin_ethereum_bitcoin_erc20_request_messages_has_swap(key)
-            .await?
-        {
-            return Ok(SwapTypes {
-                alpha_ledger: bitcoin_network.into(),
-                beta_ledger: LedgerKind::Ethereum,
-                alpha_asset: AssetKind::Bitcoin,
-                beta_asset: AssetKind::Erc20,
-                role,
-            });
-        }
+                    let ledgers = Backend::load_ledgers(connection, key)?;
+                    let assets = Backend::load_assets(connection, key)?;
+
+                    Ok::<_, diesel::result::Error>((ledgers, assets))
+                })
+                .await?;
 
-        if let Some(bitcoin_network) = self
-            .rfc003_ethereum_bitcoin_erc20_bitcoin_request_messages_has_swap(key)
-            .await?
-        {
-            return Ok(SwapTypes {
-                alpha_ledger: LedgerKind::Ethereum,
-                beta_ledger: bitcoin_network.into(),
-                alpha_asset: AssetKind::Erc20,
-                beta_asset: AssetKind::Bitcoin,
+            Ok(SwapTypes {
+                alpha_ledger: ledger_kind(&ledgers, Side::Alpha)?,
+                beta_ledger: ledger_kind(&ledgers, Side::Beta)?,
+                alpha_asset: asset_kind(&assets, Side::Alpha)?,
+                beta_asset: asset_kind(&assets, Side::Beta)?,
                 role,
-            });
+            })
         }
-
-        unreachable!("we got role for swap so the swap_id must exist")
     }
 }
 
-macro_rules! impl_has_swap {
-    ($table:ident) => {
-        paste::item! {
-            async fn [<$table _has_swap>](&self, key: &SwapId) -> anyhow::Result<Option<BitcoinNetwork>> {
-                use schema::$table as swaps;
-
-                let record: Option<QueryableSwap> = self.do_in_transaction(|connection| {
-                    let key = Text(key);
-                    swaps::table
-                        .filter(swaps::swap_id.eq(key))
-                        .select((swaps::swap_id,swaps::bitcoin_network))
-                        .first(&*connection)
-                        .optional()
-                })
-                .await?;
+fn ledger_kind(ledgers: &[QueryableLedger], side: Side) -> anyhow::Result<LedgerKind> {
+    let record = ledgers
+        .iter()
+        .find(|record| *record.side == side)
+        .ok_or_else(|| MissingRow::new("ledger", side))?;
 
-                match record {
-                    None => Ok(None),
-                    Some(swap) => {
-                        let network: BitcoinNetwork = *swap.bitcoin_network;
-                        Ok(Some(network))
-                    }
-                }
-            }
-        }
-    };
+    match *record.ledger {
+        LedgerName::Bitcoin => Ok(BitcoinNetwork::from_str(&record.network)?.into()),
+        LedgerName::Ethereum => Ok(LedgerKind::Ethereum),
+    }
 }
 
-impl Sqlite {
-    impl_has_swap!(rfc003_bitcoin_ethereum_bitcoin_ether_request_messages);
-    impl_has_swap!(rfc003_ethereum_bitcoin_ether_bitcoin_request_messages);
-    impl_has_swap!(rfc003_bitcoin_ethereum_bitcoin_erc20_request_messages);
-    impl_has_swap!(rfc003_ethereum_bitcoin_erc20_bitcoin_request_messages);
-}
+fn asset_kind(assets: &[QueryableAsset], side: Side) -> anyhow::Result<AssetKind> {
+    let record = assets
+        .iter()
+        .find(|record| *record.side == side)
+        .ok_or_else(|| MissingRow::new("asset", side))?;
 
-#[derive(Queryable, Debug, Clone, PartialEq)]
-struct QueryableSwap {
-    swap_id: Text<SwapId>,
-    bitcoin_network: Text<BitcoinNetwork>,
+    Ok(*record.asset)
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -162,7 +109,8 @@ impl From<comit_api::LedgerKind> for LedgerKind {
     }
 }
 
-#[derive(Clone, Copy, Debug, Display, EnumString, PartialEq)]
+#[derive(Clone, Copy, Debug, Display, EnumString, EnumIter, PartialEq)]
+#[strum(serialize_all = "lowercase")]
 pub enum AssetKind {
     Bitcoin,
     Ether,
@@ -1,10 +1,12 @@
 #![warn(unused_extern_crates, missing_debug_implementations, rust_2018_idioms)]
 #![forbid(unsafe_code)]
 
+pub mod gas_estimation;
+
 pub use ethbloom::{Bloom as H2048, Input};
 use hex::FromHexError;
 pub use primitive_types::U256;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use serde_hex::{CompactPfx, SerHex, SerHexSeq, StrictPfx};
 use std::{
     fmt,
@@ -156,6 +158,18 @@ pub struct TransactionReceipt {
     /// Status: either 1 (success) or 0 (failure).
     #[serde(with = "SerHex::<CompactPfx>")]
     pub status: u8,
+    /// Type of the transaction
+    #[serde(rename = "type", default)]
+    pub transaction_type: TransactionType,
+    /// Gas price actually paid, only reported by nodes aware of EIP-1559.
+    #[serde(rename = "effectiveGasPrice", default)]
+    pub effective_gas_price: Option<U256>,
+    /// Hash of the block this transaction was included in.
+    #[serde(rename = "blockHash", default)]
+    pub block_hash: Option<Hash>,
+    /// Number of the block this transaction was included in.
+    #[serde(rename = "blockNumber", default)]
+    pub block_number: Option<U256>,
 }
 
 impl TransactionReceipt {
@@ -165,7 +179,7 @@ impl TransactionReceipt {
 }
 
 /// Description of a Transaction, pending or in the chain.
-#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Transaction {
     /// Hash
     pub hash: Hash,
@@ -175,6 +189,77 @@ pub struct Transaction {
     pub value: U256,
     /// Input data
     pub input: Bytes,
+    /// Type of the transaction
+    #[serde(rename = "type", default)]
+    pub transaction_type: TransactionType,
+    /// Upper bound of the fee per gas, only set for EIP-1559 transactions.
+    #[serde(rename = "maxFeePerGas", default)]
+    pub max_fee_per_gas: Option<U256>,
+    /// Tip for the miner per gas, only set for EIP-1559 transactions.
+    #[serde(rename = "maxPriorityFeePerGas", default)]
+    pub max_priority_fee_per_gas: Option<U256>,
+}
+
+/// The envelope type of a transaction as introduced by EIP-2718, nodes omit
+/// it for legacy transactions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransactionType {
+    Legacy,
+    /// EIP-2930
+    AccessList,
+    /// EIP-1559
+    DynamicFee,
+    /// A type we do not know about, we still want to be able to look at the
+    /// blocks containing it.
+    Other(u8),
+}
+
+impl Default for TransactionType {
+    fn default() -> Self {
+        TransactionType::Legacy
+    }
+}
+
+impl From<u8> for TransactionType {
+    fn from(transaction_type: u8) -> Self {
+        match transaction_type {
+            0 => TransactionType::Legacy,
+            1 => TransactionType::AccessList,
+            2 => TransactionType::DynamicFee,
+            other => TransactionType::Other(other),
+        }
+    }
+}
+
+impl From<TransactionType> for u8 {
+    fn from(transaction_type: TransactionType) -> Self {
+        match transaction_type {
+            TransactionType::Legacy => 0,
+            TransactionType::AccessList => 1,
+            TransactionType::DynamicFee => 2,
+            TransactionType::Other(other) => other,
+        }
+    }
+}
+
+impl Serialize for TransactionType {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        <u8 as SerHex<CompactPfx>>::serialize(&u8::from(*self), serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for TransactionType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let transaction_type = <u8 as SerHex<CompactPfx>>::deserialize(deserializer)?;
+
+        Ok(transaction_type.into())
+    }
 }
 
 /// A log produced by a transaction.
@@ -201,6 +286,8 @@ pub struct Block {
     /// Logs bloom
     #[serde(rename = "logsBloom")]
     pub logs_bloom: H2048,
+    /// Number, `None` for pending blocks
+    pub number: Option<U256>,
     /// Timestamp
     pub timestamp: U256,
     /// Transactions
@@ -327,6 +414,90 @@ mod tests {
         assert_eq!(receipt.status, 1);
     }
 
+    #[test]
+    fn deserialize_transaction_without_type_as_legacy() {
+        let json = r#"
+        {
+          "hash": "0x580f02c7357f63d1ab80968c917cd2a3bb8f468d5f1dbf13da842bfb710e9f58",
+          "to": "0x49a1897a4b65ab6e87fa4fbbf7bf148ffbcccdae",
+          "value": "0xb1ad61013b39ab",
+          "input": "0x"
+        }
+        "#;
+
+        let transaction = serde_json::from_str::<Transaction>(json).unwrap();
+
+        assert_eq!(transaction.transaction_type, TransactionType::Legacy);
+        assert_eq!(transaction.max_fee_per_gas, None);
+    }
+
+    #[test]
+    fn deserialize_dynamic_fee_transaction() {
+        let json = r#"
+        {
+          "accessList": [],
+          "chainId": "0x1",
+          "hash": "0x580f02c7357f63d1ab80968c917cd2a3bb8f468d5f1dbf13da842bfb710e9f58",
+          "to": null,
+          "value": "0x0",
+          "input": "0x6100dc",
+          "maxFeePerGas": "0x2e90edd000",
+          "maxPriorityFeePerGas": "0x3b9aca00",
+          "type": "0x2"
+        }
+        "#;
+
+        let transaction = serde_json::from_str::<Transaction>(json).unwrap();
+
+        assert_eq!(transaction.transaction_type, TransactionType::DynamicFee);
+        assert_eq!(
+            transaction.max_fee_per_gas,
+            Some(U256::from(200_000_000_000u64))
+        );
+        assert_eq!(
+            transaction.max_priority_fee_per_gas,
+            Some(U256::from(1_000_000_000u64))
+        );
+    }
+
+    #[test]
+    fn deserialize_transaction_of_unknown_type() {
+        let json = r#"
+        {
+          "hash": "0x580f02c7357f63d1ab80968c917cd2a3bb8f468d5f1dbf13da842bfb710e9f58",
+          "to": null,
+          "value": "0x0",
+          "input": "0x",
+          "type": "0x7e"
+        }
+        "#;
+
+        let transaction = serde_json::from_str::<Transaction>(json).unwrap();
+
+        assert_eq!(transaction.transaction_type, TransactionType::Other(0x7e));
+    }
+
+    #[test]
+    fn deserialize_receipt_of_dynamic_fee_transaction() {
+        let json = r#"
+        {
+          "contractAddress": null,
+          "effectiveGasPrice": "0x1bf08eb00",
+          "logs": [],
+          "status": "0x1",
+          "type": "0x2"
+        }
+        "#;
+
+        let receipt = serde_json::from_str::<TransactionReceipt>(json).unwrap();
+
+        assert_eq!(receipt.transaction_type, TransactionType::DynamicFee);
+        assert_eq!(
+            receipt.effective_gas_price,
+            Some(U256::from(7_500_000_000u64))
+        );
+    }
+
     #[test]
     fn deserialize_receipt_with_status_0() {
         let json = r#"
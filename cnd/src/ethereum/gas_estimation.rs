@@ -0,0 +1,414 @@
+use crate::{
+    config::EthereumGas,
+    ethereum::{Address, Bytes, U256},
+    jsonrpc,
+};
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+const WEI_PER_GWEI: u64 = 1_000_000_000;
+
+/// How quickly the user wants an Ethereum transaction to be mined, each tier
+/// pays a different multiple of the gas price reported by the node.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FeeTier {
+    Slow,
+    Standard,
+    Fast,
+}
+
+impl Default for FeeTier {
+    fn default() -> Self {
+        FeeTier::Standard
+    }
+}
+
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+#[error("fee tier must be one of slow, standard or fast")]
+pub struct UnknownFeeTier;
+
+#[derive(Clone, Copy, Debug, PartialEq, thiserror::Error)]
+#[error("the Ethereum node estimated {estimate}, more than the configured maximum of {max}")]
+pub struct ExceedsMaximum {
+    pub estimate: U256,
+    pub max: U256,
+}
+
+impl FromStr for FeeTier {
+    type Err = UnknownFeeTier;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "slow" => Ok(FeeTier::Slow),
+            "standard" => Ok(FeeTier::Standard),
+            "fast" => Ok(FeeTier::Fast),
+            _ => Err(UnknownFeeTier),
+        }
+    }
+}
+
+/// Asks the Ethereum node how much gas a transaction needs and how much
+/// should be paid for it.
+#[derive(Debug)]
+pub struct GasEstimator {
+    client: jsonrpc::Client,
+    gas: EthereumGas,
+}
+
+/// The fee caps of an EIP-1559 transaction in wei.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DynamicFee {
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+}
+
+#[derive(Debug, Deserialize)]
+struct BlockHeader {
+    /// Absent before the London hard fork.
+    #[serde(rename = "baseFeePerGas", default)]
+    base_fee_per_gas: Option<U256>,
+}
+
+/// The transaction to estimate the gas of, `to` is absent for contract
+/// deployments.
+#[derive(Debug, Serialize)]
+struct CallRequest<'a> {
+    from: Address,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    to: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<&'a Bytes>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<U256>,
+}
+
+impl GasEstimator {
+    pub fn new(node_url: reqwest::Url, gas: EthereumGas) -> Self {
+        Self {
+            client: jsonrpc::Client::new(node_url),
+            gas,
+        }
+    }
+
+    /// Returns the gas limit for the given transaction sent by `from`: the
+    /// node's estimate times the configured multiplier, bounded by the
+    /// configured maximum.
+    pub async fn estimate_gas_limit(
+        &self,
+        from: Address,
+        to: Option<Address>,
+        data: Option<&Bytes>,
+        value: Option<U256>,
+    ) -> anyhow::Result<U256> {
+        let estimate: U256 = self
+            .client
+            .send(jsonrpc::Request::new("eth_estimateGas", vec![
+                jsonrpc::serialize(CallRequest {
+                    from,
+                    to,
+                    data,
+                    value,
+                })?,
+            ]))
+            .await
+            .context("failed to estimate gas with the Ethereum node")?;
+
+        let gas_limit = scale(
+            estimate,
+            self.gas.limit_multiplier_percent,
+            self.gas.max_limit,
+        )?;
+
+        Ok(gas_limit)
+    }
+
+    /// Returns the gas price in wei for the given tier, bounded by the
+    /// configured maximum.
+    pub async fn estimate_gas_price(&self, tier: FeeTier) -> anyhow::Result<U256> {
+        let gas_price: U256 = self
+            .client
+            .send(jsonrpc::Request::new(
+                "eth_gasPrice",
+                Vec::<serde_json::Value>::new(),
+            ))
+            .await
+            .context("failed to fetch gas price from the Ethereum node")?;
+
+        let gas_price = scale(
+            gas_price,
+            self.price_multiplier_percent(tier),
+            self.max_price(),
+        )?;
+
+        Ok(gas_price)
+    }
+
+    /// Returns the EIP-1559 fee caps for the given tier, `None` if the chain
+    /// does not support EIP-1559.
+    ///
+    /// The tier applies to the priority fee, the maximum fee leaves room for
+    /// the base fee to double until the transaction is mined.
+    pub async fn estimate_dynamic_fee(&self, tier: FeeTier) -> anyhow::Result<Option<DynamicFee>> {
+        let latest: BlockHeader = self
+            .client
+            .send(jsonrpc::Request::new("eth_getBlockByNumber", vec![
+                jsonrpc::serialize("latest")?,
+                jsonrpc::serialize(false)?,
+            ]))
+            .await
+            .context("failed to fetch latest block from the Ethereum node")?;
+        let base_fee = match latest.base_fee_per_gas {
+            Some(base_fee) => base_fee,
+            None => return Ok(None),
+        };
+
+        let priority_fee: U256 = self
+            .client
+            .send(jsonrpc::Request::new(
+                "eth_maxPriorityFeePerGas",
+                Vec::<serde_json::Value>::new(),
+            ))
+            .await
+            .context("failed to fetch priority fee from the Ethereum node")?;
+
+        let max_price = U256::from(self.max_price());
+        let max_fee_per_gas = base_fee
+            .saturating_mul(U256::from(2))
+            .saturating_add(priority_fee)
+            .min(max_price);
+        let max_priority_fee_per_gas = scale(
+            priority_fee,
+            self.price_multiplier_percent(tier),
+            self.max_price(),
+        )?
+        .min(max_fee_per_gas);
+
+        Ok(Some(DynamicFee {
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+        }))
+    }
+
+    fn price_multiplier_percent(&self, tier: FeeTier) -> u64 {
+        match tier {
+            FeeTier::Slow => self.gas.slow_price_multiplier_percent,
+            FeeTier::Standard => self.gas.standard_price_multiplier_percent,
+            FeeTier::Fast => self.gas.fast_price_multiplier_percent,
+        }
+    }
+
+    fn max_price(&self) -> u64 {
+        self.gas.max_price_gwei.saturating_mul(WEI_PER_GWEI)
+    }
+}
+
+/// Rounds up so that we never end up below the estimate. Fails if the
+/// estimate itself exceeds `max`, capping it would make the transaction fail
+/// or never be mined.
+fn scale(estimate: U256, multiplier_percent: u64, max: u64) -> Result<U256, ExceedsMaximum> {
+    let max = U256::from(max);
+    if estimate > max {
+        return Err(ExceedsMaximum { estimate, max });
+    }
+
+    // The estimate is at most `max`, the multiplication cannot overflow.
+    let scaled = (estimate * multiplier_percent + 99) / 100;
+
+    Ok(scaled.min(max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock_node;
+    use std::collections::HashMap;
+
+    fn gas() -> EthereumGas {
+        EthereumGas {
+            limit_multiplier_percent: 150,
+            max_limit: 150_000,
+            slow_price_multiplier_percent: 100,
+            standard_price_multiplier_percent: 125,
+            fast_price_multiplier_percent: 200,
+            max_price_gwei: 30,
+        }
+    }
+
+    fn estimator(results: HashMap<&'static str, serde_json::Value>) -> GasEstimator {
+        GasEstimator::new(mock_node::json_rpc(mock_node::by_method(results)), gas())
+    }
+
+    #[test]
+    fn scales_rounding_up_and_caps_at_max() {
+        let max = U256::from(100_000);
+
+        assert_eq!(
+            scale(U256::from(21_000), 150, 100_000),
+            Ok(U256::from(31_500))
+        );
+        assert_eq!(scale(U256::from(3), 150, 100_000), Ok(U256::from(5)));
+        assert_eq!(scale(U256::from(90_000), 150, 100_000), Ok(max));
+        assert_eq!(scale(max, 150, 100_000), Ok(max));
+    }
+
+    #[test]
+    fn fails_if_the_estimate_exceeds_the_maximum() {
+        assert_eq!(
+            scale(U256::from(100_001), 100, 100_000),
+            Err(ExceedsMaximum {
+                estimate: U256::from(100_001),
+                max: U256::from(100_000),
+            })
+        );
+        assert!(scale(U256::max_value(), 100, 100_000).is_err());
+    }
+
+    #[tokio::test]
+    async fn gas_limit_is_the_estimate_times_the_multiplier() {
+        let mut results = HashMap::new();
+        results.insert("eth_estimateGas", serde_json::json!("0xc350"));
+        let estimator = estimator(results);
+
+        let gas_limit = estimator
+            .estimate_gas_limit(Address::default(), Some(Address::default()), None, None)
+            .await
+            .unwrap();
+
+        assert_eq!(gas_limit, U256::from(75_000));
+    }
+
+    #[tokio::test]
+    async fn gas_limit_is_capped() {
+        let mut results = HashMap::new();
+        results.insert("eth_estimateGas", serde_json::json!("0x186a0"));
+        let estimator = estimator(results);
+
+        let gas_limit = estimator
+            .estimate_gas_limit(
+                Address::default(),
+                None,
+                Some(&Bytes::from(vec![0u8; 4])),
+                Some(U256::from(1)),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(gas_limit, U256::from(150_000));
+    }
+
+    #[tokio::test]
+    async fn fails_if_the_gas_estimate_exceeds_the_maximum() {
+        let mut results = HashMap::new();
+        results.insert("eth_estimateGas", serde_json::json!("0x30d40"));
+        let estimator = estimator(results);
+
+        let error = estimator
+            .estimate_gas_limit(Address::default(), None, None, None)
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            error.downcast_ref::<ExceedsMaximum>(),
+            Some(&ExceedsMaximum {
+                estimate: U256::from(200_000),
+                max: U256::from(150_000),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn gas_price_depends_on_the_fee_tier() {
+        let mut results = HashMap::new();
+        // 10 gwei
+        results.insert("eth_gasPrice", serde_json::json!("0x2540be400"));
+        let estimator = estimator(results);
+
+        let slow = estimator.estimate_gas_price(FeeTier::Slow).await.unwrap();
+        let standard = estimator
+            .estimate_gas_price(FeeTier::Standard)
+            .await
+            .unwrap();
+        let fast = estimator.estimate_gas_price(FeeTier::Fast).await.unwrap();
+
+        assert_eq!(slow, U256::from(10 * WEI_PER_GWEI));
+        assert_eq!(standard, U256::from(12_500_000_000u64));
+        assert_eq!(fast, U256::from(20 * WEI_PER_GWEI));
+    }
+
+    #[tokio::test]
+    async fn gas_price_is_capped() {
+        let mut results = HashMap::new();
+        // 25 gwei
+        results.insert("eth_gasPrice", serde_json::json!("0x5d21dba00"));
+        let estimator = estimator(results);
+
+        let gas_price = estimator.estimate_gas_price(FeeTier::Fast).await.unwrap();
+
+        assert_eq!(gas_price, U256::from(30 * WEI_PER_GWEI));
+    }
+
+    #[tokio::test]
+    async fn fails_if_the_gas_price_exceeds_the_maximum() {
+        let mut results = HashMap::new();
+        // 100 gwei
+        results.insert("eth_gasPrice", serde_json::json!("0x174876e800"));
+        let estimator = estimator(results);
+
+        let result = estimator.estimate_gas_price(FeeTier::Slow).await;
+
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn dynamic_fee_leaves_room_for_the_base_fee_to_double() {
+        let mut results = HashMap::new();
+        // 5 gwei
+        results.insert(
+            "eth_getBlockByNumber",
+            serde_json::json!({ "baseFeePerGas": "0x12a05f200" }),
+        );
+        // 1 gwei
+        results.insert("eth_maxPriorityFeePerGas", serde_json::json!("0x3b9aca00"));
+        let estimator = estimator(results);
+
+        let fee = estimator.estimate_dynamic_fee(FeeTier::Fast).await.unwrap();
+
+        assert_eq!(
+            fee,
+            Some(DynamicFee {
+                max_fee_per_gas: U256::from(11 * WEI_PER_GWEI),
+                max_priority_fee_per_gas: U256::from(2 * WEI_PER_GWEI),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn no_dynamic_fee_before_london() {
+        let mut results = HashMap::new();
+        results.insert("eth_getBlockByNumber", serde_json::json!({}));
+        let estimator = estimator(results);
+
+        let fee = estimator
+            .estimate_dynamic_fee(FeeTier::Standard)
+            .await
+            .unwrap();
+
+        assert_eq!(fee, None);
+    }
+
+    #[tokio::test]
+    async fn fails_if_the_node_cannot_estimate_gas() {
+        let estimator = estimator(HashMap::new());
+
+        let error = estimator
+            .estimate_gas_limit(Address::default(), None, None, None)
+            .await
+            .unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<jsonrpc::Error>(),
+            Some(jsonrpc::Error::JsonRpc { code: -32601, .. })
+        ));
+    }
+}
@@ -8,7 +8,7 @@ mod swap_resource;
 
 pub use self::{
     problem::*,
-    swap_resource::{OnFail, SwapParameters, SwapResource, SwapStatus},
+    swap_resource::{SwapParameters, SwapResource, SwapStatus},
 };
 use crate::swap_protocols::actions::lnd::Chain;
 
@@ -147,7 +147,7 @@ impl Serialize for Http<SwapProtocol> {
         S: Serializer,
     {
         match &self.0 {
-            // Currently we do not expose the hash_function protocol parameter via REST.
+            // The hash function is exposed with the parameters of the swap.
             SwapProtocol::Rfc003(_hash_function) => serializer.serialize_str("rfc003"),
         }
     }
@@ -1,10 +1,12 @@
 use crate::{
     asset,
+    bitcoin::fee_estimation::{self, FeeEstimator, CHILD_WEIGHT},
+    ethereum::gas_estimation::{FeeTier, GasEstimator},
     http_api::{problem, Http, MissingQueryParameters, UnexpectedQueryParameters},
     identity,
     swap_protocols::{
         actions::{
-            bitcoin::{SendToAddress, SpendOutput},
+            bitcoin::{SendToAddress, SpendOutput, SEQUENCE_ALLOW_NTIMELOCK_RBF},
             ethereum, lnd,
             lnd::Chain,
         },
@@ -16,10 +18,11 @@ use crate::{
     transaction,
 };
 use anyhow::Context;
+use async_trait::async_trait;
 use blockchain_contracts::bitcoin::witness;
 use http_api_problem::HttpApiProblem;
 use serde::{Deserialize, Serialize};
-use std::convert::Infallible;
+use std::convert::{Infallible, TryFrom};
 use warp::http::StatusCode;
 
 pub mod rfc003 {
@@ -41,13 +44,86 @@ pub trait ListRequiredFields {
 #[derive(Clone, Deserialize, Debug, PartialEq)]
 #[serde(untagged)]
 pub enum ActionExecutionParameters {
-    BitcoinAddressAndFee {
+    BitcoinChildPaysForParent {
+        txid: bitcoin::Txid,
         address: bitcoin::Address,
         fee_per_wu: String,
     },
+    BitcoinAddressAndFee {
+        address: bitcoin::Address,
+        fee_per_wu: Option<String>,
+        target_blocks: Option<String>,
+    },
+    EthereumFeeTier {
+        fee_tier: String,
+    },
     None {},
 }
 
+impl ActionExecutionParameters {
+    /// Asks bitcoind for a fee if the user wants to spend a bitcoin output
+    /// without specifying one.
+    pub async fn estimate_missing_fee(self, estimator: &FeeEstimator) -> anyhow::Result<Self> {
+        match self {
+            ActionExecutionParameters::BitcoinAddressAndFee {
+                address,
+                fee_per_wu: None,
+                target_blocks,
+            } => {
+                let target_blocks = target_blocks
+                    .map(|target_blocks| parse_uint::<u16>("target_blocks", &target_blocks))
+                    .transpose()?;
+
+                let fee_per_wu = estimator
+                    .estimate_fee_per_wu(target_blocks)
+                    .await
+                    .map_err(|e| {
+                        tracing::error!("Could not estimate Bitcoin fee: {:?}", e);
+                        HttpApiProblem::new("Fee estimation failed.")
+                            .set_status(StatusCode::SERVICE_UNAVAILABLE)
+                            .set_detail("Please provide a fee.")
+                    })?;
+
+                Ok(ActionExecutionParameters::BitcoinAddressAndFee {
+                    address,
+                    fee_per_wu: Some(fee_per_wu.to_string()),
+                    target_blocks: None,
+                })
+            }
+            parameters => Ok(parameters),
+        }
+    }
+
+    /// The fee tier the user asked for, the standard tier if none was given.
+    pub fn fee_tier(&self) -> anyhow::Result<FeeTier> {
+        match self {
+            ActionExecutionParameters::EthereumFeeTier { fee_tier } => {
+                fee_tier.parse::<FeeTier>().with_context(|| {
+                    HttpApiProblem::new("Invalid query parameter.")
+                        .set_status(StatusCode::BAD_REQUEST)
+                        .set_detail("Query parameter fee_tier must be slow, standard or fast.")
+                })
+            }
+            _ => Ok(FeeTier::default()),
+        }
+    }
+}
+
+fn parse_uint<T>(name: &str, value: &str) -> anyhow::Result<T>
+where
+    T: std::str::FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    value.parse::<T>().with_context(|| {
+        HttpApiProblem::new("Invalid query parameter.")
+            .set_status(StatusCode::BAD_REQUEST)
+            .set_detail(format!(
+                "Query parameter {} is not a valid unsigned integer.",
+                name
+            ))
+    })
+}
+
 #[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "kebab-case")]
 #[serde(tag = "type", content = "payload")]
@@ -62,18 +138,41 @@ pub enum ActionResponseBody {
         network: Http<bitcoin::Network>,
         #[serde(skip_serializing_if = "Option::is_none")]
         min_median_block_time: Option<Timestamp>,
+        fee_per_wu: usize,
+    },
+    /// The child transaction is unsigned, it spends an output of the
+    /// user's wallet.
+    BitcoinChildPaysForParent {
+        parent_txid: bitcoin::Txid,
+        hex: String,
+        fee_per_wu: usize,
+        network: Http<bitcoin::Network>,
     },
     EthereumDeployContract {
         data: crate::ethereum::Bytes,
         amount: asset::Ether,
         gas_limit: crate::ethereum::U256,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        gas_price: Option<crate::ethereum::U256>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max_fee_per_gas: Option<crate::ethereum::U256>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max_priority_fee_per_gas: Option<crate::ethereum::U256>,
         chain_id: ledger::ethereum::ChainId,
     },
     EthereumCallContract {
         contract_address: identity::Ethereum,
         #[serde(skip_serializing_if = "Option::is_none")]
         data: Option<crate::ethereum::Bytes>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        amount: Option<asset::Ether>,
         gas_limit: crate::ethereum::U256,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        gas_price: Option<crate::ethereum::U256>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max_fee_per_gas: Option<crate::ethereum::U256>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        max_priority_fee_per_gas: Option<crate::ethereum::U256>,
         chain_id: ledger::ethereum::ChainId,
         #[serde(skip_serializing_if = "Option::is_none")]
         min_block_timestamp: Option<Timestamp>,
@@ -109,6 +208,7 @@ impl ActionResponseBody {
     fn bitcoin_broadcast_signed_transaction(
         transaction: &transaction::Bitcoin,
         network: bitcoin::Network,
+        fee_per_wu: usize,
     ) -> Self {
         let min_median_block_time = if transaction.lock_time == 0 {
             None
@@ -123,10 +223,155 @@ impl ActionResponseBody {
             hex: bitcoin::consensus::encode::serialize_hex(transaction),
             network: Http(network),
             min_median_block_time,
+            fee_per_wu,
+        }
+    }
+
+    /// Lets the fee estimator know about a transaction spending an HTLC so
+    /// that we can offer to bump its fee once it is in the mempool.
+    pub fn record_bitcoin_spend(&self, fee_estimator: &FeeEstimator) {
+        if let ActionResponseBody::BitcoinBroadcastSignedTransaction { hex, .. } = self {
+            let transaction = hex::decode(hex)
+                .map_err(anyhow::Error::from)
+                .and_then(|bytes| {
+                    bitcoin::consensus::deserialize::<transaction::Bitcoin>(&bytes)
+                        .map_err(anyhow::Error::from)
+                });
+
+            match transaction {
+                Ok(transaction) => fee_estimator.record_spend(&transaction),
+                Err(e) => tracing::warn!("Could not record Bitcoin transaction: {:#}", e),
+            }
+        }
+    }
+
+    /// Replaces the static gas limit of Ethereum actions with the estimate of
+    /// the Ethereum node for a transaction sent by `from` and recommends a gas
+    /// price for the given tier.
+    /// On chains supporting EIP-1559 the fee caps of a dynamic fee
+    /// transaction are recommended as well, unless the action already
+    /// carries them.
+    ///
+    /// Estimating the gas limit fails if the transaction would currently
+    /// revert, e.g. a refund before the HTLC expired, in which case the
+    /// static gas limit is kept. Other actions are returned unchanged.
+    pub async fn estimate_gas(
+        self,
+        estimator: &GasEstimator,
+        from: identity::Ethereum,
+        tier: FeeTier,
+    ) -> Self {
+        let gas_price = match self {
+            ActionResponseBody::EthereumDeployContract { .. }
+            | ActionResponseBody::EthereumCallContract { .. } => {
+                match estimator.estimate_gas_price(tier).await {
+                    Ok(gas_price) => Some(gas_price),
+                    Err(e) => {
+                        tracing::warn!("Could not estimate Ethereum gas price: {:#}", e);
+                        None
+                    }
+                }
+            }
+            _ => return self,
+        };
+        let dynamic_fee = estimator
+            .estimate_dynamic_fee(tier)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Could not estimate Ethereum dynamic fee: {:#}", e);
+                None
+            });
+        let max_fee = |given: Option<crate::ethereum::U256>| {
+            given.or_else(|| dynamic_fee.map(|fee| fee.max_fee_per_gas))
+        };
+        let max_priority_fee = |given: Option<crate::ethereum::U256>| {
+            given.or_else(|| dynamic_fee.map(|fee| fee.max_priority_fee_per_gas))
+        };
+
+        match self {
+            ActionResponseBody::EthereumDeployContract {
+                data,
+                amount,
+                gas_limit,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                chain_id,
+                ..
+            } => {
+                let gas_limit = estimator
+                    .estimate_gas_limit(from, None, Some(&data), Some(amount.to_u256()))
+                    .await
+                    .unwrap_or_else(|e| {
+                        tracing::debug!("Keeping static gas limit of {}: {:#}", gas_limit, e);
+                        gas_limit
+                    });
+
+                ActionResponseBody::EthereumDeployContract {
+                    data,
+                    amount,
+                    gas_limit,
+                    gas_price,
+                    max_fee_per_gas: max_fee(max_fee_per_gas),
+                    max_priority_fee_per_gas: max_priority_fee(max_priority_fee_per_gas),
+                    chain_id,
+                }
+            }
+            ActionResponseBody::EthereumCallContract {
+                contract_address,
+                data,
+                amount,
+                gas_limit,
+                max_fee_per_gas,
+                max_priority_fee_per_gas,
+                chain_id,
+                min_block_timestamp,
+                ..
+            } => {
+                let gas_limit = estimator
+                    .estimate_gas_limit(
+                        from,
+                        Some(contract_address),
+                        data.as_ref(),
+                        amount.as_ref().map(asset::Ether::to_u256),
+                    )
+                    .await
+                    .unwrap_or_else(|e| {
+                        tracing::debug!("Keeping static gas limit of {}: {:#}", gas_limit, e);
+                        gas_limit
+                    });
+
+                ActionResponseBody::EthereumCallContract {
+                    contract_address,
+                    data,
+                    amount,
+                    gas_limit,
+                    gas_price,
+                    max_fee_per_gas: max_fee(max_fee_per_gas),
+                    max_priority_fee_per_gas: max_priority_fee(max_priority_fee_per_gas),
+                    chain_id,
+                    min_block_timestamp,
+                }
+            }
+            body => body,
         }
     }
 }
 
+/// An action as returned to the user, together with the time by which it has
+/// to be executed for the swap to remain safe.
+#[derive(Clone, Debug, Serialize)]
+pub struct ActionResponse {
+    #[serde(flatten)]
+    pub body: ActionResponseBody,
+    pub deadline: Option<Timestamp>,
+}
+
+impl ActionResponse {
+    pub fn new(body: ActionResponseBody, deadline: Option<Timestamp>) -> Self {
+        Self { body, deadline }
+    }
+}
+
 pub trait IntoResponsePayload {
     fn into_response_payload(
         self,
@@ -134,6 +379,23 @@ pub trait IntoResponsePayload {
     ) -> anyhow::Result<ActionResponseBody>;
 }
 
+/// Implemented by actions whose transactions can be sped up once they have
+/// been broadcast.
+#[async_trait]
+pub trait BumpFee: Sized {
+    /// Whether the transaction of this action is in the mempool, waiting
+    /// for a fee bump.
+    async fn is_pending(&self, fee_estimator: &FeeEstimator) -> bool;
+
+    async fn into_fee_bump_payload(
+        self,
+        parameters: ActionExecutionParameters,
+        fee_estimator: &FeeEstimator,
+    ) -> anyhow::Result<ActionResponseBody>;
+
+    fn list_fee_bump_fields() -> Vec<siren::Field>;
+}
+
 impl IntoResponsePayload for SendToAddress {
     fn into_response_payload(
         self,
@@ -194,6 +456,8 @@ impl From<ethereum::DeployContract> for ActionResponseBody {
             amount,
             chain_id,
             gas_limit,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
             data,
         } = action;
 
@@ -201,6 +465,9 @@ impl From<ethereum::DeployContract> for ActionResponseBody {
             data,
             amount,
             gas_limit: gas_limit.into(),
+            gas_price: None,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
             chain_id,
         }
     }
@@ -253,7 +520,10 @@ impl From<ethereum::CallContract> for ActionResponseBody {
         let ethereum::CallContract {
             to,
             data,
+            amount,
             gas_limit,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
             chain_id,
             min_block_timestamp,
         } = action;
@@ -261,7 +531,11 @@ impl From<ethereum::CallContract> for ActionResponseBody {
         ActionResponseBody::EthereumCallContract {
             contract_address: to,
             data,
+            amount,
             gas_limit: gas_limit.into(),
+            gas_price: None,
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
             chain_id,
             min_block_timestamp,
         }
@@ -282,13 +556,10 @@ impl IntoResponsePayload for SpendOutput {
         match query_params {
             ActionExecutionParameters::BitcoinAddressAndFee {
                 address,
-                fee_per_wu,
+                fee_per_wu: Some(fee_per_wu),
+                ..
             } => {
-                let fee_per_wu = fee_per_wu.parse::<usize>().with_context(|| {
-                    HttpApiProblem::new("Invalid query parameter.")
-                        .set_status(StatusCode::BAD_REQUEST)
-                        .set_detail("Query parameter fee-per-byte is not a valid unsigned integer.")
-                })?;
+                let fee_per_wu = parse_uint::<usize>("fee_per_wu", &fee_per_wu)?;
 
                 let network = self.network;
                 let transaction =
@@ -317,6 +588,7 @@ impl IntoResponsePayload for SpendOutput {
                 Ok(ActionResponseBody::bitcoin_broadcast_signed_transaction(
                     &transaction,
                     network,
+                    fee_per_wu,
                 ))
             }
             _ => Err(anyhow::Error::from(MissingQueryParameters {
@@ -331,7 +603,7 @@ impl IntoResponsePayload for SpendOutput {
                         name: "fee_per_wu",
                         data_type: "uint",
                         description:
-                        "The fee per weight unit you want to pay for the transaction in satoshis.",
+                        "The fee per weight unit you want to pay for the transaction in satoshis, estimated if omitted.",
                     },
                 ]
             }))
@@ -362,17 +634,300 @@ impl ListRequiredFields for SpendOutput {
                 value: None,
                 title: None,
             },
+            siren::Field {
+                name: "target_blocks".to_owned(),
+                class: vec!["bitcoin".to_owned(), "targetBlocks".to_owned()],
+                _type: Some("number".to_owned()),
+                value: None,
+                title: None,
+            },
+        ]
+    }
+}
+
+#[async_trait]
+impl BumpFee for SpendOutput {
+    async fn is_pending(&self, fee_estimator: &FeeEstimator) -> bool {
+        match fee_estimator.pending_spend(self.outpoint).await {
+            Ok(pending) => pending.is_some(),
+            Err(e) => {
+                tracing::warn!(
+                    "Could not look up transactions spending {}: {:#}",
+                    self.outpoint,
+                    e
+                );
+                false
+            }
+        }
+    }
+
+    async fn into_fee_bump_payload(
+        self,
+        query_params: ActionExecutionParameters,
+        fee_estimator: &FeeEstimator,
+    ) -> anyhow::Result<ActionResponseBody> {
+        match query_params {
+            ActionExecutionParameters::BitcoinAddressAndFee {
+                address,
+                fee_per_wu,
+                target_blocks,
+            } => {
+                let replaced = fee_estimator
+                    .pending_spend(self.outpoint)
+                    .await?
+                    .ok_or_else(|| {
+                        HttpApiProblem::new("Nothing to replace.")
+                            .set_status(StatusCode::CONFLICT)
+                            .set_detail(
+                                "None of the transactions spending the HTLC is in the mempool.",
+                            )
+                    })?;
+                // The replacement has the same weight, paying at least one more
+                // sat/WU satisfies the minimum relay fee increment of BIP125.
+                let min_fee_per_wu = usize::try_from(replaced.fee_per_wu() + 1)?;
+
+                let fee_per_wu = match fee_per_wu {
+                    Some(fee_per_wu) => {
+                        let fee_per_wu = parse_uint::<usize>("fee_per_wu", &fee_per_wu)?;
+                        if fee_per_wu < min_fee_per_wu {
+                            return Err(anyhow::Error::from(
+                                HttpApiProblem::new("Fee is too low.")
+                                    .set_status(StatusCode::BAD_REQUEST)
+                                    .set_detail(format!(
+                                        "The fee per WU has to be at least {} to replace transaction {}.",
+                                        min_fee_per_wu, replaced.txid
+                                    )),
+                            ));
+                        }
+                        fee_per_wu
+                    }
+                    None => {
+                        let target_blocks = target_blocks
+                            .map(|target_blocks| parse_uint::<u16>("target_blocks", &target_blocks))
+                            .transpose()?;
+                        let estimate = fee_estimator
+                            .estimate_fee_per_wu(target_blocks)
+                            .await
+                            .unwrap_or_else(|e| {
+                                tracing::warn!("Could not estimate Bitcoin fee: {:#}", e);
+                                min_fee_per_wu
+                            });
+
+                        estimate.max(min_fee_per_wu)
+                    }
+                };
+
+                self.into_response_payload(ActionExecutionParameters::BitcoinAddressAndFee {
+                    address,
+                    fee_per_wu: Some(fee_per_wu.to_string()),
+                    target_blocks: None,
+                })
+            }
+            _ => Err(anyhow::Error::from(MissingQueryParameters {
+                action: "bitcoin::SpendOutput",
+                parameters: &[problem::MissingQueryParameter {
+                    name: "address",
+                    data_type: "string",
+                    description: "The bitcoin address to where the funds should be sent.",
+                }],
+            })),
+        }
+    }
+
+    fn list_fee_bump_fields() -> Vec<siren::Field> {
+        vec![
+            siren::Field {
+                name: "address".to_owned(),
+                class: vec!["bitcoin".to_owned(), "address".to_owned()],
+                _type: Some("text".to_owned()),
+                value: None,
+                title: None,
+            },
+            siren::Field {
+                name: "fee_per_wu".to_owned(),
+                class: vec!["bitcoin".to_owned(), "feePerWU".to_owned()],
+                _type: Some("number".to_owned()),
+                value: None,
+                title: None,
+            },
+            siren::Field {
+                name: "target_blocks".to_owned(),
+                class: vec!["bitcoin".to_owned(), "targetBlocks".to_owned()],
+                _type: Some("number".to_owned()),
+                value: None,
+                title: None,
+            },
         ]
     }
 }
 
+#[async_trait]
+impl BumpFee for SendToAddress {
+    async fn is_pending(&self, fee_estimator: &FeeEstimator) -> bool {
+        match fee_estimator.pending_payment(&self.to).await {
+            Ok(pending) => pending.is_some(),
+            Err(e) => {
+                tracing::warn!(
+                    "Could not look up transactions paying to {}: {:#}",
+                    self.to,
+                    e
+                );
+                false
+            }
+        }
+    }
+
+    async fn into_fee_bump_payload(
+        self,
+        query_params: ActionExecutionParameters,
+        fee_estimator: &FeeEstimator,
+    ) -> anyhow::Result<ActionResponseBody> {
+        match query_params {
+            ActionExecutionParameters::BitcoinChildPaysForParent {
+                txid,
+                address,
+                fee_per_wu,
+            } => {
+                let fee_per_wu = parse_uint::<usize>("fee_per_wu", &fee_per_wu)?;
+
+                let pending = fee_estimator.pending_transaction(txid).await?.ok_or_else(|| {
+                    HttpApiProblem::new("Fund transaction not found.")
+                        .set_status(StatusCode::CONFLICT)
+                        .set_detail("The fund transaction is not in the mempool of bitcoind.")
+                })?;
+                let parent = fee_estimator.transaction(txid).await?;
+                let child_fee_per_wu =
+                    fee_estimation::child_pays_for_parent_fee_per_wu(&pending, fee_per_wu)?;
+                let child = build_child(&self, &parent, address, child_fee_per_wu)?;
+
+                Ok(ActionResponseBody::BitcoinChildPaysForParent {
+                    parent_txid: txid,
+                    hex: bitcoin::consensus::encode::serialize_hex(&child),
+                    fee_per_wu: child_fee_per_wu,
+                    network: Http(self.network),
+                })
+            }
+            _ => Err(anyhow::Error::from(MissingQueryParameters {
+                action: "bitcoin::SendToAddress",
+                parameters: &[
+                    problem::MissingQueryParameter {
+                        name: "txid",
+                        data_type: "string",
+                        description: "The id of the fund transaction stuck in the mempool.",
+                    },
+                    problem::MissingQueryParameter {
+                        name: "address",
+                        data_type: "string",
+                        description: "The bitcoin address to where the change should be sent.",
+                    },
+                    problem::MissingQueryParameter {
+                        name: "fee_per_wu",
+                        data_type: "uint",
+                        description:
+                            "The fee per weight unit in satoshis both transactions should pay together.",
+                    },
+                ],
+            })),
+        }
+    }
+
+    fn list_fee_bump_fields() -> Vec<siren::Field> {
+        vec![
+            siren::Field {
+                name: "txid".to_owned(),
+                class: vec!["bitcoin".to_owned(), "txid".to_owned()],
+                _type: Some("text".to_owned()),
+                value: None,
+                title: None,
+            },
+            siren::Field {
+                name: "address".to_owned(),
+                class: vec!["bitcoin".to_owned(), "address".to_owned()],
+                _type: Some("text".to_owned()),
+                value: None,
+                title: None,
+            },
+            siren::Field {
+                name: "fee_per_wu".to_owned(),
+                class: vec!["bitcoin".to_owned(), "feePerWU".to_owned()],
+                _type: Some("number".to_owned()),
+                value: None,
+                title: None,
+            },
+        ]
+    }
+}
+
+/// Spends the change of the fund transaction, the output that does not go to
+/// the HTLC, to `address` paying `fee_per_wu`.
+fn build_child(
+    fund: &SendToAddress,
+    parent: &transaction::Bitcoin,
+    address: bitcoin::Address,
+    fee_per_wu: usize,
+) -> anyhow::Result<transaction::Bitcoin> {
+    let htlc_script = fund.to.script_pubkey();
+    let is_htlc = |output: &bitcoin::TxOut| {
+        output.script_pubkey == htlc_script && output.value == fund.amount.as_sat()
+    };
+
+    if !parent.output.iter().any(is_htlc) {
+        return Err(anyhow::Error::from(
+            HttpApiProblem::new("Not a fund transaction.")
+                .set_status(StatusCode::BAD_REQUEST)
+                .set_detail("The transaction does not fund the HTLC of this swap."),
+        ));
+    }
+
+    let (vout, change) = parent
+        .output
+        .iter()
+        .enumerate()
+        .find(|(_, output)| !is_htlc(output))
+        .ok_or_else(|| {
+            HttpApiProblem::new("No change to spend.")
+                .set_status(StatusCode::BAD_REQUEST)
+                .set_detail("The fund transaction only pays to the HTLC.")
+        })?;
+
+    let fee = u64::try_from(fee_per_wu)? * CHILD_WEIGHT;
+    if fee >= change.value {
+        return Err(anyhow::Error::from(
+            HttpApiProblem::new("Fee is too high.")
+                .set_status(StatusCode::BAD_REQUEST)
+                .set_detail(
+                    "The fee of the child transaction is higher than the change it spends.",
+                ),
+        ));
+    }
+
+    Ok(transaction::Bitcoin {
+        version: 2,
+        lock_time: 0,
+        input: vec![bitcoin::TxIn {
+            previous_output: bitcoin::OutPoint {
+                txid: parent.txid(),
+                vout: u32::try_from(vout)?,
+            },
+            script_sig: bitcoin::Script::new(),
+            sequence: SEQUENCE_ALLOW_NTIMELOCK_RBF,
+            witness: vec![],
+        }],
+        output: vec![bitcoin::TxOut {
+            value: change.value - fee,
+            script_pubkey: address.script_pubkey(),
+        }],
+    })
+}
+
 impl IntoResponsePayload for ethereum::DeployContract {
     fn into_response_payload(
         self,
         query_params: ActionExecutionParameters,
     ) -> anyhow::Result<ActionResponseBody> {
         match query_params {
-            ActionExecutionParameters::None {} => Ok(self.into()),
+            ActionExecutionParameters::None {}
+            | ActionExecutionParameters::EthereumFeeTier { .. } => Ok(self.into()),
             _ => Err(anyhow::Error::from(UnexpectedQueryParameters {
                 action: "ethereum::ContractDeploy",
                 parameters: &["address", "fee_per_wu"],
@@ -383,7 +938,7 @@ impl IntoResponsePayload for ethereum::DeployContract {
 
 impl ListRequiredFields for ethereum::DeployContract {
     fn list_required_fields() -> Vec<siren::Field> {
-        vec![]
+        vec![fee_tier_field()]
     }
 }
 
@@ -393,7 +948,8 @@ impl IntoResponsePayload for ethereum::CallContract {
         query_params: ActionExecutionParameters,
     ) -> anyhow::Result<ActionResponseBody> {
         match query_params {
-            ActionExecutionParameters::None {} => Ok(self.into()),
+            ActionExecutionParameters::None {}
+            | ActionExecutionParameters::EthereumFeeTier { .. } => Ok(self.into()),
             _ => Err(anyhow::Error::from(UnexpectedQueryParameters {
                 action: "ethereum::SendTransaction",
                 parameters: &["address", "fee_per_wu"],
@@ -404,10 +960,83 @@ impl IntoResponsePayload for ethereum::CallContract {
 
 impl ListRequiredFields for ethereum::CallContract {
     fn list_required_fields() -> Vec<siren::Field> {
+        vec![fee_tier_field()]
+    }
+}
+
+/// Optional, the gas price of Ethereum actions is recommended for the
+/// standard tier if omitted.
+fn fee_tier_field() -> siren::Field {
+    siren::Field {
+        name: "fee_tier".to_owned(),
+        class: vec!["ethereum".to_owned(), "feeTier".to_owned()],
+        _type: Some("text".to_owned()),
+        value: None,
+        title: None,
+    }
+}
+
+#[async_trait]
+impl BumpFee for ethereum::DeployContract {
+    async fn is_pending(&self, _: &FeeEstimator) -> bool {
+        false
+    }
+
+    async fn into_fee_bump_payload(
+        self,
+        _: ActionExecutionParameters,
+        _: &FeeEstimator,
+    ) -> anyhow::Result<ActionResponseBody> {
+        Err(anyhow::anyhow!(
+            "bumping the fee of ethereum transactions is not supported"
+        ))
+    }
+
+    fn list_fee_bump_fields() -> Vec<siren::Field> {
         vec![]
     }
 }
 
+#[async_trait]
+impl BumpFee for ethereum::CallContract {
+    async fn is_pending(&self, _: &FeeEstimator) -> bool {
+        false
+    }
+
+    async fn into_fee_bump_payload(
+        self,
+        _: ActionExecutionParameters,
+        _: &FeeEstimator,
+    ) -> anyhow::Result<ActionResponseBody> {
+        Err(anyhow::anyhow!(
+            "bumping the fee of ethereum transactions is not supported"
+        ))
+    }
+
+    fn list_fee_bump_fields() -> Vec<siren::Field> {
+        vec![]
+    }
+}
+
+#[async_trait]
+impl BumpFee for Infallible {
+    async fn is_pending(&self, _: &FeeEstimator) -> bool {
+        unreachable!("how did you manage to construct Infallible?")
+    }
+
+    async fn into_fee_bump_payload(
+        self,
+        _: ActionExecutionParameters,
+        _: &FeeEstimator,
+    ) -> anyhow::Result<ActionResponseBody> {
+        unreachable!("how did you manage to construct Infallible?")
+    }
+
+    fn list_fee_bump_fields() -> Vec<siren::Field> {
+        unreachable!("how did you manage to construct Infallible?")
+    }
+}
+
 impl ListRequiredFields for Infallible {
     fn list_required_fields() -> Vec<siren::Field> {
         unreachable!("how did you manage to construct Infallible?")
@@ -426,7 +1055,10 @@ impl IntoResponsePayload for Infallible {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::{ethereum::U256, identity, swap_protocols::ledger::ethereum::ChainId};
+    use crate::{
+        asset::ethereum::FromWei, ethereum::U256, identity,
+        swap_protocols::ledger::ethereum::ChainId,
+    };
     use bitcoin::Address as BitcoinAddress;
     use std::str::FromStr;
 
@@ -447,11 +1079,148 @@ mod test {
             res,
             Ok(ActionExecutionParameters::BitcoinAddressAndFee {
                 address: "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".parse().unwrap(),
-                fee_per_wu: "10.59".to_string(),
+                fee_per_wu: Some("10.59".to_string()),
+                target_blocks: None,
+            })
+        );
+    }
+
+    #[test]
+    fn given_bitcoin_identity_and_target_blocks_deserialize_to_ditto() {
+        let s = "address=1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa&target_blocks=2";
+
+        let res = serde_urlencoded::from_str::<ActionExecutionParameters>(s);
+        assert_eq!(
+            res,
+            Ok(ActionExecutionParameters::BitcoinAddressAndFee {
+                address: "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".parse().unwrap(),
+                fee_per_wu: None,
+                target_blocks: Some("2".to_string()),
+            })
+        );
+    }
+
+    #[test]
+    fn given_txid_address_and_fee_deserialize_to_child_pays_for_parent() {
+        let s = "txid=4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b&address=1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa&fee_per_wu=10";
+
+        let res = serde_urlencoded::from_str::<ActionExecutionParameters>(s);
+        assert_eq!(
+            res,
+            Ok(ActionExecutionParameters::BitcoinChildPaysForParent {
+                txid: "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b"
+                    .parse()
+                    .unwrap(),
+                address: "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".parse().unwrap(),
+                fee_per_wu: "10".to_string(),
             })
         );
     }
 
+    #[test]
+    fn child_spends_the_change_of_the_fund_transaction() {
+        let fund = SendToAddress {
+            to: "3J98t1WpEZ73CNmQviecrnyiWrnqRhWNLy".parse().unwrap(),
+            amount: asset::Bitcoin::from_sat(100_000),
+            network: bitcoin::Network::Regtest,
+        };
+        let change_address: BitcoinAddress = "1BvBMSEYstWetqTFn5Au4m4GFg7xJaNVN2".parse().unwrap();
+        let parent = transaction::Bitcoin {
+            version: 2,
+            lock_time: 0,
+            input: vec![],
+            output: vec![
+                bitcoin::TxOut {
+                    value: 50_000,
+                    script_pubkey: change_address.script_pubkey(),
+                },
+                bitcoin::TxOut {
+                    value: 100_000,
+                    script_pubkey: fund.to.script_pubkey(),
+                },
+            ],
+        };
+
+        let child = build_child(&fund, &parent, change_address.clone(), 10).unwrap();
+
+        assert_eq!(child.input[0].previous_output, bitcoin::OutPoint {
+            txid: parent.txid(),
+            vout: 0,
+        });
+        assert_eq!(child.output, vec![bitcoin::TxOut {
+            value: 50_000 - 10 * CHILD_WEIGHT,
+            script_pubkey: change_address.script_pubkey(),
+        }]);
+        assert!(build_child(&fund, &parent, change_address, 200).is_err());
+    }
+
+    #[test]
+    fn given_fee_tier_deserialize_to_ditto() {
+        let s = "fee_tier=fast";
+
+        let res = serde_urlencoded::from_str::<ActionExecutionParameters>(s);
+        assert_eq!(
+            res,
+            Ok(ActionExecutionParameters::EthereumFeeTier {
+                fee_tier: "fast".to_string(),
+            })
+        );
+        assert_eq!(res.unwrap().fee_tier().unwrap(), FeeTier::Fast);
+    }
+
+    #[test]
+    fn unknown_fee_tier_is_rejected() {
+        let params = ActionExecutionParameters::EthereumFeeTier {
+            fee_tier: "ludicrous".to_string(),
+        };
+
+        assert!(params.fee_tier().is_err());
+        assert_eq!(
+            ActionExecutionParameters::None {}.fee_tier().unwrap(),
+            FeeTier::Standard
+        );
+    }
+
+    #[test]
+    fn call_contract_serializes_gas_price() {
+        let addr =
+            identity::Ethereum::from_str("0A81e8be41b21f651a71aaB1A85c6813b8bBcCf8").unwrap();
+        let contract = ActionResponseBody::EthereumCallContract {
+            contract_address: addr,
+            data: None,
+            amount: None,
+            gas_limit: U256::from(1),
+            gas_price: Some(U256::from(20_000_000_000u64)),
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            chain_id: ChainId::from(3),
+            min_block_timestamp: None,
+        };
+        let serialized = serde_json::to_string(&contract).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"type":"ethereum-call-contract","payload":{"contract_address":"0x0a81e8be41b21f651a71aab1a85c6813b8bbccf8","gas_limit":"0x1","gas_price":"0x4a817c800","chain_id":3}}"#
+        );
+    }
+
+    #[test]
+    fn deploy_contract_serializes_dynamic_fee() {
+        let contract = ActionResponseBody::EthereumDeployContract {
+            data: crate::ethereum::Bytes::from(vec![0x60, 0x00]),
+            amount: asset::Ether::from_wei(1u32),
+            gas_limit: U256::from(1),
+            gas_price: None,
+            max_fee_per_gas: Some(U256::from(30_000_000_000u64)),
+            max_priority_fee_per_gas: Some(U256::from(2_000_000_000u64)),
+            chain_id: ChainId::from(3),
+        };
+        let serialized = serde_json::to_string(&contract).unwrap();
+        assert_eq!(
+            serialized,
+            r#"{"type":"ethereum-deploy-contract","payload":{"data":"0x6000","amount":"1","gas_limit":"0x1","max_fee_per_gas":"0x6fc23ac00","max_priority_fee_per_gas":"0x77359400","chain_id":3}}"#
+        );
+    }
+
     #[test]
     fn call_contract_serializes_correctly_to_json_with_none() {
         let addr =
@@ -460,7 +1229,11 @@ mod test {
         let contract = ActionResponseBody::EthereumCallContract {
             contract_address: addr,
             data: None,
+            amount: None,
             gas_limit: U256::from(1),
+            gas_price: None,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
             chain_id,
             min_block_timestamp: None,
         };
@@ -508,4 +1281,26 @@ mod test {
 
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn action_response_serializes_deadline_next_to_type_and_payload() {
+        let with_deadline = ActionResponse::new(
+            ActionResponseBody::from(SendToAddress {
+                to: BitcoinAddress::from_str("2N3pk6v15FrDiRNKYVuxnnugn1Yg7wfQRL9").unwrap(),
+                amount: asset::Bitcoin::from_sat(100_000_000),
+                network: bitcoin::Network::Regtest,
+            }),
+            Some(Timestamp::from(1_000_000)),
+        );
+        let without_deadline = ActionResponse::new(ActionResponseBody::None, None);
+
+        assert_eq!(
+            serde_json::to_string(&with_deadline).unwrap(),
+            r#"{"type":"bitcoin-send-amount-to-address","payload":{"to":"2N3pk6v15FrDiRNKYVuxnnugn1Yg7wfQRL9","amount":"100000000","network":"regtest"},"deadline":1000000}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&without_deadline).unwrap(),
+            r#"{"type":"none","deadline":null}"#
+        );
+    }
 }
@@ -1,10 +1,15 @@
 use crate::{
-    db,
+    backup, db,
     http_api::routes::{
         rfc003::handlers::{post_swap::UnsupportedSwap, InvalidAction, InvalidActionInvocation},
         LndActionError,
     },
     network::comit_ln::SwapExists,
+    swap_protocols::{
+        expiries::{ExpiryViolation, PointOfNoReturnPassed},
+        rfc003::UnsupportedHashFunction,
+        SwapError, SwapNotFailed, SwapNotRetryable,
+    },
 };
 use http_api_problem::HttpApiProblem;
 use warp::{
@@ -116,8 +121,73 @@ pub fn from_anyhow(e: anyhow::Error) -> HttpApiProblem {
             .set_detail("The requested combination of ledgers and assets is not supported.");
     }
 
-    if e.is::<LndActionError>() {
-        return HttpApiProblem::new("Action not found.").set_status(StatusCode::NOT_FOUND);
+    if e.is::<UnsupportedHashFunction>() {
+        tracing::warn!("{}", e);
+
+        return HttpApiProblem::new("Hash function not supported.")
+            .set_status(StatusCode::BAD_REQUEST)
+            .set_detail("The HTLCs cannot be locked with the requested hash function.");
+    }
+
+    if e.is::<ExpiryViolation>() {
+        tracing::warn!("{}", e);
+
+        return HttpApiProblem::new("Unsafe swap expiries.")
+            .set_status(StatusCode::BAD_REQUEST)
+            .set_detail(e.to_string());
+    }
+
+    if let Some(e) = e.downcast_ref::<PointOfNoReturnPassed>() {
+        tracing::warn!("{}", e);
+
+        let mut problem = HttpApiProblem::new("Point of no return passed.")
+            .set_status(StatusCode::CONFLICT)
+            .set_detail("Funding this swap is no longer safe.");
+
+        problem
+            .set_value("deadline", &e.deadline)
+            .expect("timestamps will never fail to serialize");
+
+        return problem;
+    }
+
+    if e.is::<SwapNotFailed>() {
+        tracing::warn!("{}", e);
+
+        return HttpApiProblem::new("Swap has not failed.")
+            .set_status(StatusCode::CONFLICT)
+            .set_detail("Only swaps that failed can be retried.");
+    }
+
+    if e.is::<SwapNotRetryable>() {
+        tracing::warn!("{}", e);
+
+        return HttpApiProblem::new("Swap cannot be retried.")
+            .set_status(StatusCode::CONFLICT)
+            .set_detail(
+                "The swap failed for a reason that retrying does not resolve, see its errors.",
+            );
+    }
+
+    if let Some(err) = e.downcast_ref::<LndActionError>() {
+        return match err {
+            LndActionError::NotFound => {
+                HttpApiProblem::new("Action not found.").set_status(StatusCode::NOT_FOUND)
+            }
+            LndActionError::ExecutionDisabled => {
+                HttpApiProblem::new("Action execution disabled.")
+                    .set_status(StatusCode::BAD_REQUEST)
+                    .set_detail("cnd only executes lnd actions if `execute_actions` is enabled in the `[lightning.lnd]` section.")
+            }
+        };
+    }
+
+    if let Some(err) = e.downcast_ref::<backup::Error>() {
+        tracing::warn!("{}", e);
+
+        return HttpApiProblem::new("Backup not possible.")
+            .set_status(StatusCode::BAD_REQUEST)
+            .set_detail(err.to_string());
     }
 
     if let Some(err) = e.downcast_ref::<LedgerNotConfigured>() {
@@ -133,6 +203,28 @@ pub fn from_anyhow(e: anyhow::Error) -> HttpApiProblem {
     HttpApiProblem::with_title_and_type_from_status(StatusCode::INTERNAL_SERVER_ERROR)
 }
 
+/// Describes why a watcher of a swap failed.
+pub fn swap_error(error: &SwapError) -> HttpApiProblem {
+    let mut problem = HttpApiProblem::new(format!("Watching the {} ledger failed.", error.side))
+        .set_status(StatusCode::INTERNAL_SERVER_ERROR)
+        .set_detail(error.message.clone());
+
+    problem
+        .set_value("ledger", &error.side.to_string())
+        .expect("strings will never fail to serialize");
+    problem
+        .set_value("phase", &error.phase.to_string())
+        .expect("strings will never fail to serialize");
+    problem
+        .set_value("retryable", &error.retryable)
+        .expect("booleans will never fail to serialize");
+    problem
+        .set_value("timestamp", &error.at)
+        .expect("timestamps will never fail to serialize");
+
+    problem
+}
+
 pub async fn unpack_problem(rejection: Rejection) -> Result<impl Reply, Rejection> {
     if let Some(problem) = rejection.find::<HttpApiProblem>() {
         let code = problem.status.unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
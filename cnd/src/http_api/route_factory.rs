@@ -1,5 +1,5 @@
 use crate::{
-    config::settings::AllowedOrigins,
+    config::{settings::AllowedOrigins, Data},
     http_api,
     network::LocalPeerId,
     swap_protocols::{self, rfc003::SwapId, Facade, LocalSwapId, Rfc003Facade},
@@ -66,6 +66,22 @@ pub fn create(
         .and(rfc003_facade.clone())
         .and_then(http_api::routes::rfc003::get_swaps);
 
+    let rfc003_retry = warp::post()
+        .and(rfc003)
+        .and(warp::path::param::<SwapId>())
+        .and(warp::path("retry"))
+        .and(warp::path::end())
+        .and(rfc003_facade.clone())
+        .and_then(http_api::routes::rfc003::retry);
+
+    let rfc003_get_events = warp::get()
+        .and(rfc003)
+        .and(warp::path::param::<SwapId>())
+        .and(warp::path("events"))
+        .and(warp::path::end())
+        .and(rfc003_facade.clone())
+        .and_then(http_api::routes::rfc003::get_events);
+
     let rfc003_action = warp::method()
         .and(rfc003)
         .and(warp::path::param::<SwapId>())
@@ -140,6 +156,14 @@ pub fn create(
         .and(facade.clone())
         .and_then(http_api::routes::get_halight_swap);
 
+    let retry = swaps
+        .and(warp::post())
+        .and(warp::path::param::<LocalSwapId>())
+        .and(warp::path("retry"))
+        .and(warp::path::end())
+        .and(facade.clone())
+        .and_then(http_api::routes::retry);
+
     let lightning_action_init = swaps
         .and(warp::get())
         .and(warp::path::param::<LocalSwapId>())
@@ -153,6 +177,7 @@ pub fn create(
         .and(warp::path::param::<LocalSwapId>())
         .and(warp::path("fund"))
         .and(warp::path::end())
+        .and(warp::query::<http_api::action::ActionExecutionParameters>())
         .and(facade.clone())
         .and_then(http_api::routes::action_fund);
 
@@ -161,6 +186,7 @@ pub fn create(
         .and(warp::path::param::<LocalSwapId>())
         .and(warp::path("redeem"))
         .and(warp::path::end())
+        .and(warp::query::<http_api::action::ActionExecutionParameters>())
         .and(facade.clone())
         .and_then(http_api::routes::action_redeem);
 
@@ -169,12 +195,23 @@ pub fn create(
         .and(warp::path::param::<LocalSwapId>())
         .and(warp::path("refund"))
         .and(warp::path::end())
-        .and(facade)
+        .and(warp::query::<http_api::action::ActionExecutionParameters>())
+        .and(facade.clone())
         .and_then(http_api::routes::action_refund);
 
+    let lightning_action_execute = swaps
+        .and(warp::post())
+        .and(warp::path::param::<LocalSwapId>())
+        .and(warp::path::param::<String>())
+        .and(warp::path::end())
+        .and(facade)
+        .and_then(http_api::routes::execute_action);
+
     preflight_cors_route
         .or(rfc003_get_swap)
         .or(rfc003_post_swap)
+        .or(rfc003_retry)
+        .or(rfc003_get_events)
         .or(rfc003_action)
         .or(get_swaps)
         .or(get_peers)
@@ -185,12 +222,32 @@ pub fn create(
         .or(halight_bitcoin_han_ether)
         .or(halight_bitcoin_herc20)
         .or(get_halight_swap)
+        .or(retry)
         .or(lightning_action_init)
         .or(lightning_action_fund)
         .or(lightning_action_redeem)
         .or(lightning_action_refund)
+        .or(lightning_action_execute)
         .recover(http_api::unpack_problem)
         .with(warp::log("http"))
         .with(cors)
         .boxed()
 }
+
+/// The routes of the admin API, served on a socket of its own without CORS
+/// headers.
+pub fn create_admin(data: Data) -> BoxedFilter<(impl Reply,)> {
+    let data = warp::any().map(move || data.clone());
+
+    let post_backup = warp::post()
+        .and(warp::path!("admin" / "backup"))
+        .and(warp::path::end())
+        .and(data)
+        .and(warp::body::json())
+        .and_then(http_api::routes::admin::post_backup);
+
+    post_backup
+        .recover(http_api::unpack_problem)
+        .with(warp::log("http::admin"))
+        .boxed()
+}
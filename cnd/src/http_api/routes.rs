@@ -1,24 +1,34 @@
+pub mod admin;
 pub mod index;
 pub mod peers;
 pub mod rfc003;
 
 use crate::{
     asset,
+    config::ConfirmationTargets,
+    db::InvoiceCancellation,
     ethereum::Bytes,
     htlc_location,
-    http_api::{action::ActionResponseBody, problem, route_factory, Http},
+    http_api::{
+        action::{ActionExecutionParameters, ActionResponse, ActionResponseBody},
+        problem, route_factory, Http,
+    },
     network::comit_ln,
     swap_protocols::{
         actions::{
             ethereum,
             lnd::{self, Chain},
         },
+        can_retry,
+        expiries::{self, ConfirmationTime, LatestBlockTime},
         halight::{self, Settled},
-        ledger::ethereum::ChainId,
+        ledger::{ethereum::ChainId, Ethereum},
         rfc003::{ledger_state::HtlcState, LedgerState},
         state::Get,
-        Facade, FundAction, InitAction, LocalSwapId, RedeemAction, RefundAction, Role,
+        swap_registry, EthereumHtlc, Facade, FundAction, InitAction, LocalSwapId, RedeemAction,
+        RefundAction, Role, SwapError,
     },
+    timestamp::Timestamp,
     transaction,
 };
 use blockchain_contracts::ethereum::rfc003::ether_htlc::EtherHtlc;
@@ -55,12 +65,18 @@ pub async fn handle_get_halight_swap(
 
     let finalized_swap = facade.get_finalized_swap(swap_id).await;
 
+    let errors = facade.swap_errors.get(swap_id).await;
+
     let (alpha_ledger_state, beta_ledger_state, finalized_swap) =
         match (alpha_ledger_state, beta_ledger_state, finalized_swap) {
             (Some(alpha_ledger_state), Some(beta_ledger_state), Some(finalized_swap)) => {
                 (alpha_ledger_state, beta_ledger_state, finalized_swap)
             }
             _ => {
+                if let Some(refusal) = facade.get_refusal(swap_id).await {
+                    return make_refused_swap_entity(swap_id, refusal);
+                }
+
                 let empty_swap = siren::Entity::default().with_class_member("swaps");
 
                 tracing::debug!(
@@ -71,6 +87,19 @@ pub async fn handle_get_halight_swap(
             }
         };
 
+    // Actions executed by cnd change the state of the swap and are therefore
+    // POSTed instead of fetched.
+    let lnd_method = if facade.lnd_action_executor.is_some() {
+        http::Method::POST
+    } else {
+        http::Method::GET
+    };
+
+    // Also hidden if we cannot get the time of the latest block.
+    let fund_is_safe = ensure_before_point_of_no_return(&facade, &finalized_swap)
+        .await
+        .is_ok();
+
     match finalized_swap.role {
         Role::Alice => {
             let state = AliceHanEthereumHalightBitcoinState {
@@ -79,13 +108,16 @@ pub async fn handle_get_halight_swap(
                 finalized_swap,
             };
 
-            let maybe_action_names = vec![
-                state.init_action().map(|_| "init"),
-                state.fund_action().map(|_| "fund"),
-                state.redeem_action().map(|_| "redeem"),
-                state.refund_action().map(|_| "refund"),
+            let maybe_actions = vec![
+                state.init_action().map(|_| ("init", lnd_method.clone())),
+                state
+                    .fund_action()
+                    .filter(|_| fund_is_safe)
+                    .map(|_| ("fund", http::Method::GET)),
+                state.redeem_action().map(|_| ("redeem", lnd_method)),
+                state.refund_action().map(|_| ("refund", http::Method::GET)),
             ];
-            make_swap_entity(swap_id, state, maybe_action_names)
+            make_swap_entity(swap_id, state, maybe_actions, errors)
         }
         Role::Bob => {
             let state = BobHanEthereumHalightBitcoinState {
@@ -95,19 +127,76 @@ pub async fn handle_get_halight_swap(
             };
 
             // Bob cannot init and refund in this swap combination
-            let maybe_action_names = vec![
-                state.fund_action().map(|_| "fund"),
-                state.redeem_action().map(|_| "redeem"),
+            let maybe_actions = vec![
+                state
+                    .fund_action()
+                    .filter(|_| fund_is_safe)
+                    .map(|_| ("fund", lnd_method)),
+                state.redeem_action().map(|_| ("redeem", http::Method::GET)),
             ];
-            make_swap_entity(swap_id, state, maybe_action_names)
+            make_swap_entity(swap_id, state, maybe_actions, errors)
         }
     }
 }
 
+/// The point of no return of a han-ethereum-ether/halight-lightning-bitcoin
+/// swap, funding after it is unsafe.
+fn fund_deadline(
+    swap: &comit_ln::FinalizedSwap,
+    confirmation_targets: &ConfirmationTargets,
+) -> Timestamp {
+    expiries::han_halight_fund_deadline(
+        swap.alpha_expiry,
+        swap.beta_expiry.into(),
+        Ethereum::confirmation_time_secs(confirmation_targets),
+    )
+}
+
+/// Checks the point of no return against the latest Ethereum block, the
+/// Ethereum HTLC expiry being the one Bob relies on.
+async fn ensure_before_point_of_no_return(
+    facade: &Facade,
+    swap: &comit_ln::FinalizedSwap,
+) -> anyhow::Result<()> {
+    let deadline = fund_deadline(swap, &facade.confirmation_targets);
+    let now = LatestBlockTime::<Ethereum>::latest_block_time(facade).await?;
+    expiries::ensure_before_point_of_no_return(deadline, now)?;
+
+    Ok(())
+}
+
+/// A refused swap never got finalized, hence all we know about it is why it
+/// was refused.
+fn make_refused_swap_entity(
+    swap_id: LocalSwapId,
+    refusal: comit_ln::Refusal,
+) -> anyhow::Result<siren::Entity> {
+    let swap = RefusedSwapResource {
+        status: SwapStatus::NotSwapped,
+        refused_by: refusal.refused_by,
+        reason: refusal.reason,
+    };
+
+    let entity = siren::Entity::default()
+        .with_class_member("swaps")
+        .with_properties(swap)
+        .map_err(|e| {
+            tracing::error!("failed to set properties of entity: {:?}", e);
+            HttpApiProblem::with_title_and_type_from_status(StatusCode::INTERNAL_SERVER_ERROR)
+        })?
+        .with_link(siren::NavigationalLink::new(
+            &["self"],
+            route_factory::swap_path(swap_id),
+        ));
+
+    Ok(entity)
+}
+
 fn make_swap_entity<S>(
     swap_id: LocalSwapId,
     state: S,
-    maybe_action_names: Vec<Option<&str>>,
+    maybe_actions: Vec<Option<(&str, http::Method)>>,
+    errors: Vec<SwapError>,
 ) -> anyhow::Result<siren::Entity>
 where
     S: GetSwapStatus
@@ -119,8 +208,13 @@ where
 {
     let role = state.get_role();
     let swap = SwapResource {
-        status: state.get_swap_status(),
+        status: if errors.is_empty() {
+            state.get_swap_status()
+        } else {
+            SwapStatus::InternalFailure
+        },
         role: Http(role),
+        errors: errors.iter().map(problem::swap_error).collect(),
     };
 
     let mut entity = siren::Entity::default()
@@ -193,20 +287,40 @@ where
     );
     entity.push_sub_entity(beta_state_sub);
 
-    Ok(maybe_action_names
+    // None of the regular actions are safe to execute while we are not watching the
+    // ledgers, the only way forward is to retry if that may help.
+    if !errors.is_empty() {
+        if !can_retry(&errors) {
+            return Ok(entity);
+        }
+
+        return Ok(entity.with_action(siren::Action {
+            name: "retry".to_owned(),
+            class: vec![],
+            method: Some(http::Method::POST),
+            href: format!("/swaps/{}/retry", swap_id),
+            title: None,
+            _type: None,
+            fields: vec![],
+        }));
+    }
+
+    Ok(maybe_actions
         .into_iter()
         .filter_map(|action| action)
-        .fold(entity, |acc, action_name| {
-            let siren_action = make_siren_action(swap_id, action_name);
-            acc.with_action(siren_action)
-        }))
+        .map(|(action_name, method)| make_siren_action(swap_id, action_name, method))
+        .fold(entity, |acc, siren_action| acc.with_action(siren_action)))
 }
 
-fn make_siren_action(swap_id: LocalSwapId, action_name: &str) -> siren::Action {
+fn make_siren_action(
+    swap_id: LocalSwapId,
+    action_name: &str,
+    method: http::Method,
+) -> siren::Action {
     siren::Action {
         name: action_name.to_owned(),
         class: vec![],
-        method: Some(http::Method::GET),
+        method: Some(method),
         href: format!("/swaps/{}/{}", swap_id, action_name),
         title: None,
         _type: None,
@@ -221,12 +335,22 @@ enum SwapStatus {
     InProgress,
     Swapped,
     NotSwapped,
+    InternalFailure,
 }
 
 #[derive(Debug, Serialize)]
 struct SwapResource {
     pub status: SwapStatus,
     pub role: Http<Role>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<HttpApiProblem>,
+}
+
+#[derive(Debug, Serialize)]
+struct RefusedSwapResource {
+    pub status: SwapStatus,
+    pub refused_by: comit_ln::RefusedBy,
+    pub reason: String,
 }
 
 trait GetSwapStatus {
@@ -488,6 +612,10 @@ impl From<halight::State> for Transaction {
                 transactions: HashMap::new(),
                 status: EscrowStatus::Funded,
             },
+            halight::State::Rejected(_) => Transaction {
+                transactions: HashMap::new(),
+                status: EscrowStatus::IncorrectlyFunded,
+            },
             halight::State::Settled(_) => Transaction {
                 transactions: HashMap::new(),
                 status: EscrowStatus::Redeemed,
@@ -530,23 +658,67 @@ impl InitAction for AliceHanEthereumHalightBitcoinState {
 }
 
 impl FundAction for AliceHanEthereumHalightBitcoinState {
-    type Output = ethereum::DeployContract;
+    type Output = ActionResponseBody;
 
     fn fund_action(&self) -> Option<Self::Output> {
         match self.beta_ledger_state {
             halight::State::Opened(_) => {
-                let eth_htlc = self.finalized_swap.han_params();
-                let data = eth_htlc.into();
                 let amount = self.finalized_swap.alpha_asset.clone();
-                let gas_limit = EtherHtlc::deploy_tx_gas_limit();
                 let chain_id = ChainId::regtest();
 
-                Some(ethereum::DeployContract {
-                    data,
-                    amount,
-                    gas_limit,
-                    chain_id,
-                })
+                let action = match self.finalized_swap.alpha_htlc {
+                    EthereumHtlc::Deployed => {
+                        let eth_htlc = match self.finalized_swap.han_params() {
+                            Ok(eth_htlc) => eth_htlc,
+                            Err(e) => {
+                                tracing::error!(
+                                    "cannot fund swap {}: {}",
+                                    self.finalized_swap.swap_id,
+                                    e
+                                );
+                                return None;
+                            }
+                        };
+
+                        ethereum::DeployContract {
+                            data: eth_htlc.into(),
+                            amount,
+                            gas_limit: EtherHtlc::deploy_tx_gas_limit(),
+                            max_fee_per_gas: None,
+                            max_priority_fee_per_gas: None,
+                            chain_id,
+                        }
+                        .into()
+                    }
+                    EthereumHtlc::SwapRegistry(registry) => {
+                        let data =
+                            match self.finalized_swap.swap_registry_entry().initiate_payload() {
+                                Ok(data) => data,
+                                Err(e) => {
+                                    tracing::error!(
+                                        "cannot fund swap {}: {}",
+                                        self.finalized_swap.swap_id,
+                                        e
+                                    );
+                                    return None;
+                                }
+                            };
+
+                        ethereum::CallContract {
+                            to: registry,
+                            data: Some(data),
+                            amount: Some(amount),
+                            gas_limit: swap_registry::INITIATE_GAS_LIMIT,
+                            max_fee_per_gas: None,
+                            max_priority_fee_per_gas: None,
+                            chain_id,
+                            min_block_timestamp: None,
+                        }
+                        .into()
+                    }
+                };
+
+                Some(action)
             }
             _ => None,
         }
@@ -581,17 +753,35 @@ impl RefundAction for AliceHanEthereumHalightBitcoinState {
 
     fn refund_action(&self) -> Option<Self::Output> {
         match (&self.alpha_ledger_state, &self.beta_ledger_state) {
-            (LedgerState::Funded { htlc_location, .. }, halight::State::Accepted(_)) => {
+            (LedgerState::Funded { htlc_location, .. }, halight::State::Accepted(_))
+            | (LedgerState::Funded { htlc_location, .. }, halight::State::Rejected(_)) => {
                 let to = *htlc_location;
-                let data = None;
-                let gas_limit = EtherHtlc::refund_tx_gas_limit();
+                let (data, gas_limit) = match self.finalized_swap.alpha_htlc {
+                    EthereumHtlc::Deployed => (None, EtherHtlc::refund_tx_gas_limit()),
+                    EthereumHtlc::SwapRegistry(_) => {
+                        match swap_registry::refund_payload(self.finalized_swap.secret_hash) {
+                            Ok(data) => (Some(data), swap_registry::REFUND_GAS_LIMIT),
+                            Err(e) => {
+                                tracing::error!(
+                                    "cannot refund swap {}: {}",
+                                    self.finalized_swap.swap_id,
+                                    e
+                                );
+                                return None;
+                            }
+                        }
+                    }
+                };
                 let chain_id = ChainId::regtest();
                 let min_block_timestamp = Some(self.finalized_swap.alpha_expiry);
 
                 Some(ethereum::CallContract {
                     to,
                     data,
+                    amount: None,
                     gas_limit,
+                    max_fee_per_gas: None,
+                    max_priority_fee_per_gas: None,
                     chain_id,
                     min_block_timestamp,
                 })
@@ -640,15 +830,27 @@ impl RedeemAction for BobHanEthereumHalightBitcoinState {
                 halight::State::Settled(Settled { secret }),
             ) => {
                 let to = *htlc_location;
-                let data = Some(Bytes::from(secret.into_raw_secret().to_vec()));
-                let gas_limit = EtherHtlc::redeem_tx_gas_limit();
+                let (data, gas_limit) = match self.finalized_swap.alpha_htlc {
+                    EthereumHtlc::Deployed => (
+                        Bytes::from(secret.into_raw_secret().to_vec()),
+                        EtherHtlc::redeem_tx_gas_limit(),
+                    ),
+                    EthereumHtlc::SwapRegistry(_) => (
+                        swap_registry::redeem_payload(*secret),
+                        swap_registry::REDEEM_GAS_LIMIT,
+                    ),
+                };
+                let data = Some(data);
                 let chain_id: ChainId = ChainId::regtest();
                 let min_block_timestamp = None;
 
                 Some(ethereum::CallContract {
                     to,
                     data,
+                    amount: None,
                     gas_limit,
+                    max_fee_per_gas: None,
+                    max_priority_fee_per_gas: None,
                     chain_id,
                     min_block_timestamp,
                 })
@@ -658,6 +860,16 @@ impl RedeemAction for BobHanEthereumHalightBitcoinState {
     }
 }
 
+#[allow(clippy::needless_pass_by_value)]
+pub async fn retry(swap_id: LocalSwapId, facade: Facade) -> Result<impl Reply, Rejection> {
+    facade
+        .retry(swap_id)
+        .await
+        .map(|_| warp::reply::with_status(warp::reply(), StatusCode::ACCEPTED))
+        .map_err(problem::from_anyhow)
+        .map_err(into_rejection)
+}
+
 #[allow(clippy::needless_pass_by_value)]
 pub async fn action_init(swap_id: LocalSwapId, facade: Facade) -> Result<impl Reply, Rejection> {
     handle_action_init(swap_id, facade)
@@ -671,7 +883,7 @@ pub async fn action_init(swap_id: LocalSwapId, facade: Facade) -> Result<impl Re
 async fn handle_action_init(
     swap_id: LocalSwapId,
     facade: Facade,
-) -> anyhow::Result<ActionResponseBody> {
+) -> anyhow::Result<ActionResponse> {
     let alpha_ledger_state: LedgerState<
         asset::Ether,
         htlc_location::Ethereum,
@@ -708,12 +920,16 @@ async fn handle_action_init(
 
     let response = maybe_response.ok_or(LndActionError::NotFound)?;
 
-    Ok(response)
+    Ok(ActionResponse::new(response, None))
 }
 
 #[allow(clippy::needless_pass_by_value)]
-pub async fn action_fund(swap_id: LocalSwapId, facade: Facade) -> Result<impl Reply, Rejection> {
-    handle_action_fund(swap_id, facade)
+pub async fn action_fund(
+    swap_id: LocalSwapId,
+    query_params: ActionExecutionParameters,
+    facade: Facade,
+) -> Result<impl Reply, Rejection> {
+    handle_action_fund(swap_id, query_params, facade)
         .await
         .map(|body| warp::reply::json(&body))
         .map_err(problem::from_anyhow)
@@ -723,8 +939,11 @@ pub async fn action_fund(swap_id: LocalSwapId, facade: Facade) -> Result<impl Re
 #[allow(clippy::unit_arg, clippy::let_unit_value, clippy::cognitive_complexity)]
 async fn handle_action_fund(
     swap_id: LocalSwapId,
+    query_params: ActionExecutionParameters,
     facade: Facade,
-) -> anyhow::Result<ActionResponseBody> {
+) -> anyhow::Result<ActionResponse> {
+    let fee_tier = query_params.fee_tier()?;
+
     let alpha_ledger_state: LedgerState<
         asset::Ether,
         htlc_location::Ethereum,
@@ -746,6 +965,10 @@ async fn handle_action_fund(
         .await
         .ok_or_else(|| anyhow::anyhow!("swap with id {} not found", swap_id))?;
 
+    let deadline = fund_deadline(&finalized_swap, &facade.confirmation_targets);
+    ensure_before_point_of_no_return(&facade, &finalized_swap).await?;
+
+    let from = finalized_swap.ethereum_identity();
     let maybe_response = match finalized_swap.role {
         Role::Alice => {
             let state = AliceHanEthereumHalightBitcoinState {
@@ -767,14 +990,21 @@ async fn handle_action_fund(
         }
     };
 
-    let response = maybe_response.ok_or(LndActionError::NotFound)?;
+    let response = maybe_response
+        .ok_or(LndActionError::NotFound)?
+        .estimate_gas(&facade.ethereum_gas_estimator, from, fee_tier)
+        .await;
 
-    Ok(response)
+    Ok(ActionResponse::new(response, Some(deadline)))
 }
 
 #[allow(clippy::needless_pass_by_value)]
-pub async fn action_redeem(swap_id: LocalSwapId, facade: Facade) -> Result<impl Reply, Rejection> {
-    handle_action_redeem(swap_id, facade)
+pub async fn action_redeem(
+    swap_id: LocalSwapId,
+    query_params: ActionExecutionParameters,
+    facade: Facade,
+) -> Result<impl Reply, Rejection> {
+    handle_action_redeem(swap_id, query_params, facade)
         .await
         .map(|body| warp::reply::json(&body))
         .map_err(problem::from_anyhow)
@@ -784,8 +1014,11 @@ pub async fn action_redeem(swap_id: LocalSwapId, facade: Facade) -> Result<impl
 #[allow(clippy::unit_arg, clippy::let_unit_value, clippy::cognitive_complexity)]
 async fn handle_action_redeem(
     swap_id: LocalSwapId,
+    query_params: ActionExecutionParameters,
     facade: Facade,
-) -> anyhow::Result<ActionResponseBody> {
+) -> anyhow::Result<ActionResponse> {
+    let fee_tier = query_params.fee_tier()?;
+
     let alpha_ledger_state: LedgerState<
         asset::Ether,
         htlc_location::Ethereum,
@@ -807,6 +1040,16 @@ async fn handle_action_redeem(
         .await
         .ok_or_else(|| anyhow::anyhow!("swap with id {} not found", swap_id))?;
 
+    // Alice settles the invoice through lnd which takes care of its expiry.
+    let deadline = match finalized_swap.role {
+        Role::Alice => None,
+        Role::Bob => Some(expiries::redeem_deadline(
+            finalized_swap.alpha_expiry,
+            Ethereum::confirmation_time_secs(&facade.confirmation_targets),
+        )),
+    };
+
+    let from = finalized_swap.ethereum_identity();
     let maybe_response = match finalized_swap.role {
         Role::Alice => {
             let state = AliceHanEthereumHalightBitcoinState {
@@ -828,14 +1071,21 @@ async fn handle_action_redeem(
         }
     };
 
-    let response = maybe_response.ok_or(LndActionError::NotFound)?;
+    let response = maybe_response
+        .ok_or(LndActionError::NotFound)?
+        .estimate_gas(&facade.ethereum_gas_estimator, from, fee_tier)
+        .await;
 
-    Ok(response)
+    Ok(ActionResponse::new(response, deadline))
 }
 
 #[allow(clippy::needless_pass_by_value)]
-pub async fn action_refund(swap_id: LocalSwapId, facade: Facade) -> Result<impl Reply, Rejection> {
-    handle_action_refund(swap_id, facade)
+pub async fn action_refund(
+    swap_id: LocalSwapId,
+    query_params: ActionExecutionParameters,
+    facade: Facade,
+) -> Result<impl Reply, Rejection> {
+    handle_action_refund(swap_id, query_params, facade)
         .await
         .map(|body| warp::reply::json(&body))
         .map_err(problem::from_anyhow)
@@ -845,8 +1095,11 @@ pub async fn action_refund(swap_id: LocalSwapId, facade: Facade) -> Result<impl
 #[allow(clippy::unit_arg, clippy::let_unit_value, clippy::cognitive_complexity)]
 async fn handle_action_refund(
     swap_id: LocalSwapId,
+    query_params: ActionExecutionParameters,
     facade: Facade,
-) -> anyhow::Result<ActionResponseBody> {
+) -> anyhow::Result<ActionResponse> {
+    let fee_tier = query_params.fee_tier()?;
+
     let alpha_ledger_state: LedgerState<
         asset::Ether,
         htlc_location::Ethereum,
@@ -868,6 +1121,7 @@ async fn handle_action_refund(
         .await
         .ok_or_else(|| anyhow::anyhow!("swap with id {} not found", swap_id))?;
 
+    let from = finalized_swap.ethereum_identity();
     let maybe_response = match finalized_swap.role {
         Role::Alice => {
             let state = AliceHanEthereumHalightBitcoinState {
@@ -881,13 +1135,114 @@ async fn handle_action_refund(
         _ => None,
     };
 
-    let response = maybe_response.ok_or(LndActionError::NotFound)?;
+    let response = maybe_response
+        .ok_or(LndActionError::NotFound)?
+        .estimate_gas(&facade.ethereum_gas_estimator, from, fee_tier)
+        .await;
+
+    Ok(ActionResponse::new(response, None))
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub async fn execute_action(
+    swap_id: LocalSwapId,
+    action_name: String,
+    facade: Facade,
+) -> Result<impl Reply, Rejection> {
+    handle_execute_action(swap_id, &action_name, facade)
+        .await
+        .map(|body| warp::reply::json(&body))
+        .map_err(problem::from_anyhow)
+        .map_err(into_rejection)
+}
+
+/// Executes the lnd action of a swap through lnd instead of returning it to
+/// the client, ethereum actions still have to be executed by the client.
+async fn handle_execute_action(
+    swap_id: LocalSwapId,
+    action_name: &str,
+    facade: Facade,
+) -> anyhow::Result<ActionResponse> {
+    let executor = facade
+        .lnd_action_executor
+        .clone()
+        .ok_or(LndActionError::ExecutionDisabled)?;
+
+    let alpha_ledger_state: LedgerState<
+        asset::Ether,
+        htlc_location::Ethereum,
+        transaction::Ethereum,
+    > = facade
+        .alpha_ledger_states
+        .get(&swap_id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("alpha ledger state not found for {}", swap_id))?;
+
+    let beta_ledger_state: halight::State = facade
+        .beta_ledger_states
+        .get(&swap_id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("beta ledger state not found for {}", swap_id))?;
+
+    let finalized_swap = facade
+        .get_finalized_swap(swap_id)
+        .await
+        .ok_or_else(|| anyhow::anyhow!("swap with id {} not found", swap_id))?;
+
+    match (finalized_swap.role, action_name) {
+        (Role::Alice, "init") => {
+            let cancellation = InvoiceCancellation {
+                swap_id,
+                secret_hash: finalized_swap.secret_hash,
+                expiry: finalized_swap.alpha_expiry,
+            };
+            let state = AliceHanEthereumHalightBitcoinState {
+                alpha_ledger_state,
+                beta_ledger_state,
+                finalized_swap,
+            };
+            let action = state.init_action().ok_or(LndActionError::NotFound)?;
+
+            executor.add_hold_invoice(action).await?;
+            facade
+                .invoice_cancellations
+                .as_ref()
+                .ok_or(LndActionError::ExecutionDisabled)?
+                .schedule(cancellation)
+                .await?;
+        }
+        (Role::Alice, "redeem") => {
+            let state = AliceHanEthereumHalightBitcoinState {
+                alpha_ledger_state,
+                beta_ledger_state,
+                finalized_swap,
+            };
+            let action = state.redeem_action().ok_or(LndActionError::NotFound)?;
+
+            executor.settle_invoice(action).await?;
+        }
+        (Role::Bob, "fund") => {
+            ensure_before_point_of_no_return(&facade, &finalized_swap).await?;
+
+            let state = BobHanEthereumHalightBitcoinState {
+                alpha_ledger_state,
+                beta_ledger_state,
+                finalized_swap,
+            };
+            let action = state.fund_action().ok_or(LndActionError::NotFound)?;
+
+            executor.send_payment(action).await?;
+        }
+        _ => return Err(LndActionError::NotFound.into()),
+    }
 
-    Ok(response)
+    Ok(ActionResponse::new(ActionResponseBody::None, None))
 }
 
 #[derive(Debug, Clone, Copy, thiserror::Error)]
 pub enum LndActionError {
     #[error("action not found")]
     NotFound,
+    #[error("execution of lnd actions is disabled")]
+    ExecutionDisabled,
 }
@@ -0,0 +1,91 @@
+use crate::{
+    backup, config,
+    http_api::{problem, routes::into_rejection},
+};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use warp::{http::StatusCode, Rejection, Reply};
+
+#[derive(Deserialize, Debug)]
+pub struct BackupBody {
+    passphrase: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct BackupCreated {
+    path: PathBuf,
+}
+
+/// Writes a backup to a new directory in the `backups` directory of the data
+/// directory.
+pub async fn post_backup(data: config::Data, body: BackupBody) -> Result<impl Reply, Rejection> {
+    let dir = backup::new_backup_dir(&data.dir);
+
+    // Taking the snapshot and deriving the key from the passphrase block.
+    let backup = {
+        let dir = dir.clone();
+        tokio::task::spawn_blocking(move || backup::create(&data, &dir, &body.passphrase))
+    };
+
+    backup
+        .await
+        .map_err(anyhow::Error::new)
+        .and_then(|result| result)
+        .map(|_| {
+            warp::reply::with_status(
+                warp::reply::json(&BackupCreated { path: dir }),
+                StatusCode::CREATED,
+            )
+        })
+        .map_err(problem::from_anyhow)
+        .map_err(into_rejection)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{config, db::Database, http_api::route_factory, seed::RootSeed};
+    use rand::rngs::OsRng;
+    use warp::http::StatusCode;
+
+    #[tokio::test]
+    async fn backup_is_written_to_the_backups_directory() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let data = config::Data {
+            dir: data_dir.path().to_path_buf(),
+            postgres: None,
+        };
+        Database::new(&data).unwrap();
+        RootSeed::from_dir_or_generate(data_dir.path(), OsRng).unwrap();
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/admin/backup")
+            .json(&serde_json::json!({ "passphrase": "passphrase" }))
+            .reply(&route_factory::create_admin(data))
+            .await;
+
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body: serde_json::Value = serde_json::from_slice(response.body()).unwrap();
+        let backup_dir = body["path"].as_str().unwrap();
+        assert!(backup_dir.starts_with(&data_dir.path().join("backups").display().to_string()));
+        assert!(std::path::Path::new(backup_dir).join("cnd.sqlite").exists());
+    }
+
+    #[tokio::test]
+    async fn backup_without_passphrase_is_rejected() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let data = config::Data {
+            dir: data_dir.path().to_path_buf(),
+            postgres: None,
+        };
+
+        let response = warp::test::request()
+            .method("POST")
+            .path("/admin/backup")
+            .json(&serde_json::json!({ "passphrase": "" }))
+            .reply(&route_factory::create_admin(data))
+            .await;
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
@@ -5,9 +5,10 @@ use crate::{
     identity,
     network::{DialInformation, ListenAddresses},
     swap_protocols::{
-        halight, han, Facade, HanEtherereumHalightBitcoinCreateSwapParams, LocalSwapId,
-        Rfc003Facade, Role,
+        expiries, halight, han, EthereumHtlc, Facade, HanEtherereumHalightBitcoinCreateSwapParams,
+        LocalSwapId, Rfc003Facade, Role,
     },
+    timestamp::Timestamp,
 };
 use http_api_problem::HttpApiProblem;
 use libp2p::{Multiaddr, PeerId};
@@ -67,7 +68,21 @@ pub async fn post_han_ethereum_halight_bitcoin(
         .map_err(problem::from_anyhow)
         .map_err(warp::reject::custom)?;
 
-    let swap_params: HanEtherereumHalightBitcoinCreateSwapParams = body.clone().into();
+    let now = Timestamp::now();
+    expiries::validate(
+        &facade.expiries,
+        Timestamp::from(body.alpha.absolute_expiry),
+        expiries::cltv_expiry_to_timestamp(body.beta.cltv_expiry, now),
+        now,
+    )
+    .map_err(anyhow::Error::new)
+    .map_err(problem::from_anyhow)
+    .map_err(warp::reject::custom)?;
+
+    let swap_params = HanEtherereumHalightBitcoinCreateSwapParams {
+        ethereum_htlc: facade.ethereum_htlc,
+        ..body.clone().into()
+    };
 
     let swap_id = LocalSwapId::default();
     let reply = warp::reply::reply();
@@ -177,6 +192,7 @@ impl From<Body<HanEthereumEther, HalightLightningBitcoin>>
             ethereum_identity: body.alpha.identity.into(),
             ethereum_absolute_expiry: body.alpha.absolute_expiry.into(),
             ethereum_amount: body.alpha.amount,
+            ethereum_htlc: EthereumHtlc::Deployed,
             lightning_identity: body.beta.identity,
             lightning_cltv_expiry: body.beta.cltv_expiry.into(),
             lightning_amount: body.beta.amount.0,
@@ -9,7 +9,9 @@ use crate::{
         route_factory,
         routes::{
             into_rejection,
-            rfc003::handlers::{handle_action, handle_get_swap, handle_post_swap},
+            rfc003::handlers::{
+                handle_action, handle_get_events, handle_get_swap, handle_post_swap, handle_retry,
+            },
         },
     },
     swap_protocols::{
@@ -69,6 +71,24 @@ pub async fn get_swaps(dependencies: Rfc003Facade) -> Result<impl Reply, Rejecti
         .map_err(into_rejection)
 }
 
+#[allow(clippy::needless_pass_by_value)]
+pub async fn retry(id: SwapId, dependencies: Rfc003Facade) -> Result<impl Reply, Rejection> {
+    handle_retry(dependencies, id)
+        .await
+        .map(|_| warp::reply::with_status(warp::reply(), warp::http::StatusCode::ACCEPTED))
+        .map_err(problem::from_anyhow)
+        .map_err(into_rejection)
+}
+
+#[allow(clippy::needless_pass_by_value)]
+pub async fn get_events(id: SwapId, dependencies: Rfc003Facade) -> Result<impl Reply, Rejection> {
+    handle_get_events(dependencies, id)
+        .await
+        .map(|events| warp::reply::json(&events))
+        .map_err(problem::from_anyhow)
+        .map_err(into_rejection)
+}
+
 #[allow(clippy::needless_pass_by_value)]
 pub async fn action(
     method: http::Method,
@@ -1,11 +1,18 @@
 mod action;
+mod get_events;
 mod get_swap;
 mod get_swaps;
 pub mod post_swap;
+mod retry;
 
 pub use self::{
-    action::{handle_action, InvalidAction, InvalidActionInvocation},
+    action::{
+        fund_deadline, handle_action, with_fee_bumps, without_funding, without_unsafe_funding,
+        InvalidAction, InvalidActionInvocation,
+    },
+    get_events::handle_get_events,
     get_swap::handle_get_swap,
     get_swaps::handle_get_swaps,
     post_swap::handle_post_swap,
+    retry::handle_retry,
 };
@@ -1,9 +1,11 @@
 use crate::{
+    bitcoin::fee_estimation::FeeEstimator,
+    config::ConfirmationTargets,
     db::{DetermineTypes, LoadAcceptedSwap, Save},
     http_api::{
         action::{
-            rfc003::ToSirenAction, ActionExecutionParameters, ActionResponseBody,
-            IntoResponsePayload, ListRequiredFields,
+            rfc003::ToSirenAction, ActionExecutionParameters, ActionResponse, ActionResponseBody,
+            BumpFee, IntoResponsePayload, ListRequiredFields,
         },
         route_factory::new_action_link,
         routes::rfc003::decline::{to_swap_decline_reason, DeclineBody},
@@ -14,6 +16,7 @@ use crate::{
     seed::Rfc003DeriveSwapSeed,
     swap_protocols::{
         actions::Actions,
+        expiries::{self, ConfirmationTime, LatestBlockTime},
         rfc003::{
             self,
             actions::{Action, ActionKind},
@@ -21,8 +24,9 @@ use crate::{
             state::{Get, Insert},
             LedgerState, SwapCommunication, SwapId,
         },
-        Rfc003Facade,
+        Rfc003Facade, Role,
     },
+    timestamp::Timestamp,
 };
 use anyhow::Context;
 use serde::{Deserialize, Serialize};
@@ -40,7 +44,7 @@ pub async fn handle_action(
     body: serde_json::Value,
     query_params: ActionExecutionParameters,
     dependencies: Rfc003Facade,
-) -> anyhow::Result<ActionResponseBody> {
+) -> anyhow::Result<ActionResponse> {
     let types = dependencies.determine_types(&swap_id).await?;
 
     with_swap_types!(types, {
@@ -59,6 +63,7 @@ pub async fn handle_action(
             .await?
             .ok_or_else(|| anyhow::anyhow!("beta ledger state not found for {}", swap_id))?;
         let secret_source = dependencies.rfc003_derive_swap_seed(swap_id);
+        let ethereum_identity = swap_communication.ethereum_identity(types.role);
 
         let state = RoleState::new(
             swap_communication,
@@ -67,16 +72,43 @@ pub async fn handle_action(
             secret_source,
         );
 
-        let action = state
-            .actions()
+        let action = with_fee_bumps(state.actions()?, &dependencies.bitcoin_fee_estimator)
+            .await
             .into_iter()
             .select_action(action_kind, method)?;
 
-        match action {
+        let request = state.request();
+        let deadline = action_deadline(
+            action_kind,
+            types.role,
+            request,
+            &dependencies.confirmation_targets,
+        );
+        if let Some(deadline) = deadline {
+            if matches!(
+                action,
+                Action::Deploy(_) | Action::Fund(_) | Action::BumpFundFee(_)
+            ) {
+                let now = LatestBlockTime::<BL>::latest_block_time(&dependencies).await?;
+                expiries::ensure_before_point_of_no_return(deadline, now)?;
+            }
+        }
+
+        let fee_tier = query_params.fee_tier()?;
+
+        let body = match action {
             Action::Accept(_) => {
                 let body =
                     AcceptBody::deserialize(&body).context("failed to deserialize accept body")?;
 
+                let now = LatestBlockTime::<BL>::latest_block_time(&dependencies).await?;
+                expiries::validate(
+                    &dependencies.expiries,
+                    request.alpha_expiry,
+                    request.beta_expiry,
+                    now,
+                )?;
+
                 let channel = dependencies
                     .pending_request_for(swap_id)
                     .await
@@ -157,16 +189,161 @@ pub async fn handle_action(
             }
             Action::Redeem(action) => {
                 tracing::trace!("received redeem action");
+                let query_params = query_params
+                    .estimate_missing_fee(&dependencies.bitcoin_fee_estimator)
+                    .await?;
                 action.into_response_payload(query_params)
             }
             Action::Refund(action) => {
                 tracing::trace!("received refund action");
+                let query_params = query_params
+                    .estimate_missing_fee(&dependencies.bitcoin_fee_estimator)
+                    .await?;
                 action.into_response_payload(query_params)
             }
-        }
+            Action::BumpRedeemFee(action) => {
+                tracing::trace!("received bump redeem fee action");
+                action
+                    .into_fee_bump_payload(query_params, &dependencies.bitcoin_fee_estimator)
+                    .await
+            }
+            Action::BumpRefundFee(action) => {
+                tracing::trace!("received bump refund fee action");
+                action
+                    .into_fee_bump_payload(query_params, &dependencies.bitcoin_fee_estimator)
+                    .await
+            }
+            Action::BumpFundFee(action) => {
+                tracing::trace!("received bump fund fee action");
+                action
+                    .into_fee_bump_payload(query_params, &dependencies.bitcoin_fee_estimator)
+                    .await
+            }
+        }?;
+        body.record_bitcoin_spend(&dependencies.bitcoin_fee_estimator);
+        let body = match ethereum_identity {
+            Some(from) => {
+                body.estimate_gas(&dependencies.ethereum_gas_estimator, from, fee_tier)
+                    .await
+            }
+            None => body,
+        };
+
+        Ok(ActionResponse::new(body, deadline))
     })
 }
 
+/// The time by which an action has to be executed for the swap to remain
+/// safe, `None` if it can be executed at any time.
+pub fn action_deadline<AL, BL, AA, BA, AI, BI>(
+    action_kind: ActionKind,
+    role: Role,
+    request: &rfc003::Request<AL, BL, AA, BA, AI, BI>,
+    confirmation_targets: &ConfirmationTargets,
+) -> Option<Timestamp>
+where
+    AL: ConfirmationTime,
+    BL: ConfirmationTime,
+{
+    let alpha_confirmation_secs = AL::confirmation_time_secs(confirmation_targets);
+    let beta_confirmation_secs = BL::confirmation_time_secs(confirmation_targets);
+
+    match action_kind {
+        ActionKind::Deploy | ActionKind::Fund | ActionKind::BumpFundFee => {
+            Some(fund_deadline(role, request, confirmation_targets))
+        }
+        ActionKind::Redeem | ActionKind::BumpRedeemFee => Some(match role {
+            Role::Alice => expiries::redeem_deadline(request.beta_expiry, beta_confirmation_secs),
+            Role::Bob => expiries::redeem_deadline(request.alpha_expiry, alpha_confirmation_secs),
+        }),
+        ActionKind::Accept
+        | ActionKind::Decline
+        | ActionKind::Refund
+        | ActionKind::BumpRefundFee => None,
+    }
+}
+
+/// The point of no return of a swap, funding after it is unsafe.
+pub fn fund_deadline<AL, BL, AA, BA, AI, BI>(
+    role: Role,
+    request: &rfc003::Request<AL, BL, AA, BA, AI, BI>,
+    confirmation_targets: &ConfirmationTargets,
+) -> Timestamp
+where
+    AL: ConfirmationTime,
+    BL: ConfirmationTime,
+{
+    expiries::rfc003_fund_deadline(
+        role,
+        request.beta_expiry,
+        AL::confirmation_time_secs(confirmation_targets),
+        BL::confirmation_time_secs(confirmation_targets),
+    )
+}
+
+/// Funding a swap after its point of no return would give the counterparty
+/// the chance to take both assets, hence we stop offering it, including
+/// bumping the fee of a fund transaction that has not been mined yet.
+pub fn without_unsafe_funding<Accept, Decline, Deploy, Fund, Redeem, Refund>(
+    actions: Vec<Action<Accept, Decline, Deploy, Fund, Redeem, Refund>>,
+    fund_deadline: Timestamp,
+    now: Timestamp,
+) -> Vec<Action<Accept, Decline, Deploy, Fund, Redeem, Refund>> {
+    if expiries::ensure_before_point_of_no_return(fund_deadline, now).is_ok() {
+        return actions;
+    }
+
+    without_funding(actions)
+}
+
+pub fn without_funding<Accept, Decline, Deploy, Fund, Redeem, Refund>(
+    actions: Vec<Action<Accept, Decline, Deploy, Fund, Redeem, Refund>>,
+) -> Vec<Action<Accept, Decline, Deploy, Fund, Redeem, Refund>> {
+    actions
+        .into_iter()
+        .filter(|action| {
+            !matches!(
+                action,
+                Action::Deploy(_) | Action::Fund(_) | Action::BumpFundFee(_)
+            )
+        })
+        .collect()
+}
+
+/// Offers to bump the fee of every transaction the user broadcast that is
+/// still in the mempool, right after the action for broadcasting it.
+pub async fn with_fee_bumps<Accept, Decline, Deploy, Fund, Redeem, Refund>(
+    actions: Vec<Action<Accept, Decline, Deploy, Fund, Redeem, Refund>>,
+    fee_estimator: &FeeEstimator,
+) -> Vec<Action<Accept, Decline, Deploy, Fund, Redeem, Refund>>
+where
+    Fund: BumpFee + Clone,
+    Redeem: BumpFee + Clone,
+    Refund: BumpFee + Clone,
+{
+    let mut with_fee_bumps = Vec::with_capacity(actions.len());
+
+    for action in actions {
+        let fee_bump = match &action {
+            Action::Fund(fund) if fund.is_pending(fee_estimator).await => {
+                Some(Action::BumpFundFee(fund.clone()))
+            }
+            Action::Redeem(redeem) if redeem.is_pending(fee_estimator).await => {
+                Some(Action::BumpRedeemFee(redeem.clone()))
+            }
+            Action::Refund(refund) if refund.is_pending(fee_estimator).await => {
+                Some(Action::BumpRefundFee(refund.clone()))
+            }
+            _ => None,
+        };
+
+        with_fee_bumps.push(action);
+        with_fee_bumps.extend(fee_bump);
+    }
+
+    with_fee_bumps
+}
+
 #[derive(Debug, thiserror::Error, PartialEq)]
 #[error("attempt to invoke {action_kind} action with http method {method}, which is an invalid combination")]
 pub struct InvalidActionInvocation {
@@ -254,8 +431,19 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::spectral_ext::AnyhowResultAssertions;
+    use crate::{
+        asset,
+        config::BitcoinFees,
+        mock_node,
+        spectral_ext::AnyhowResultAssertions,
+        swap_protocols::{
+            actions::{bitcoin::SendToAddress, ethereum::CallContract},
+            ledger::ethereum::ChainId,
+        },
+    };
+    use serde_json::json;
     use spectral::prelude::*;
+    use std::{collections::HashMap, convert::Infallible};
 
     fn actions() -> Vec<Action<(), (), (), (), (), ()>> {
         Vec::new()
@@ -381,6 +569,124 @@ mod tests {
                 method: http::Method::POST,
             });
     }
+
+    fn fund() -> SendToAddress {
+        SendToAddress {
+            to: "1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".parse().unwrap(),
+            amount: asset::Bitcoin::from_sat(100_000),
+            network: ::bitcoin::Network::Regtest,
+        }
+    }
+
+    fn redeem() -> CallContract {
+        CallContract {
+            to: "0A81e8be41b21f651a71aaB1A85c6813b8bBcCf8".parse().unwrap(),
+            data: None,
+            amount: None,
+            gas_limit: 100_000,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            chain_id: ChainId::regtest(),
+            min_block_timestamp: None,
+        }
+    }
+
+    /// A bitcoind whose mempool holds nothing but the given transaction.
+    fn fee_estimator(mempool: Option<::bitcoin::Transaction>) -> FeeEstimator {
+        let mut results = HashMap::new();
+        match mempool {
+            Some(transaction) => {
+                results.insert("getrawmempool", json!([transaction.txid()]));
+                results.insert(
+                    "getrawtransaction",
+                    json!(::bitcoin::consensus::encode::serialize_hex(&transaction)),
+                );
+                results.insert(
+                    "getmempoolentry",
+                    json!({ "weight": 500, "fees": { "base": 0.000_01 } }),
+                );
+            }
+            None => {
+                results.insert("getrawmempool", json!([]));
+            }
+        }
+
+        FeeEstimator::new(
+            mock_node::json_rpc(mock_node::by_method(results)),
+            BitcoinFees {
+                target_blocks: 6,
+                floor_sat_per_wu: 1,
+                ceiling_sat_per_wu: 100,
+            },
+        )
+    }
+
+    fn fund_transaction(fund: &SendToAddress) -> ::bitcoin::Transaction {
+        ::bitcoin::Transaction {
+            version: 2,
+            lock_time: 0,
+            input: vec![::bitcoin::TxIn {
+                previous_output: ::bitcoin::OutPoint::default(),
+                script_sig: ::bitcoin::Script::new(),
+                sequence: 0xFFFF_FFFF,
+                witness: Vec::new(),
+            }],
+            output: vec![::bitcoin::TxOut {
+                value: fund.amount.as_sat(),
+                script_pubkey: fund.to.script_pubkey(),
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn fee_bumps_are_only_offered_for_bitcoin_transactions() {
+        let fund = fund();
+        let redeem = redeem();
+        let given_actions: Vec<Action<(), (), Infallible, _, _, Infallible>> =
+            vec![Action::Fund(fund.clone()), Action::Redeem(redeem.clone())];
+
+        let fee_estimator = fee_estimator(Some(fund_transaction(&fund)));
+
+        let actions = with_fee_bumps(given_actions, &fee_estimator).await;
+
+        assert_eq!(actions, vec![
+            Action::Fund(fund.clone()),
+            Action::BumpFundFee(fund),
+            Action::Redeem(redeem),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn fund_fee_bump_is_not_offered_before_the_fund_transaction_is_broadcast() {
+        let given_actions: Vec<Action<(), (), Infallible, _, _, Infallible>> =
+            vec![Action::Fund(fund()), Action::Redeem(redeem())];
+
+        let fee_estimator = fee_estimator(None);
+
+        let actions = with_fee_bumps(given_actions.clone(), &fee_estimator).await;
+
+        assert_eq!(actions, given_actions);
+    }
+
+    #[test]
+    fn funding_is_hidden_after_the_point_of_no_return() {
+        let deadline = Timestamp::from(1_000_000);
+        let given_actions: Vec<Action<(), (), (), (), (), ()>> = vec![
+            Action::Deploy(()),
+            Action::Fund(()),
+            Action::BumpFundFee(()),
+            Action::Refund(()),
+        ];
+
+        assert_eq!(
+            without_unsafe_funding(given_actions.clone(), deadline, deadline),
+            given_actions
+        );
+        assert_eq!(
+            without_unsafe_funding(given_actions, deadline, deadline.plus(1)),
+            vec![Action::Refund(())]
+        );
+    }
 }
 
 impl From<ActionKind> for http::Method {
@@ -392,6 +698,9 @@ impl From<ActionKind> for http::Method {
             ActionKind::Fund => http::Method::GET,
             ActionKind::Refund => http::Method::GET,
             ActionKind::Redeem => http::Method::GET,
+            ActionKind::BumpRedeemFee => http::Method::GET,
+            ActionKind::BumpRefundFee => http::Method::GET,
+            ActionKind::BumpFundFee => http::Method::GET,
         }
     }
 }
@@ -416,6 +725,9 @@ where
             Action::Accept(_) | Action::Decline(_) => Err(anyhow::anyhow!(
                 "IntoResponsePayload is not available for Accept/Decline"
             )),
+            Action::BumpRedeemFee(_) | Action::BumpRefundFee(_) | Action::BumpFundFee(_) => Err(
+                anyhow::anyhow!("IntoResponsePayload is not available for fee bumps, use BumpFee"),
+            ),
         }
     }
 }
@@ -426,9 +738,9 @@ where
     Accept: ListRequiredFields + Debug,
     Decline: ListRequiredFields + Debug,
     Deploy: ListRequiredFields + Debug,
-    Fund: ListRequiredFields + Debug,
-    Redeem: ListRequiredFields + Debug,
-    Refund: ListRequiredFields + Debug,
+    Fund: ListRequiredFields + BumpFee + Debug,
+    Redeem: ListRequiredFields + BumpFee + Debug,
+    Refund: ListRequiredFields + BumpFee + Debug,
 {
     fn to_siren_action(&self, id: &SwapId) -> siren::Action {
         let action_kind = ActionKind::from(self);
@@ -448,6 +760,9 @@ where
             Action::Fund(_) => Fund::list_required_fields(),
             Action::Redeem(_) => Redeem::list_required_fields(),
             Action::Refund(_) => Refund::list_required_fields(),
+            Action::BumpRedeemFee(_) => Redeem::list_fee_bump_fields(),
+            Action::BumpRefundFee(_) => Refund::list_fee_bump_fields(),
+            Action::BumpFundFee(_) => Fund::list_fee_bump_fields(),
         };
 
         siren::Action {
@@ -473,6 +788,9 @@ impl<Accept, Decline, Deploy, Fund, Redeem, Refund> Display
             Action::Fund { .. } => "Fund",
             Action::Redeem { .. } => "Redeem",
             Action::Refund { .. } => "Refund",
+            Action::BumpRedeemFee { .. } => "BumpRedeemFee",
+            Action::BumpRefundFee { .. } => "BumpRefundFee",
+            Action::BumpFundFee { .. } => "BumpFundFee",
         };
         write!(f, "{}", s)
     }
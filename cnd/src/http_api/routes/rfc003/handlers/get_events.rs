@@ -0,0 +1,48 @@
+use crate::{
+    db::{self, LedgerEventStore, Retrieve},
+    swap_protocols::{rfc003::SwapId, Rfc003Facade},
+};
+use chrono::NaiveDateTime;
+use serde::Serialize;
+
+/// A ledger event of an rfc003 swap as it is returned by the HTTP API.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct LedgerEvent {
+    pub ledger: String,
+    pub event: String,
+    pub transaction_id: String,
+    pub block_hash: Option<String>,
+    pub block_height: Option<u32>,
+    pub block_time: Option<NaiveDateTime>,
+    pub data: serde_json::Value,
+    pub timestamp: NaiveDateTime,
+}
+
+impl From<db::LedgerEvent> for LedgerEvent {
+    fn from(ledger_event: db::LedgerEvent) -> Self {
+        let block = ledger_event.block;
+
+        LedgerEvent {
+            ledger: ledger_event.side.to_string(),
+            event: ledger_event.event,
+            transaction_id: ledger_event.transaction_id,
+            block_hash: block.as_ref().map(|block| block.block_hash.clone()),
+            block_height: block.as_ref().map(|block| block.block_height),
+            block_time: block.map(|block| block.block_time),
+            data: ledger_event.data,
+            timestamp: ledger_event.at,
+        }
+    }
+}
+
+pub async fn handle_get_events(
+    dependencies: Rfc003Facade,
+    id: SwapId,
+) -> anyhow::Result<Vec<LedgerEvent>> {
+    // Unknown swaps are an error rather than a swap without events.
+    Retrieve::get(&dependencies, &id).await?;
+
+    let events = dependencies.db.ledger_events(id).await?;
+
+    Ok(events.into_iter().map(LedgerEvent::from).collect())
+}
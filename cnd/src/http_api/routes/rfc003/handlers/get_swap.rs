@@ -1,6 +1,6 @@
 use crate::{
     db::{DetermineTypes, Retrieve},
-    http_api::swap_resource::{build_rfc003_siren_entity, IncludeState, OnFail},
+    http_api::swap_resource::{build_rfc003_siren_entity, IncludeState},
     swap_protocols::{rfc003::SwapId, Rfc003Facade},
 };
 
@@ -11,5 +11,5 @@ pub async fn handle_get_swap(
     let swap = Retrieve::get(&dependencies, &id).await?;
     let types = dependencies.determine_types(&id).await?;
 
-    build_rfc003_siren_entity(&dependencies, swap, types, IncludeState::Yes, OnFail::Error).await
+    build_rfc003_siren_entity(&dependencies, swap, types, IncludeState::Yes).await
 }
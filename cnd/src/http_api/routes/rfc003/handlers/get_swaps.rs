@@ -1,6 +1,6 @@
 use crate::{
     db::{DetermineTypes, Retrieve},
-    http_api::swap_resource::{build_rfc003_siren_entity, IncludeState, OnFail},
+    http_api::swap_resource::{build_rfc003_siren_entity, IncludeState},
     swap_protocols::Rfc003Facade,
 };
 
@@ -10,14 +10,8 @@ pub async fn handle_get_swaps(dependencies: Rfc003Facade) -> anyhow::Result<sire
     for swap in Retrieve::all(&dependencies).await?.into_iter() {
         let types = dependencies.determine_types(&swap.swap_id).await?;
 
-        let sub_entity = build_rfc003_siren_entity(
-            &dependencies,
-            swap,
-            types,
-            IncludeState::No,
-            OnFail::NoAction,
-        )
-        .await?;
+        let sub_entity =
+            build_rfc003_siren_entity(&dependencies, swap, types, IncludeState::No).await?;
         entity.push_sub_entity(siren::SubEntity::from_entity(sub_entity, &["item"]));
     }
 
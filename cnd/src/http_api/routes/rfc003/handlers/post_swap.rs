@@ -1,12 +1,15 @@
 use crate::{
-    db::{LoadAcceptedSwap, Save, Sqlite, Swap},
+    asset::AssetKind,
+    comit_api::LedgerKind,
+    db::{Database, LoadAcceptedSwap, Save, Swap},
     htlc_location,
     http_api::{HttpAsset, HttpLedger},
     identity,
     init_swap::init_accepted_swap,
-    network::{DialInformation, SendRequest},
+    network::{DialInformation, RequestError, SendRequest},
     seed::Rfc003DeriveSwapSeed,
     swap_protocols::{
+        expiries,
         rfc003::{
             self,
             events::{HtlcDeployed, HtlcFunded, HtlcRedeemed, HtlcRefunded},
@@ -32,7 +35,7 @@ async fn initiate_request<AL, BL, AA, BA, AH, BH, AI, BI, AT, BT>(
     swap_request: rfc003::Request<AL, BL, AA, BA, AI, BI>,
 ) -> anyhow::Result<()>
 where
-    Sqlite:
+    Database:
         Save<Request<AL, BL, AA, BA, AI, BI>> + Save<Accept<AI, BI>> + Save<Swap> + Save<Decline>,
     AL: Clone + Send + Sync + 'static,
     BL: Clone + Send + Sync + 'static,
@@ -48,6 +51,8 @@ where
     Accept<AI, BI>: Copy,
     rfc003::Request<AL, BL, AA, BA, AI, BI>: TryInto<OutboundRequest> + Clone,
     <rfc003::Request<AL, BL, AA, BA, AI, BI> as TryInto<OutboundRequest>>::Error: Debug,
+    LedgerKind: From<AL> + From<BL>,
+    AssetKind: From<AA> + From<BA>,
     Rfc003Facade: LoadAcceptedSwap<AL, BL, AA, BA, AI, BI>
         + HtlcFunded<AL, AA, AH, AI, AT>
         + HtlcFunded<BL, BA, BH, BI, BT>
@@ -60,6 +65,17 @@ where
 {
     tracing::trace!("initiating new request: {}", swap_request.swap_id);
 
+    rfc003::ensure_htlc_supports(
+        swap_request.hash_function,
+        &LedgerKind::from(swap_request.alpha_ledger.clone()),
+        &AssetKind::from(swap_request.alpha_asset.clone()),
+    )?;
+    rfc003::ensure_htlc_supports(
+        swap_request.hash_function,
+        &LedgerKind::from(swap_request.beta_ledger.clone()),
+        &AssetKind::from(swap_request.beta_asset.clone()),
+    )?;
+
     let counterparty = peer.peer_id.clone();
 
     Save::save(&dependencies, Swap::new(id, Role::Alice, counterparty)).await?;
@@ -81,10 +97,27 @@ where
 
     let future = {
         async move {
-            let response = dependencies
+            let response = match dependencies
                 .send_request(peer.clone(), swap_request.clone())
                 .await
-                .with_context(|| format!("Failed to send swap request to {}", peer.clone()))?;
+            {
+                Ok(response) => response,
+                Err(RequestError::Timeout) => {
+                    tracing::info!("Swap request timed out: {}", id);
+                    dependencies
+                        .insert(id, SwapCommunication::TimedOut {
+                            request: swap_request,
+                        })
+                        .await;
+
+                    return Ok(());
+                }
+                Err(e) => {
+                    return Err(e).with_context(|| {
+                        format!("Failed to send swap request to {}", peer.clone())
+                    })
+                }
+            };
 
             match response {
                 Ok(accept) => {
@@ -126,9 +159,19 @@ pub async fn handle_post_swap(
 ) -> anyhow::Result<SwapCreated> {
     let id = SwapId::default();
     let seed = dependencies.rfc003_derive_swap_seed(id);
-    let secret_hash = seed.derive_secret().hash();
 
-    let body = SwapRequestBody::deserialize(&body)?;
+    let mut body = SwapRequestBody::deserialize(&body)?;
+    let hash_function = body.hash_function;
+    let secret_hash = seed.derive_secret().hash_with(hash_function);
+
+    let alpha_expiry = *body.alpha_expiry.get_or_insert_with(default_alpha_expiry);
+    let beta_expiry = *body.beta_expiry.get_or_insert_with(default_beta_expiry);
+    expiries::validate(
+        &dependencies.expiries,
+        alpha_expiry,
+        beta_expiry,
+        Timestamp::now(),
+    )?;
 
     match body {
         SwapRequestBody {
@@ -140,6 +183,7 @@ pub async fn handle_post_swap(
             beta_expiry,
             identities,
             peer,
+            ..
         } => {
             let identities = identities.into_bitcoin_ethereum_identities(&seed)?;
             let request = new_request(
@@ -151,6 +195,7 @@ pub async fn handle_post_swap(
                 alpha_expiry,
                 beta_expiry,
                 identities,
+                hash_function,
                 secret_hash,
             );
             initiate_request::<
@@ -176,6 +221,7 @@ pub async fn handle_post_swap(
             beta_expiry,
             identities,
             peer,
+            ..
         } => {
             let identities = identities.into_bitcoin_ethereum_identities(&seed)?;
             let request = new_request(
@@ -187,6 +233,7 @@ pub async fn handle_post_swap(
                 alpha_expiry,
                 beta_expiry,
                 identities,
+                hash_function,
                 secret_hash,
             );
             initiate_request::<
@@ -212,6 +259,7 @@ pub async fn handle_post_swap(
             beta_expiry,
             identities,
             peer,
+            ..
         } => {
             let identities = identities.into_bitcoin_ethereum_identities(&seed)?;
             let request = new_request(
@@ -223,6 +271,7 @@ pub async fn handle_post_swap(
                 alpha_expiry,
                 beta_expiry,
                 identities,
+                hash_function,
                 secret_hash,
             );
             initiate_request::<
@@ -248,6 +297,7 @@ pub async fn handle_post_swap(
             beta_expiry,
             identities,
             peer,
+            ..
         } => {
             let identities = identities.into_ethereum_bitcoin_identities(&seed)?;
             let request = new_request(
@@ -259,6 +309,7 @@ pub async fn handle_post_swap(
                 alpha_expiry,
                 beta_expiry,
                 identities,
+                hash_function,
                 secret_hash,
             );
             initiate_request::<
@@ -284,6 +335,7 @@ pub async fn handle_post_swap(
             beta_expiry,
             identities,
             peer,
+            ..
         } => {
             let identities = identities.into_ethereum_bitcoin_identities(&seed)?;
             let request = new_request(
@@ -295,6 +347,7 @@ pub async fn handle_post_swap(
                 alpha_expiry,
                 beta_expiry,
                 identities,
+                hash_function,
                 secret_hash,
             );
             initiate_request::<
@@ -320,6 +373,7 @@ pub async fn handle_post_swap(
             beta_expiry,
             identities,
             peer,
+            ..
         } => {
             let identities = identities.into_ethereum_bitcoin_identities(&seed)?;
             let request = new_request(
@@ -331,6 +385,7 @@ pub async fn handle_post_swap(
                 alpha_expiry,
                 beta_expiry,
                 identities,
+                hash_function,
                 secret_hash,
             );
             initiate_request::<
@@ -356,6 +411,7 @@ pub async fn handle_post_swap(
             beta_expiry,
             identities,
             peer,
+            ..
         } => {
             let identities = identities.into_bitcoin_ethereum_identities(&seed)?;
             let request = new_request(
@@ -367,6 +423,7 @@ pub async fn handle_post_swap(
                 alpha_expiry,
                 beta_expiry,
                 identities,
+                hash_function,
                 secret_hash,
             );
             initiate_request::<
@@ -392,6 +449,7 @@ pub async fn handle_post_swap(
             beta_expiry,
             identities,
             peer,
+            ..
         } => {
             let identities = identities.into_bitcoin_ethereum_identities(&seed)?;
             let request = new_request(
@@ -403,6 +461,7 @@ pub async fn handle_post_swap(
                 alpha_expiry,
                 beta_expiry,
                 identities,
+                hash_function,
                 secret_hash,
             );
             initiate_request::<
@@ -428,6 +487,7 @@ pub async fn handle_post_swap(
             beta_expiry,
             identities,
             peer,
+            ..
         } => {
             let identities = identities.into_bitcoin_ethereum_identities(&seed)?;
             let request = new_request(
@@ -439,6 +499,7 @@ pub async fn handle_post_swap(
                 alpha_expiry,
                 beta_expiry,
                 identities,
+                hash_function,
                 secret_hash,
             );
             initiate_request::<
@@ -464,6 +525,7 @@ pub async fn handle_post_swap(
             beta_expiry,
             identities,
             peer,
+            ..
         } => {
             let identities = identities.into_ethereum_bitcoin_identities(&seed)?;
             let request = new_request(
@@ -475,6 +537,7 @@ pub async fn handle_post_swap(
                 alpha_expiry,
                 beta_expiry,
                 identities,
+                hash_function,
                 secret_hash,
             );
             initiate_request::<
@@ -500,6 +563,7 @@ pub async fn handle_post_swap(
             beta_expiry,
             identities,
             peer,
+            ..
         } => {
             let identities = identities.into_ethereum_bitcoin_identities(&seed)?;
             let request = new_request(
@@ -511,6 +575,7 @@ pub async fn handle_post_swap(
                 alpha_expiry,
                 beta_expiry,
                 identities,
+                hash_function,
                 secret_hash,
             );
             initiate_request::<
@@ -536,6 +601,7 @@ pub async fn handle_post_swap(
             beta_expiry,
             identities,
             peer,
+            ..
         } => {
             let identities = identities.into_ethereum_bitcoin_identities(&seed)?;
             let request = new_request(
@@ -547,6 +613,7 @@ pub async fn handle_post_swap(
                 alpha_expiry,
                 beta_expiry,
                 identities,
+                hash_function,
                 secret_hash,
             );
             initiate_request::<
@@ -587,6 +654,7 @@ fn new_request<AL, BL, AA, BA, AI, BI>(
     alpha_expiry: Option<Timestamp>,
     beta_expiry: Option<Timestamp>,
     identities: Identities<AI, BI>,
+    hash_function: HashFunction,
     secret_hash: SecretHash,
 ) -> rfc003::Request<AL, BL, AA, BA, AI, BI> {
     rfc003::Request {
@@ -595,7 +663,7 @@ fn new_request<AL, BL, AA, BA, AI, BI>(
         beta_ledger,
         alpha_asset,
         beta_asset,
-        hash_function: HashFunction::Sha256,
+        hash_function,
         alpha_ledger_refund_identity: identities.alpha_ledger_refund_identity,
         beta_ledger_redeem_identity: identities.beta_ledger_redeem_identity,
         alpha_expiry: alpha_expiry.unwrap_or_else(default_alpha_expiry),
@@ -634,6 +702,8 @@ struct SwapRequestBody {
     beta_ledger: HttpLedger,
     alpha_expiry: Option<Timestamp>,
     beta_expiry: Option<Timestamp>,
+    #[serde(default)]
+    hash_function: HashFunction,
     #[serde(flatten)]
     identities: HttpIdentities,
     peer: DialInformation,
@@ -800,6 +870,43 @@ mod tests {
         assert_that(&body).is_ok();
     }
 
+    #[test]
+    fn hash_function_of_swap_request_body_defaults_to_sha256() {
+        let body = r#"{
+                "alpha_ledger": {
+                    "name": "bitcoin",
+                    "network": "regtest"
+                },
+                "beta_ledger": {
+                    "name": "ethereum",
+                    "chain_id": 1337
+                },
+                "alpha_asset": {
+                    "name": "bitcoin",
+                    "quantity": "100000000"
+                },
+                "beta_asset": {
+                    "name": "ether",
+                    "quantity": "10000000000000000000"
+                },
+                "beta_ledger_redeem_identity": "0x00a329c0648769a73afac7f9381e08fb43dbea72",
+                "peer": "Qma9T5YraSnpRDZqRR4krcSJabThc8nwZuJV3LercPHufi"
+            }"#;
+        let with_hash160 = body.replace(r#""peer""#, r#""hash_function": "HASH160", "peer""#);
+
+        let body = serde_json::from_str::<SwapRequestBody>(body);
+        let with_hash160 = serde_json::from_str::<SwapRequestBody>(&with_hash160);
+
+        assert_that(&body)
+            .is_ok()
+            .map(|b| &b.hash_function)
+            .is_equal_to(&HashFunction::Sha256);
+        assert_that(&with_hash160)
+            .is_ok()
+            .map(|b| &b.hash_function)
+            .is_equal_to(&HashFunction::Hash160);
+    }
+
     #[test]
     fn given_peer_id_with_address_can_deserialize_swap_request_body() {
         let body = r#"{
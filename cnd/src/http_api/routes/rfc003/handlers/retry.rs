@@ -0,0 +1,26 @@
+#![allow(clippy::type_repetition_in_bounds)]
+use crate::{
+    db::{DetermineTypes, LoadAcceptedSwap},
+    init_swap::retry_accepted_swap,
+    swap_protocols::{rfc003::SwapId, Rfc003Facade},
+};
+
+#[allow(clippy::cognitive_complexity)]
+pub async fn handle_retry(dependencies: Rfc003Facade, id: SwapId) -> anyhow::Result<()> {
+    let sides = dependencies.swap_errors.sides_to_retry(id).await?;
+
+    let types = dependencies.determine_types(&id).await?;
+
+    with_swap_types!(types, {
+        for side in sides {
+            let accepted =
+                LoadAcceptedSwap::<AL, BL, AA, BA, AI, BI>::load_accepted_swap(&dependencies, &id)
+                    .await?;
+
+            retry_accepted_swap::<_, _, _, _, AH, BH, _, _, AT, BT>(&dependencies, accepted, side)
+                .await?;
+        }
+    });
+
+    Ok(())
+}
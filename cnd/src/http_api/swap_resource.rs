@@ -4,22 +4,26 @@ use crate::{
     db::{Swap, SwapTypes},
     http_api::{
         action::rfc003::ToSirenAction,
-        route_factory,
-        routes::rfc003::{LedgerState, SwapCommunication, SwapState},
+        problem, route_factory,
+        routes::rfc003::{
+            handlers::{fund_deadline, with_fee_bumps, without_funding, without_unsafe_funding},
+            LedgerState, SwapCommunication, SwapState,
+        },
         Http, HttpAsset, HttpLedger,
     },
     seed::Rfc003DeriveSwapSeed,
     swap_protocols::{
         actions::Actions,
+        can_retry,
+        expiries::LatestBlockTime,
         rfc003::{self, state::Get, SwapId},
         HashFunction, Rfc003Facade, SwapProtocol,
     },
 };
-use anyhow::anyhow;
 use http_api_problem::HttpApiProblem;
 use libp2p::PeerId;
 use serde::Serialize;
-use warp::http::StatusCode;
+use warp::http::{self, StatusCode};
 
 #[derive(Debug, Serialize)]
 pub struct SwapResource<S> {
@@ -31,6 +35,8 @@ pub struct SwapResource<S> {
     pub parameters: SwapParameters,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub state: Option<S>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<HttpApiProblem>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -39,6 +45,7 @@ pub struct SwapParameters {
     beta_ledger: HttpLedger,
     alpha_asset: HttpAsset,
     beta_asset: HttpAsset,
+    hash_function: HashFunction,
 }
 
 #[derive(Debug, Clone, Copy, Serialize, PartialEq)]
@@ -63,6 +70,7 @@ where
             alpha_asset: HttpAsset::from(request.alpha_asset),
             beta_ledger: HttpLedger::from(request.beta_ledger),
             beta_asset: HttpAsset::from(request.beta_asset),
+            hash_function: request.hash_function,
         }
     }
 }
@@ -72,12 +80,6 @@ pub enum IncludeState {
     No,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub enum OnFail {
-    Error,
-    NoAction,
-}
-
 // This is due to the introduction of a trust per Bitcoin network in the
 // `with_swap_types!` macro and can be iteratively improved
 #[allow(clippy::cognitive_complexity)]
@@ -86,18 +88,11 @@ pub async fn build_rfc003_siren_entity(
     swap: Swap,
     types: SwapTypes,
     include_state: IncludeState,
-    on_fail: OnFail,
 ) -> anyhow::Result<siren::Entity> {
     let id = swap.swap_id;
 
     with_swap_types!(types, {
-        let swap_has_failed = dependencies.swap_error_states.has_failed(&id).await;
-
-        if swap_has_failed && on_fail == OnFail::Error {
-            return Err(anyhow!(HttpApiProblem::with_title_and_type_from_status(
-                StatusCode::INTERNAL_SERVER_ERROR,
-            )));
-        }
+        let errors = dependencies.swap_errors.get(id).await;
 
         let swap_communication: rfc003::SwapCommunication<AL, BL, AA, BA, AI, BI> = dependencies
             .get(&id)
@@ -118,6 +113,7 @@ pub async fn build_rfc003_siren_entity(
         let alpha_ledger = LedgerState::from(alpha_ledger_state.clone());
         let beta_ledger = LedgerState::from(beta_ledger_state.clone());
         let parameters = SwapParameters::from(swap_communication.request().clone());
+        let hash_function = swap_communication.request().hash_function;
 
         let secret_source = dependencies.rfc003_derive_swap_seed(id);
 
@@ -128,19 +124,48 @@ pub async fn build_rfc003_siren_entity(
                 beta_ledger_state,
                 secret_source,
             );
-            state.actions()
+            let fund_deadline = fund_deadline(
+                types.role,
+                state.request(),
+                &dependencies.confirmation_targets,
+            );
+
+            let actions = match state.actions() {
+                Ok(actions) => actions,
+                Err(e) => {
+                    tracing::error!("cannot build the actions of swap {}: {:#}", id, e);
+                    Vec::new()
+                }
+            };
+            let actions = with_fee_bumps(actions, &dependencies.bitcoin_fee_estimator).await;
+
+            match LatestBlockTime::<BL>::latest_block_time(dependencies).await {
+                Ok(now) => without_unsafe_funding(actions, fund_deadline, now),
+                Err(e) => {
+                    tracing::warn!(
+                        "cannot tell whether funding swap {} is safe, hiding it: {:#}",
+                        id,
+                        e
+                    );
+                    without_funding(actions)
+                }
+            }
         };
 
-        let status = SwapStatus::new(
-            communication.status,
-            alpha_ledger.status,
-            beta_ledger.status,
-        );
+        let status = if errors.is_empty() {
+            SwapStatus::new(
+                communication.status,
+                alpha_ledger.status,
+                beta_ledger.status,
+            )
+        } else {
+            SwapStatus::InternalFailure
+        };
 
         let swap = SwapResource {
             id: Http(id),
             status,
-            protocol: Http(SwapProtocol::Rfc003(HashFunction::Sha256)),
+            protocol: Http(SwapProtocol::Rfc003(hash_function)),
             parameters,
             role: swap.role.to_string(),
             counterparty: Http(swap.counterparty),
@@ -152,6 +177,7 @@ pub async fn build_rfc003_siren_entity(
                 }),
                 IncludeState::No => None,
             },
+            errors: errors.iter().map(problem::swap_error).collect(),
         };
 
         let entity = siren::Entity::default()
@@ -174,8 +200,14 @@ pub async fn build_rfc003_siren_entity(
                 .with_class_member("protocol-spec"),
             );
 
-        if swap_has_failed && on_fail == OnFail::NoAction {
-            return Ok(entity);
+        // None of the regular actions are safe to execute while we are not watching the
+        // ledgers, the only way forward is to retry if that may help.
+        if !errors.is_empty() {
+            if !can_retry(&errors) {
+                return Ok(entity);
+            }
+
+            return Ok(entity.with_action(retry_action(&id)));
         }
 
         let entity = actions.into_iter().fold(entity, |acc, action| {
@@ -186,3 +218,15 @@ pub async fn build_rfc003_siren_entity(
         Ok(entity)
     })
 }
+
+fn retry_action(id: &SwapId) -> siren::Action {
+    siren::Action {
+        href: route_factory::new_action_link(id, "retry"),
+        name: "retry".to_owned(),
+        method: Some(http::Method::POST),
+        _type: None,
+        fields: vec![],
+        class: vec![],
+        title: None,
+    }
+}
@@ -2,14 +2,16 @@ use crate::{
     db::AcceptedSwap,
     swap_protocols::{
         rfc003::{
-            create_swap::{create_watcher, OngoingSwap},
+            create_swap::{create_watcher, History, HtlcParams, LedgerEvents, OngoingSwap},
             events::{HtlcDeployed, HtlcFunded, HtlcRedeemed, HtlcRefunded},
             state::Insert,
-            Accept, Request, SwapCommunication,
+            Accept, LedgerState, LedgerStates, Request, SwapCommunication, SwapId,
         },
-        Rfc003Facade,
+        Rfc003Facade, Side,
     },
 };
+use chrono::{NaiveDateTime, Utc};
+use std::sync::Arc;
 use tracing_futures::Instrument;
 
 #[allow(clippy::cognitive_complexity)]
@@ -25,7 +27,9 @@ where
         + HtlcRedeemed<AL, AA, AH, AI, AT>
         + HtlcRedeemed<BL, BA, BH, BI, BT>
         + HtlcRefunded<AL, AA, AH, AI, AT>
-        + HtlcRefunded<BL, BA, BH, BI, BT>,
+        + HtlcRefunded<BL, BA, BH, BI, BT>
+        + LedgerEvents<AA, AH, AT>
+        + LedgerEvents<BA, BH, BT>,
     AL: Clone + Send + Sync + 'static,
     BL: Clone + Send + Sync + 'static,
     AA: Ord + Clone + Send + Sync + 'static,
@@ -50,30 +54,154 @@ where
         .await;
 
     let swap = OngoingSwap::new(request, accept);
+    let failed_sides = dependencies.swap_errors.failed_sides(id).await;
 
     tracing::trace!("initialising accepted swap: {}", id);
 
-    tokio::task::spawn(
-        create_watcher::<_, _, _, _, AH, _, AT>(
-            dependencies.clone(),
+    // A side that failed before stays failed until the user asks us to retry it.
+    if failed_sides.contains(&Side::Alpha) {
+        dependencies
+            .alpha_ledger_states
+            .insert(id, LedgerState::<AA, AH, AT>::NotDeployed)
+            .await;
+    } else {
+        let history = dependencies.load_history(id, Side::Alpha).await?;
+        spawn_watcher::<_, _, AH, _, AT>(
+            dependencies,
             dependencies.alpha_ledger_states.clone(),
             id,
+            Side::Alpha,
             swap.alpha_htlc_params(),
             accepted_at,
-        )
-        .instrument(tracing::info_span!("alpha")),
-    );
+            history,
+        );
+    }
 
-    tokio::task::spawn(
-        create_watcher::<_, _, _, _, BH, _, BT>(
-            dependencies.clone(),
+    if failed_sides.contains(&Side::Beta) {
+        dependencies
+            .beta_ledger_states
+            .insert(id, LedgerState::<BA, BH, BT>::NotDeployed)
+            .await;
+    } else {
+        let history = dependencies.load_history(id, Side::Beta).await?;
+        spawn_watcher::<_, _, BH, _, BT>(
+            dependencies,
             dependencies.beta_ledger_states.clone(),
             id,
+            Side::Beta,
             swap.beta_htlc_params(),
             accepted_at,
-        )
-        .instrument(tracing::info_span!("beta")),
-    );
+            history,
+        );
+    }
 
     Ok(())
 }
+
+/// Restarts the watcher of a side of an accepted swap that previously failed.
+#[allow(clippy::cognitive_complexity)]
+pub async fn retry_accepted_swap<AL, BL, AA, BA, AH, BH, AI, BI, AT, BT>(
+    dependencies: &Rfc003Facade,
+    accepted: AcceptedSwap<AL, BL, AA, BA, AI, BI>,
+    side: Side,
+) -> anyhow::Result<()>
+where
+    Rfc003Facade: HtlcFunded<AL, AA, AH, AI, AT>
+        + HtlcFunded<BL, BA, BH, BI, BT>
+        + HtlcDeployed<AL, AA, AH, AI, AT>
+        + HtlcDeployed<BL, BA, BH, BI, BT>
+        + HtlcRedeemed<AL, AA, AH, AI, AT>
+        + HtlcRedeemed<BL, BA, BH, BI, BT>
+        + HtlcRefunded<AL, AA, AH, AI, AT>
+        + HtlcRefunded<BL, BA, BH, BI, BT>
+        + LedgerEvents<AA, AH, AT>
+        + LedgerEvents<BA, BH, BT>,
+    AL: Clone + Send + Sync + 'static,
+    BL: Clone + Send + Sync + 'static,
+    AA: Ord + Clone + Send + Sync + 'static,
+    BA: Ord + Clone + Send + Sync + 'static,
+    AH: Clone + Send + Sync + 'static,
+    BH: Clone + Send + Sync + 'static,
+    AI: Clone + Send + Sync + 'static,
+    BI: Clone + Send + Sync + 'static,
+    AT: Clone + Send + Sync + 'static,
+    BT: Clone + Send + Sync + 'static,
+{
+    let (request, accept, accepted_at) = accepted;
+    let id = request.swap_id;
+    let retried_at = Utc::now().naive_local();
+
+    tracing::info!("retrying {} watcher of swap {}", side, id);
+
+    let swap = OngoingSwap::new(request, accept);
+
+    match side {
+        Side::Alpha => {
+            let history = dependencies.load_history(id, side).await?;
+            spawn_watcher::<_, _, AH, _, AT>(
+                dependencies,
+                dependencies.alpha_ledger_states.clone(),
+                id,
+                side,
+                swap.alpha_htlc_params(),
+                accepted_at,
+                history,
+            )
+        }
+        Side::Beta => {
+            let history = dependencies.load_history(id, side).await?;
+            spawn_watcher::<_, _, BH, _, BT>(
+                dependencies,
+                dependencies.beta_ledger_states.clone(),
+                id,
+                side,
+                swap.beta_htlc_params(),
+                accepted_at,
+                history,
+            )
+        }
+    }
+
+    dependencies.swap_errors.clear(id, side, retried_at).await?;
+
+    Ok(())
+}
+
+fn spawn_watcher<L, A, H, I, T>(
+    dependencies: &Rfc003Facade,
+    ledger_states: Arc<LedgerStates>,
+    id: SwapId,
+    side: Side,
+    htlc_params: HtlcParams<L, A, I>,
+    accepted_at: NaiveDateTime,
+    history: History<A, H, T>,
+) where
+    Rfc003Facade: HtlcFunded<L, A, H, I, T>
+        + HtlcDeployed<L, A, H, I, T>
+        + HtlcRedeemed<L, A, H, I, T>
+        + HtlcRefunded<L, A, H, I, T>
+        + LedgerEvents<A, H, T>,
+    L: Clone + Send + Sync + 'static,
+    A: Ord + Clone + Send + Sync + 'static,
+    H: Clone + Send + Sync + 'static,
+    I: Clone + Send + Sync + 'static,
+    T: Clone + Send + Sync + 'static,
+{
+    let span = match side {
+        Side::Alpha => tracing::info_span!("alpha"),
+        Side::Beta => tracing::info_span!("beta"),
+    };
+
+    tokio::task::spawn(
+        create_watcher::<_, _, _, _, H, _, T>(
+            dependencies.clone(),
+            ledger_states,
+            id,
+            side,
+            htlc_params,
+            accepted_at,
+            history,
+        )
+        .instrument(span),
+    );
+}
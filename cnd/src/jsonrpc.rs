@@ -39,7 +39,9 @@ impl Client {
 
         match response {
             Response::Success { result } => Ok(result),
-            Response::Error { code, message } => Err(Error::JsonRpc { code, message }),
+            Response::Error {
+                error: ErrorObject { code, message },
+            } => Err(Error::JsonRpc { code, message }),
         }
     }
 }
@@ -63,11 +65,19 @@ impl<T> Request<T> {
     }
 }
 
+/// Nodes like bitcoind send both `result` and `error`, one of them `null`,
+/// hence the error is tried first: a `null` error does not deserialize.
 #[derive(serde::Deserialize, Debug)]
 #[serde(untagged)]
 pub enum Response<T> {
+    Error { error: ErrorObject },
     Success { result: T },
-    Error { code: i64, message: String },
+}
+
+#[derive(serde::Deserialize, Debug)]
+pub struct ErrorObject {
+    code: i64,
+    message: String,
 }
 
 pub fn serialize<T>(t: T) -> anyhow::Result<serde_json::Value>
@@ -78,3 +88,30 @@ where
 
     Ok(value)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_error_response() {
+        let response = serde_json::from_str::<Response<Option<String>>>(
+            r#"{"result":null,"error":{"code":-5,"message":"Transaction not in mempool"},"id":"1"}"#,
+        )
+        .unwrap();
+
+        assert!(matches!(response, Response::Error {
+            error: ErrorObject { code: -5, .. }
+        }));
+    }
+
+    #[test]
+    fn deserialize_null_result() {
+        let response = serde_json::from_str::<Response<Option<String>>>(
+            r#"{"result":null,"error":null,"id":"1"}"#,
+        )
+        .unwrap();
+
+        assert!(matches!(response, Response::Success { result: None }));
+    }
+}
@@ -30,6 +30,7 @@ pub mod libp2p_comit_ext;
 pub mod db;
 
 pub mod asset;
+pub mod backup;
 pub mod bitcoin;
 pub mod btsieve;
 pub mod comit_api;
@@ -39,6 +40,8 @@ pub mod http_api;
 pub mod init_swap;
 pub mod lightning;
 pub mod load_swaps;
+#[cfg(test)]
+pub mod mock_node;
 #[macro_use]
 pub mod network;
 #[cfg(test)]
@@ -130,4 +133,13 @@ pub fn lnd_dir() -> Option<PathBuf> {
     lnd_default_dir()
 }
 
+/// Returns the directory used by c-lightning, `~/.lightning` unless
+/// overridden.
+pub fn c_lightning_dir() -> Option<PathBuf> {
+    if let Ok(dir) = env::var("LIGHTNING_DIR") {
+        return Some(PathBuf::from(dir));
+    }
+    directories::UserDirs::new().map(|d| d.home_dir().to_path_buf().join(".lightning"))
+}
+
 pub type Never = std::convert::Infallible;
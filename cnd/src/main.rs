@@ -15,24 +15,30 @@
 use crate::cli::Options;
 use anyhow::Context;
 use cnd::{
+    backup,
+    bitcoin::fee_estimation::FeeEstimator,
     btsieve::{
         bitcoin::{self, BitcoindConnector},
         ethereum::{self, Web3Connector},
     },
-    config::{self, validation::validate_blockchain_config, Settings},
-    db::Sqlite,
+    config::{self, validation::validate_blockchain_config, LightningBackend, Settings},
+    db::Database,
+    ethereum::gas_estimation::GasEstimator,
     file_lock::TryLockExclusive,
     http_api::route_factory,
     jsonrpc, load_swaps,
     network::{Swarm, SwarmWorker},
     seed::RootSeed,
     swap_protocols::{
-        halight::States, rfc003, rfc003::SwapCommunicationStates, Facade, LedgerStates,
-        Rfc003Facade, SwapErrorStates,
+        halight::States, rfc003, rfc003::SwapCommunicationStates, EthereumHtlc, Facade,
+        LedgerStates, Rfc003Facade, SwapErrors,
     },
 };
 
-use cnd::swap_protocols::halight::LndConnectorParams;
+use cnd::swap_protocols::halight::{
+    CLightningConnectorParams, InvoiceCancellations, LightningConnectorParams, LndActionExecutor,
+    LndConnectorParams,
+};
 use rand::rngs::OsRng;
 use std::{process, sync::Arc};
 use structopt::StructOpt;
@@ -58,7 +64,12 @@ fn main() -> anyhow::Result<()> {
 
     crate::trace::init_tracing(settings.logging.level)?;
 
-    let database = Sqlite::new_in_dir(&settings.data.dir)?;
+    if let Some(command) = options.command {
+        run_command(command, &settings)?;
+        process::exit(0);
+    }
+
+    let database = Database::new(&settings.data)?;
 
     let seed = RootSeed::from_dir_or_generate(&settings.data.dir, OsRng)?;
 
@@ -71,7 +82,9 @@ fn main() -> anyhow::Result<()> {
         .build()?;
 
     let bitcoin_connector = {
-        let config::Bitcoin { bitcoind, network } = &settings.bitcoin;
+        let config::Bitcoin {
+            bitcoind, network, ..
+        } = &settings.bitcoin;
         let connector = BitcoindConnector::new(bitcoind.node_url.clone(), *network)?;
 
         runtime.block_on(async {
@@ -90,8 +103,15 @@ fn main() -> anyhow::Result<()> {
         Arc::new(bitcoin::Cache::new(connector, BITCOIN_BLOCK_CACHE_CAPACITY))
     };
 
+    let bitcoin_fee_estimator = Arc::new(FeeEstimator::new(
+        settings.bitcoin.bitcoind.node_url.clone(),
+        settings.bitcoin.fees,
+    ));
+
     let ethereum_connector = {
-        let config::Ethereum { parity, chain_id } = &settings.ethereum;
+        let config::Ethereum {
+            parity, chain_id, ..
+        } = &settings.ethereum;
         let connector = Web3Connector::new(parity.node_url.clone());
 
         runtime.block_on(async {
@@ -115,19 +135,58 @@ fn main() -> anyhow::Result<()> {
         ))
     };
 
-    let lnd_connector_params = LndConnectorParams::new(
-        settings.lightning.lnd.rest_api_url.clone(),
-        100,
-        settings.lightning.lnd.cert_path.clone(),
-        settings.lightning.lnd.readonly_macaroon_path.clone(),
-    )
-    .map_err(|err| {
-        tracing::warn!(
-            "Could not read initialise lnd configuration, halight will not be available: {:?}",
-            err
-        );
-    })
-    .ok();
+    let ethereum_gas_estimator = Arc::new(GasEstimator::new(
+        settings.ethereum.parity.node_url.clone(),
+        settings.ethereum.gas,
+    ));
+
+    let (lightning_connector_params, lnd_action_executor) = match settings.lightning.backend {
+        LightningBackend::Lnd => {
+            let lnd = &settings.lightning.lnd;
+            let lnd_connector_params = LndConnectorParams::new(
+                lnd.rest_api_url.clone(),
+                100,
+                lnd.cert_path.clone(),
+                if lnd.execute_actions {
+                    lnd.admin_macaroon_path.clone()
+                } else {
+                    lnd.readonly_macaroon_path.clone()
+                },
+            )
+            .map_err(|err| {
+                tracing::warn!(
+                    "Could not read initialise lnd configuration, halight will not be available: {:?}",
+                    err
+                );
+            })
+            .ok();
+            let lnd_action_executor = lnd_connector_params
+                .clone()
+                .filter(|_| lnd.execute_actions)
+                .map(LndActionExecutor::from);
+
+            (
+                lnd_connector_params.map(LightningConnectorParams::Lnd),
+                lnd_action_executor,
+            )
+        }
+        LightningBackend::CLightning => {
+            if settings.lightning.lnd.execute_actions {
+                tracing::warn!("execute_actions is only supported with lnd, ignoring it");
+            }
+            let c_lightning_connector_params = CLightningConnectorParams::new(
+                settings.lightning.c_lightning.rpc_socket.clone(),
+                100,
+            );
+
+            (
+                Some(LightningConnectorParams::CLightning(
+                    c_lightning_connector_params,
+                )),
+                None,
+            )
+        }
+    };
 
     // RCF003 protocol
     let rfc003_alpha_ledger_states = Arc::new(rfc003::LedgerStates::default());
@@ -141,20 +200,25 @@ fn main() -> anyhow::Result<()> {
     // HALight
     let halight_states = Arc::new(States::default());
 
-    let swap_error_states = Arc::new(SwapErrorStates::default());
+    let swap_errors = runtime.block_on(SwapErrors::load(database.clone()))?;
+
+    let invoice_cancellations = lnd_action_executor.clone().map(|executor| {
+        InvoiceCancellations::new(executor, Arc::clone(&halight_states), database.clone())
+    });
 
     let swarm = Swarm::new(
         &settings,
         seed,
         Arc::clone(&bitcoin_connector),
         Arc::clone(&ethereum_connector),
-        lnd_connector_params,
+        lightning_connector_params,
         Arc::clone(&swap_communication_states),
         Arc::clone(&rfc003_alpha_ledger_states),
         Arc::clone(&rfc003_beta_ledger_states),
         Arc::clone(&alpha_ledger_states),
         Arc::clone(&beta_ledger_states),
         Arc::clone(&halight_states),
+        swap_errors.clone(),
         &database,
         runtime.handle().clone(),
     )?;
@@ -162,11 +226,15 @@ fn main() -> anyhow::Result<()> {
     // RCF003 protocol
     let rfc003_facade = Rfc003Facade {
         bitcoin_connector,
-        ethereum_connector,
+        bitcoin_fee_estimator,
+        ethereum_connector: Arc::clone(&ethereum_connector),
+        ethereum_gas_estimator: Arc::clone(&ethereum_gas_estimator),
+        expiries: settings.expiries,
+        confirmation_targets: settings.confirmation_targets,
         alpha_ledger_states: Arc::clone(&rfc003_alpha_ledger_states),
         beta_ledger_states: Arc::clone(&&rfc003_beta_ledger_states),
         swap_communication_states,
-        swap_error_states,
+        swap_errors: swap_errors.clone(),
         seed,
         db: database,
         swarm: swarm.clone(),
@@ -177,11 +245,35 @@ fn main() -> anyhow::Result<()> {
         swarm: swarm.clone(),
         alpha_ledger_states: Arc::clone(&alpha_ledger_states),
         beta_ledger_states: Arc::clone(&halight_states),
+        swap_errors,
+        expiries: settings.expiries,
+        confirmation_targets: settings.confirmation_targets,
+        ethereum_connector,
+        ethereum_gas_estimator,
+        lnd_action_executor,
+        invoice_cancellations: invoice_cancellations.clone(),
+        ethereum_htlc: settings
+            .ethereum
+            .swap_registry
+            .map_or(EthereumHtlc::Deployed, EthereumHtlc::SwapRegistry),
     };
 
     let http_api_listener = runtime.block_on(bind_http_api_socket(&settings))?;
+    let admin_api_listener = match settings.http_api.admin_socket {
+        Some(socket) => Some(runtime.block_on(TcpListener::bind(socket))?),
+        None => None,
+    };
     runtime.block_on(load_swaps::load_swaps_from_database(rfc003_facade.clone()))?;
+    if let Some(invoice_cancellations) = invoice_cancellations {
+        runtime.block_on(invoice_cancellations.load())?;
+    }
 
+    if let Some(admin_api_listener) = admin_api_listener {
+        runtime.spawn(make_admin_api_worker(
+            settings.data.clone(),
+            admin_api_listener,
+        ));
+    }
     runtime.spawn(make_http_api_worker(
         settings,
         rfc003_facade,
@@ -242,6 +334,21 @@ async fn make_http_api_worker(
     }
 }
 
+/// Construct the worker that is going to process admin API requests.
+async fn make_admin_api_worker(data: config::Data, incoming_requests: tokio::net::TcpListener) {
+    let routes = route_factory::create_admin(data);
+
+    match incoming_requests.local_addr() {
+        Ok(socket) => {
+            tracing::info!("Starting admin HTTP server on {} ...", socket);
+            warp::serve(routes).serve_incoming(incoming_requests).await;
+        }
+        Err(e) => {
+            tracing::error!("Cannot start admin HTTP server because {:?}", e);
+        }
+    }
+}
+
 /// Construct the worker that is going to process network (i.e. COMIT)
 /// communication.
 async fn make_network_api_worker(swarm: Swarm) {
@@ -276,6 +383,29 @@ fn read_config(options: &Options) -> anyhow::Result<config::File> {
         .with_context(|| format!("failed to read config file {}", default_path.display()))
 }
 
+/// Run a subcommand instead of starting cnd.
+fn run_command(command: cli::Command, settings: &Settings) -> anyhow::Result<()> {
+    match command {
+        cli::Command::Backup { to, passphrase } => {
+            let dir = to.unwrap_or_else(|| backup::new_backup_dir(&settings.data.dir));
+
+            backup::create(&settings.data, &dir, &passphrase)?;
+
+            tracing::info!("Wrote backup to {}", dir.display());
+        }
+        cli::Command::Restore { from, passphrase } => {
+            std::fs::create_dir_all(&settings.data.dir)?;
+            let _locked_datadir = settings.data.dir.try_lock_exclusive()?;
+
+            backup::restore(&from, &settings.data, &passphrase)?;
+
+            tracing::info!("Restored backup from {}", from.display());
+        }
+    }
+
+    Ok(())
+}
+
 #[allow(clippy::print_stdout)] // Don't use the logger so its easier to cut'n'paste
 fn dump_config(settings: Settings) -> anyhow::Result<()> {
     let file = config::File::from(settings);
@@ -0,0 +1,114 @@
+//! Stand-ins for the nodes cnd talks to: JSON-RPC over HTTP (Ethereum and
+//! Bitcoin nodes), JSON-RPC over a unix socket (c-lightning) and REST (lnd).
+
+use serde_json::Value;
+use std::collections::HashMap;
+use warp::{http, path::FullPath, Filter};
+
+/// The error a mock node answers a JSON-RPC request with.
+#[derive(Clone, Copy, Debug)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: &'static str,
+}
+
+pub const METHOD_NOT_FOUND: RpcError = RpcError {
+    code: -32601,
+    message: "Method not found",
+};
+
+/// Answers JSON-RPC requests with the result stored for their method, unknown
+/// methods are not found.
+pub fn by_method(
+    results: HashMap<&'static str, Value>,
+) -> impl Fn(&Value) -> Result<Value, RpcError> + Clone + Send + Sync + 'static {
+    move |request| {
+        let method = request["method"].as_str().unwrap_or_default();
+
+        results.get(method).cloned().ok_or(METHOD_NOT_FOUND)
+    }
+}
+
+/// Serves JSON-RPC over HTTP, each request is answered by `answer`.
+pub fn json_rpc<F>(answer: F) -> reqwest::Url
+where
+    F: Fn(&Value) -> Result<Value, RpcError> + Clone + Send + Sync + 'static,
+{
+    let routes = warp::post()
+        .and(warp::body::json())
+        .map(move |request: Value| warp::reply::json(&response(&request, answer(&request))));
+    let (address, server) = warp::serve(routes).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    reqwest::Url::parse(&format!("http://{}", address)).expect("valid url")
+}
+
+/// Serves JSON-RPC on a unix socket the way c-lightning does: responses are
+/// terminated by an empty line.
+#[cfg(unix)]
+pub fn json_rpc_socket<F>(rpc_socket: &std::path::Path, answer: F)
+where
+    F: Fn(&Value) -> Result<Value, RpcError> + Send + 'static,
+{
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::UnixListener,
+    };
+
+    let mut listener = UnixListener::bind(rpc_socket).expect("bind unix socket");
+
+    tokio::spawn(async move {
+        while let Ok((mut stream, _)) = listener.accept().await {
+            let mut buffer = [0u8; 4096];
+            let read = stream.read(&mut buffer).await.unwrap_or(0);
+            let request = serde_json::from_slice(&buffer[..read]).unwrap_or(Value::Null);
+
+            let response = response(&request, answer(&request));
+            let _ = stream
+                .write_all(format!("{}\n\n", response).as_bytes())
+                .await;
+        }
+    });
+}
+
+/// Serves the given bodies by path, anything else is answered with 404.
+pub fn rest(responses: HashMap<String, String>) -> reqwest::Url {
+    rest_by(move |path| match responses.get(path) {
+        Some(body) => (http::StatusCode::OK, body.clone()),
+        None => (http::StatusCode::NOT_FOUND, String::new()),
+    })
+}
+
+/// Serves REST requests, each is answered by `answer` given its path.
+pub fn rest_by<F>(answer: F) -> reqwest::Url
+where
+    F: Fn(&str) -> (http::StatusCode, String) + Clone + Send + Sync + 'static,
+{
+    let routes = warp::path::full().map(move |path: FullPath| {
+        let (status, body) = answer(path.as_str());
+
+        http::Response::builder()
+            .status(status)
+            .body(body)
+            .expect("valid response")
+    });
+    let (address, server) = warp::serve(routes).bind_ephemeral(([127, 0, 0, 1], 0));
+    tokio::spawn(server);
+
+    reqwest::Url::parse(&format!("http://{}", address)).expect("valid url")
+}
+
+fn response(request: &Value, answer: Result<Value, RpcError>) -> Value {
+    match answer {
+        Ok(result) => serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "result": result,
+        }),
+        Err(RpcError { code, message }) => serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": request["id"],
+            "error": { "code": code, "message": message },
+        }),
+    }
+}
@@ -9,22 +9,27 @@ pub mod transport;
 pub use transport::ComitTransport;
 
 use crate::{
-    asset::AssetKind,
+    asset::{self, AssetKind},
     btsieve::{
         bitcoin::{self, BitcoindConnector},
         ethereum::{self, Web3Connector},
     },
     comit_api::LedgerKind,
-    config::Settings,
-    db::{Save, Sqlite, Swap},
+    config::{Expiries, Settings},
+    db::{Database, Save, Swap},
     htlc_location,
     http_api::LedgerNotConfigured,
+    identity,
     libp2p_comit_ext::{FromHeader, ToHeader},
     network::comit_ln::ComitLN,
     seed::RootSeed,
     swap_protocols::{
         halight,
-        halight::{LndConnectorAsReceiver, LndConnectorAsSender, LndConnectorParams, States},
+        halight::{
+            CLightningConnectorAsReceiver, CLightningConnectorAsSender, LightningConnectorParams,
+            LndConnectorAsReceiver, LndConnectorAsSender, States, WaitForAccepted,
+            WaitForCancelled, WaitForOpened, WaitForSettled,
+        },
         han, ledger,
         rfc003::{
             self,
@@ -33,8 +38,8 @@ use crate::{
             state::Insert,
             LedgerState, SwapCommunication, SwapCommunicationStates, SwapId,
         },
-        HanEtherereumHalightBitcoinCreateSwapParams, HashFunction, LedgerStates, LocalSwapId, Role,
-        SwapProtocol,
+        EthereumHtlc, HanEtherereumHalightBitcoinCreateSwapParams, HashFunction, LedgerStates,
+        LocalSwapId, Role, Side, SwapErrors, SwapProtocol,
     },
     transaction,
 };
@@ -42,8 +47,9 @@ use anyhow::Context;
 use async_trait::async_trait;
 use futures::{
     channel::oneshot::{self, Sender},
+    future::{self, Either},
     stream::StreamExt,
-    Future,
+    Future, TryFutureExt,
 };
 use libp2p::{
     identity::{ed25519, Keypair},
@@ -53,17 +59,18 @@ use libp2p::{
 };
 use libp2p_comit::{
     frame::{OutboundRequest, ValidatedInboundRequest},
-    BehaviourOutEvent, PendingInboundRequest, Rfc003Comit,
+    BehaviourOutEvent, Capabilities, PendingInboundRequest, Rfc003Comit,
 };
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{
-    collections::{HashMap, HashSet},
+    collections::HashMap,
     convert::{TryFrom, TryInto},
     fmt::{Debug, Display},
     io,
     pin::Pin,
     sync::Arc,
     task::{self, Poll},
+    time::Duration,
 };
 use tokio::{runtime::Handle, sync::Mutex};
 use tracing_futures::Instrument;
@@ -84,14 +91,15 @@ impl Swarm {
         seed: RootSeed,
         bitcoin_connector: Arc<bitcoin::Cache<BitcoindConnector>>,
         ethereum_connector: Arc<ethereum::Cache<Web3Connector>>,
-        lnd_connector_params: Option<LndConnectorParams>,
+        lightning_connector_params: Option<LightningConnectorParams>,
         swap_communication_states: Arc<SwapCommunicationStates>,
         rfc003_alpha_ledger_states: Arc<rfc003::LedgerStates>,
         rfc003_beta_ledger_states: Arc<rfc003::LedgerStates>,
         alpha_ledger_states: Arc<LedgerStates>,
         beta_ledger_states: Arc<LedgerStates>,
         halight_states: Arc<States>,
-        database: &Sqlite,
+        swap_errors: SwapErrors,
+        database: &Database,
         task_executor: tokio::runtime::Handle,
     ) -> anyhow::Result<Self> {
         let local_key_pair = derive_key_pair(&seed);
@@ -102,13 +110,16 @@ impl Swarm {
         let behaviour = ComitNode::new(
             bitcoin_connector,
             ethereum_connector,
-            lnd_connector_params,
+            lightning_connector_params,
+            settings.expiries,
+            Duration::from_secs(settings.network.request_timeout_secs),
             swap_communication_states,
             rfc003_alpha_ledger_states,
             rfc003_beta_ledger_states,
             alpha_ledger_states,
             beta_ledger_states,
             halight_states,
+            swap_errors,
             seed,
             database.clone(),
             task_executor.clone(),
@@ -149,6 +160,18 @@ impl Swarm {
         guard.get_finalized_swap(id)
     }
 
+    pub async fn get_refusal(&self, id: LocalSwapId) -> Option<comit_ln::Refusal> {
+        let guard = self.inner.lock().await;
+
+        guard.get_refusal(id)
+    }
+
+    pub async fn retry_failed_swap(&self, id: LocalSwapId, side: Side) -> anyhow::Result<()> {
+        let mut guard = self.inner.lock().await;
+
+        guard.retry_failed_swap(id, side)
+    }
+
     // On Bob's side, when an announce message is received execute the required
     // communication protocols and write the finalized swap to the database.  Then
     // spawn the same as is done for Alice.
@@ -211,12 +234,12 @@ pub struct ComitNode {
     #[behaviour(ignore)]
     pub ethereum_connector: Arc<ethereum::Cache<Web3Connector>>,
     #[behaviour(ignore)]
-    lnd_connector_params: Option<Arc<LndConnectorParams>>,
+    lightning_connector_params: Option<Arc<LightningConnectorParams>>,
 
     #[behaviour(ignore)]
     pub seed: RootSeed,
     #[behaviour(ignore)]
-    pub db: Sqlite,
+    pub db: Database,
     #[behaviour(ignore)]
     task_executor: Handle,
 
@@ -238,6 +261,9 @@ pub struct ComitNode {
 
     #[behaviour(ignore)]
     halight_states: Arc<States>,
+
+    #[behaviour(ignore)]
+    swap_errors: SwapErrors,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -265,6 +291,19 @@ pub enum RequestError {
     Connecting(io::ErrorKind),
     #[error("unable to send the data on the existing connection")]
     Connection,
+    #[error("peer node did not respond in time")]
+    Timeout,
+    #[error("peer node does not understand the request")]
+    NotUnderstood,
+}
+
+impl From<libp2p_comit::RequestError> for RequestError {
+    fn from(error: libp2p_comit::RequestError) -> Self {
+        match error {
+            libp2p_comit::RequestError::Timeout => RequestError::Timeout,
+            libp2p_comit::RequestError::Connection => RequestError::Connection,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, serde::Deserialize)]
@@ -277,32 +316,39 @@ impl ComitNode {
     pub fn new(
         bitcoin_connector: Arc<bitcoin::Cache<BitcoindConnector>>,
         ethereum_connector: Arc<ethereum::Cache<Web3Connector>>,
-        lnd_connector_params: Option<LndConnectorParams>,
+        lightning_connector_params: Option<LightningConnectorParams>,
+        expiries: Expiries,
+        request_timeout: Duration,
         swap_communication_states: Arc<SwapCommunicationStates>,
         rfc003_alpha_ledger_states: Arc<rfc003::LedgerStates>,
         rfc003_beta_ledger_states: Arc<rfc003::LedgerStates>,
         alpha_ledger_states: Arc<LedgerStates>,
         beta_ledger_states: Arc<LedgerStates>,
         halight_states: Arc<States>,
+        swap_errors: SwapErrors,
         seed: RootSeed,
-        db: Sqlite,
+        db: Database,
         task_executor: Handle,
     ) -> Result<Self, io::Error> {
-        let mut swap_headers = HashSet::new();
-        swap_headers.insert("id".into());
-        swap_headers.insert("alpha_ledger".into());
-        swap_headers.insert("beta_ledger".into());
-        swap_headers.insert("alpha_asset".into());
-        swap_headers.insert("beta_asset".into());
-        swap_headers.insert("protocol".into());
+        let capabilities = Capabilities::default().with_request_type("SWAP", vec![
+            "id",
+            "alpha_ledger",
+            "beta_ledger",
+            "alpha_asset",
+            "beta_asset",
+            "protocol",
+        ]);
 
-        let mut known_headers = HashMap::new();
-        known_headers.insert("SWAP".into(), swap_headers);
+        let lightning_connector_params = lightning_connector_params.map(Arc::new);
 
         Ok(Self {
-            rfc003_comit: Rfc003Comit::new(known_headers),
+            rfc003_comit: Rfc003Comit::new(capabilities).with_request_timeout(request_timeout),
             mdns: Mdns::new()?,
-            comit_ln: ComitLN::new(seed),
+            comit_ln: ComitLN::new(
+                seed,
+                lightning_connector_params.clone(),
+                expiries.min_gap_secs,
+            ),
             bitcoin_connector,
             ethereum_connector,
             rfc003_alpha_ledger_states,
@@ -314,8 +360,9 @@ impl ComitNode {
             db,
             response_channels: Arc::new(Mutex::new(HashMap::new())),
             task_executor,
-            lnd_connector_params: lnd_connector_params.map(Arc::new),
+            lightning_connector_params,
             halight_states,
+            swap_errors,
         })
     }
 
@@ -323,10 +370,28 @@ impl ComitNode {
         &mut self,
         peer_id: DialInformation,
         request: OutboundRequest,
-    ) -> impl futures::Future<Output = Result<libp2p_comit::frame::Response, ()>> + Send + 'static + Unpin
-    {
-        self.rfc003_comit
-            .send_request((peer_id.peer_id, peer_id.address_hint), request)
+    ) -> impl futures::Future<Output = Result<libp2p_comit::frame::Response, RequestError>>
+           + Send
+           + 'static
+           + Unpin {
+        // Peers that only speak version 1 never tell us their capabilities,
+        // they decline what they don't understand.
+        if let Some(capabilities) = self.rfc003_comit.peer_capabilities(&peer_id.peer_id) {
+            if !capabilities.understands(&request) {
+                tracing::warn!(
+                    "{} does not understand {} requests with our headers",
+                    peer_id.peer_id,
+                    request.request_type()
+                );
+                return Either::Left(future::ready(Err(RequestError::NotUnderstood)));
+            }
+        }
+
+        Either::Right(
+            self.rfc003_comit
+                .send_request((peer_id.peer_id, peer_id.address_hint), request)
+                .map_err(RequestError::from),
+        )
     }
 
     pub fn initiate_communication(
@@ -342,8 +407,127 @@ impl ComitNode {
         self.comit_ln.get_finalized_swap(id)
     }
 
+    pub fn get_refusal(&self, id: LocalSwapId) -> Option<comit_ln::Refusal> {
+        self.comit_ln.get_refusal(id)
+    }
+
+    /// Restarts the watcher of a side of a finalized swap that previously
+    /// failed.
+    pub fn retry_failed_swap(&mut self, id: LocalSwapId, side: Side) -> anyhow::Result<()> {
+        let swap = self
+            .get_finalized_swap(id)
+            .ok_or_else(|| anyhow::anyhow!("swap with id {} not found", id))?;
+
+        match side {
+            Side::Alpha => {
+                let htlc_params = HtlcParams {
+                    asset: swap.alpha_asset,
+                    ledger: ledger::Ethereum::default(),
+                    redeem_identity: swap.alpha_ledger_redeem_identity,
+                    refund_identity: swap.alpha_ledger_refund_identity,
+                    expiry: swap.alpha_expiry,
+                    hash_function: HashFunction::Sha256,
+                    secret_hash: swap.secret_hash,
+                };
+
+                self.spawn_han_watcher(id, htlc_params, swap.alpha_htlc, swap.role);
+            }
+            Side::Beta => self.spawn_halight_watcher(id, side, swap.halight_params(), swap.role)?,
+        }
+
+        Ok(())
+    }
+
+    fn spawn_han_watcher(
+        &self,
+        id: LocalSwapId,
+        htlc_params: HtlcParams<ledger::Ethereum, asset::Ether, identity::Ethereum>,
+        ethereum_htlc: EthereumHtlc,
+        role: Role,
+    ) {
+        tokio::task::spawn(han::new_han_ethereum_ether_swap(
+            id,
+            self.ethereum_connector.clone(),
+            self.alpha_ledger_states.clone(),
+            htlc_params,
+            ethereum_htlc,
+            role,
+            self.swap_errors.clone(),
+        ));
+    }
+
+    fn spawn_halight_watcher(
+        &self,
+        id: LocalSwapId,
+        side: Side,
+        halight_params: halight::Params,
+        role: Role,
+    ) -> anyhow::Result<()> {
+        let connector_params = self
+            .lightning_connector_params
+            .as_ref()
+            .ok_or_else(|| LedgerNotConfigured {
+                ledger: "lightning",
+            })?;
+
+        match (&**connector_params, role) {
+            (LightningConnectorParams::Lnd(params), Role::Alice) => {
+                let connector: LndConnectorAsReceiver = params.clone().into();
+                self.spawn_halight_connector(id, side, halight_params, role, connector);
+            }
+            (LightningConnectorParams::Lnd(params), Role::Bob) => {
+                let connector: LndConnectorAsSender = params.clone().into();
+                self.spawn_halight_connector(id, side, halight_params, role, connector);
+            }
+            (LightningConnectorParams::CLightning(params), Role::Alice) => {
+                let connector: CLightningConnectorAsReceiver = params.clone().into();
+                self.spawn_halight_connector(id, side, halight_params, role, connector);
+            }
+            (LightningConnectorParams::CLightning(params), Role::Bob) => {
+                let connector: CLightningConnectorAsSender = params.clone().into();
+                self.spawn_halight_connector(id, side, halight_params, role, connector);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn spawn_halight_connector<C>(
+        &self,
+        id: LocalSwapId,
+        side: Side,
+        params: halight::Params,
+        role: Role,
+        connector: C,
+    ) where
+        C: WaitForOpened
+            + WaitForAccepted
+            + WaitForSettled
+            + WaitForCancelled
+            + Send
+            + Sync
+            + 'static,
+    {
+        let span = match side {
+            Side::Alpha => tracing::error_span!("alpha_ledger", swap_id = %id, role = %role),
+            Side::Beta => tracing::error_span!("beta_ledger", swap_id = %id, role = %role),
+        };
+
+        tokio::task::spawn(
+            halight::new_halight_swap(
+                id,
+                side,
+                params,
+                self.halight_states.clone(),
+                connector,
+                self.swap_errors.clone(),
+            )
+            .instrument(span),
+        );
+    }
+
     fn supports_halight(&self) -> anyhow::Result<()> {
-        match self.lnd_connector_params {
+        match self.lightning_connector_params {
             Some(_) => Ok(()),
             None => Err(anyhow::Error::from(LedgerNotConfigured {
                 ledger: "lightning",
@@ -356,7 +540,7 @@ impl ComitNode {
 // iteratively improved
 #[allow(clippy::cognitive_complexity)]
 async fn handle_request(
-    db: Sqlite,
+    db: Database,
     swap_communication_states: Arc<SwapCommunicationStates>,
     alpha_ledger_states: Arc<rfc003::LedgerStates>,
     beta_ledger_states: Arc<rfc003::LedgerStates>,
@@ -384,6 +568,20 @@ async fn handle_request(
                         .take_header("beta_asset")
                         .map(AssetKind::from_header));
 
+                    if let Err(e) =
+                        rfc003::ensure_htlc_supports(hash_function, &alpha_ledger, &alpha_asset)
+                            .and_then(|_| {
+                                rfc003::ensure_htlc_supports(
+                                    hash_function,
+                                    &beta_ledger,
+                                    &beta_asset,
+                                )
+                            })
+                    {
+                        tracing::warn!("{}", e);
+                        return Err(decline_response(SwapDeclineReason::UnsupportedHashFunction));
+                    }
+
                     match (alpha_ledger, beta_ledger, alpha_asset, beta_asset) {
                         (
                             LedgerKind::BitcoinRegtest,
@@ -399,7 +597,7 @@ async fn handle_request(
                                 beta_asset,
                                 hash_function,
                                 body!(request.take_body_as()),
-                            );
+                            )?;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -438,7 +636,7 @@ async fn handle_request(
                                 beta_asset,
                                 hash_function,
                                 body!(request.take_body_as()),
-                            );
+                            )?;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -477,7 +675,7 @@ async fn handle_request(
                                 beta_asset,
                                 hash_function,
                                 body!(request.take_body_as()),
-                            );
+                            )?;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -516,7 +714,7 @@ async fn handle_request(
                                 beta_asset,
                                 hash_function,
                                 body!(request.take_body_as()),
-                            );
+                            )?;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -555,7 +753,7 @@ async fn handle_request(
                                 beta_asset,
                                 hash_function,
                                 body!(request.take_body_as()),
-                            );
+                            )?;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -594,7 +792,7 @@ async fn handle_request(
                                 beta_asset,
                                 hash_function,
                                 body!(request.take_body_as()),
-                            );
+                            )?;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -633,7 +831,7 @@ async fn handle_request(
                                 beta_asset,
                                 hash_function,
                                 body!(request.take_body_as()),
-                            );
+                            )?;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -673,7 +871,7 @@ async fn handle_request(
                                 beta_asset,
                                 hash_function,
                                 body!(request.take_body_as()),
-                            );
+                            )?;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -713,7 +911,7 @@ async fn handle_request(
                                 beta_asset,
                                 hash_function,
                                 body!(request.take_body_as()),
-                            );
+                            )?;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -753,7 +951,7 @@ async fn handle_request(
                                 beta_asset,
                                 hash_function,
                                 body!(request.take_body_as()),
-                            );
+                            )?;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -792,7 +990,7 @@ async fn handle_request(
                                 beta_asset,
                                 hash_function,
                                 body!(request.take_body_as()),
-                            );
+                            )?;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -831,7 +1029,7 @@ async fn handle_request(
                                 beta_asset,
                                 hash_function,
                                 body!(request.take_body_as()),
-                            );
+                            )?;
                             insert_state_for_bob::<
                                 _,
                                 _,
@@ -861,20 +1059,7 @@ async fn handle_request(
                                     "swapping {:?} to {:?} from {:?} to {:?} is currently not supported", alpha_asset, beta_asset, alpha_ledger, beta_ledger
                                 );
 
-                            let decline_body = DeclineResponseBody {
-                                reason: Some(SwapDeclineReason::UnsupportedSwap),
-                            };
-
-                            Err(libp2p_comit::frame::Response::empty()
-                                .with_header(
-                                    "decision",
-                                    Decision::Declined
-                                        .to_header()
-                                        .expect("Decision should not fail to serialize"),
-                                )
-                                .with_body(serde_json::to_value(decline_body).expect(
-                                    "decline body should always serialize into serde_json::Value",
-                                )))
+                            Err(decline_response(SwapDeclineReason::UnsupportedSwap))
                         }
                     }
                 }
@@ -1108,11 +1293,11 @@ impl SendRequest for Swarm {
             }
             Err(e) => {
                 tracing::error!(
-                    "Unable to request over connection {:?}:{:?}",
+                    "Unable to request over connection {:?}: {}",
                     dial_information,
                     e
                 );
-                Err(RequestError::Connection)
+                Err(e)
             }
         }
     }
@@ -1174,82 +1359,48 @@ impl libp2p::swarm::NetworkBehaviourEventProcess<comit_ln::BehaviourOutEvent> fo
             } => {
                 let role = create_swap_params.role;
 
-                match self.lnd_connector_params {
-                    None => { tracing::error!("Internal Failure: lnd connectors are not initialised, no action has been taken. This should be unreachable.") }
-                    Some(ref lnd_connector_params) => {
-                        match role {
-                            Role::Alice => {
-                                tokio::task::spawn({
-                                    let lnd_connector: LndConnectorAsReceiver = (**lnd_connector_params).clone().into();
-                                    halight::new_halight_swap(local_swap_id, secret_hash, self.halight_states.clone(), lnd_connector)
-                                        .instrument(
-                                            tracing::error_span!("beta_ledger", swap_id = %local_swap_id, role = %role),
-                                        )
-                                });
-
-                                tokio::task::spawn({
-                                    let connector = self.ethereum_connector.clone();
-                                    let alice_ethereum_identity = create_swap_params.ethereum_identity;
-                                    let bob_ethereum_identity = ethereum_identity;
-
-                                    let asset = create_swap_params.ethereum_amount.clone();
-                                    let ledger = ledger::Ethereum::default();
-                                    let expiry = create_swap_params.ethereum_absolute_expiry;
-
-                                    han::new_han_ethereum_ether_swap(
-                                        local_swap_id,
-                                        connector,
-                                        self.alpha_ledger_states.clone(),
-                                        HtlcParams {
-                                            asset,
-                                            ledger,
-                                            redeem_identity: bob_ethereum_identity,
-                                            refund_identity: alice_ethereum_identity.into(),
-                                            expiry,
-                                            secret_hash,
-                                        },
-                                        role,
-                                    )
-                                });
-                            }
-
-                            Role::Bob => {
-                                tokio::task::spawn({
-                                    let lnd_connector: LndConnectorAsSender = (**lnd_connector_params).clone().into();
-                                    self::halight::new_halight_swap(local_swap_id, secret_hash, self.halight_states.clone(), lnd_connector)
-                                        .instrument(
-                                            tracing::error_span!("beta_ledger", swap_id = %local_swap_id, role = %role),
-                                        )
-                                });
-
-                                tokio::task::spawn({
-                                    let connector = self.ethereum_connector.clone();
-                                    let alice_ethereum_identity = ethereum_identity;
-                                    let bob_ethereum_identity = create_swap_params.ethereum_identity;
-
-                                    let asset = create_swap_params.ethereum_amount.clone();
-                                    let ledger = ledger::Ethereum::default();
-                                    let expiry = create_swap_params.ethereum_absolute_expiry;
-
-                                    self::han::new_han_ethereum_ether_swap(
-                                        local_swap_id,
-                                        connector,
-                                        self.alpha_ledger_states.clone(),
-                                        HtlcParams {
-                                            asset,
-                                            ledger,
-                                            redeem_identity: bob_ethereum_identity.into(),
-                                            refund_identity: alice_ethereum_identity,
-                                            expiry,
-                                            secret_hash,
-                                        },
-                                        role,
-                                    )
-                                });
-                            }
-                        }
+                let (redeem_identity, refund_identity): (identity::Ethereum, identity::Ethereum) =
+                    match role {
+                        Role::Alice => (
+                            ethereum_identity,
+                            create_swap_params.ethereum_identity.into(),
+                        ),
+                        Role::Bob => (
+                            create_swap_params.ethereum_identity.into(),
+                            ethereum_identity,
+                        ),
+                    };
+                let htlc_params = HtlcParams {
+                    asset: create_swap_params.ethereum_amount.clone(),
+                    ledger: ledger::Ethereum::default(),
+                    redeem_identity,
+                    refund_identity,
+                    expiry: create_swap_params.ethereum_absolute_expiry,
+                    hash_function: HashFunction::Sha256,
+                    secret_hash,
+                };
+
+                let halight_params = match self.get_finalized_swap(local_swap_id) {
+                    Some(swap) => swap.halight_params(),
+                    None => {
+                        tracing::error!("Internal Failure: swap {} was finalized but its parameters are incomplete, no action has been taken.", local_swap_id);
+                        return;
                     }
+                };
+
+                // Han-halight swaps always have the Lightning payment on the beta ledger.
+                if let Err(e) =
+                    self.spawn_halight_watcher(local_swap_id, Side::Beta, halight_params, role)
+                {
+                    tracing::error!("Internal Failure: {:?}, no action has been taken. This should be unreachable.", e);
+                    return;
                 }
+                self.spawn_han_watcher(
+                    local_swap_id,
+                    htlc_params,
+                    create_swap_params.ethereum_htlc,
+                    role,
+                );
             }
         }
     }
@@ -1296,6 +1447,24 @@ impl<AL, BL, AA, BA, AI, BI> From<Request<AL, BL, AA, BA, AI, BI>> for RequestBo
     }
 }
 
+fn decline_response(reason: SwapDeclineReason) -> libp2p_comit::frame::Response {
+    let decline_body = DeclineResponseBody {
+        reason: Some(reason),
+    };
+
+    libp2p_comit::frame::Response::empty()
+        .with_header(
+            "decision",
+            Decision::Declined
+                .to_header()
+                .expect("Decision should not fail to serialize"),
+        )
+        .with_body(
+            serde_json::to_value(decline_body)
+                .expect("decline body should always serialize into serde_json::Value"),
+        )
+}
+
 fn rfc003_swap_request<AL, BL, AA, BA, AI, BI>(
     id: SwapId,
     alpha_ledger: AL,
@@ -1304,8 +1473,17 @@ fn rfc003_swap_request<AL, BL, AA, BA, AI, BI>(
     beta_asset: BA,
     hash_function: HashFunction,
     body: rfc003::messages::RequestBody<AI, BI>,
-) -> rfc003::Request<AL, BL, AA, BA, AI, BI> {
-    rfc003::Request {
+) -> Result<rfc003::Request<AL, BL, AA, BA, AI, BI>, libp2p_comit::frame::Response> {
+    if body.secret_hash.as_bytes().len() != hash_function.digest_length() {
+        tracing::error!(
+            "secret hash {} is not a {} hash",
+            body.secret_hash,
+            hash_function
+        );
+        return Err(decline_response(SwapDeclineReason::BadJsonField));
+    }
+
+    Ok(rfc003::Request {
         swap_id: id,
         alpha_asset,
         beta_asset,
@@ -1317,5 +1495,5 @@ fn rfc003_swap_request<AL, BL, AA, BA, AI, BI>(
         alpha_expiry: body.alpha_expiry,
         beta_expiry: body.beta_expiry,
         secret_hash: body.secret_hash,
-    }
+    })
 }
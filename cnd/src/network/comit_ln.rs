@@ -5,29 +5,33 @@ use crate::{
         protocols::{
             announce,
             announce::{behaviour::Announce, SwapDigest},
-            ethereum_identity, finalize, lightning_identity, secret_hash,
+            ethereum_identity, finalize, lightning_identity, refuse, secret_hash,
         },
     },
     seed::{DeriveSwapSeed, RootSeed},
     swap_protocols::{
+        cltv_budget::BlockDelta,
+        halight::{self, LightningConnectorParams},
         ledger::{ethereum::ChainId, lightning, Ethereum},
-        rfc003::{create_swap::HtlcParams, DeriveSecret, Secret, SecretHash},
-        HanEtherereumHalightBitcoinCreateSwapParams, LocalSwapId, Role, SharedSwapId,
+        rfc003::{create_swap::HtlcParams, DeriveSecret, FromErr, Secret, SecretHash},
+        swap_registry, EthereumHtlc, HanEtherereumHalightBitcoinCreateSwapParams, HashFunction,
+        LocalSwapId, Role, SharedSwapId,
     },
     timestamp::Timestamp,
 };
 use blockchain_contracts::ethereum::rfc003::ether_htlc::EtherHtlc;
-use digest::Digest;
-use futures::AsyncWriteExt;
+use futures::{channel::mpsc, AsyncWriteExt, StreamExt};
 use libp2p::{
     swarm::{
         NetworkBehaviour, NetworkBehaviourAction, NetworkBehaviourEventProcess, PollParameters,
     },
-    NetworkBehaviour,
+    NetworkBehaviour, PeerId,
 };
 use std::{
     collections::{HashMap, VecDeque},
+    convert::TryInto,
     fmt,
+    sync::Arc,
     task::{Context, Poll},
 };
 
@@ -42,6 +46,31 @@ pub enum BehaviourOutEvent {
     },
 }
 
+/// A swap that was not finalized because one of the parties refused to take
+/// part in it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Refusal {
+    pub refused_by: RefusedBy,
+    pub reason: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefusedBy {
+    /// Our Lightning node cannot take part in the payment of the swap.
+    Us,
+    Counterparty,
+}
+
+/// The outcome of checking whether our Lightning node can safely take part in
+/// the payment of a swap.
+#[derive(Debug)]
+struct PaymentChecked {
+    peer: PeerId,
+    swap_id: SharedSwapId,
+    result: anyhow::Result<()>,
+}
+
 #[derive(NetworkBehaviour, Debug)]
 #[behaviour(out_event = "BehaviourOutEvent", poll_method = "poll")]
 pub struct ComitLN {
@@ -50,6 +79,7 @@ pub struct ComitLN {
     ethereum_identity: oneshot_behaviour::Behaviour<ethereum_identity::Message>,
     lightning_identity: oneshot_behaviour::Behaviour<lightning_identity::Message>,
     finalize: oneshot_behaviour::Behaviour<finalize::Message>,
+    refuse: oneshot_behaviour::Behaviour<refuse::Message>,
 
     #[behaviour(ignore)]
     events: VecDeque<BehaviourOutEvent>,
@@ -68,6 +98,16 @@ pub struct ComitLN {
     communication_state: HashMap<SharedSwapId, CommunicationState>,
     #[behaviour(ignore)]
     secret_hashes: HashMap<SharedSwapId, SecretHash>,
+    #[behaviour(ignore)]
+    refusals: HashMap<LocalSwapId, Refusal>,
+    #[behaviour(ignore)]
+    lightning_connector_params: Option<Arc<LightningConnectorParams>>,
+    #[behaviour(ignore)]
+    min_margin_secs: u32,
+    #[behaviour(ignore)]
+    payment_checks_sender: mpsc::UnboundedSender<PaymentChecked>,
+    #[behaviour(ignore)]
+    payment_checks: mpsc::UnboundedReceiver<PaymentChecked>,
 
     #[behaviour(ignore)]
     pub seed: RootSeed,
@@ -83,13 +123,20 @@ struct CommunicationState {
 }
 
 impl ComitLN {
-    pub fn new(seed: RootSeed) -> Self {
+    pub fn new(
+        seed: RootSeed,
+        lightning_connector_params: Option<Arc<LightningConnectorParams>>,
+        min_margin_secs: u32,
+    ) -> Self {
+        let (payment_checks_sender, payment_checks) = mpsc::unbounded();
+
         ComitLN {
             announce: Default::default(),
             secret_hash: Default::default(),
             ethereum_identity: Default::default(),
             lightning_identity: Default::default(),
             finalize: Default::default(),
+            refuse: Default::default(),
             events: VecDeque::new(),
             swaps_waiting_for_announcement: Default::default(),
             swaps: Default::default(),
@@ -98,16 +145,123 @@ impl ComitLN {
             lightning_identities: Default::default(),
             communication_state: Default::default(),
             secret_hashes: Default::default(),
+            refusals: Default::default(),
+            lightning_connector_params,
+            min_margin_secs,
+            payment_checks_sender,
+            payment_checks,
             seed,
         }
     }
 
+    /// Asks our Lightning node whether it can take part in the payment of the
+    /// swap and whether the CLTVs along the route leave at least
+    /// `min_margin_secs` before the Ethereum HTLC expires. If not, the swap is
+    /// refused before any funds are locked.
+    fn check_payment_and_finalize(&mut self, peer: PeerId, swap_id: SharedSwapId) {
+        let connector_params = match &self.lightning_connector_params {
+            Some(connector_params) => connector_params.clone(),
+            None => {
+                self.finalize.send(peer, finalize::Message::new(swap_id));
+                return;
+            }
+        };
+
+        let (role, alpha_expiry, params) = match self.halight_params(swap_id) {
+            Some(halight_params) => halight_params,
+            None => {
+                tracing::warn!("cannot check the payment of unknown swap {}", swap_id);
+                return;
+            }
+        };
+        let min_margin_secs = self.min_margin_secs;
+        let sender = self.payment_checks_sender.clone();
+
+        tokio::task::spawn(async move {
+            let result = connector_params
+                .check_payment(role, params, alpha_expiry, min_margin_secs)
+                .await;
+            let _ = sender.unbounded_send(PaymentChecked {
+                peer,
+                swap_id,
+                result,
+            });
+        });
+    }
+
+    fn halight_params(&self, swap_id: SharedSwapId) -> Option<(Role, Timestamp, halight::Params)> {
+        let local_swap_id = self.local_swap_id(swap_id)?;
+        let create_swap_params = self.swaps.get(&local_swap_id)?;
+        let params = halight::Params {
+            secret_hash: *self.secret_hashes.get(&swap_id)?,
+            amount: create_swap_params.lightning_amount,
+            cltv_expiry: create_swap_params.lightning_cltv_expiry.into(),
+            counterparty: *self.lightning_identities.get(&swap_id)?,
+        };
+
+        Some((
+            create_swap_params.role,
+            create_swap_params.ethereum_absolute_expiry,
+            params,
+        ))
+    }
+
+    /// Lets the counterparty know that we do not take part in the swap
+    /// instead of finalizing it.
+    fn refuse(&mut self, peer: PeerId, swap_id: SharedSwapId, error: anyhow::Error) {
+        tracing::warn!("refusing swap {}: {:#}", swap_id, error);
+
+        let reason = format!("{:#}", error);
+        self.refuse
+            .send(peer, refuse::Message::new(swap_id, reason.clone()));
+
+        if let Some(local_swap_id) = self.local_swap_id(swap_id) {
+            self.refusals.insert(local_swap_id, Refusal {
+                refused_by: RefusedBy::Us,
+                reason,
+            });
+        }
+    }
+
+    pub fn get_refusal(&self, swap_id: LocalSwapId) -> Option<Refusal> {
+        self.refusals.get(&swap_id).cloned()
+    }
+
+    /// Checks the payment once we exchanged everything needed to finalize the
+    /// swap.
+    fn finalize_if_done(&mut self, peer: PeerId, swap_id: SharedSwapId) {
+        let done = match self.communication_state.get(&swap_id) {
+            Some(state) => {
+                self.ethereum_identities.contains_key(&swap_id)
+                    && self.lightning_identities.contains_key(&swap_id)
+                    && state.lightning_identity_sent
+                    && state.ethereum_identity_sent
+                    && state.secret_hash_sent_or_received
+            }
+            None => {
+                tracing::warn!("no communication state for swap {}", swap_id);
+                false
+            }
+        };
+
+        if done {
+            self.check_payment_and_finalize(peer, swap_id);
+        }
+    }
+
+    fn local_swap_id(&self, swap_id: SharedSwapId) -> Option<LocalSwapId> {
+        self.swap_ids
+            .iter()
+            .find_map(|(key, value)| if *value == swap_id { Some(key) } else { None })
+            .copied()
+    }
+
     pub fn initiate_communication(
         &mut self,
         id: LocalSwapId,
         create_swap_params: HanEtherereumHalightBitcoinCreateSwapParams,
     ) -> anyhow::Result<()> {
-        let digest = create_swap_params.clone().digest();
+        let digest = create_swap_params.clone().swap_digest();
 
         if self.swaps_waiting_for_announcement.contains_key(&digest) {
             anyhow::bail!(SwapExists)
@@ -185,6 +339,7 @@ impl ComitLN {
             beta_ledger_refund_identity,
             alpha_expiry: create_swap_params.ethereum_absolute_expiry,
             beta_expiry: create_swap_params.lightning_cltv_expiry,
+            alpha_htlc: create_swap_params.ethereum_htlc,
             swap_id,
             secret,
             secret_hash: match self.secret_hashes.get(&id).copied() {
@@ -197,9 +352,31 @@ impl ComitLN {
 
     fn poll<BIE>(
         &mut self,
-        _cx: &mut Context<'_>,
+        cx: &mut Context<'_>,
         _params: &mut impl PollParameters,
     ) -> Poll<NetworkBehaviourAction<BIE, BehaviourOutEvent>> {
+        while let Poll::Ready(Some(checked)) = self.payment_checks.poll_next_unpin(cx) {
+            let refused = self
+                .local_swap_id(checked.swap_id)
+                .map_or(false, |local_swap_id| {
+                    self.refusals.contains_key(&local_swap_id)
+                });
+
+            match checked.result {
+                Ok(()) if refused => {
+                    tracing::info!("not finalizing refused swap {}", checked.swap_id)
+                }
+                Ok(()) => self
+                    .finalize
+                    .send(checked.peer, finalize::Message::new(checked.swap_id)),
+                Err(error) => self.refuse(checked.peer, checked.swap_id, error),
+            }
+
+            // The finalize and refuse behaviours have already been polled, make
+            // sure we get polled again so that they send the message.
+            cx.waker().wake_by_ref();
+        }
+
         if let Some(event) = self.events.pop_front() {
             return Poll::Ready(NetworkBehaviourAction::GenerateEvent(event));
         }
@@ -231,7 +408,8 @@ pub struct FinalizedSwap {
     pub beta_ledger_refund_identity: identity::Lightning,
     pub beta_ledger_redeem_identity: identity::Lightning,
     pub alpha_expiry: Timestamp,
-    pub beta_expiry: Timestamp,
+    pub beta_expiry: BlockDelta,
+    pub alpha_htlc: EthereumHtlc,
     pub swap_id: LocalSwapId,
     pub secret_hash: SecretHash,
     pub secret: Option<Secret>,
@@ -239,16 +417,49 @@ pub struct FinalizedSwap {
 }
 
 impl FinalizedSwap {
-    pub fn han_params(&self) -> EtherHtlc {
+    /// Fails if the secret hash of the swap is not 32 bytes long.
+    pub fn han_params(&self) -> Result<EtherHtlc, FromErr> {
+        self.alpha_htlc_params().try_into()
+    }
+
+    /// The swap as it is initiated in the swap registry.
+    pub fn swap_registry_entry(&self) -> swap_registry::Entry {
+        self.alpha_htlc_params().into()
+    }
+
+    fn alpha_htlc_params(&self) -> HtlcParams<Ethereum, asset::Ether, identity::Ethereum> {
         HtlcParams {
             asset: self.alpha_asset.clone(),
             ledger: Ethereum::new(ChainId::regtest()),
             redeem_identity: self.alpha_ledger_redeem_identity,
             refund_identity: self.alpha_ledger_refund_identity,
             expiry: self.alpha_expiry,
+            hash_function: HashFunction::Sha256,
+            secret_hash: self.secret_hash,
+        }
+    }
+
+    /// The Ethereum identity of the user, the sender of their Ethereum
+    /// transactions.
+    pub fn ethereum_identity(&self) -> identity::Ethereum {
+        match self.role {
+            Role::Alice => self.alpha_ledger_refund_identity,
+            Role::Bob => self.alpha_ledger_redeem_identity,
+        }
+    }
+
+    pub fn halight_params(&self) -> halight::Params {
+        let counterparty = match self.role {
+            Role::Alice => self.beta_ledger_refund_identity,
+            Role::Bob => self.beta_ledger_redeem_identity,
+        };
+
+        halight::Params {
             secret_hash: self.secret_hash,
+            amount: self.beta_asset,
+            cltv_expiry: self.beta_expiry.into(),
+            counterparty,
         }
-        .into()
     }
 }
 
@@ -266,13 +477,6 @@ impl NetworkBehaviourEventProcess<oneshot_behaviour::OutEvent<secret_hash::Messa
                 self.secret_hashes
                     .insert(swap_id, SecretHash::from(secret_hash));
 
-                let state = self
-                    .communication_state
-                    .get_mut(&swap_id)
-                    .expect("must exist");
-
-                state.secret_hash_sent_or_received = true;
-
                 (peer, swap_id)
             }
             oneshot_behaviour::OutEvent::Sent {
@@ -286,28 +490,19 @@ impl NetworkBehaviourEventProcess<oneshot_behaviour::OutEvent<secret_hash::Messa
                 self.secret_hashes
                     .insert(swap_id, SecretHash::from(secret_hash));
 
-                let state = self
-                    .communication_state
-                    .get_mut(&swap_id)
-                    .expect("should exist");
-
-                state.secret_hash_sent_or_received = true;
-
                 (peer, swap_id)
             }
         };
 
-        let state = self.communication_state.get(&swap_id).unwrap();
-
-        // check if we are done
-        if self.ethereum_identities.contains_key(&swap_id)
-            && self.lightning_identities.contains_key(&swap_id)
-            && state.lightning_identity_sent
-            && state.ethereum_identity_sent
-            && state.secret_hash_sent_or_received
-        {
-            self.finalize.send(peer, finalize::Message::new(swap_id));
+        match self.communication_state.get_mut(&swap_id) {
+            Some(state) => state.secret_hash_sent_or_received = true,
+            None => {
+                tracing::warn!("secret hash exchanged for unknown swap {}", swap_id);
+                return;
+            }
         }
+
+        self.finalize_if_done(peer, swap_id);
     }
 }
 
@@ -371,7 +566,9 @@ impl NetworkBehaviourEventProcess<announce::behaviour::BehaviourOutEvent> for Co
                         .register_addresses(peer.clone(), addresses.clone());
                     self.lightning_identity
                         .register_addresses(peer.clone(), addresses.clone());
-                    self.finalize.register_addresses(peer.clone(), addresses);
+                    self.finalize
+                        .register_addresses(peer.clone(), addresses.clone());
+                    self.refuse.register_addresses(peer.clone(), addresses);
 
                     self.ethereum_identity.send(
                         peer.clone(),
@@ -397,10 +594,24 @@ impl NetworkBehaviourEventProcess<announce::behaviour::BehaviourOutEvent> for Co
                 swap_digest,
                 swap_id,
             } => {
-                let local_swap_id = self
-                    .swaps_waiting_for_announcement
-                    .remove(&swap_digest)
-                    .expect("we must know about this digest");
+                let local_swap_id = match self.swaps_waiting_for_announcement.remove(&swap_digest) {
+                    Some(local_swap_id) => local_swap_id,
+                    None => {
+                        tracing::warn!(
+                            "Peer {} confirmed a swap ({}) we don't know about",
+                            peer,
+                            swap_digest
+                        );
+                        return;
+                    }
+                };
+                let create_swap_params = match self.swaps.get(&local_swap_id) {
+                    Some(create_swap_params) => create_swap_params.clone(),
+                    None => {
+                        tracing::warn!("no parameters for swap {}", local_swap_id);
+                        return;
+                    }
+                };
 
                 self.swap_ids.insert(local_swap_id, swap_id);
 
@@ -411,9 +622,9 @@ impl NetworkBehaviourEventProcess<announce::behaviour::BehaviourOutEvent> for Co
                     .register_addresses(peer.clone(), addresses.clone());
                 self.lightning_identity
                     .register_addresses(peer.clone(), addresses.clone());
-                self.finalize.register_addresses(peer.clone(), addresses);
-
-                let create_swap_params = self.swaps.get(&local_swap_id).unwrap();
+                self.finalize
+                    .register_addresses(peer.clone(), addresses.clone());
+                self.refuse.register_addresses(peer.clone(), addresses);
 
                 self.ethereum_identity.send(
                     peer.clone(),
@@ -432,10 +643,16 @@ impl NetworkBehaviourEventProcess<announce::behaviour::BehaviourOutEvent> for Co
 
                 let seed = self.seed.derive_swap_seed(local_swap_id);
                 let secret_hash = seed.derive_secret().hash();
+                let message = match secret_hash::Message::new(swap_id, secret_hash) {
+                    Ok(message) => message,
+                    Err(e) => {
+                        tracing::error!("cannot share secret hash of swap {}: {}", swap_id, e);
+                        return;
+                    }
+                };
 
                 self.secret_hashes.insert(swap_id, secret_hash);
-                self.secret_hash
-                    .send(peer, secret_hash::Message::new(swap_id, secret_hash));
+                self.secret_hash.send(peer, message);
 
                 self.communication_state
                     .insert(swap_id, CommunicationState::default());
@@ -469,28 +686,19 @@ impl NetworkBehaviourEventProcess<oneshot_behaviour::OutEvent<ethereum_identity:
                 peer,
                 message: ethereum_identity::Message { swap_id, .. },
             } => {
-                let state = self
-                    .communication_state
-                    .get_mut(&swap_id)
-                    .expect("this should exist");
-
-                state.ethereum_identity_sent = true;
+                match self.communication_state.get_mut(&swap_id) {
+                    Some(state) => state.ethereum_identity_sent = true,
+                    None => {
+                        tracing::warn!("sent ethereum identity for unknown swap {}", swap_id);
+                        return;
+                    }
+                }
 
                 (peer, swap_id)
             }
         };
 
-        let state = self.communication_state.get(&swap_id).unwrap();
-
-        // check if we are done
-        if self.ethereum_identities.contains_key(&swap_id)
-            && self.lightning_identities.contains_key(&swap_id)
-            && state.lightning_identity_sent
-            && state.ethereum_identity_sent
-            && state.secret_hash_sent_or_received
-        {
-            self.finalize.send(peer, finalize::Message::new(swap_id));
-        }
+        self.finalize_if_done(peer, swap_id);
     }
 }
 
@@ -503,10 +711,19 @@ impl NetworkBehaviourEventProcess<oneshot_behaviour::OutEvent<lightning_identity
                 peer,
                 message: lightning_identity::Message { swap_id, pubkey },
             } => {
-                self.lightning_identities.insert(
-                    swap_id,
-                    bitcoin::PublicKey::from_slice(&pubkey).unwrap().into(),
-                );
+                let pubkey = match bitcoin::PublicKey::from_slice(&pubkey) {
+                    Ok(pubkey) => pubkey,
+                    Err(e) => {
+                        tracing::warn!(
+                            "peer {} sent an invalid lightning identity for swap {}: {}",
+                            peer,
+                            swap_id,
+                            e
+                        );
+                        return;
+                    }
+                };
+                self.lightning_identities.insert(swap_id, pubkey.into());
 
                 (peer, swap_id)
             }
@@ -514,106 +731,105 @@ impl NetworkBehaviourEventProcess<oneshot_behaviour::OutEvent<lightning_identity
                 peer,
                 message: lightning_identity::Message { swap_id, .. },
             } => {
-                let state = self
-                    .communication_state
-                    .get_mut(&swap_id)
-                    .expect("this should exist");
-
-                state.lightning_identity_sent = true;
+                match self.communication_state.get_mut(&swap_id) {
+                    Some(state) => state.lightning_identity_sent = true,
+                    None => {
+                        tracing::warn!("sent lightning identity for unknown swap {}", swap_id);
+                        return;
+                    }
+                }
 
                 (peer, swap_id)
             }
         };
 
-        let state = self.communication_state.get(&swap_id).unwrap();
-
-        // check if we are done
-        if self.ethereum_identities.contains_key(&swap_id)
-            && self.lightning_identities.contains_key(&swap_id)
-            && state.lightning_identity_sent
-            && state.ethereum_identity_sent
-            && state.secret_hash_sent_or_received
-        {
-            self.finalize.send(peer, finalize::Message::new(swap_id));
-        }
+        self.finalize_if_done(peer, swap_id);
     }
 }
 
 impl NetworkBehaviourEventProcess<oneshot_behaviour::OutEvent<finalize::Message>> for ComitLN {
     fn inject_event(&mut self, event: oneshot_behaviour::OutEvent<finalize::Message>) {
-        let (_, swap_id) = match event {
+        let (swap_id, finalized) = match event {
             oneshot_behaviour::OutEvent::Received {
-                peer,
                 message: finalize::Message { swap_id },
-            } => {
-                let state = self
-                    .communication_state
-                    .get_mut(&swap_id)
-                    .expect("this should exist");
+                ..
+            } => match self.communication_state.get_mut(&swap_id) {
+                Some(state) => {
+                    state.received_finalized = true;
+                    (swap_id, state.sent_finalized)
+                }
+                None => {
+                    tracing::warn!("received finalize for unknown swap {}", swap_id);
+                    return;
+                }
+            },
+            oneshot_behaviour::OutEvent::Sent {
+                message: finalize::Message { swap_id },
+                ..
+            } => match self.communication_state.get_mut(&swap_id) {
+                Some(state) => {
+                    state.sent_finalized = true;
+                    (swap_id, state.received_finalized)
+                }
+                None => {
+                    tracing::warn!("sent finalize for unknown swap {}", swap_id);
+                    return;
+                }
+            },
+        };
 
-                state.received_finalized = true;
+        if !finalized {
+            return;
+        }
 
-                (peer, swap_id)
+        match self.finalized_swap_event(swap_id) {
+            Some(event) => {
+                if let BehaviourOutEvent::SwapFinalized { local_swap_id, .. } = &event {
+                    let local_swap_id = *local_swap_id;
+                    self.swaps_waiting_for_announcement
+                        .retain(|_, id| *id != local_swap_id);
+                }
+
+                self.events.push_back(event);
             }
-            oneshot_behaviour::OutEvent::Sent {
+            None => tracing::warn!("finalized swap {} is incomplete", swap_id),
+        }
+    }
+}
+
+impl ComitLN {
+    fn finalized_swap_event(&self, swap_id: SharedSwapId) -> Option<BehaviourOutEvent> {
+        let local_swap_id = self.local_swap_id(swap_id)?;
+
+        Some(BehaviourOutEvent::SwapFinalized {
+            local_swap_id,
+            swap_params: self.swaps.get(&local_swap_id).cloned()?,
+            secret_hash: self.secret_hashes.get(&swap_id).copied()?,
+            ethereum_identity: self.ethereum_identities.get(&swap_id).copied()?,
+        })
+    }
+}
+
+impl NetworkBehaviourEventProcess<oneshot_behaviour::OutEvent<refuse::Message>> for ComitLN {
+    fn inject_event(&mut self, event: oneshot_behaviour::OutEvent<refuse::Message>) {
+        match event {
+            oneshot_behaviour::OutEvent::Received {
                 peer,
-                message: finalize::Message { swap_id },
+                message: refuse::Message { swap_id, reason },
             } => {
-                let state = self
-                    .communication_state
-                    .get_mut(&swap_id)
-                    .expect("this should exist");
-
-                state.sent_finalized = true;
+                tracing::info!("peer {} refused swap {}: {}", peer, swap_id, reason);
 
-                (peer, swap_id)
+                match self.local_swap_id(swap_id) {
+                    Some(local_swap_id) => {
+                        self.refusals.insert(local_swap_id, Refusal {
+                            refused_by: RefusedBy::Counterparty,
+                            reason,
+                        });
+                    }
+                    None => tracing::warn!("peer {} refused unknown swap {}", peer, swap_id),
+                }
             }
-        };
-
-        let state = self
-            .communication_state
-            .get_mut(&swap_id)
-            .expect("this should exist");
-
-        if state.sent_finalized && state.received_finalized {
-            let local_swap_id = self
-                .swap_ids
-                .iter()
-                .find_map(
-                    |(key, value)| {
-                        if *value == swap_id {
-                            Some(key)
-                        } else {
-                            None
-                        }
-                    },
-                )
-                .copied()
-                .unwrap();
-
-            let create_swap_params = self
-                .swaps
-                .get(&local_swap_id)
-                .cloned()
-                .expect("create swap params exist");
-
-            let secret_hash = self
-                .secret_hashes
-                .get(&swap_id)
-                .copied()
-                .expect("must exist");
-
-            let ethereum_identity = self.ethereum_identities.get(&swap_id).copied().unwrap();
-
-            self.swaps_waiting_for_announcement
-                .retain(|_, id| *id != local_swap_id);
-
-            self.events.push_back(BehaviourOutEvent::SwapFinalized {
-                local_swap_id,
-                swap_params: create_swap_params,
-                secret_hash,
-                ethereum_identity,
-            });
+            oneshot_behaviour::OutEvent::Sent { .. } => {}
         }
     }
 }
@@ -638,7 +854,7 @@ mod tests {
         ether: asset::Ether,
         lnbtc: asset::Bitcoin,
         ethereum_absolute_expiry: Timestamp,
-        lightning_cltv_expiry: Timestamp,
+        lightning_cltv_expiry: BlockDelta,
     ) -> HanEtherereumHalightBitcoinCreateSwapParams {
         HanEtherereumHalightBitcoinCreateSwapParams {
             role: Role::Alice,
@@ -649,6 +865,7 @@ mod tests {
             ethereum_identity: EthereumIdentity::from(identity::Ethereum::random()),
             ethereum_absolute_expiry,
             ethereum_amount: ether,
+            ethereum_htlc: EthereumHtlc::Deployed,
             lightning_identity: lightning::PublicKey::random(),
             lightning_cltv_expiry,
             lightning_amount: lnbtc,
@@ -660,7 +877,7 @@ mod tests {
         ether: asset::Ether,
         lnbtc: asset::Bitcoin,
         ethereum_absolute_expiry: Timestamp,
-        lightning_cltv_expiry: Timestamp,
+        lightning_cltv_expiry: BlockDelta,
     ) -> HanEtherereumHalightBitcoinCreateSwapParams {
         HanEtherereumHalightBitcoinCreateSwapParams {
             role: Role::Bob,
@@ -671,24 +888,80 @@ mod tests {
             ethereum_identity: EthereumIdentity::from(identity::Ethereum::random()),
             ethereum_absolute_expiry,
             ethereum_amount: ether,
+            ethereum_htlc: EthereumHtlc::Deployed,
             lightning_identity: lightning::PublicKey::random(),
             lightning_cltv_expiry,
             lightning_amount: lnbtc,
         }
     }
 
+    #[test]
+    fn only_registry_swaps_have_the_registry_in_their_digest() {
+        let params = make_bob_swap_params(
+            PeerId::random(),
+            Ether::from_wei(1_000u32),
+            asset::Bitcoin::from_sat(1_000),
+            Timestamp::from(100),
+            BlockDelta::from(200),
+        );
+        let in_registry = |registry: &str| HanEtherereumHalightBitcoinCreateSwapParams {
+            ethereum_htlc: EthereumHtlc::SwapRegistry(registry.parse().unwrap()),
+            ..params.clone()
+        };
+
+        assert_eq!(params.clone().swap_digest(), params.clone().digest());
+        assert_ne!(
+            in_registry("0a81e8be41b21f651a71aab1a85c6813b8bbccf8").swap_digest(),
+            params.swap_digest()
+        );
+        assert_ne!(
+            in_registry("0a81e8be41b21f651a71aab1a85c6813b8bbccf8").swap_digest(),
+            in_registry("d51ecee7414c4445534f74208538683702cbb3e4").swap_digest()
+        );
+    }
+
+    #[test]
+    fn refusal_of_the_counterparty_is_recorded() {
+        let mut comit_ln = ComitLN::new(RootSeed::new_random(thread_rng()).unwrap(), None, 60 * 60);
+        let local_swap_id = LocalSwapId::default();
+        let swap_id = SharedSwapId::default();
+        comit_ln.swap_ids.insert(local_swap_id, swap_id);
+
+        NetworkBehaviourEventProcess::<oneshot_behaviour::OutEvent<refuse::Message>>::inject_event(
+            &mut comit_ln,
+            oneshot_behaviour::OutEvent::Received {
+                peer: PeerId::random(),
+                message: refuse::Message::new(swap_id, "no route".to_owned()),
+            },
+        );
+
+        assert_eq!(
+            comit_ln.get_refusal(local_swap_id),
+            Some(Refusal {
+                refused_by: RefusedBy::Counterparty,
+                reason: "no route".to_owned(),
+            })
+        );
+    }
+
     #[tokio::test]
     async fn finalize_lightning_ethereum_swap_success() {
         // arrange
-        let (mut alice_swarm, _, alice_peer_id) =
-            test_swarm::new(ComitLN::new(RootSeed::new_random(thread_rng()).unwrap()));
-        let (mut bob_swarm, bob_addr, bob_peer_id) =
-            test_swarm::new(ComitLN::new(RootSeed::new_random(thread_rng()).unwrap()));
+        let (mut alice_swarm, _, alice_peer_id) = test_swarm::new(ComitLN::new(
+            RootSeed::new_random(thread_rng()).unwrap(),
+            None,
+            60 * 60,
+        ));
+        let (mut bob_swarm, bob_addr, bob_peer_id) = test_swarm::new(ComitLN::new(
+            RootSeed::new_random(thread_rng()).unwrap(),
+            None,
+            60 * 60,
+        ));
 
         let ether = Ether::from_wei(9_001_000_000_000_000_000_000u128);
         let lnbtc = asset::Bitcoin::from_sat(42);
         let ethereum_expiry = Timestamp::from(100);
-        let lightning_expiry = Timestamp::from(200);
+        let lightning_expiry = BlockDelta::from(200);
 
         alice_swarm
             .initiate_communication(
@@ -735,8 +1008,12 @@ mod tests {
                     ethereum_identity: _bob_eth_id,
                 },
             ) => {
-                assert_eq!(bob_swap_params.digest(), alice_swap_params.digest());
+                assert_eq!(bob_swap_params.swap_digest(), alice_swap_params.swap_digest());
             }
+            (alice_event, bob_event) => panic!(
+                "expected both swaps to be finalized, got {:?} and {:?}",
+                alice_event, bob_event
+            ),
         }
     }
 }
@@ -3,4 +3,5 @@ pub mod bitcoin_identity;
 pub mod ethereum_identity;
 pub mod finalize;
 pub mod lightning_identity;
+pub mod refuse;
 pub mod secret_hash;
@@ -0,0 +1,41 @@
+use crate::{network::oneshot_protocol, swap_protocols::SharedSwapId};
+use serde::{Deserialize, Serialize};
+
+/// The message for the refuse protocol, sent instead of the finalize message
+/// if we cannot take part in a swap.
+#[derive(Clone, Deserialize, Debug, Serialize)]
+pub struct Message {
+    pub swap_id: SharedSwapId,
+    pub reason: String,
+}
+
+impl Message {
+    pub fn new(swap_id: SharedSwapId, reason: String) -> Self {
+        Self { swap_id, reason }
+    }
+}
+
+impl oneshot_protocol::Message for Message {
+    const INFO: &'static str = "/comit/swap/refuse/1.0.0";
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use spectral::prelude::*;
+
+    #[test]
+    fn serialization_format_stability_test() {
+        let given = Message {
+            swap_id: SharedSwapId::nil(),
+            reason: "insufficient liquidity".to_owned(),
+        };
+
+        let actual = serde_json::to_string(&given);
+
+        assert_that(&actual).is_ok_containing(
+            r#"{"swap_id":"00000000-0000-0000-0000-000000000000","reason":"insufficient liquidity"}"#
+                .to_owned(),
+        )
+    }
+}
@@ -1,6 +1,9 @@
 use crate::{
     network::oneshot_protocol,
-    swap_protocols::{rfc003::SecretHash, SharedSwapId},
+    swap_protocols::{
+        rfc003::{FromErr, SecretHash},
+        SharedSwapId,
+    },
 };
 use serde::{Deserialize, Serialize};
 use serde_hex::{SerHex, Strict};
@@ -15,11 +18,12 @@ pub struct Message {
 }
 
 impl Message {
-    pub fn new(swap_id: SharedSwapId, secret_hash: SecretHash) -> Self {
-        Self {
+    /// Fails if `secret_hash` is not a SHA-256 hash.
+    pub fn new(swap_id: SharedSwapId, secret_hash: SecretHash) -> Result<Self, FromErr> {
+        Ok(Self {
             swap_id,
-            secret_hash: secret_hash.into_raw(),
-        }
+            secret_hash: secret_hash.into_raw()?,
+        })
     }
 }
 
@@ -146,8 +146,15 @@ impl Arbitrary for Quickcheck<crate::asset::Erc20> {
 }
 
 impl Arbitrary for Quickcheck<HashFunction> {
-    fn arbitrary<G: Gen>(_g: &mut G) -> Self {
-        Quickcheck(HashFunction::Sha256)
+    fn arbitrary<G: Gen>(g: &mut G) -> Self {
+        let hash_function = match g.next_u32() % 3 {
+            0 => HashFunction::Sha256,
+            1 => HashFunction::Hash160,
+            2 => HashFunction::Keccak256,
+            _ => unreachable!(),
+        };
+
+        Quickcheck(hash_function)
     }
 }
 
@@ -186,6 +193,10 @@ impl Arbitrary for Quickcheck<transaction::Ethereum> {
             to: Option::<Quickcheck<crate::ethereum::Address>>::arbitrary(g).map(|i| i.0),
             value: *Quickcheck::<crate::ethereum::U256>::arbitrary(g),
             input: Bytes(Arbitrary::arbitrary(g)),
+            transaction_type: crate::ethereum::TransactionType::from(u8::arbitrary(g)),
+            max_fee_per_gas: Option::<Quickcheck<crate::ethereum::U256>>::arbitrary(g).map(|i| i.0),
+            max_priority_fee_per_gas: Option::<Quickcheck<crate::ethereum::U256>>::arbitrary(g)
+                .map(|i| i.0),
         })
     }
 }
@@ -200,9 +211,13 @@ impl Arbitrary for Quickcheck<Timestamp> {
 
 impl Arbitrary for Quickcheck<SecretHash> {
     fn arbitrary<G: Gen>(g: &mut G) -> Self {
-        let bytes = *Quickcheck::<[u8; 32]>::arbitrary(g);
+        let secret_hash = match g.next_u32() % 2 {
+            0 => SecretHash::from(*Quickcheck::<[u8; 32]>::arbitrary(g)),
+            1 => SecretHash::from(*Quickcheck::<[u8; 20]>::arbitrary(g)),
+            _ => unreachable!(),
+        };
 
-        Quickcheck(SecretHash::from(bytes))
+        Quickcheck(secret_hash)
     }
 }
 
@@ -1,4 +1,9 @@
 use crate::swap_protocols::{rfc003::SwapId, LocalSwapId};
+use aes_ctr::{
+    stream_cipher::{generic_array::GenericArray, NewStreamCipher, SyncStreamCipher},
+    Aes256Ctr,
+};
+use hmac::{Hmac, Mac};
 use pem::{encode, Pem};
 use rand::Rng;
 use sha2::{Digest, Sha256};
@@ -43,6 +48,10 @@ impl DeriveSwapSeed for RootSeed {
 }
 
 const SEED_LENGTH: usize = 32;
+const SALT_LENGTH: usize = 16;
+const IV_LENGTH: usize = 16;
+const MAC_LENGTH: usize = 32;
+const ENCRYPTED_SEED_LENGTH: usize = SALT_LENGTH + IV_LENGTH + SEED_LENGTH + MAC_LENGTH;
 
 #[derive(Clone, Copy, PartialEq)]
 struct Seed([u8; SEED_LENGTH]);
@@ -153,6 +162,106 @@ impl RootSeed {
         Ok(random_seed)
     }
 
+    /// Read the seed from the directory, fails if there is none.
+    pub fn from_dir<D>(data_dir: D) -> Result<RootSeed, Error>
+    where
+        D: AsRef<OsStr>,
+    {
+        let path = seed_path_from_dir(Path::new(&data_dir));
+
+        Self::from_file(&path)
+    }
+
+    /// Write the seed encrypted with 'passphrase' to 'file'.
+    ///
+    /// The AES-256-CTR and HMAC-SHA256 keys are derived from the passphrase
+    /// with Argon2id and a random salt, hence the file can be stored wherever
+    /// the swap database is stored.
+    pub fn write_encrypted_to<R>(
+        &self,
+        file: &Path,
+        passphrase: &str,
+        mut rand: R,
+    ) -> Result<(), Error>
+    where
+        R: Rng,
+    {
+        let mut salt = [0u8; SALT_LENGTH];
+        rand.try_fill(&mut salt[..])?;
+        let mut iv = [0u8; IV_LENGTH];
+        rand.try_fill(&mut iv[..])?;
+
+        let (encryption_key, mac_key) = derive_keys(passphrase, &salt)?;
+
+        let mut ciphertext = (self.0).0;
+        Aes256Ctr::new(
+            GenericArray::from_slice(&encryption_key),
+            GenericArray::from_slice(&iv),
+        )
+        .apply_keystream(&mut ciphertext);
+
+        let mut contents = Vec::with_capacity(ENCRYPTED_SEED_LENGTH);
+        contents.extend_from_slice(&salt);
+        contents.extend_from_slice(&iv);
+        contents.extend_from_slice(&ciphertext);
+        let mac = mac(&mac_key, &contents).result().code();
+        contents.extend_from_slice(&mac);
+
+        let pem = Pem {
+            tag: String::from("ENCRYPTED SEED"),
+            contents,
+        };
+
+        ensure_directory_exists(file.to_path_buf())?;
+        fs::write(file, encode(&pem))?;
+
+        Ok(())
+    }
+
+    /// Read a seed written by `write_encrypted_to` and decrypt it with
+    /// 'passphrase'.
+    pub fn from_encrypted_file(file: &Path, passphrase: &str) -> Result<RootSeed, Error> {
+        let contents = fs::read_to_string(file)?;
+        let pem = pem::parse(contents)?;
+
+        if pem.contents.len() != ENCRYPTED_SEED_LENGTH {
+            return Err(Error::IncorrectLength(pem.contents.len()));
+        }
+
+        let (authenticated, expected_mac) =
+            pem.contents.split_at(ENCRYPTED_SEED_LENGTH - MAC_LENGTH);
+        let (salt, rest) = authenticated.split_at(SALT_LENGTH);
+        let (iv, ciphertext) = rest.split_at(IV_LENGTH);
+
+        let (encryption_key, mac_key) = derive_keys(passphrase, salt)?;
+
+        mac(&mac_key, authenticated)
+            .verify(expected_mac)
+            .map_err(|_| Error::WrongPassphrase)?;
+
+        let mut seed = [0u8; SEED_LENGTH];
+        seed.copy_from_slice(ciphertext);
+        Aes256Ctr::new(
+            GenericArray::from_slice(&encryption_key),
+            GenericArray::from_slice(iv),
+        )
+        .apply_keystream(&mut seed);
+
+        Ok(RootSeed::from(seed))
+    }
+
+    /// Write the seed to the directory, the seed it replaces is kept next to
+    /// it as `seed.pem.before-restore`.
+    pub fn restore_to_dir(&self, data_dir: &Path) -> Result<(), Error> {
+        let path = seed_path_from_dir(data_dir);
+
+        if path.exists() {
+            fs::rename(&path, path.with_extension("pem.before-restore"))?;
+        }
+
+        self.write_to(path)
+    }
+
     fn from_file<D>(seed_file: D) -> Result<RootSeed, Error>
     where
         D: AsRef<OsStr>,
@@ -220,6 +329,30 @@ fn ensure_directory_exists(file: PathBuf) -> Result<(), Error> {
     Ok(())
 }
 
+/// Derives the encryption and the MAC key of an encrypted seed.
+fn derive_keys(passphrase: &str, salt: &[u8]) -> Result<([u8; 32], [u8; 32]), Error> {
+    let config = argon2::Config {
+        variant: argon2::Variant::Argon2id,
+        hash_length: 64,
+        ..argon2::Config::default()
+    };
+    let hash = argon2::hash_raw(passphrase.as_bytes(), salt, &config)?;
+
+    let mut encryption_key = [0u8; 32];
+    encryption_key.copy_from_slice(&hash[..32]);
+    let mut mac_key = [0u8; 32];
+    mac_key.copy_from_slice(&hash[32..]);
+
+    Ok((encryption_key, mac_key))
+}
+
+fn mac(key: &[u8], data: &[u8]) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_varkey(key).expect("HMAC takes keys of any length");
+    mac.input(data);
+
+    mac
+}
+
 fn default_seed_path() -> Result<PathBuf, Error> {
     let default_path = crate::data_dir().ok_or(Error::NoDefaultPath)?;
     Ok(seed_path_from_dir(&default_path))
@@ -242,6 +375,10 @@ pub enum Error {
     Rand(#[from] rand::Error),
     #[error("no default path")]
     NoDefaultPath,
+    #[error("key derivation: {0}")]
+    KeyDerivation(#[from] argon2::Error),
+    #[error("wrong passphrase or corrupted seed file")]
+    WrongPassphrase,
 }
 
 impl From<[u8; SEED_LENGTH]> for RootSeed {
@@ -360,4 +497,32 @@ mbKANv2qKGmNVg1qtquj6Hx1pFPelpqOfE2JaJJAMEg1FlFhNRNlFlE=
         let rinsed = RootSeed::from_file(path).expect("Read from temp file");
         assert_eq!(seed.0, rinsed.0);
     }
+
+    #[test]
+    fn round_trip_through_encrypted_file() {
+        let tmpfile = tempfile::NamedTempFile::new().expect("Could not create temp file");
+
+        let seed = RootSeed::new_random(OsRng).unwrap();
+        seed.write_encrypted_to(tmpfile.path(), "correct horse battery staple", OsRng)
+            .expect("Write encrypted seed to temp file");
+
+        let decrypted =
+            RootSeed::from_encrypted_file(tmpfile.path(), "correct horse battery staple")
+                .expect("Read from temp file");
+        assert_eq!(seed, decrypted);
+    }
+
+    #[test]
+    fn encrypted_seed_does_not_decrypt_with_another_passphrase() {
+        let tmpfile = tempfile::NamedTempFile::new().expect("Could not create temp file");
+
+        let seed = RootSeed::new_random(OsRng).unwrap();
+        seed.write_encrypted_to(tmpfile.path(), "correct horse battery staple", OsRng)
+            .expect("Write encrypted seed to temp file");
+
+        match RootSeed::from_encrypted_file(tmpfile.path(), "incorrect horse battery staple") {
+            Err(Error::WrongPassphrase) => {} // pass
+            _ => panic!("should fail with WrongPassphrase error"),
+        }
+    }
 }
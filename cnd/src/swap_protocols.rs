@@ -1,4 +1,6 @@
 pub mod actions;
+pub mod cltv_budget;
+pub mod expiries;
 mod facade;
 pub mod halight;
 pub mod han;
@@ -10,6 +12,7 @@ mod rfc003_facade;
 pub mod state;
 mod swap_error_states;
 mod swap_id;
+pub mod swap_registry;
 
 pub use self::{facade::*, ledger_states::*, rfc003_facade::*, swap_error_states::*, swap_id::*};
 
@@ -32,6 +35,29 @@ pub enum HashFunction {
     #[strum(serialize = "SHA-256")]
     #[serde(rename = "SHA-256")]
     Sha256,
+    /// RIPEMD-160 of the SHA-256 hash, what Bitcoin's `OP_HASH160` computes.
+    #[strum(serialize = "HASH160")]
+    #[serde(rename = "HASH160")]
+    Hash160,
+    #[strum(serialize = "KECCAK-256")]
+    #[serde(rename = "KECCAK-256")]
+    Keccak256,
+}
+
+impl Default for HashFunction {
+    fn default() -> Self {
+        HashFunction::Sha256
+    }
+}
+
+impl HashFunction {
+    /// The length of the hashes produced by this function in bytes.
+    pub fn digest_length(self) -> usize {
+        match self {
+            HashFunction::Sha256 | HashFunction::Keccak256 => 32,
+            HashFunction::Hash160 => 20,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
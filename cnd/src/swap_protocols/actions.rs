@@ -7,12 +7,13 @@ pub trait Actions {
     /// Hence they get to choose the type here.
     type ActionKind;
 
-    fn actions(&self) -> Vec<Self::ActionKind>;
+    /// Fails if an action cannot be built from the parameters of the swap.
+    fn actions(&self) -> anyhow::Result<Vec<Self::ActionKind>>;
 }
 
 pub mod bitcoin {
     use crate::asset;
-    use bitcoin::Address;
+    use bitcoin::{Address, OutPoint};
     use blockchain_contracts::bitcoin::witness::{PrimedInput, PrimedTransaction};
 
     #[derive(Debug, Clone, PartialEq)]
@@ -26,11 +27,21 @@ pub mod bitcoin {
     pub struct SpendOutput {
         // Remember: One man's input is another man's output!
         pub output: PrimedInput,
+        /// Where `output` is, to find our transactions spending it.
+        pub outpoint: OutPoint,
         pub network: bitcoin::Network,
     }
 
+    /// Opts into replace-by-fee (BIP125) while still enforcing the lock time
+    /// of refund transactions.
+    pub const SEQUENCE_ALLOW_NTIMELOCK_RBF: u32 = 0xFFFF_FFFD;
+
     impl SpendOutput {
-        pub fn spend_to(self, to_address: Address) -> PrimedTransaction {
+        /// The resulting transaction signals replaceability so that its fee
+        /// can be bumped should it get stuck in the mempool.
+        pub fn spend_to(mut self, to_address: Address) -> PrimedTransaction {
+            self.output.input_parameters.sequence = SEQUENCE_ALLOW_NTIMELOCK_RBF;
+
             PrimedTransaction {
                 inputs: vec![self.output],
                 output_address: to_address,
@@ -41,7 +52,10 @@ pub mod bitcoin {
 
 pub mod ethereum {
     use crate::{
-        asset, ethereum::Bytes, identity, swap_protocols::ledger::ethereum::ChainId,
+        asset,
+        ethereum::{Bytes, U256},
+        identity,
+        swap_protocols::ledger::ethereum::ChainId,
         timestamp::Timestamp,
     };
 
@@ -50,6 +64,10 @@ pub mod ethereum {
         pub data: Bytes,
         pub amount: asset::Ether,
         pub gas_limit: u64,
+        /// EIP-1559 fee caps, absent if the transaction should be sent as a
+        /// legacy transaction.
+        pub max_fee_per_gas: Option<U256>,
+        pub max_priority_fee_per_gas: Option<U256>,
         pub chain_id: ChainId,
     }
 
@@ -57,7 +75,11 @@ pub mod ethereum {
     pub struct CallContract {
         pub to: identity::Ethereum,
         pub data: Option<Bytes>,
+        /// The ether sent along with the call, if any.
+        pub amount: Option<asset::Ether>,
         pub gas_limit: u64,
+        pub max_fee_per_gas: Option<U256>,
+        pub max_priority_fee_per_gas: Option<U256>,
         pub chain_id: ChainId,
         pub min_block_timestamp: Option<Timestamp>,
     }
@@ -0,0 +1,181 @@
+//! Relates the block height based CLTVs of a Lightning payment to the
+//! wall-clock based expiry of the Ethereum HTLC of a han/halight swap.
+
+use crate::timestamp::Timestamp;
+use serde::{Deserialize, Serialize};
+
+/// The average time it takes to mine a Bitcoin block.
+pub const SECONDS_PER_BLOCK: u32 = 10 * 60;
+
+/// A number of blocks relative to the tip of the chain, e.g. the CLTV delta of
+/// a Lightning payment.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Deserialize, Serialize)]
+#[serde(transparent)]
+pub struct BlockDelta(u32);
+
+impl BlockDelta {
+    pub fn to_bytes(self) -> [u8; 4] {
+        self.0.to_le_bytes()
+    }
+}
+
+impl From<u32> for BlockDelta {
+    fn from(item: u32) -> Self {
+        Self(item)
+    }
+}
+
+impl From<BlockDelta> for u32 {
+    fn from(item: BlockDelta) -> Self {
+        item.0
+    }
+}
+
+/// The most recent block of the Bitcoin chain, the anchor for converting
+/// between block heights and wall-clock time.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChainTip {
+    pub height: u32,
+    pub time: Timestamp,
+}
+
+impl ChainTip {
+    /// Estimates when the block at `height` is mined, assuming the average
+    /// block time. Blocks at or below the tip are considered mined already.
+    pub fn estimated_time_of(&self, height: u32) -> Timestamp {
+        let blocks = height.saturating_sub(self.height);
+
+        self.time.plus(blocks.saturating_mul(SECONDS_PER_BLOCK))
+    }
+
+    /// Estimates the height of the chain at `time`, assuming the average
+    /// block time.
+    pub fn estimated_height_at(&self, time: Timestamp) -> u32 {
+        let secs = u32::from(time).saturating_sub(self.time.into());
+
+        self.height.saturating_add(secs / SECONDS_PER_BLOCK)
+    }
+}
+
+/// The CLTV deltas of the HTLCs making up a Lightning payment.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CltvBudget {
+    pub tip: ChainTip,
+    /// The CLTV delta of the HTLC offered to the recipient, as agreed on in
+    /// the swap.
+    pub final_cltv_delta: u32,
+    /// The CLTV deltas the intermediate nodes of the route add on top of
+    /// `final_cltv_delta`, zero if the route is not known.
+    pub route_cltv_delta: u32,
+}
+
+impl CltvBudget {
+    /// The height at which the HTLC offered by the sender expires.
+    pub fn expiry_height(&self) -> u32 {
+        self.tip
+            .height
+            .saturating_add(self.final_cltv_delta)
+            .saturating_add(self.route_cltv_delta)
+    }
+
+    /// The latest time at which the sender learns the secret: every node on
+    /// the route may hold on to it until the HTLC it offered expires.
+    pub fn worst_case_settlement(&self) -> Timestamp {
+        self.tip.estimated_time_of(self.expiry_height())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error)]
+#[error("the Ethereum HTLC expires at {}, less than {min_margin_secs} seconds after the Lightning payment may be settled at {}", u32::from(*.refund), u32::from(*.settlement))]
+pub struct UnsafeCltvBudget {
+    pub refund: Timestamp,
+    pub settlement: Timestamp,
+    pub min_margin_secs: u32,
+}
+
+/// Checks that the Ethereum HTLC cannot be refunded before the sender of the
+/// Lightning payment learned the secret and had `min_margin_secs` to redeem
+/// it.
+pub fn verify(
+    budget: &CltvBudget,
+    alpha_expiry: Timestamp,
+    min_margin_secs: u32,
+) -> Result<(), UnsafeCltvBudget> {
+    let settlement = budget.worst_case_settlement();
+
+    if settlement.plus(min_margin_secs) > alpha_expiry {
+        return Err(UnsafeCltvBudget {
+            refund: alpha_expiry,
+            settlement,
+            min_margin_secs,
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u32 = 1_000_000;
+    const HOUR: u32 = 60 * 60;
+
+    fn tip() -> ChainTip {
+        ChainTip {
+            height: 600_000,
+            time: Timestamp::from(NOW),
+        }
+    }
+
+    #[test]
+    fn converts_between_heights_and_time() {
+        let tip = tip();
+
+        assert_eq!(tip.estimated_time_of(600_006), Timestamp::from(NOW + HOUR));
+        assert_eq!(tip.estimated_time_of(599_000), Timestamp::from(NOW));
+        assert_eq!(
+            tip.estimated_height_at(Timestamp::from(NOW + HOUR)),
+            600_006
+        );
+        assert_eq!(
+            tip.estimated_height_at(Timestamp::from(NOW - HOUR)),
+            600_000
+        );
+    }
+
+    #[test]
+    fn route_cltv_deltas_delay_the_worst_case_settlement() {
+        let budget = CltvBudget {
+            tip: tip(),
+            final_cltv_delta: 144,
+            route_cltv_delta: 40 + 40,
+        };
+
+        assert_eq!(budget.expiry_height(), 600_224);
+        assert_eq!(
+            budget.worst_case_settlement(),
+            Timestamp::from(NOW + 224 * SECONDS_PER_BLOCK)
+        );
+    }
+
+    #[test]
+    fn ethereum_htlc_has_to_expire_after_settlement_and_margin() {
+        let budget = CltvBudget {
+            tip: tip(),
+            final_cltv_delta: 144,
+            route_cltv_delta: 0,
+        };
+        let settlement = Timestamp::from(NOW + 24 * HOUR);
+
+        assert_eq!(verify(&budget, settlement.plus(HOUR), HOUR), Ok(()));
+        assert_eq!(
+            verify(&budget, settlement.plus(HOUR - 1), HOUR),
+            Err(UnsafeCltvBudget {
+                refund: settlement.plus(HOUR - 1),
+                settlement,
+                min_margin_secs: HOUR
+            })
+        );
+    }
+}
@@ -0,0 +1,290 @@
+use crate::{
+    config::{ConfirmationTargets, Expiries},
+    swap_protocols::{
+        cltv_budget::SECONDS_PER_BLOCK,
+        ledger::{bitcoin, Ethereum},
+        Role,
+    },
+    timestamp::Timestamp,
+};
+
+const ETHEREUM_SECONDS_PER_BLOCK: u32 = 15;
+
+/// The reason why a pair of expiries was deemed unsafe.
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error)]
+pub enum ExpiryViolation {
+    #[error("alpha expiry must be at least {min_gap_secs} seconds after beta expiry but is only {gap_secs} seconds after it")]
+    GapTooSmall { gap_secs: i64, min_gap_secs: u32 },
+    #[error("beta expiry must be at least {min_secs} seconds from now but is {secs} seconds away")]
+    BetaExpiresTooSoon { secs: i64, min_secs: u32 },
+    #[error("alpha expiry must be at most {max_secs} seconds from now but is {secs} seconds away")]
+    SwapTooLong { secs: i64, max_secs: u32 },
+}
+
+/// Checks that the given expiries leave enough time to complete the swap
+/// safely without locking up funds for longer than configured.
+pub fn validate(
+    policy: &Expiries,
+    alpha_expiry: Timestamp,
+    beta_expiry: Timestamp,
+    now: Timestamp,
+) -> Result<(), ExpiryViolation> {
+    let secs_between = |from: Timestamp, to: Timestamp| i64::from(to) - i64::from(from);
+
+    let gap_secs = secs_between(beta_expiry, alpha_expiry);
+    if gap_secs < i64::from(policy.min_gap_secs) {
+        return Err(ExpiryViolation::GapTooSmall {
+            gap_secs,
+            min_gap_secs: policy.min_gap_secs,
+        });
+    }
+
+    let secs = secs_between(now, beta_expiry);
+    if secs < i64::from(policy.min_time_to_beta_expiry_secs) {
+        return Err(ExpiryViolation::BetaExpiresTooSoon {
+            secs,
+            min_secs: policy.min_time_to_beta_expiry_secs,
+        });
+    }
+
+    let secs = secs_between(now, alpha_expiry);
+    if secs > i64::from(policy.max_duration_secs) {
+        return Err(ExpiryViolation::SwapTooLong {
+            secs,
+            max_secs: policy.max_duration_secs,
+        });
+    }
+
+    Ok(())
+}
+
+/// Estimates when an HTLC with the given CLTV delta offered now will expire.
+pub fn cltv_expiry_to_timestamp(cltv_expiry: u32, now: Timestamp) -> Timestamp {
+    now.plus(cltv_expiry.saturating_mul(SECONDS_PER_BLOCK))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error)]
+#[error("funding is no longer safe, the point of no return was at {}", u32::from(*.deadline))]
+pub struct PointOfNoReturnPassed {
+    pub deadline: Timestamp,
+}
+
+/// The time of the latest block of ledger `L`.
+///
+/// HTLC expiries are enforced against block times, which can lag behind or
+/// run ahead of our clock, hence the point of no return is checked against
+/// it.
+#[async_trait::async_trait]
+pub trait LatestBlockTime<L> {
+    async fn latest_block_time(&self) -> anyhow::Result<Timestamp>;
+}
+
+/// How long it takes until a transaction on a ledger can be relied upon.
+pub trait ConfirmationTime {
+    fn confirmation_time_secs(targets: &ConfirmationTargets) -> u32;
+}
+
+impl ConfirmationTime for bitcoin::Mainnet {
+    fn confirmation_time_secs(targets: &ConfirmationTargets) -> u32 {
+        targets.bitcoin_blocks.saturating_mul(SECONDS_PER_BLOCK)
+    }
+}
+
+impl ConfirmationTime for bitcoin::Testnet {
+    fn confirmation_time_secs(targets: &ConfirmationTargets) -> u32 {
+        targets.bitcoin_blocks.saturating_mul(SECONDS_PER_BLOCK)
+    }
+}
+
+impl ConfirmationTime for bitcoin::Regtest {
+    fn confirmation_time_secs(targets: &ConfirmationTargets) -> u32 {
+        targets.bitcoin_blocks.saturating_mul(SECONDS_PER_BLOCK)
+    }
+}
+
+impl ConfirmationTime for Ethereum {
+    fn confirmation_time_secs(targets: &ConfirmationTargets) -> u32 {
+        targets
+            .ethereum_blocks
+            .saturating_mul(ETHEREUM_SECONDS_PER_BLOCK)
+    }
+}
+
+/// The point of no return of an RFC003 swap: the last moment at which funding
+/// still leaves enough time for the remaining steps to confirm before the
+/// beta HTLC expires.
+///
+/// Alice funds first and then needs Bob's beta funding and her own redeem to
+/// confirm, Bob only needs Alice's redeem to confirm after his funding.
+pub fn rfc003_fund_deadline(
+    role: Role,
+    beta_expiry: Timestamp,
+    alpha_confirmation_secs: u32,
+    beta_confirmation_secs: u32,
+) -> Timestamp {
+    let remaining_confirmations = match role {
+        Role::Alice => {
+            alpha_confirmation_secs.saturating_add(beta_confirmation_secs.saturating_mul(2))
+        }
+        Role::Bob => beta_confirmation_secs.saturating_mul(2),
+    };
+
+    beta_expiry.minus(remaining_confirmations)
+}
+
+/// The point of no return of a han-ethereum-ether/halight-lightning-bitcoin
+/// swap.
+///
+/// Alice can settle the invoice until the payment's CLTV expires, Bob needs
+/// to be able to redeem the Ethereum HTLC after that.
+pub fn han_halight_fund_deadline(
+    alpha_expiry: Timestamp,
+    cltv_expiry: u32,
+    alpha_confirmation_secs: u32,
+) -> Timestamp {
+    alpha_expiry
+        .minus(cltv_expiry.saturating_mul(SECONDS_PER_BLOCK))
+        .minus(alpha_confirmation_secs)
+}
+
+/// A redeem transaction has to be confirmed before the HTLC expires,
+/// otherwise the counterparty can refund it.
+pub fn redeem_deadline(expiry: Timestamp, confirmation_secs: u32) -> Timestamp {
+    expiry.minus(confirmation_secs)
+}
+
+pub fn ensure_before_point_of_no_return(
+    deadline: Timestamp,
+    now: Timestamp,
+) -> Result<(), PointOfNoReturnPassed> {
+    if now > deadline {
+        return Err(PointOfNoReturnPassed { deadline });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: u32 = 1_000_000;
+    const HOUR: u32 = 60 * 60;
+
+    fn policy() -> Expiries {
+        Expiries {
+            min_gap_secs: HOUR,
+            min_time_to_beta_expiry_secs: 2 * HOUR,
+            max_duration_secs: 24 * HOUR,
+        }
+    }
+
+    fn validate_in(alpha_secs: u32, beta_secs: u32) -> Result<(), ExpiryViolation> {
+        let now = Timestamp::from(NOW);
+
+        validate(&policy(), now.plus(alpha_secs), now.plus(beta_secs), now)
+    }
+
+    #[test]
+    fn accepts_expiries_within_policy() {
+        assert_eq!(validate_in(12 * HOUR, 6 * HOUR), Ok(()));
+        assert_eq!(validate_in(24 * HOUR, 2 * HOUR), Ok(()));
+    }
+
+    #[test]
+    fn rejects_alpha_expiring_too_close_to_beta() {
+        assert_eq!(
+            validate_in(6 * HOUR, 6 * HOUR - 1),
+            Err(ExpiryViolation::GapTooSmall {
+                gap_secs: 1,
+                min_gap_secs: HOUR
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_alpha_expiring_before_beta() {
+        assert_eq!(
+            validate_in(5 * HOUR, 6 * HOUR),
+            Err(ExpiryViolation::GapTooSmall {
+                gap_secs: -i64::from(HOUR),
+                min_gap_secs: HOUR
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_beta_expiring_too_soon() {
+        assert_eq!(
+            validate_in(12 * HOUR, HOUR),
+            Err(ExpiryViolation::BetaExpiresTooSoon {
+                secs: i64::from(HOUR),
+                min_secs: 2 * HOUR
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_beta_expiry_in_the_past() {
+        let now = Timestamp::from(NOW);
+
+        assert_eq!(
+            validate(&policy(), now, Timestamp::from(NOW - HOUR), now),
+            Err(ExpiryViolation::BetaExpiresTooSoon {
+                secs: -i64::from(HOUR),
+                min_secs: 2 * HOUR
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_swaps_locking_up_funds_for_too_long() {
+        assert_eq!(
+            validate_in(24 * HOUR + 1, 6 * HOUR),
+            Err(ExpiryViolation::SwapTooLong {
+                secs: i64::from(24 * HOUR + 1),
+                max_secs: 24 * HOUR
+            })
+        );
+    }
+
+    #[test]
+    fn alice_has_to_fund_early_enough_for_three_confirmations() {
+        let beta_expiry = Timestamp::from(NOW);
+
+        assert_eq!(
+            rfc003_fund_deadline(Role::Alice, beta_expiry, 600, 180),
+            Timestamp::from(NOW - 960)
+        );
+        assert_eq!(
+            rfc003_fund_deadline(Role::Bob, beta_expiry, 600, 180),
+            Timestamp::from(NOW - 360)
+        );
+    }
+
+    #[test]
+    fn han_halight_deadline_leaves_time_for_cltv_and_redeem() {
+        assert_eq!(
+            han_halight_fund_deadline(Timestamp::from(NOW), 144, 180),
+            Timestamp::from(NOW - 24 * HOUR - 180)
+        );
+    }
+
+    #[test]
+    fn funding_is_rejected_after_the_point_of_no_return() {
+        let deadline = Timestamp::from(NOW);
+
+        assert_eq!(ensure_before_point_of_no_return(deadline, deadline), Ok(()));
+        assert_eq!(
+            ensure_before_point_of_no_return(deadline, deadline.plus(1)),
+            Err(PointOfNoReturnPassed { deadline })
+        );
+    }
+
+    #[test]
+    fn cltv_expiry_is_converted_using_average_block_time() {
+        let now = Timestamp::from(NOW);
+
+        assert_eq!(cltv_expiry_to_timestamp(144, now), now.plus(24 * HOUR));
+    }
+}
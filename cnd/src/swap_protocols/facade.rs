@@ -1,16 +1,32 @@
 use crate::{
     asset,
+    btsieve::{
+        ethereum::{self, Web3Connector},
+        LatestBlock,
+    },
+    config::{ConfirmationTargets, Expiries},
     db::CreatedSwap,
+    ethereum::gas_estimation::GasEstimator,
     identity,
     network::{comit_ln, protocols::announce::SwapDigest, DialInformation, Swarm},
-    swap_protocols::{halight, LedgerStates, LocalSwapId, Role},
+    swap_protocols::{
+        cltv_budget::BlockDelta,
+        expiries::LatestBlockTime,
+        halight::{self, InvoiceCancellations, LndActionExecutor},
+        ledger::Ethereum,
+        LedgerStates, LocalSwapId, Role, Side, SwapErrors,
+    },
     timestamp::Timestamp,
 };
+use chrono::Utc;
 use digest::{Digest, IntoDigestInput};
 use std::sync::Arc;
 
 /// This represent the information available on a swap
 /// before communication with the other node has started
+///
+/// Announce swaps with their `swap_digest`, the derived digest ignores where
+/// the Ethereum HTLC lives.
 #[derive(Clone, Digest, Debug, PartialEq)]
 #[digest(hash = "SwapDigest")]
 pub struct HanEtherereumHalightBitcoinCreateSwapParams {
@@ -25,13 +41,40 @@ pub struct HanEtherereumHalightBitcoinCreateSwapParams {
     #[digest(prefix = "2002")]
     pub ethereum_amount: asset::Ether,
     #[digest(ignore)]
+    pub ethereum_htlc: EthereumHtlc,
+    #[digest(ignore)]
     pub lightning_identity: identity::Lightning,
     #[digest(prefix = "3001")]
-    pub lightning_cltv_expiry: Timestamp,
+    pub lightning_cltv_expiry: BlockDelta,
     #[digest(prefix = "3002")]
     pub lightning_amount: asset::Bitcoin,
 }
 
+impl HanEtherereumHalightBitcoinCreateSwapParams {
+    /// The digest both parties announce the swap with. Swaps deploying their
+    /// HTLC keep the digest of nodes predating the swap registry, swaps
+    /// hosted by a registry only match if both parties use the same one.
+    pub fn swap_digest(self) -> SwapDigest {
+        match self.ethereum_htlc {
+            EthereumHtlc::Deployed => self.digest(),
+            ethereum_htlc => SwapInRegistry {
+                swap: self.digest(),
+                ethereum_htlc,
+            }
+            .digest(),
+        }
+    }
+}
+
+#[derive(Digest)]
+#[digest(hash = "SwapDigest")]
+struct SwapInRegistry {
+    #[digest(prefix = "0001")]
+    swap: SwapDigest,
+    #[digest(prefix = "2003")]
+    ethereum_htlc: EthereumHtlc,
+}
+
 impl IntoDigestInput for asset::Bitcoin {
     fn into_digest_input(self) -> Vec<u8> {
         self.to_le_bytes().to_vec()
@@ -59,12 +102,42 @@ impl From<EthereumIdentity> for identity::Ethereum {
     }
 }
 
+/// Where the Ethereum HTLC of a swap lives. Being part of the swap digest of
+/// registry swaps, both parties have to use the same swap registry for their
+/// swaps to match.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EthereumHtlc {
+    /// A contract is deployed for the swap.
+    Deployed,
+    /// The swap registry contract at the given address hosts the HTLC.
+    SwapRegistry(identity::Ethereum),
+}
+
+impl IntoDigestInput for EthereumHtlc {
+    fn into_digest_input(self) -> Vec<u8> {
+        match self {
+            EthereumHtlc::Deployed => vec![0u8],
+            EthereumHtlc::SwapRegistry(address) => {
+                let mut bytes = vec![1u8];
+                bytes.extend_from_slice(&<[u8; 20]>::from(address));
+                bytes
+            }
+        }
+    }
+}
+
 impl IntoDigestInput for Timestamp {
     fn into_digest_input(self) -> Vec<u8> {
         self.to_bytes().to_vec()
     }
 }
 
+impl IntoDigestInput for BlockDelta {
+    fn into_digest_input(self) -> Vec<u8> {
+        self.to_bytes().to_vec()
+    }
+}
+
 /// This is a facade that implements all the required traits and forwards them
 /// to another implementation. This allows us to keep the number of arguments to
 /// HTTP API controllers small and still access all the functionality we need.
@@ -74,6 +147,17 @@ pub struct Facade {
     // We currently only support Han-HALight, therefor 'alpha' is Ethereum and 'beta' is Lightning.
     pub alpha_ledger_states: Arc<LedgerStates>,
     pub beta_ledger_states: Arc<halight::States>,
+    pub swap_errors: SwapErrors,
+    pub expiries: Expiries,
+    pub confirmation_targets: ConfirmationTargets,
+    pub ethereum_connector: Arc<ethereum::Cache<Web3Connector>>,
+    pub ethereum_gas_estimator: Arc<GasEstimator>,
+    /// Where the Ethereum HTLCs of new swaps live.
+    pub ethereum_htlc: EthereumHtlc,
+    /// Present if cnd is configured to execute lnd actions itself.
+    pub lnd_action_executor: Option<LndActionExecutor>,
+    /// Present if cnd is configured to execute lnd actions itself.
+    pub invoice_cancellations: Option<InvoiceCancellations>,
 }
 
 impl Facade {
@@ -92,4 +176,38 @@ impl Facade {
     pub async fn get_finalized_swap(&self, id: LocalSwapId) -> Option<comit_ln::FinalizedSwap> {
         self.swarm.get_finalized_swap(id).await
     }
+
+    pub async fn get_refusal(&self, id: LocalSwapId) -> Option<comit_ln::Refusal> {
+        self.swarm.get_refusal(id).await
+    }
+
+    /// Restarts the watchers of all sides of the swap that failed, provided
+    /// they all failed for reasons that retrying may resolve.
+    ///
+    /// The errors of a side are only cleared once its watcher was restarted.
+    pub async fn retry(&self, id: LocalSwapId) -> anyhow::Result<()> {
+        for side in self.swap_errors.sides_to_retry(id).await? {
+            let retried_at = Utc::now().naive_local();
+
+            tracing::info!("retrying {} watcher of swap {}", side, id);
+
+            if side == Side::Beta {
+                self.beta_ledger_states.reset(&id).await;
+            }
+
+            self.swarm.retry_failed_swap(id, side).await?;
+            self.swap_errors.clear(id, side, retried_at).await?;
+        }
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl LatestBlockTime<Ethereum> for Facade {
+    async fn latest_block_time(&self) -> anyhow::Result<Timestamp> {
+        let block = self.ethereum_connector.latest_block().await?;
+
+        Ok(Timestamp::from(block.timestamp.low_u32()))
+    }
 }
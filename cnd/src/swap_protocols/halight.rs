@@ -1,15 +1,17 @@
 use crate::{
     asset, identity,
     swap_protocols::{
+        cltv_budget::{self, CltvBudget},
         rfc003::{Secret, SecretHash},
         state,
         state::Update,
-        LocalSwapId,
+        InsertFailedSwap, LocalSwapId, Phase, Role, Side, SwapError, SwapErrors,
     },
+    timestamp::Timestamp,
 };
 use futures::{
     future::{self, Either},
-    Stream, TryFutureExt, TryStreamExt,
+    Stream, StreamExt, TryFutureExt,
 };
 use genawaiter::sync::Gen;
 use std::{
@@ -18,9 +20,67 @@ use std::{
 };
 use tokio::sync::Mutex;
 
+mod c_lightning;
 mod connector;
+mod invoice_cancellations;
 
-pub use connector::*;
+pub use self::{c_lightning::*, connector::*, invoice_cancellations::*};
+
+/// Connects to the Lightning node selected in the configuration.
+#[derive(Clone, Debug)]
+pub enum LightningConnectorParams {
+    Lnd(LndConnectorParams),
+    CLightning(CLightningConnectorParams),
+}
+
+impl LightningConnectorParams {
+    /// Checks that the payment of the swap can go through our Lightning node
+    /// and is settled early enough.
+    pub async fn check_payment(
+        &self,
+        role: Role,
+        params: Params,
+        alpha_expiry: Timestamp,
+        min_margin_secs: u32,
+    ) -> anyhow::Result<()> {
+        match (self, role) {
+            (LightningConnectorParams::Lnd(connector_params), Role::Alice) => {
+                let connector = LndConnectorAsReceiver::from(connector_params.clone());
+                check_payment(&connector, params, alpha_expiry, min_margin_secs).await
+            }
+            (LightningConnectorParams::Lnd(connector_params), Role::Bob) => {
+                let connector = LndConnectorAsSender::from(connector_params.clone());
+                check_payment(&connector, params, alpha_expiry, min_margin_secs).await
+            }
+            (LightningConnectorParams::CLightning(connector_params), Role::Alice) => {
+                let connector = CLightningConnectorAsReceiver::from(connector_params.clone());
+                check_payment(&connector, params, alpha_expiry, min_margin_secs).await
+            }
+            (LightningConnectorParams::CLightning(connector_params), Role::Bob) => {
+                let connector = CLightningConnectorAsSender::from(connector_params.clone());
+                check_payment(&connector, params, alpha_expiry, min_margin_secs).await
+            }
+        }
+    }
+}
+
+/// Alice needs enough inbound capacity to receive the payment, Bob needs a
+/// route to Alice's node. The Ethereum HTLC must not expire before the
+/// payment can be settled at the latest plus `min_margin_secs`.
+async fn check_payment<C>(
+    connector: &C,
+    params: Params,
+    alpha_expiry: Timestamp,
+    min_margin_secs: u32,
+) -> anyhow::Result<()>
+where
+    C: CheckLiquidity,
+{
+    let budget = connector.check_liquidity(params).await?;
+    cltv_budget::verify(&budget, alpha_expiry, min_margin_secs)?;
+
+    Ok(())
+}
 
 /// Htlc Lightning Bitcoin atomic swap protocol.
 
@@ -38,21 +98,39 @@ pub struct CreatedSwap {
 ///
 /// This function delegates to the `new` function for the actual protocol
 /// implementation. Its main purpose is to annotate the protocol instance with
-/// logging information and store the events yielded by the protocol.
+/// logging information, store the events yielded by the protocol and record
+/// the error against the given side of the swap in case the protocol fails.
 pub async fn new_halight_swap<C>(
     id: LocalSwapId,
-    secret_hash: SecretHash,
+    side: Side,
+    params: Params,
     state_store: Arc<States>,
     connector: C,
+    swap_errors: SwapErrors,
 ) where
     C: WaitForOpened + WaitForAccepted + WaitForSettled + WaitForCancelled,
 {
-    let mut events = new(&connector, Params { secret_hash })
-        .inspect_ok(|event| tracing::info!("yielded event {}", event))
-        .inspect_err(|error| tracing::error!("swap failed with {:?}", error));
-
-    while let Ok(Some(event)) = events.try_next().await {
-        state_store.update(&id, event).await;
+    let mut events = new(&connector, params);
+    let mut phase = Phase::Init;
+
+    while let Some(event) = events.next().await {
+        match event {
+            Ok(event) => {
+                tracing::info!("yielded event {}", event);
+                if let Event::Rejected(rejected) = event {
+                    tracing::warn!("payment must not be settled: {}", rejected);
+                }
+                phase = event.next_phase(phase);
+                state_store.update(&id, event).await;
+            }
+            Err(error) => {
+                tracing::error!("swap failed with {:?}", error);
+                swap_errors
+                    .insert_failed_swap(id.into(), SwapError::new(side, phase, &error))
+                    .await;
+                return;
+            }
+        }
     }
 
     tracing::info!("swap finished");
@@ -79,12 +157,37 @@ pub trait WaitForCancelled {
     async fn wait_for_cancelled(&self, params: Params) -> anyhow::Result<Cancelled>;
 }
 
+/// Fails if the payment of the swap cannot go through our Lightning node,
+/// otherwise estimates its CLTV deltas starting at the current tip of the
+/// Bitcoin chain.
+#[async_trait::async_trait]
+pub trait CheckLiquidity {
+    async fn check_liquidity(&self, params: Params) -> anyhow::Result<CltvBudget>;
+}
+
+/// The reason why a payment cannot go through our Lightning node.
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum InsufficientLiquidity {
+    #[error("no route to {to} can carry {amount}: {reason}")]
+    NoRoute {
+        to: identity::Lightning,
+        amount: asset::Bitcoin,
+        reason: String,
+    },
+    #[error("no channel can receive {amount}, the largest inbound capacity is {capacity}")]
+    InboundCapacity {
+        amount: asset::Bitcoin,
+        capacity: asset::Bitcoin,
+    },
+}
+
 /// Represents states that an invoice can be in.
 #[derive(Debug, Clone, Copy)]
 pub enum State {
     None,
     Opened(Opened),
     Accepted(Accepted),
+    Rejected(Rejected),
     Settled(Settled),
     Cancelled(Cancelled),
 }
@@ -110,6 +213,12 @@ pub enum Event {
     /// the payment is `InFlight`, it also reached the recipient.
     Accepted(Accepted),
 
+    /// The payment was accepted but does not match the agreed parameters.
+    ///
+    /// The recipient must not settle such a payment, the swap can only be
+    /// completed by cancelling it.
+    Rejected(Rejected),
+
     /// The payment is settled and therefore the preimage was revealed.
     Settled(Settled),
 
@@ -117,14 +226,55 @@ pub enum Event {
     Cancelled(Cancelled),
 }
 
+impl Event {
+    /// The phase the protocol is in after this event has been observed.
+    fn next_phase(&self, current: Phase) -> Phase {
+        match self {
+            Event::Started => current,
+            Event::Opened(_) => Phase::Fund,
+            Event::Accepted(_) | Event::Rejected(_) | Event::Settled(_) | Event::Cancelled(_) => {
+                Phase::RedeemOrRefund
+            }
+        }
+    }
+}
+
 /// Represents the data available at said state.
 ///
 /// These empty types are useful because they give us additional type safety.
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Opened;
 
-#[derive(Debug, Clone, Copy, PartialEq)]
-pub struct Accepted;
+/// What the Lightning node reported about the accepted payment, values are
+/// `None` if the node does not expose them to our role.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Accepted {
+    pub amount: Option<asset::Bitcoin>,
+    /// The smallest CLTV delta of the HTLCs paying the invoice.
+    pub cltv_delta: Option<u32>,
+    /// The node on the other end of the payment.
+    pub counterparty: Option<identity::Lightning>,
+}
+
+/// The reason why an accepted payment does not match the agreed parameters.
+#[derive(Debug, Clone, Copy, PartialEq, thiserror::Error)]
+pub enum Rejected {
+    #[error("invoice is underpaid, expected {expected} but got {paid}")]
+    Underpaid {
+        expected: asset::Bitcoin,
+        paid: asset::Bitcoin,
+    },
+    #[error("payment was accepted with a CLTV delta of {cltv_delta} blocks but at least {min_cltv_delta} were agreed on")]
+    CltvTooShort {
+        cltv_delta: u32,
+        min_cltv_delta: u32,
+    },
+    #[error("payment was made to {actual} instead of {expected}")]
+    WrongCounterparty {
+        expected: identity::Lightning,
+        actual: identity::Lightning,
+    },
+}
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Settled {
@@ -137,6 +287,14 @@ pub struct Cancelled;
 #[derive(Default, Debug)]
 pub struct States(Mutex<HashMap<LocalSwapId, State>>);
 
+impl States {
+    /// Forgets the state of a swap so that the protocol can be started from
+    /// scratch, e.g. when retrying a failed swap.
+    pub async fn reset(&self, key: &LocalSwapId) {
+        let _ = self.0.lock().await.remove(key);
+    }
+}
+
 impl State {
     pub fn transition_to_opened(&mut self, opened: Opened) {
         match std::mem::replace(self, State::None) {
@@ -152,10 +310,19 @@ impl State {
         }
     }
 
+    pub fn transition_to_rejected(&mut self, rejected: Rejected) {
+        match std::mem::replace(self, State::None) {
+            State::Opened(_) => *self = State::Rejected(rejected),
+            other => panic!("expected state Opened, got {:?}", other),
+        }
+    }
+
     pub fn transition_to_settled(&mut self, settled: Settled) {
         match std::mem::replace(self, State::None) {
             State::Accepted(_) => *self = State::Settled(settled),
-            other => panic!("expected state Accepted, got {:?}", other),
+            // The recipient settled the invoice despite our validation.
+            State::Rejected(_) => *self = State::Settled(settled),
+            other => panic!("expected state Accepted or Rejected, got {:?}", other),
         }
     }
 
@@ -165,7 +332,12 @@ impl State {
             State::Opened(_) => *self = State::Cancelled(cancelled),
             // Alice cancels invoice after Bob has accepted it.
             State::Accepted(_) => *self = State::Cancelled(cancelled),
-            other => panic!("expected state Opened or Accepted, got {:?}", other),
+            // Alice cancels invoice because Bob's payment was rejected.
+            State::Rejected(_) => *self = State::Cancelled(cancelled),
+            other => panic!(
+                "expected state Opened, Accepted or Rejected, got {:?}",
+                other
+            ),
         }
     }
 }
@@ -196,6 +368,9 @@ impl state::Update<Event> for States {
             (Event::Accepted(accepted), Entry::Occupied(mut state)) => {
                 state.get_mut().transition_to_accepted(accepted)
             }
+            (Event::Rejected(rejected), Entry::Occupied(mut state)) => {
+                state.get_mut().transition_to_rejected(rejected)
+            }
             (Event::Settled(settled), Entry::Occupied(mut state)) => {
                 state.get_mut().transition_to_settled(settled)
             }
@@ -227,18 +402,21 @@ where
             co.yield_(Ok(Event::Started)).await;
 
             let opened_or_error = connector
-                .wait_for_opened(params.clone())
+                .wait_for_opened(params)
                 .map_ok(Event::Opened)
                 .await;
             co.yield_(opened_or_error).await;
 
             let accepted_or_error = connector
-                .wait_for_accepted(params.clone())
-                .map_ok(Event::Accepted)
+                .wait_for_accepted(params)
+                .map_ok(|accepted| match params.validate(&accepted) {
+                    Ok(()) => Event::Accepted(accepted),
+                    Err(rejected) => Event::Rejected(rejected),
+                })
                 .await;
             co.yield_(accepted_or_error).await;
 
-            let settled = connector.wait_for_settled(params.clone());
+            let settled = connector.wait_for_settled(params);
             let cancelled = connector.wait_for_cancelled(params);
 
             match future::try_select(settled, cancelled).await {
@@ -258,7 +436,126 @@ where
     })
 }
 
+/// The parameters of a halight swap as agreed on during negotiation.
 #[derive(Clone, Copy, Debug)]
 pub struct Params {
     pub secret_hash: SecretHash,
+    pub amount: asset::Bitcoin,
+    /// The minimum CLTV delta of the HTLCs paying the invoice.
+    pub cltv_expiry: u32,
+    /// The node that pays the invoice if we are the recipient, the node the
+    /// payment is sent to otherwise.
+    pub counterparty: identity::Lightning,
+}
+
+impl Params {
+    /// Checks the accepted payment against the agreed parameters, values
+    /// that were not reported by the Lightning node are not checked.
+    pub fn validate(&self, accepted: &Accepted) -> Result<(), Rejected> {
+        if let Some(paid) = accepted.amount {
+            if paid < self.amount {
+                return Err(Rejected::Underpaid {
+                    expected: self.amount,
+                    paid,
+                });
+            }
+        }
+
+        if let Some(cltv_delta) = accepted.cltv_delta {
+            if cltv_delta < self.cltv_expiry {
+                return Err(Rejected::CltvTooShort {
+                    cltv_delta,
+                    min_cltv_delta: self.cltv_expiry,
+                });
+            }
+        }
+
+        if let Some(actual) = accepted.counterparty {
+            if actual != self.counterparty {
+                return Err(Rejected::WrongCounterparty {
+                    expected: self.counterparty,
+                    actual,
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> Params {
+        Params {
+            secret_hash: Secret::from([1u8; 32]).hash(),
+            amount: asset::Bitcoin::from_sat(10_000),
+            cltv_expiry: 144,
+            counterparty: identity::Lightning::random(),
+        }
+    }
+
+    #[test]
+    fn payment_matching_the_params_is_valid() {
+        let params = params();
+        let accepted = Accepted {
+            amount: Some(asset::Bitcoin::from_sat(10_000)),
+            cltv_delta: Some(150),
+            counterparty: Some(params.counterparty),
+        };
+
+        assert_eq!(params.validate(&accepted), Ok(()));
+        assert_eq!(params.validate(&Accepted::default()), Ok(()));
+    }
+
+    #[test]
+    fn underpaid_invoice_is_rejected() {
+        let accepted = Accepted {
+            amount: Some(asset::Bitcoin::from_sat(9_999)),
+            ..Accepted::default()
+        };
+
+        assert_eq!(
+            params().validate(&accepted),
+            Err(Rejected::Underpaid {
+                expected: asset::Bitcoin::from_sat(10_000),
+                paid: asset::Bitcoin::from_sat(9_999)
+            })
+        );
+    }
+
+    #[test]
+    fn too_short_cltv_is_rejected() {
+        let accepted = Accepted {
+            cltv_delta: Some(40),
+            ..Accepted::default()
+        };
+
+        assert_eq!(
+            params().validate(&accepted),
+            Err(Rejected::CltvTooShort {
+                cltv_delta: 40,
+                min_cltv_delta: 144
+            })
+        );
+    }
+
+    #[test]
+    fn payment_to_another_node_is_rejected() {
+        let params = params();
+        let actual = identity::Lightning::random();
+        let accepted = Accepted {
+            counterparty: Some(actual),
+            ..Accepted::default()
+        };
+
+        assert_eq!(
+            params.validate(&accepted),
+            Err(Rejected::WrongCounterparty {
+                expected: params.counterparty,
+                actual
+            })
+        );
+    }
 }
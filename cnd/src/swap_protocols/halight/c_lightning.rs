@@ -0,0 +1,716 @@
+//! Connectors watching halight swaps through c-lightning's JSON-RPC
+//! interface.
+//!
+//! c-lightning does not support hold invoices natively, the receiver relies on
+//! the `holdinvoice` plugin.
+
+use crate::{
+    asset, identity, jsonrpc,
+    swap_protocols::{
+        cltv_budget::{ChainTip, CltvBudget},
+        halight::{
+            Accepted, Cancelled, CheckLiquidity, InsufficientLiquidity, Opened, Params, Settled,
+            WaitForAccepted, WaitForCancelled, WaitForOpened, WaitForSettled,
+        },
+        rfc003::{Secret, SecretHash},
+    },
+    timestamp::Timestamp,
+};
+use anyhow::{Context, Error};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// The JSON-RPC error code c-lightning responds with if a method does not
+/// exist, i.e. a plugin is missing.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+#[derive(Clone, Debug)]
+pub struct CLightningConnectorParams {
+    rpc_socket: PathBuf,
+    retry_interval_ms: u64,
+}
+
+impl CLightningConnectorParams {
+    pub fn new(rpc_socket: PathBuf, retry_interval_ms: u64) -> Self {
+        Self {
+            rpc_socket,
+            retry_interval_ms,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Client {
+    rpc_socket: PathBuf,
+}
+
+#[derive(Debug, Deserialize)]
+struct Response<T> {
+    result: Option<T>,
+    error: Option<RpcError>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl Client {
+    async fn call<Req, Res>(&self, method: &str, params: Req) -> Result<Res, Error>
+    where
+        Req: Serialize,
+        Res: DeserializeOwned,
+    {
+        let request = serde_json::to_vec(&jsonrpc::Request::new(method, params))?;
+        let response = send(&self.rpc_socket, &request)
+            .await
+            .with_context(|| format!("failed to call {} on c-lightning", method))?;
+        let response = serde_json::from_slice::<Response<Res>>(&response)
+            .with_context(|| format!("failed to deserialize response to {}", method))?;
+
+        match response {
+            Response {
+                error: Some(RpcError { code, message }),
+                ..
+            } => Err(jsonrpc::Error::JsonRpc { code, message }.into()),
+            Response {
+                result: Some(result),
+                ..
+            } => Ok(result),
+            _ => Err(anyhow::anyhow!(
+                "c-lightning responded to {} without result",
+                method
+            )),
+        }
+    }
+}
+
+/// Sends a request to c-lightning and returns the raw response.
+///
+/// c-lightning keeps the connection open after responding, each response is
+/// terminated by an empty line.
+#[cfg(unix)]
+async fn send(rpc_socket: &Path, request: &[u8]) -> Result<Vec<u8>, Error> {
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::UnixStream,
+    };
+
+    let mut stream = UnixStream::connect(rpc_socket).await?;
+    stream.write_all(request).await?;
+
+    let mut response = Vec::new();
+    let mut buffer = [0u8; 4096];
+    loop {
+        let read = stream.read(&mut buffer).await?;
+        response.extend_from_slice(&buffer[..read]);
+
+        if read == 0 || response.ends_with(b"\n\n") {
+            return Ok(response);
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn send(_: &Path, _: &[u8]) -> Result<Vec<u8>, Error> {
+    anyhow::bail!("c-lightning's JSON-RPC socket is only available on unix")
+}
+
+#[derive(Debug, Deserialize)]
+struct GetInfo {
+    blockheight: u32,
+}
+
+#[derive(Debug, Deserialize)]
+struct GetRawBlockByHeight {
+    /// The hex encoded block, `null` if the Bitcoin backend does not know it.
+    block: Option<String>,
+}
+
+impl GetRawBlockByHeight {
+    /// The time field of the header, the first 80 bytes of the block.
+    fn time(&self) -> Result<u32, Error> {
+        let block = self
+            .block
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("the Bitcoin backend does not know the block"))?;
+        let header = block
+            .get(..160)
+            .ok_or_else(|| anyhow::anyhow!("block is too short: {}", block))?;
+        let header: bitcoin::BlockHeader = bitcoin::consensus::deserialize(&hex::decode(header)?)?;
+
+        Ok(header.time)
+    }
+}
+
+/// The block c-lightning synced to. c-lightning only reports the height, the
+/// time of the block is read from its Bitcoin backend plugin.
+async fn chain_tip(client: &Client) -> Result<ChainTip, Error> {
+    let info: GetInfo = client.call("getinfo", serde_json::json!({})).await?;
+    let block: GetRawBlockByHeight = client
+        .call(
+            "getrawblockbyheight",
+            serde_json::json!({ "height": info.blockheight }),
+        )
+        .await?;
+
+    Ok(ChainTip {
+        height: info.blockheight,
+        time: Timestamp::from(block.time()?),
+    })
+}
+
+/// Payment status.  These mirror the status of payments used by `listpays`.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum PayStatus {
+    Pending,
+    Complete,
+    Failed,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListPays {
+    pays: Vec<Pay>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Pay {
+    status: PayStatus,
+    preimage: Option<Secret>,
+    destination: Option<identity::Lightning>,
+    /// The amount delivered to the destination, excluding fees.
+    amount_msat: Option<Msat>,
+}
+
+/// c-lightning reports amounts in millisatoshi, either as a number or as a
+/// string with an `msat` suffix.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Msat {
+    Number(u64),
+    String(String),
+}
+
+impl Msat {
+    fn to_bitcoin(&self) -> Result<asset::Bitcoin, Error> {
+        let msat = match self {
+            Msat::Number(msat) => *msat,
+            Msat::String(msat) => msat
+                .trim_end_matches("msat")
+                .parse()
+                .with_context(|| format!("invalid amount: {}", msat))?,
+        };
+
+        Ok(asset::Bitcoin::from_sat(msat / 1000))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct GetRoute {
+    route: Vec<RouteHop>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RouteHop {
+    /// The CLTV delta of the HTLC offered to this hop, relative to the
+    /// current height.
+    delay: u32,
+}
+
+#[derive(Clone, Debug)]
+pub struct CLightningConnectorAsSender {
+    client: Client,
+    retry_interval_ms: u64,
+}
+
+impl From<CLightningConnectorParams> for CLightningConnectorAsSender {
+    fn from(params: CLightningConnectorParams) -> Self {
+        Self {
+            client: Client {
+                rpc_socket: params.rpc_socket,
+            },
+            retry_interval_ms: params.retry_interval_ms,
+        }
+    }
+}
+
+impl CLightningConnectorAsSender {
+    async fn wait_for_pay(
+        &self,
+        secret_hash: SecretHash,
+        statuses: &[PayStatus],
+    ) -> Result<Pay, Error> {
+        loop {
+            let response: ListPays = self
+                .client
+                .call(
+                    "listpays",
+                    serde_json::json!({ "payment_hash": format!("{:x}", secret_hash) }),
+                )
+                .await?;
+
+            if let Some(pay) = response
+                .pays
+                .into_iter()
+                .find(|pay| statuses.contains(&pay.status))
+            {
+                return Ok(pay);
+            }
+
+            tokio::time::delay_for(Duration::from_millis(self.retry_interval_ms)).await;
+        }
+    }
+}
+
+impl CLightningConnectorAsSender {
+    /// Asks c-lightning for a route to the counterparty that can carry the
+    /// payment.
+    async fn get_route(&self, params: Params) -> Result<GetRoute, Error> {
+        let result = self
+            .client
+            .call::<_, GetRoute>(
+                "getroute",
+                serde_json::json!({
+                    "id": params.counterparty,
+                    "msatoshi": params.amount.as_sat() * 1000,
+                    "riskfactor": 1,
+                    "cltv": params.cltv_expiry,
+                }),
+            )
+            .await;
+
+        match result {
+            Ok(route) => Ok(route),
+            Err(e) => match e.downcast_ref::<jsonrpc::Error>() {
+                Some(jsonrpc::Error::JsonRpc { message, .. }) => {
+                    Err(InsufficientLiquidity::NoRoute {
+                        to: params.counterparty,
+                        amount: params.amount,
+                        reason: message.clone(),
+                    }
+                    .into())
+                }
+                _ => Err(e),
+            },
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl CheckLiquidity for CLightningConnectorAsSender {
+    async fn check_liquidity(&self, params: Params) -> Result<CltvBudget, Error> {
+        let route = self.get_route(params).await?;
+        let tip = chain_tip(&self.client).await?;
+        let total_cltv_delta = route
+            .route
+            .first()
+            .map(|hop| hop.delay)
+            .unwrap_or(params.cltv_expiry);
+
+        Ok(CltvBudget {
+            tip,
+            final_cltv_delta: params.cltv_expiry,
+            route_cltv_delta: total_cltv_delta.saturating_sub(params.cltv_expiry),
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl WaitForOpened for CLightningConnectorAsSender {
+    async fn wait_for_opened(&self, _params: Params) -> Result<Opened, Error> {
+        // At this stage there is no way for the sender to know when the invoice is
+        // added on receiver's side.
+        Ok(Opened)
+    }
+}
+
+#[async_trait::async_trait]
+impl WaitForAccepted for CLightningConnectorAsSender {
+    async fn wait_for_accepted(&self, params: Params) -> Result<Accepted, Error> {
+        let pay = self
+            .wait_for_pay(params.secret_hash, &[
+                PayStatus::Pending,
+                PayStatus::Complete,
+            ])
+            .await?;
+
+        Ok(Accepted {
+            amount: pay.amount_msat.as_ref().map(Msat::to_bitcoin).transpose()?,
+            cltv_delta: None,
+            counterparty: pay.destination,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl WaitForSettled for CLightningConnectorAsSender {
+    async fn wait_for_settled(&self, params: Params) -> Result<Settled, Error> {
+        let pay = self
+            .wait_for_pay(params.secret_hash, &[PayStatus::Complete])
+            .await?;
+
+        let secret = pay.preimage.ok_or_else(|| {
+            anyhow::anyhow!(
+                "Pre-image is not present on c-lightning response for a complete payment: {}",
+                params.secret_hash
+            )
+        })?;
+
+        Ok(Settled { secret })
+    }
+}
+
+#[async_trait::async_trait]
+impl WaitForCancelled for CLightningConnectorAsSender {
+    async fn wait_for_cancelled(&self, params: Params) -> Result<Cancelled, Error> {
+        self.wait_for_pay(params.secret_hash, &[PayStatus::Failed])
+            .await?;
+
+        Ok(Cancelled)
+    }
+}
+
+/// Hold invoice states.  These mirror the states used by the `holdinvoice`
+/// plugin.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, strum_macros::Display)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum HoldInvoiceState {
+    Open,
+    Accepted,
+    Settled,
+    Canceled,
+}
+
+#[derive(Debug, Deserialize)]
+struct HoldInvoice {
+    state: HoldInvoiceState,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListInvoices {
+    invoices: Vec<Invoice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Invoice {
+    payment_preimage: Option<Secret>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListFunds {
+    channels: Vec<FundsChannel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FundsChannel {
+    state: String,
+    connected: bool,
+    /// Our balance in the channel.
+    channel_sat: u64,
+    channel_total_sat: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct CLightningConnectorAsReceiver {
+    client: Client,
+    retry_interval_ms: u64,
+}
+
+impl From<CLightningConnectorParams> for CLightningConnectorAsReceiver {
+    fn from(params: CLightningConnectorParams) -> Self {
+        Self {
+            client: Client {
+                rpc_socket: params.rpc_socket,
+            },
+            retry_interval_ms: params.retry_interval_ms,
+        }
+    }
+}
+
+impl CLightningConnectorAsReceiver {
+    async fn find_hold_invoice(
+        &self,
+        secret_hash: SecretHash,
+        expected_states: &[HoldInvoiceState],
+    ) -> Result<Option<HoldInvoice>, Error> {
+        let result = self
+            .client
+            .call::<_, HoldInvoice>(
+                "holdinvoicelookup",
+                serde_json::json!({ "payment_hash": format!("{:x}", secret_hash) }),
+            )
+            .await;
+
+        let invoice = match result {
+            Ok(invoice) => invoice,
+            Err(e) => match e.downcast_ref::<jsonrpc::Error>() {
+                Some(jsonrpc::Error::JsonRpc { code, .. }) if *code == METHOD_NOT_FOUND => {
+                    return Err(e.context("the holdinvoice plugin is not installed"))
+                }
+                Some(jsonrpc::Error::JsonRpc { message, .. }) => {
+                    tracing::debug!("hold invoice not found: {}", message);
+                    return Ok(None);
+                }
+                _ => return Err(e),
+            },
+        };
+
+        if expected_states.contains(&invoice.state) {
+            Ok(Some(invoice))
+        } else {
+            tracing::debug!("hold invoice exists but is in state {}", invoice.state);
+            Ok(None)
+        }
+    }
+
+    async fn wait_for_hold_invoice(
+        &self,
+        secret_hash: SecretHash,
+        expected_states: &[HoldInvoiceState],
+    ) -> Result<HoldInvoice, Error> {
+        loop {
+            if let Some(invoice) = self.find_hold_invoice(secret_hash, expected_states).await? {
+                return Ok(invoice);
+            }
+
+            tokio::time::delay_for(Duration::from_millis(self.retry_interval_ms)).await;
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl CheckLiquidity for CLightningConnectorAsReceiver {
+    async fn check_liquidity(&self, params: Params) -> Result<CltvBudget, Error> {
+        let response: ListFunds = self.client.call("listfunds", serde_json::json!({})).await?;
+
+        // A payment is only split if the sender supports it, hence a single
+        // channel has to be able to receive the whole amount.
+        let capacity = response
+            .channels
+            .iter()
+            .filter(|channel| channel.connected && channel.state == "CHANNELD_NORMAL")
+            .map(|channel| {
+                channel
+                    .channel_total_sat
+                    .saturating_sub(channel.channel_sat)
+            })
+            .max()
+            .unwrap_or(0);
+
+        let capacity = asset::Bitcoin::from_sat(capacity);
+        if capacity < params.amount {
+            return Err(InsufficientLiquidity::InboundCapacity {
+                amount: params.amount,
+                capacity,
+            }
+            .into());
+        }
+
+        // The route is chosen by the sender, we can only account for the CLTV
+        // delta of the HTLC offered to us.
+        Ok(CltvBudget {
+            tip: chain_tip(&self.client).await?,
+            final_cltv_delta: params.cltv_expiry,
+            route_cltv_delta: 0,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl WaitForOpened for CLightningConnectorAsReceiver {
+    async fn wait_for_opened(&self, params: Params) -> Result<Opened, Error> {
+        self.wait_for_hold_invoice(params.secret_hash, &[
+            HoldInvoiceState::Open,
+            HoldInvoiceState::Accepted,
+            HoldInvoiceState::Settled,
+        ])
+        .await?;
+
+        Ok(Opened)
+    }
+}
+
+#[async_trait::async_trait]
+impl WaitForAccepted for CLightningConnectorAsReceiver {
+    async fn wait_for_accepted(&self, params: Params) -> Result<Accepted, Error> {
+        self.wait_for_hold_invoice(params.secret_hash, &[
+            HoldInvoiceState::Accepted,
+            HoldInvoiceState::Settled,
+        ])
+        .await?;
+
+        // The plugin only reports the absolute expiry of the HTLCs and not how
+        // much was paid, there is nothing we can validate.
+        Ok(Accepted::default())
+    }
+}
+
+#[async_trait::async_trait]
+impl WaitForSettled for CLightningConnectorAsReceiver {
+    async fn wait_for_settled(&self, params: Params) -> Result<Settled, Error> {
+        self.wait_for_hold_invoice(params.secret_hash, &[HoldInvoiceState::Settled])
+            .await?;
+
+        // The plugin does not return the preimage, the invoice it created does.
+        let response: ListInvoices = self
+            .client
+            .call(
+                "listinvoices",
+                serde_json::json!({ "payment_hash": format!("{:x}", params.secret_hash) }),
+            )
+            .await?;
+
+        let secret = response
+            .invoices
+            .into_iter()
+            .find_map(|invoice| invoice.payment_preimage)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Pre-image is not present on c-lightning response for a settled invoice: {}",
+                    params.secret_hash
+                )
+            })?;
+
+        Ok(Settled { secret })
+    }
+}
+
+#[async_trait::async_trait]
+impl WaitForCancelled for CLightningConnectorAsReceiver {
+    async fn wait_for_cancelled(&self, params: Params) -> Result<Cancelled, Error> {
+        self.wait_for_hold_invoice(params.secret_hash, &[HoldInvoiceState::Canceled])
+            .await?;
+
+        Ok(Cancelled)
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::mock_node;
+
+    fn secret() -> Secret {
+        Secret::from([1u8; 32])
+    }
+
+    fn params() -> Params {
+        Params {
+            secret_hash: secret().hash(),
+            amount: asset::Bitcoin::from_sat(10_000),
+            cltv_expiry: 144,
+            counterparty: identity::Lightning::random(),
+        }
+    }
+
+    #[tokio::test]
+    async fn sender_finds_preimage_of_complete_payment() {
+        let dir = tempfile::tempdir().unwrap();
+        let rpc_socket = dir.path().join("lightning-rpc");
+        mock_node::json_rpc_socket(&rpc_socket, move |_| {
+            Ok(serde_json::json!({
+                "pays": [{
+                    "bolt11": "lnbcrt100u1p0",
+                    "status": "complete",
+                    "preimage": format!("{:x}", secret()),
+                    "amount_sent_msat": "10000000msat"
+                }]
+            }))
+        });
+        let connector =
+            CLightningConnectorAsSender::from(CLightningConnectorParams::new(rpc_socket, 10));
+
+        let settled = connector.wait_for_settled(params()).await.unwrap();
+
+        assert_eq!(settled.secret, secret());
+    }
+
+    #[test]
+    fn deserialize_amount_of_pay() {
+        let pay = serde_json::from_str::<Pay>(
+            r#"{"status":"complete","amount_msat":"10000999msat","destination":"02c2a8efce029526d364c2cf39d89e3cdda05e5df7b2cbfc098b4e3d02b70b5275"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            pay.amount_msat.unwrap().to_bitcoin().unwrap(),
+            asset::Bitcoin::from_sat(10_000)
+        );
+        assert!(pay.destination.is_some());
+    }
+
+    #[tokio::test]
+    async fn receiver_only_counts_inbound_capacity_of_usable_channels() {
+        let dir = tempfile::tempdir().unwrap();
+        let rpc_socket = dir.path().join("lightning-rpc");
+        mock_node::json_rpc_socket(&rpc_socket, move |_| {
+            Ok(serde_json::json!({
+                "outputs": [],
+                "channels": [
+                    { "state": "CHANNELD_NORMAL", "connected": true, "channel_sat": 95_000, "channel_total_sat": 100_000 },
+                    { "state": "CHANNELD_AWAITING_LOCKIN", "connected": true, "channel_sat": 0, "channel_total_sat": 100_000 },
+                    { "state": "CHANNELD_NORMAL", "connected": false, "channel_sat": 0, "channel_total_sat": 100_000 }
+                ]
+            }))
+        });
+        let connector =
+            CLightningConnectorAsReceiver::from(CLightningConnectorParams::new(rpc_socket, 10));
+
+        let error = connector.check_liquidity(params()).await.unwrap_err();
+
+        assert_eq!(
+            error.downcast_ref::<InsufficientLiquidity>(),
+            Some(&InsufficientLiquidity::InboundCapacity {
+                amount: asset::Bitcoin::from_sat(10_000),
+                capacity: asset::Bitcoin::from_sat(5_000),
+            })
+        );
+    }
+
+    #[test]
+    fn deserialize_route() {
+        let route = serde_json::from_str::<GetRoute>(
+            r#"{"route":[{"id":"02c2a8efce029526d364c2cf39d89e3cdda05e5df7b2cbfc098b4e3d02b70b5275","channel":"103x1x0","direction":1,"msatoshi":10000000,"amount_msat":"10000000msat","delay":224,"style":"tlv"}]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(route.route[0].delay, 224);
+    }
+
+    #[test]
+    fn time_of_raw_block_is_read_from_its_header() {
+        let genesis = serde_json::from_str::<GetRawBlockByHeight>(
+            r#"{"blockhash":"000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f","block":"0100000000000000000000000000000000000000000000000000000000000000000000003ba3edfd7a7b12b27ac72c3e67768f617fc81bc3888a51323a9fb8aa4b1e5e4a29ab5f49ffff001d1dac2b7c0101000000"}"#,
+        )
+        .unwrap();
+
+        assert_eq!(genesis.time().unwrap(), 1_231_006_505);
+    }
+
+    #[test]
+    fn deserialize_hold_invoice_lookup() {
+        let invoice =
+            serde_json::from_str::<HoldInvoice>(r#"{"state":"ACCEPTED","htlc_expiry":650}"#)
+                .unwrap();
+
+        assert_eq!(invoice.state, HoldInvoiceState::Accepted);
+    }
+
+    #[test]
+    fn rpc_errors_are_json_rpc_errors() {
+        let response = serde_json::from_str::<Response<HoldInvoice>>(
+            r#"{"jsonrpc":"2.0","id":"1","error":{"code":-32601,"message":"Unknown command 'holdinvoicelookup'"}}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            response.error.map(|error| error.code),
+            Some(METHOD_NOT_FOUND)
+        );
+    }
+}
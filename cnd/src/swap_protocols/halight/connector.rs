@@ -1,16 +1,22 @@
-use crate::swap_protocols::{
-    halight::{
-        Accepted, Cancelled, Opened, Params, Settled, WaitForAccepted, WaitForCancelled,
-        WaitForOpened, WaitForSettled,
+use crate::{
+    asset, identity,
+    swap_protocols::{
+        actions::lnd,
+        cltv_budget::{ChainTip, CltvBudget},
+        halight::{
+            Accepted, Cancelled, CheckLiquidity, InsufficientLiquidity, Opened, Params, Settled,
+            WaitForAccepted, WaitForCancelled, WaitForOpened, WaitForSettled,
+        },
+        rfc003::{Secret, SecretHash},
     },
-    rfc003::{Secret, SecretHash},
+    timestamp::Timestamp,
 };
 use anyhow::{Context, Error};
 use reqwest::{
     header::{HeaderMap, HeaderValue},
     StatusCode, Url,
 };
-use serde::{de, export::fmt, Deserialize, Deserializer};
+use serde::{de, de::DeserializeOwned, export::fmt, Deserialize, Deserializer, Serialize};
 use std::{
     convert::{TryFrom, TryInto},
     io::Read,
@@ -51,6 +57,48 @@ struct Invoice {
     pub state: InvoiceState,
     #[serde(deserialize_with = "deserialize_r_preimage")]
     pub r_preimage: Option<[u8; 32]>,
+    #[serde(default)]
+    pub htlcs: Vec<InvoiceHtlc>,
+}
+
+/// HTLC states.  These mirror the invoice HTLC states used by lnd.
+// ref: https://api.lightning.community/#invoicehtlcstate
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+enum InvoiceHtlcState {
+    Accepted,
+    Settled,
+    Canceled,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct InvoiceHtlc {
+    pub accept_height: u32,
+    pub expiry_height: u32,
+    pub state: InvoiceHtlcState,
+}
+
+impl Invoice {
+    /// lnd does not know which node paid the invoice, only the amount and
+    /// the CLTV of the HTLCs can be checked.
+    fn accepted(&self) -> Result<Accepted, Error> {
+        let amount = self
+            .amt_paid_sat
+            .parse()
+            .with_context(|| format!("invalid amount paid: {}", self.amt_paid_sat))?;
+        let cltv_delta = self
+            .htlcs
+            .iter()
+            .filter(|htlc| htlc.state != InvoiceHtlcState::Canceled)
+            .map(|htlc| htlc.expiry_height.saturating_sub(htlc.accept_height))
+            .min();
+
+        Ok(Accepted {
+            amount: Some(asset::Bitcoin::from_sat(amount)),
+            cltv_delta,
+            counterparty: None,
+        })
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -60,18 +108,117 @@ struct PaymentsResponse {
 
 #[derive(Clone, Debug, Deserialize)]
 struct Payment {
+    pub value_sat: Option<String>,
     pub value_msat: Option<String>,
     pub payment_preimage: Option<Secret>,
     pub status: PaymentStatus,
     pub payment_hash: SecretHash,
+    #[serde(default)]
+    pub htlcs: Vec<HtlcAttempt>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct HtlcAttempt {
+    pub route: Route,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Route {
+    /// The absolute height at which the first HTLC of the route expires.
+    #[serde(default)]
+    pub total_time_lock: u32,
+    #[serde(default)]
+    pub hops: Vec<Hop>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct Hop {
+    pub pub_key: identity::Lightning,
+}
+
+impl Payment {
+    /// The sender only learns about the absolute time lock of its HTLCs,
+    /// hence the CLTV delta is not reported.
+    fn accepted(&self) -> Result<Accepted, Error> {
+        let amount = self
+            .value_sat
+            .as_ref()
+            .map(|value_sat| value_sat.parse().map(asset::Bitcoin::from_sat))
+            .transpose()
+            .context("invalid payment value")?;
+        let counterparty = self
+            .htlcs
+            .iter()
+            .find_map(|htlc| htlc.route.hops.last())
+            .map(|hop| hop.pub_key);
+
+        Ok(Accepted {
+            amount,
+            cltv_delta: None,
+            counterparty,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct QueryRoutesResponse {
+    #[serde(default)]
+    routes: Vec<Route>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ListChannelsResponse {
+    #[serde(default)]
+    channels: Vec<Channel>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Channel {
+    remote_balance: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GetInfoResponse {
+    block_height: u32,
+    /// Unix time of the block at `block_height`, lnd encodes 64 bit integers
+    /// as strings.
+    best_header_timestamp: String,
+}
+
+/// A message sent by one of lnd's streaming REST endpoints, these are
+/// newline-delimited JSON objects.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum StreamMessage<T> {
+    Result(T),
+    Error(StreamError),
+}
+
+#[derive(Debug, Deserialize, thiserror::Error)]
+#[error("lnd stream failed: {message}")]
+struct StreamError {
+    message: String,
 }
 
+/// gRPC's answer to calls of a service the server does not run, which is
+/// what lnd's REST proxy passes on for sub-servers it was built without.
+const UNKNOWN_SERVICE: &str = "unknown service";
+
+/// How long we wait at most before subscribing to one of lnd's streams
+/// again.
+const MAX_RESUBSCRIBE_DELAY: Duration = Duration::from_secs(60);
+
+/// lnd does not serve a streaming endpoint because it was built without the
+/// sub-server behind it.
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+#[error("lnd does not serve this endpoint")]
+struct EndpointUnavailable;
+
 #[derive(Clone, Debug)]
 pub struct LndConnectorParams {
     lnd_url: Url,
     retry_interval_ms: u64,
-    certificate: Certificate,
-    macaroon: Macaroon,
+    client: reqwest::Client,
 }
 
 impl LndConnectorParams {
@@ -86,8 +233,7 @@ impl LndConnectorParams {
         Ok(LndConnectorParams {
             lnd_url,
             retry_interval_ms,
-            certificate,
-            macaroon,
+            client: client(&certificate, &macaroon)?,
         })
     }
 }
@@ -132,8 +278,7 @@ impl TryFrom<Vec<u8>> for Macaroon {
 pub struct LndConnectorAsSender {
     lnd_url: Url,
     retry_interval_ms: u64,
-    certificate: Certificate,
-    macaroon: Macaroon,
+    client: reqwest::Client,
 }
 
 impl From<LndConnectorParams> for LndConnectorAsSender {
@@ -141,8 +286,7 @@ impl From<LndConnectorParams> for LndConnectorAsSender {
         Self {
             lnd_url: params.lnd_url,
             retry_interval_ms: params.retry_interval_ms,
-            certificate: params.certificate,
-            macaroon: params.macaroon,
+            client: params.client,
         }
     }
 }
@@ -154,12 +298,37 @@ impl LndConnectorAsSender {
             .expect("append valid string to url")
     }
 
+    fn query_routes_url(&self, params: Params) -> Url {
+        let mut url = self
+            .lnd_url
+            .join(&format!(
+                "/v1/graph/routes/{}/{}",
+                params.counterparty,
+                params.amount.as_sat()
+            ))
+            .expect("append valid string to url");
+        url.query_pairs_mut()
+            .append_pair("final_cltv_delta", &params.cltv_expiry.to_string())
+            .append_pair("fee_limit.fixed", &fee_limit_sat(params.amount).to_string());
+
+        url
+    }
+
+    fn track_payment_url(&self, secret_hash: SecretHash) -> Result<Url, Error> {
+        Ok(self
+            .lnd_url
+            .join("/v2/router/track/")
+            .expect("append valid string to url")
+            .join(&url_safe_base64(secret_hash))?)
+    }
+
     async fn find_payment(
         &self,
         secret_hash: SecretHash,
-        status: PaymentStatus,
+        statuses: &[PaymentStatus],
     ) -> Result<Option<Payment>, Error> {
-        let response = client(&self.certificate, &self.macaroon)?
+        let response = self
+            .client
             .get(self.payment_url())
             .send()
             .await?
@@ -169,10 +338,97 @@ impl LndConnectorAsSender {
             .payments
             .unwrap_or_default()
             .into_iter()
-            .find(|payment| payment.payment_hash == secret_hash && payment.status == status);
+            .find(|payment| {
+                payment.payment_hash == secret_hash && statuses.contains(&payment.status)
+            });
 
         Ok(payment)
     }
+
+    /// Follows the payment through lnd's router until it reached one of the
+    /// given statuses. Only if lnd was built without the router sub-server
+    /// we fall back to looking for it in the list of payments.
+    async fn wait_for_payment(
+        &self,
+        secret_hash: SecretHash,
+        statuses: &[PaymentStatus],
+    ) -> Result<Payment, Error> {
+        let url = self.track_payment_url(secret_hash)?;
+        if let Some(payment) = follow(
+            &self.client,
+            url,
+            self.retry_interval_ms,
+            |payment: &Payment| statuses.contains(&payment.status),
+        )
+        .await
+        {
+            return Ok(payment);
+        }
+
+        loop {
+            if let Some(payment) = self.find_payment(secret_hash, statuses).await? {
+                return Ok(payment);
+            }
+
+            tokio::time::delay_for(Duration::from_millis(self.retry_interval_ms)).await;
+        }
+    }
+}
+
+impl LndConnectorAsSender {
+    /// Asks lnd for a route to the counterparty that can carry the payment.
+    async fn query_route(&self, params: Params) -> Result<Route, Error> {
+        let no_route = |reason: String| InsufficientLiquidity::NoRoute {
+            to: params.counterparty,
+            amount: params.amount,
+            reason,
+        };
+
+        let response = self
+            .client
+            .get(self.query_routes_url(params))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let status_code = response.status();
+            let lnd_error = response.json::<LndError>().await.with_context(|| {
+                format!(
+                    "lnd responded with {} but the error could not be deserialized",
+                    status_code
+                )
+            })?;
+
+            return Err(no_route(lnd_error.message).into());
+        }
+
+        let response = response
+            .json::<QueryRoutesResponse>()
+            .await
+            .context("failed to deserialize routes")?;
+
+        match response.routes.into_iter().next() {
+            Some(route) => Ok(route),
+            None => Err(no_route("lnd did not find any route".to_owned()).into()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl CheckLiquidity for LndConnectorAsSender {
+    async fn check_liquidity(&self, params: Params) -> Result<CltvBudget, Error> {
+        let route = self.query_route(params).await?;
+        let tip = chain_tip(&self.client, &self.lnd_url).await?;
+        let final_cltv_delta = params.cltv_expiry;
+
+        Ok(CltvBudget {
+            tip,
+            final_cltv_delta,
+            route_cltv_delta: route
+                .total_time_lock
+                .saturating_sub(tip.height.saturating_add(final_cltv_delta)),
+        })
+    }
 }
 
 #[async_trait::async_trait]
@@ -187,34 +443,23 @@ impl WaitForOpened for LndConnectorAsSender {
 #[async_trait::async_trait]
 impl WaitForAccepted for LndConnectorAsSender {
     async fn wait_for_accepted(&self, params: Params) -> Result<Accepted, Error> {
-        // No validation of the parameters because once the payment has been
-        // sent the sender cannot cancel it.
-        while self
-            .find_payment(params.secret_hash, PaymentStatus::InFlight)
-            .await?
-            .is_none()
-        {
-            tokio::time::delay_for(Duration::from_millis(self.retry_interval_ms)).await;
-        }
+        let payment = self
+            .wait_for_payment(params.secret_hash, &[
+                PaymentStatus::InFlight,
+                PaymentStatus::Succeeded,
+            ])
+            .await?;
 
-        Ok(Accepted)
+        payment.accepted()
     }
 }
 
 #[async_trait::async_trait]
 impl WaitForSettled for LndConnectorAsSender {
     async fn wait_for_settled(&self, params: Params) -> Result<Settled, Error> {
-        let payment = loop {
-            match self
-                .find_payment(params.secret_hash, PaymentStatus::Succeeded)
-                .await?
-            {
-                Some(payment) => break payment,
-                None => {
-                    tokio::time::delay_for(Duration::from_millis(self.retry_interval_ms)).await;
-                }
-            }
-        };
+        let payment = self
+            .wait_for_payment(params.secret_hash, &[PaymentStatus::Succeeded])
+            .await?;
 
         let secret = match payment.payment_preimage {
             Some(secret) => Ok(secret),
@@ -230,13 +475,8 @@ impl WaitForSettled for LndConnectorAsSender {
 #[async_trait::async_trait]
 impl WaitForCancelled for LndConnectorAsSender {
     async fn wait_for_cancelled(&self, params: Params) -> Result<Cancelled, Error> {
-        while self
-            .find_payment(params.secret_hash, PaymentStatus::Failed)
-            .await?
-            .is_none()
-        {
-            tokio::time::delay_for(Duration::from_millis(self.retry_interval_ms)).await;
-        }
+        self.wait_for_payment(params.secret_hash, &[PaymentStatus::Failed])
+            .await?;
 
         Ok(Cancelled)
     }
@@ -252,8 +492,7 @@ impl WaitForCancelled for LndConnectorAsSender {
 pub struct LndConnectorAsReceiver {
     lnd_url: Url,
     retry_interval_ms: u64,
-    certificate: Certificate,
-    macaroon: Macaroon,
+    client: reqwest::Client,
 }
 
 impl From<LndConnectorParams> for LndConnectorAsReceiver {
@@ -261,8 +500,7 @@ impl From<LndConnectorParams> for LndConnectorAsReceiver {
         Self {
             lnd_url: params.lnd_url,
             retry_interval_ms: params.retry_interval_ms,
-            certificate: params.certificate,
-            macaroon: params.macaroon,
+            client: params.client,
         }
     }
 }
@@ -276,13 +514,28 @@ impl LndConnectorAsReceiver {
             .join(format!("{:x}", secret_hash).as_str())?)
     }
 
+    fn channels_url(&self) -> Url {
+        self.lnd_url
+            .join("/v1/channels?active_only=true")
+            .expect("append valid string to url")
+    }
+
+    fn subscribe_invoice_url(&self, secret_hash: SecretHash) -> Result<Url, Error> {
+        Ok(self
+            .lnd_url
+            .join("/v2/invoices/subscribe/")
+            .expect("append valid string to url")
+            .join(&url_safe_base64(secret_hash))?)
+    }
+
     #[tracing::instrument(level = "debug", skip(self))]
     async fn find_invoice(
         &self,
         secret_hash: SecretHash,
-        expected_state: InvoiceState,
+        expected_states: &[InvoiceState],
     ) -> Result<Option<Invoice>, Error> {
-        let response = client(&self.certificate, &self.macaroon)?
+        let response = self
+            .client
             .get(self.invoice_url(secret_hash)?)
             .send()
             .await?;
@@ -313,13 +566,42 @@ impl LndConnectorAsReceiver {
             .await
             .context("failed to deserialize response as invoice")?;
 
-        if invoice.state == expected_state {
+        if expected_states.contains(&invoice.state) {
             Ok(Some(invoice))
         } else {
             tracing::debug!("invoice exists but is in state {}", invoice.state);
             Ok(None)
         }
     }
+
+    /// Subscribes to updates of the invoice until it reached one of the given
+    /// states. Only if lnd was built without the invoices sub-server we fall
+    /// back to fetching the invoice.
+    async fn wait_for_invoice(
+        &self,
+        secret_hash: SecretHash,
+        expected_states: &[InvoiceState],
+    ) -> Result<Invoice, Error> {
+        let url = self.subscribe_invoice_url(secret_hash)?;
+        if let Some(invoice) = follow(
+            &self.client,
+            url,
+            self.retry_interval_ms,
+            |invoice: &Invoice| expected_states.contains(&invoice.state),
+        )
+        .await
+        {
+            return Ok(invoice);
+        }
+
+        loop {
+            if let Some(invoice) = self.find_invoice(secret_hash, expected_states).await? {
+                return Ok(invoice);
+            }
+
+            tokio::time::delay_for(Duration::from_millis(self.retry_interval_ms)).await;
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, thiserror::Error)]
@@ -330,18 +612,59 @@ struct LndError {
     code: u32,
 }
 
+#[async_trait::async_trait]
+impl CheckLiquidity for LndConnectorAsReceiver {
+    async fn check_liquidity(&self, params: Params) -> Result<CltvBudget, Error> {
+        let response = self
+            .client
+            .get(self.channels_url())
+            .send()
+            .await?
+            .json::<ListChannelsResponse>()
+            .await
+            .context("failed to deserialize channels")?;
+
+        // lnd does not split payments unless told to, hence a single channel
+        // has to be able to receive the whole amount.
+        let mut capacity = 0;
+        for channel in response.channels {
+            let remote_balance = channel
+                .remote_balance
+                .parse::<u64>()
+                .with_context(|| format!("invalid remote balance: {}", channel.remote_balance))?;
+            capacity = capacity.max(remote_balance);
+        }
+
+        let capacity = asset::Bitcoin::from_sat(capacity);
+        if capacity < params.amount {
+            return Err(InsufficientLiquidity::InboundCapacity {
+                amount: params.amount,
+                capacity,
+            }
+            .into());
+        }
+
+        // The route is chosen by the sender, we can only account for the CLTV
+        // delta of the HTLC offered to us.
+        Ok(CltvBudget {
+            tip: chain_tip(&self.client, &self.lnd_url).await?,
+            final_cltv_delta: params.cltv_expiry,
+            route_cltv_delta: 0,
+        })
+    }
+}
+
 #[async_trait::async_trait]
 impl WaitForOpened for LndConnectorAsReceiver {
     async fn wait_for_opened(&self, params: Params) -> Result<Opened, Error> {
         // Do we want to validate that the user used the correct swap parameters
         // when adding the invoice?
-        while self
-            .find_invoice(params.secret_hash, InvoiceState::Open)
-            .await?
-            .is_none()
-        {
-            tokio::time::delay_for(Duration::from_millis(self.retry_interval_ms)).await;
-        }
+        self.wait_for_invoice(params.secret_hash, &[
+            InvoiceState::Open,
+            InvoiceState::Accepted,
+            InvoiceState::Settled,
+        ])
+        .await?;
 
         Ok(Opened)
     }
@@ -350,33 +673,25 @@ impl WaitForOpened for LndConnectorAsReceiver {
 #[async_trait::async_trait]
 impl WaitForAccepted for LndConnectorAsReceiver {
     async fn wait_for_accepted(&self, params: Params) -> Result<Accepted, Error> {
-        // Validation that sender payed the correct invoice is provided by LND.
-        // Since the sender uses the params to make the payment (as apposed to
-        // the invoice) LND guarantees that the params match the invoice when
-        // updating the invoice status.
-        while self
-            .find_invoice(params.secret_hash, InvoiceState::Accepted)
-            .await?
-            .is_none()
-        {
-            tokio::time::delay_for(Duration::from_millis(self.retry_interval_ms)).await;
-        }
-        Ok(Accepted)
+        // lnd only checks the payment against the invoice, which the user may
+        // have added with values other than the agreed ones.
+        let invoice = self
+            .wait_for_invoice(params.secret_hash, &[
+                InvoiceState::Accepted,
+                InvoiceState::Settled,
+            ])
+            .await?;
+
+        invoice.accepted()
     }
 }
 
 #[async_trait::async_trait]
 impl WaitForSettled for LndConnectorAsReceiver {
     async fn wait_for_settled(&self, params: Params) -> Result<Settled, Error> {
-        let invoice = loop {
-            match self
-                .find_invoice(params.secret_hash, InvoiceState::Settled)
-                .await?
-            {
-                Some(invoice) => break invoice,
-                None => tokio::time::delay_for(Duration::from_millis(self.retry_interval_ms)).await,
-            }
-        };
+        let invoice = self
+            .wait_for_invoice(params.secret_hash, &[InvoiceState::Settled])
+            .await?;
 
         let preimage = invoice
             .r_preimage
@@ -391,14 +706,277 @@ impl WaitForSettled for LndConnectorAsReceiver {
 #[async_trait::async_trait]
 impl WaitForCancelled for LndConnectorAsReceiver {
     async fn wait_for_cancelled(&self, params: Params) -> Result<Cancelled, Error> {
-        while self
-            .find_invoice(params.secret_hash, InvoiceState::Cancelled)
-            .await?
-            .is_none()
+        self.wait_for_invoice(params.secret_hash, &[InvoiceState::Cancelled])
+            .await?;
+
+        Ok(Cancelled)
+    }
+}
+
+/// How long lnd may try to find a route for a payment sent by cnd.
+const PAYMENT_TIMEOUT_SECS: u32 = 60;
+
+/// Executes lnd actions on behalf of the user, this needs lnd's admin
+/// macaroon.
+#[derive(Clone, Debug)]
+pub struct LndActionExecutor {
+    lnd_url: Url,
+    client: reqwest::Client,
+}
+
+impl From<LndConnectorParams> for LndActionExecutor {
+    fn from(params: LndConnectorParams) -> Self {
+        Self {
+            lnd_url: params.lnd_url,
+            client: params.client,
+        }
+    }
+}
+
+#[cfg(test)]
+impl LndActionExecutor {
+    pub fn new(lnd_url: Url) -> Self {
+        Self {
+            lnd_url,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+// lnd's REST API expects bytes to be base64 encoded and 64 bit integers to be
+// strings.
+#[derive(Debug, Serialize)]
+struct AddHoldInvoiceRequest {
+    hash: String,
+    value: String,
+    expiry: String,
+    cltv_expiry: String,
+}
+
+#[derive(Debug, Serialize)]
+struct SettleInvoiceRequest {
+    preimage: String,
+}
+
+#[derive(Debug, Serialize)]
+struct CancelInvoiceRequest {
+    payment_hash: String,
+}
+
+#[derive(Debug, Serialize)]
+struct SendPaymentRequest {
+    dest: String,
+    amt: String,
+    payment_hash: String,
+    final_cltv_delta: u32,
+    timeout_seconds: u32,
+    fee_limit_sat: String,
+}
+
+impl LndActionExecutor {
+    pub async fn add_hold_invoice(&self, action: lnd::AddHoldInvoice) -> Result<(), Error> {
+        self.post("/v2/invoices/hodl", &AddHoldInvoiceRequest {
+            hash: base64::encode(action.secret_hash.as_bytes()),
+            value: action.amount.as_sat().to_string(),
+            expiry: action.expiry.to_string(),
+            cltv_expiry: action.cltv_expiry.to_string(),
+        })
+        .await
+        .with_context(|| format!("failed to add hold invoice for {}", action.secret_hash))
+    }
+
+    pub async fn settle_invoice(&self, action: lnd::SettleInvoice) -> Result<(), Error> {
+        self.post("/v2/invoices/settle", &SettleInvoiceRequest {
+            preimage: base64::encode(action.secret.as_raw_secret()),
+        })
+        .await
+        .with_context(|| format!("failed to settle invoice for {}", action.secret.hash()))
+    }
+
+    /// Only needs the hash of the invoice so that cancellations can be done
+    /// after a restart, see `InvoiceCancellations`.
+    pub async fn cancel_invoice(&self, secret_hash: SecretHash) -> Result<(), Error> {
+        self.post("/v2/invoices/cancel", &CancelInvoiceRequest {
+            payment_hash: base64::encode(secret_hash.as_bytes()),
+        })
+        .await
+        .with_context(|| format!("failed to cancel invoice for {}", secret_hash))
+    }
+
+    /// Sends the payment through lnd's router and returns once lnd reported
+    /// the first update, the outcome of the payment is picked up by the
+    /// `WaitFor*` implementations of [`LndConnectorAsSender`].
+    pub async fn send_payment(&self, action: lnd::SendPayment) -> Result<(), Error> {
+        let amount = action.amount.as_sat();
+        let request = self
+            .client
+            .post(self.url("/v2/router/send"))
+            .json(&SendPaymentRequest {
+                dest: base64::encode(action.to_public_key.to_bytes()),
+                amt: amount.to_string(),
+                payment_hash: base64::encode(action.secret_hash.as_bytes()),
+                final_cltv_delta: action.final_cltv_delta,
+                timeout_seconds: PAYMENT_TIMEOUT_SECS,
+                fee_limit_sat: fee_limit_sat(action.amount).to_string(),
+            });
+
+        let payment = subscribe(request, |_: &Payment| true)
+            .await
+            .with_context(|| format!("failed to send payment for {}", action.secret_hash))?
+            .ok_or_else(|| anyhow::anyhow!("lnd did not report the status of the payment"))?;
+
+        if payment.status == PaymentStatus::Failed {
+            anyhow::bail!("payment for {} failed", action.secret_hash)
+        }
+
+        Ok(())
+    }
+
+    fn url(&self, path: &str) -> Url {
+        self.lnd_url.join(path).expect("append valid string to url")
+    }
+
+    async fn post(&self, path: &str, body: &impl Serialize) -> Result<(), Error> {
+        let response = self.client.post(self.url(path)).json(body).send().await?;
+
+        if !response.status().is_success() {
+            let status_code = response.status();
+            let lnd_error = response.json::<LndError>().await.with_context(|| {
+                format!(
+                    "lnd responded with {} but the error could not be deserialized",
+                    status_code
+                )
+            })?;
+
+            return Err(lnd_error.into());
+        }
+
+        Ok(())
+    }
+}
+
+/// The block lnd synced to.
+async fn chain_tip(client: &reqwest::Client, lnd_url: &Url) -> Result<ChainTip, Error> {
+    let info = client
+        .get(
+            lnd_url
+                .join("/v1/getinfo")
+                .expect("append valid string to url"),
+        )
+        .send()
+        .await?
+        .json::<GetInfoResponse>()
+        .await
+        .context("failed to deserialize lnd info")?;
+
+    let time = info
+        .best_header_timestamp
+        .parse::<u32>()
+        .with_context(|| format!("invalid block time: {}", info.best_header_timestamp))?;
+
+    Ok(ChainTip {
+        height: info.block_height,
+        time: Timestamp::from(time),
+    })
+}
+
+/// We are willing to pay up to 1% of the amount in routing fees.
+fn fee_limit_sat(amount: asset::Bitcoin) -> u64 {
+    amount.as_sat() / 100
+}
+
+/// lnd expects hashes in URL paths to be URL-safe base64 encoded.
+fn url_safe_base64(secret_hash: SecretHash) -> String {
+    base64::encode_config(secret_hash.as_bytes(), base64::URL_SAFE)
+}
+
+/// Subscribes to one of lnd's streaming endpoints until a message satisfies
+/// `predicate`. Whenever the stream fails or ends, e.g. because lnd does not
+/// know about the payment yet, we subscribe again with an exponential
+/// backoff starting at `retry_interval_ms`.
+///
+/// Returns `None` if lnd does not serve the endpoint at all.
+async fn follow<T>(
+    client: &reqwest::Client,
+    url: Url,
+    retry_interval_ms: u64,
+    predicate: impl Fn(&T) -> bool,
+) -> Option<T>
+where
+    T: DeserializeOwned,
+{
+    let mut delay = Duration::from_millis(retry_interval_ms);
+
+    loop {
+        match subscribe(client.get(url.clone()), &predicate).await {
+            Ok(Some(item)) => return Some(item),
+            Ok(None) => tracing::debug!("lnd closed the stream of {}", url.path()),
+            Err(e) if e.is::<EndpointUnavailable>() => {
+                tracing::info!("lnd does not serve {}, polling instead", url.path());
+                return None;
+            }
+            Err(e) => tracing::debug!("failed to subscribe to {}: {:#}", url.path(), e),
+        }
+
+        tokio::time::delay_for(delay).await;
+        delay = (delay * 2).min(MAX_RESUBSCRIBE_DELAY);
+    }
+}
+
+/// Reads messages from one of lnd's streaming endpoints until one satisfies
+/// `predicate`, returns `None` if lnd closes the stream before that.
+async fn subscribe<T>(
+    request: reqwest::RequestBuilder,
+    predicate: impl Fn(&T) -> bool,
+) -> Result<Option<T>, Error>
+where
+    T: DeserializeOwned,
+{
+    let mut response = request.send().await?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        if status == StatusCode::NOT_FOUND
+            || status == StatusCode::NOT_IMPLEMENTED
+            || body.contains(UNKNOWN_SERVICE)
         {
-            tokio::time::delay_for(Duration::from_millis(self.retry_interval_ms)).await;
+            return Err(EndpointUnavailable.into());
         }
-        Ok(Cancelled)
+
+        anyhow::bail!("lnd responded with {}: {}", status, body)
+    }
+
+    let mut buffer = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        buffer.extend_from_slice(&chunk);
+
+        while let Some(end) = buffer.iter().position(|byte| *byte == b'\n') {
+            let line = buffer.drain(..=end).collect::<Vec<u8>>();
+            if let Some(item) = parse_stream_message(&line)?.filter(|item| predicate(item)) {
+                return Ok(Some(item));
+            }
+        }
+    }
+
+    // the last message is not necessarily followed by a newline
+    Ok(parse_stream_message(&buffer)?.filter(|item| predicate(item)))
+}
+
+fn parse_stream_message<T>(line: &[u8]) -> Result<Option<T>, Error>
+where
+    T: DeserializeOwned,
+{
+    if line.iter().all(u8::is_ascii_whitespace) {
+        return Ok(None);
+    }
+
+    match serde_json::from_slice(line).context("failed to deserialize lnd stream message")? {
+        StreamMessage::Result(item) => Ok(Some(item)),
+        StreamMessage::Error(error) if error.message.contains(UNKNOWN_SERVICE) => {
+            Err(EndpointUnavailable.into())
+        }
+        StreamMessage::Error(error) => Err(error.into()),
     }
 }
 
@@ -478,7 +1056,398 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::mock_node;
     use spectral::prelude::*;
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    fn secret() -> Secret {
+        Secret::from([1u8; 32])
+    }
+
+    fn params() -> Params {
+        Params {
+            secret_hash: secret().hash(),
+            amount: asset::Bitcoin::from_sat(10_000),
+            cltv_expiry: 144,
+            counterparty: identity::Lightning::random(),
+        }
+    }
+
+    fn payment_json(status: &str, preimage: Option<Secret>) -> String {
+        serde_json::json!({
+            "payment_hash": format!("{:x}", secret().hash()),
+            "payment_preimage": preimage,
+            "value_msat": "10000000",
+            "status": status,
+        })
+        .to_string()
+    }
+
+    fn invoice_json(state: &str, r_preimage: Option<Secret>) -> String {
+        serde_json::json!({
+            "r_preimage": r_preimage.map(|secret| base64::encode(secret.as_raw_secret())),
+            "value": "10000",
+            "value_msat": "10000000",
+            "expiry": "3600",
+            "cltv_expiry": "350",
+            "amt_paid_sat": "0",
+            "amt_paid_msat": "0",
+            "state": state,
+        })
+        .to_string()
+    }
+
+    fn stream(results: &[String]) -> String {
+        results
+            .iter()
+            .map(|result| format!("{{\"result\":{}}}\n", result))
+            .collect()
+    }
+
+    fn sender(lnd_url: Url) -> LndConnectorAsSender {
+        LndConnectorAsSender {
+            lnd_url,
+            retry_interval_ms: 10,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn receiver(lnd_url: Url) -> LndConnectorAsReceiver {
+        LndConnectorAsReceiver {
+            lnd_url,
+            retry_interval_ms: 10,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn encoded_hash() -> String {
+        url_safe_base64(secret().hash())
+    }
+
+    #[tokio::test]
+    async fn sender_follows_payment_stream_until_it_succeeded() {
+        let mut responses = HashMap::new();
+        responses.insert(
+            format!("/v2/router/track/{}", encoded_hash()),
+            stream(&[
+                payment_json("IN_FLIGHT", None),
+                payment_json("SUCCEEDED", Some(secret())),
+            ]),
+        );
+        let connector = sender(mock_node::rest(responses));
+
+        let settled = connector.wait_for_settled(params()).await.unwrap();
+
+        assert_eq!(settled.secret, secret());
+    }
+
+    #[tokio::test]
+    async fn sender_falls_back_to_polling_if_router_is_not_available() {
+        let track = format!("/v2/router/track/{}", encoded_hash());
+        let payments = format!(
+            "{{\"payments\":[{}]}}",
+            payment_json("SUCCEEDED", Some(secret()))
+        );
+        let connector = sender(mock_node::rest_by(move |path| {
+            if path == track {
+                (
+                    StatusCode::NOT_IMPLEMENTED,
+                    r#"{"error":"unknown service routerrpc.Router","message":"unknown service routerrpc.Router","code":12}"#.to_owned(),
+                )
+            } else if path == "/v1/payments" {
+                (StatusCode::OK, payments.clone())
+            } else {
+                (StatusCode::NOT_FOUND, String::new())
+            }
+        }));
+
+        let settled = connector.wait_for_settled(params()).await.unwrap();
+
+        assert_eq!(settled.secret, secret());
+    }
+
+    #[tokio::test]
+    async fn sender_subscribes_again_until_lnd_tracks_the_payment_instead_of_polling() {
+        let track = format!("/v2/router/track/{}", encoded_hash());
+        let subscriptions = Arc::new(AtomicUsize::new(0));
+        let polls = Arc::new(AtomicUsize::new(0));
+        let connector = sender(mock_node::rest_by({
+            let subscriptions = subscriptions.clone();
+            let polls = polls.clone();
+            move |path| {
+                if path != track {
+                    polls.fetch_add(1, Ordering::SeqCst);
+                    return (StatusCode::NOT_FOUND, String::new());
+                }
+
+                if subscriptions.fetch_add(1, Ordering::SeqCst) < 2 {
+                    (
+                        StatusCode::OK,
+                        r#"{"error":{"code":2,"message":"payment isn't initiated"}}"#.to_owned(),
+                    )
+                } else {
+                    (
+                        StatusCode::OK,
+                        stream(&[payment_json("SUCCEEDED", Some(secret()))]),
+                    )
+                }
+            }
+        }));
+
+        let settled = connector.wait_for_settled(params()).await.unwrap();
+
+        assert_eq!(settled.secret, secret());
+        assert_eq!(subscriptions.load(Ordering::SeqCst), 3);
+        assert_eq!(polls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn receiver_follows_invoice_subscription_until_it_is_settled() {
+        let mut responses = HashMap::new();
+        responses.insert(
+            format!("/v2/invoices/subscribe/{}", encoded_hash()),
+            stream(&[
+                invoice_json("OPEN", None),
+                invoice_json("ACCEPTED", None),
+                invoice_json("SETTLED", Some(secret())),
+            ]),
+        );
+        let connector = receiver(mock_node::rest(responses));
+
+        connector.wait_for_accepted(params()).await.unwrap();
+        let settled = connector.wait_for_settled(params()).await.unwrap();
+
+        assert_eq!(settled.secret, secret());
+    }
+
+    #[tokio::test]
+    async fn receiver_subscribes_again_if_the_stream_ends_instead_of_polling() {
+        let subscribe = format!("/v2/invoices/subscribe/{}", encoded_hash());
+        let subscriptions = Arc::new(AtomicUsize::new(0));
+        let polls = Arc::new(AtomicUsize::new(0));
+        let connector = receiver(mock_node::rest_by({
+            let subscriptions = subscriptions.clone();
+            let polls = polls.clone();
+            move |path| {
+                if path != subscribe {
+                    polls.fetch_add(1, Ordering::SeqCst);
+                    return (StatusCode::NOT_FOUND, String::new());
+                }
+
+                if subscriptions.fetch_add(1, Ordering::SeqCst) == 0 {
+                    (StatusCode::OK, stream(&[invoice_json("OPEN", None)]))
+                } else {
+                    (
+                        StatusCode::OK,
+                        stream(&[invoice_json("SETTLED", Some(secret()))]),
+                    )
+                }
+            }
+        }));
+
+        let settled = connector.wait_for_settled(params()).await.unwrap();
+
+        assert_eq!(settled.secret, secret());
+        assert_eq!(subscriptions.load(Ordering::SeqCst), 2);
+        assert_eq!(polls.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn receiver_falls_back_to_polling_if_invoices_sub_server_is_not_available() {
+        let mut responses = HashMap::new();
+        responses.insert(
+            format!("/v2/invoices/subscribe/{}", encoded_hash()),
+            r#"{"error":{"message":"unknown service invoicesrpc.Invoices"}}"#.to_owned(),
+        );
+        responses.insert(
+            format!("/v1/invoice/{:x}", secret().hash()),
+            invoice_json("SETTLED", Some(secret())),
+        );
+        let connector = receiver(mock_node::rest(responses));
+
+        let settled = connector.wait_for_settled(params()).await.unwrap();
+
+        assert_eq!(settled.secret, secret());
+    }
+
+    #[tokio::test]
+    async fn receiver_reports_amount_paid_and_smallest_cltv_delta() {
+        let mut invoice =
+            serde_json::from_str::<serde_json::Value>(&invoice_json("ACCEPTED", None)).unwrap();
+        invoice["amt_paid_sat"] = "9000".into();
+        invoice["htlcs"] = serde_json::json!([
+            { "accept_height": 100, "expiry_height": 250, "state": "ACCEPTED" },
+            { "accept_height": 100, "expiry_height": 140, "state": "ACCEPTED" },
+            { "accept_height": 100, "expiry_height": 110, "state": "CANCELED" },
+        ]);
+        let mut responses = HashMap::new();
+        responses.insert(
+            format!("/v2/invoices/subscribe/{}", encoded_hash()),
+            stream(&[invoice.to_string()]),
+        );
+        let connector = receiver(mock_node::rest(responses));
+
+        let accepted = connector.wait_for_accepted(params()).await.unwrap();
+
+        assert_eq!(accepted, Accepted {
+            amount: Some(asset::Bitcoin::from_sat(9000)),
+            cltv_delta: Some(40),
+            counterparty: None,
+        });
+    }
+
+    #[tokio::test]
+    async fn sender_reports_last_hop_of_route_as_counterparty() {
+        let payee = identity::Lightning::random();
+        let mut payment =
+            serde_json::from_str::<serde_json::Value>(&payment_json("IN_FLIGHT", None)).unwrap();
+        payment["value_sat"] = "10000".into();
+        payment["htlcs"] = serde_json::json!([{
+            "route": {
+                "hops": [
+                    { "pub_key": identity::Lightning::random() },
+                    { "pub_key": payee },
+                ]
+            }
+        }]);
+        let mut responses = HashMap::new();
+        responses.insert(
+            format!("/v2/router/track/{}", encoded_hash()),
+            stream(&[payment.to_string()]),
+        );
+        let connector = sender(mock_node::rest(responses));
+
+        let accepted = connector.wait_for_accepted(params()).await.unwrap();
+
+        assert_eq!(accepted, Accepted {
+            amount: Some(asset::Bitcoin::from_sat(10_000)),
+            cltv_delta: None,
+            counterparty: Some(payee),
+        });
+    }
+
+    #[tokio::test]
+    async fn sender_refuses_swap_if_lnd_finds_no_route() {
+        let params = params();
+        let mut responses = HashMap::new();
+        responses.insert(
+            format!("/v1/graph/routes/{}/10000", params.counterparty),
+            r#"{"routes":[]}"#.to_owned(),
+        );
+        let connector = sender(mock_node::rest(responses));
+
+        let error = connector.check_liquidity(params).await.unwrap_err();
+
+        assert!(matches!(
+            error.downcast_ref::<InsufficientLiquidity>(),
+            Some(InsufficientLiquidity::NoRoute { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn sender_budget_includes_cltv_deltas_of_the_route() {
+        let params = params();
+        let mut responses = HashMap::new();
+        responses.insert(
+            "/v1/getinfo".to_owned(),
+            r#"{"block_height":600000,"best_header_timestamp":"1590000000"}"#.to_owned(),
+        );
+        responses.insert(
+            format!("/v1/graph/routes/{}/10000", params.counterparty),
+            r#"{"routes":[{"total_time_lock":600224,"hops":[]}]}"#.to_owned(),
+        );
+        let connector = sender(mock_node::rest(responses));
+
+        let budget = connector.check_liquidity(params).await.unwrap();
+
+        assert_eq!(budget.tip, ChainTip {
+            height: 600_000,
+            time: Timestamp::from(1_590_000_000),
+        });
+        assert_eq!(budget.final_cltv_delta, 144);
+        assert_eq!(budget.route_cltv_delta, 80);
+    }
+
+    #[tokio::test]
+    async fn receiver_needs_a_channel_with_enough_inbound_capacity() {
+        let mut responses = HashMap::new();
+        responses.insert(
+            "/v1/channels".to_owned(),
+            r#"{"channels":[{"remote_balance":"6000"},{"remote_balance":"8000"}]}"#.to_owned(),
+        );
+        let connector = receiver(mock_node::rest(responses));
+
+        let error = connector.check_liquidity(params()).await.unwrap_err();
+
+        assert_eq!(
+            error.downcast_ref::<InsufficientLiquidity>(),
+            Some(&InsufficientLiquidity::InboundCapacity {
+                amount: asset::Bitcoin::from_sat(10_000),
+                capacity: asset::Bitcoin::from_sat(8000),
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn executor_reports_payments_that_failed_immediately() {
+        let mut responses = HashMap::new();
+        responses.insert(
+            "/v2/router/send".to_owned(),
+            stream(&[payment_json("FAILED", None)]),
+        );
+        let executor = LndActionExecutor {
+            lnd_url: mock_node::rest(responses),
+            client: reqwest::Client::new(),
+        };
+
+        let result = executor
+            .send_payment(lnd::SendPayment {
+                to_public_key: identity::Lightning::random(),
+                amount: asset::Bitcoin::from_sat(10_000),
+                secret_hash: secret().hash(),
+                final_cltv_delta: 144,
+                chain: lnd::Chain::Bitcoin,
+                network: bitcoin::Network::Regtest,
+                self_public_key: identity::Lightning::random(),
+            })
+            .await;
+
+        assert_that(&result).is_err();
+    }
+
+    #[tokio::test]
+    async fn executor_settles_invoice_with_secret() {
+        let mut responses = HashMap::new();
+        responses.insert("/v2/invoices/settle".to_owned(), "{}".to_owned());
+        let executor = LndActionExecutor {
+            lnd_url: mock_node::rest(responses),
+            client: reqwest::Client::new(),
+        };
+
+        let result = executor
+            .settle_invoice(lnd::SettleInvoice {
+                secret: secret(),
+                chain: lnd::Chain::Bitcoin,
+                network: bitcoin::Network::Regtest,
+                self_public_key: identity::Lightning::random(),
+            })
+            .await;
+
+        assert_that(&result).is_ok();
+    }
+
+    #[test]
+    fn stream_error_messages_are_errors() {
+        let result = parse_stream_message::<Invoice>(br#"{"error":{"message":"boom"}}"#);
+
+        assert_that(&result).is_err();
+    }
 
     #[test]
     fn deserialize_ln_invoice_preimage_present() {
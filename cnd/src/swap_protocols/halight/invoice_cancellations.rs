@@ -0,0 +1,171 @@
+use crate::{
+    db::{Database, InvoiceCancellation, InvoiceCancellationStore, Save},
+    swap_protocols::{
+        halight::{LndActionExecutor, State, States},
+        state::Get,
+        LocalSwapId,
+    },
+    timestamp::Timestamp,
+};
+use std::{collections::HashSet, sync::Arc, time::Duration};
+use tokio::sync::Mutex;
+
+/// Cancels the hold invoices cnd added on behalf of Alice once the alpha
+/// HTLC expired. Alice refunds her ether then and must not settle the invoice
+/// anymore, cancelling it releases Bob's payment right away instead of only
+/// when its CLTV expires.
+///
+/// Cancellations are persisted until they are done so that they are
+/// scheduled again when cnd restarts.
+#[derive(Clone, Debug)]
+pub struct InvoiceCancellations {
+    executor: LndActionExecutor,
+    states: Arc<States>,
+    db: Database,
+    scheduled: Arc<Mutex<HashSet<LocalSwapId>>>,
+}
+
+impl InvoiceCancellations {
+    pub fn new(executor: LndActionExecutor, states: Arc<States>, db: Database) -> Self {
+        Self {
+            executor,
+            states,
+            db,
+            scheduled: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    /// Schedules the cancellations that were pending when cnd stopped.
+    pub async fn load(&self) -> anyhow::Result<()> {
+        for cancellation in self.db.all_invoice_cancellations().await? {
+            if self.scheduled.lock().await.insert(cancellation.swap_id) {
+                self.spawn(cancellation);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Does nothing if the invoice of the swap is already scheduled to be
+    /// cancelled.
+    pub async fn schedule(&self, cancellation: InvoiceCancellation) -> anyhow::Result<()> {
+        let swap_id = cancellation.swap_id;
+        if !self.scheduled.lock().await.insert(swap_id) {
+            return Ok(());
+        }
+
+        if let Err(e) = self.db.save(cancellation).await {
+            self.scheduled.lock().await.remove(&swap_id);
+            return Err(e);
+        }
+        self.spawn(cancellation);
+
+        Ok(())
+    }
+
+    fn spawn(&self, cancellation: InvoiceCancellation) {
+        let cancellations = self.clone();
+
+        tokio::spawn(async move { cancellations.cancel_after_expiry(cancellation).await });
+    }
+
+    /// Cancellations are only attempted once, lnd still cancels the payment
+    /// once its CLTV expires if this fails.
+    async fn cancel_after_expiry(&self, cancellation: InvoiceCancellation) {
+        let InvoiceCancellation {
+            swap_id,
+            secret_hash,
+            expiry,
+        } = cancellation;
+
+        let secs_until_expiry = u32::from(expiry).saturating_sub(Timestamp::now().into());
+        tokio::time::delay_for(Duration::from_secs(u64::from(secs_until_expiry))).await;
+
+        match self.states.get(&swap_id).await {
+            Ok(Some(State::Settled(_))) | Ok(Some(State::Cancelled(_))) => {}
+            // After a restart we no longer know the state of the invoice,
+            // lnd refuses to cancel it if it was settled.
+            Ok(_) => {
+                if let Err(e) = self.executor.cancel_invoice(secret_hash).await {
+                    tracing::error!("failed to cancel invoice of swap {}: {:#}", swap_id, e);
+                }
+            }
+            Err(e) => tracing::error!("failed to get halight state of swap {}: {:#}", swap_id, e),
+        }
+
+        if let Err(e) = self.db.delete_invoice_cancellation(swap_id).await {
+            tracing::error!(
+                "failed to delete invoice cancellation of swap {}: {:#}",
+                swap_id,
+                e
+            );
+        }
+        self.scheduled.lock().await.remove(&swap_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{db::Sqlite, mock_node, swap_protocols::rfc003::SecretHash};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Counts how often lnd was asked to cancel an invoice.
+    fn lnd(cancelled: Arc<AtomicUsize>) -> LndActionExecutor {
+        LndActionExecutor::new(mock_node::rest_by(move |path| {
+            if path == "/v2/invoices/cancel" {
+                cancelled.fetch_add(1, Ordering::SeqCst);
+                (http::StatusCode::OK, "{}".to_owned())
+            } else {
+                (http::StatusCode::NOT_FOUND, String::new())
+            }
+        }))
+    }
+
+    fn cancellation(expiry: Timestamp) -> InvoiceCancellation {
+        InvoiceCancellation {
+            swap_id: LocalSwapId::default(),
+            secret_hash: SecretHash::from([1u8; 32]),
+            expiry,
+        }
+    }
+
+    #[tokio::test]
+    async fn invoice_is_cancelled_once_if_scheduled_twice() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::from(Sqlite::new_in_dir(dir.path()).unwrap());
+        let cancelled = Arc::new(AtomicUsize::new(0));
+        let cancellations = InvoiceCancellations::new(
+            lnd(Arc::clone(&cancelled)),
+            Arc::new(States::default()),
+            db.clone(),
+        );
+        let cancellation = cancellation(Timestamp::now().plus(1));
+
+        cancellations.schedule(cancellation).await.unwrap();
+        cancellations.schedule(cancellation).await.unwrap();
+        tokio::time::delay_for(Duration::from_secs(3)).await;
+
+        assert_eq!(cancelled.load(Ordering::SeqCst), 1);
+        assert_eq!(db.all_invoice_cancellations().await.unwrap(), vec![]);
+    }
+
+    #[tokio::test]
+    async fn pending_cancellations_are_scheduled_again_after_a_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = Database::from(Sqlite::new_in_dir(dir.path()).unwrap());
+        db.save(cancellation(Timestamp::now())).await.unwrap();
+        let cancelled = Arc::new(AtomicUsize::new(0));
+        let cancellations = InvoiceCancellations::new(
+            lnd(Arc::clone(&cancelled)),
+            Arc::new(States::default()),
+            db.clone(),
+        );
+
+        cancellations.load().await.unwrap();
+        tokio::time::delay_for(Duration::from_secs(1)).await;
+
+        assert_eq!(cancelled.load(Ordering::SeqCst), 1);
+        assert_eq!(db.all_invoice_cancellations().await.unwrap(), vec![]);
+    }
+}
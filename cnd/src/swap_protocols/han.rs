@@ -1,6 +1,9 @@
 use crate::{
     asset,
-    btsieve::ethereum::{Cache, Web3Connector},
+    btsieve::{
+        ethereum::{Cache, Web3Connector},
+        StartOfSwap,
+    },
     htlc_location, identity,
     swap_protocols::{
         han, ledger,
@@ -11,7 +14,10 @@ use crate::{
             },
             LedgerState,
         },
-        state, LedgerStates, LocalSwapId, Role,
+        state,
+        swap_registry::SwapRegistry,
+        EthereumHtlc, InsertFailedSwap, LedgerStates, LocalSwapId, Phase, Role, Side, SwapError,
+        SwapErrors,
     },
     transaction,
 };
@@ -40,17 +46,41 @@ pub async fn new_han_ethereum_ether_swap(
     connector: Arc<Cache<Web3Connector>>,
     ethereum_ledger_state: Arc<LedgerStates>,
     htlc_params: HtlcParams<ledger::Ethereum, asset::Ether, identity::Ethereum>,
+    ethereum_htlc: EthereumHtlc,
     role: Role,
+    swap_errors: SwapErrors,
 ) {
-    han::create_watcher::<_, _, _, _, htlc_location::Ethereum, _, transaction::Ethereum>(
-        connector.as_ref(),
-        ethereum_ledger_state,
-        swap_id,
-        htlc_params,
-        Utc::now().naive_local(),
-    )
-    .instrument(tracing::error_span!("alpha_ledger", swap_id = %swap_id, role = %role))
-    .await
+    let span = tracing::error_span!("alpha_ledger", swap_id = %swap_id, role = %role);
+    let accepted_at = Utc::now().naive_local();
+
+    match ethereum_htlc {
+        EthereumHtlc::Deployed => {
+            han::create_watcher::<_, _, _, _, _, htlc_location::Ethereum, _, transaction::Ethereum>(
+                connector.as_ref(),
+                &swap_errors,
+                ethereum_ledger_state,
+                swap_id,
+                htlc_params,
+                accepted_at,
+            )
+            .instrument(span)
+            .await
+        }
+        EthereumHtlc::SwapRegistry(address) => {
+            let registry = SwapRegistry::new(connector, address);
+
+            han::create_watcher::<_, _, _, _, _, htlc_location::Ethereum, _, transaction::Ethereum>(
+                &registry,
+                &swap_errors,
+                ethereum_ledger_state,
+                swap_id,
+                htlc_params,
+                accepted_at,
+            )
+            .instrument(span)
+            .await
+        }
+    }
 }
 
 /// Returns a future that tracks the swap negotiated from the given request and
@@ -64,8 +94,9 @@ pub async fn new_han_ethereum_ether_swap(
 ///
 /// It is highly unlikely for Bob to fund the HTLC now, yet the current
 /// implementation is still waiting for that.
-async fn create_watcher<C, S, L, A, H, I, T>(
+async fn create_watcher<C, E, S, L, A, H, I, T>(
     ethereum_connector: &C,
+    swap_errors: &E,
     ledger_state: Arc<S>,
     swap_id: LocalSwapId,
     htlc_params: HtlcParams<L, A, I>,
@@ -75,6 +106,7 @@ async fn create_watcher<C, S, L, A, H, I, T>(
         + HtlcDeployed<L, A, H, I, T>
         + HtlcRedeemed<L, A, H, I, T>
         + HtlcRefunded<L, A, H, I, T>,
+    E: InsertFailedSwap,
     S: state::Update<SwapEvent<A, H, T>> + state::Insert<LedgerState<A, H, T>>,
     L: Clone,
     A: Ord + Clone,
@@ -89,10 +121,16 @@ async fn create_watcher<C, S, L, A, H, I, T>(
     // construct a generator that watches alpha and beta ledger concurrently
     let mut generator = Gen::new({
         |co| async {
-            watch_ledger::<C, L, A, H, I, T>(&ethereum_connector, co, htlc_params, accepted_at)
-                .await
+            watch_ledger::<C, L, A, H, I, T>(
+                &ethereum_connector,
+                co,
+                htlc_params,
+                accepted_at.into(),
+            )
+            .await
         }
     });
+    let mut phase = Phase::Deploy;
 
     loop {
         // wait for events to be emitted as the generator executes
@@ -100,6 +138,7 @@ async fn create_watcher<C, S, L, A, H, I, T>(
             // every event that is yielded is passed on
             GeneratorState::Yielded(event) => {
                 tracing::info!("swap {} yielded event {}", swap_id, event);
+                phase = event.next_phase();
                 ledger_state.update(&swap_id, event).await;
             }
             // the generator stopped executing, this means there are no more events that can be
@@ -110,6 +149,9 @@ async fn create_watcher<C, S, L, A, H, I, T>(
             }
             GeneratorState::Complete(Err(e)) => {
                 tracing::error!("swap {} failed with {:?}", swap_id, e);
+                swap_errors
+                    .insert_failed_swap(swap_id.into(), SwapError::new(Side::Alpha, phase, &e))
+                    .await;
                 return;
             }
         }
@@ -123,7 +165,7 @@ async fn watch_ledger<C, L, A, H, I, T>(
     ethereum_connector: &C,
     co: Co<SwapEvent<A, H, T>>,
     htlc_params: HtlcParams<L, A, I>,
-    start_of_swap: NaiveDateTime,
+    start_of_swap: StartOfSwap,
 ) -> anyhow::Result<()>
 where
     C: HtlcFunded<L, A, H, I, T>
@@ -1,7 +1,7 @@
 use crate::{
     asset, htlc_location, identity,
     swap_protocols::{
-        rfc003::{Secret, SecretHash},
+        rfc003::{FromErr, Secret, SecretHash},
         state, LocalSwapId,
     },
     transaction,
@@ -12,9 +12,13 @@ use futures::{
     Stream,
 };
 use genawaiter::sync::{Co, Gen};
-use std::collections::{hash_map::Entry, HashMap};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    convert::TryFrom,
+};
 use tokio::sync::Mutex;
 
+pub mod actions;
 mod connector_impls;
 
 use crate::{ethereum::Bytes, timestamp::Timestamp};
@@ -62,6 +66,30 @@ pub trait WaitForRefunded {
     ) -> anyhow::Result<Refunded>;
 }
 
+/// How a swap registry gets hold of the tokens of a herc20 swap, it pulls
+/// them with `transferFrom` while the swap is initiated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Funding {
+    /// The funder has to `approve` the registry before funding.
+    Approve,
+    /// The allowance of the funder for the registry already covers the swap.
+    Approved,
+    /// The token supports EIP-2612, the funder signs this permit instead of
+    /// approving the registry.
+    Permit(actions::Permit),
+}
+
+/// Detects how the refund identity of a herc20 swap can fund it through the
+/// swap registry at `registry`.
+#[async_trait::async_trait]
+pub trait DetectFunding {
+    async fn detect_funding(
+        &self,
+        params: Params,
+        registry: identity::Ethereum,
+    ) -> anyhow::Result<Funding>;
+}
+
 /// Represents states that an ERC20 HTLC can be in.
 #[derive(Debug, Clone)]
 pub enum State {
@@ -269,25 +297,28 @@ pub struct Params {
 }
 
 impl Params {
-    pub fn bytecode(&self) -> Bytes {
-        Erc20Htlc::from(self.clone()).into()
+    /// Fails if the secret hash is not 32 bytes long.
+    pub fn bytecode(&self) -> Result<Bytes, FromErr> {
+        Erc20Htlc::try_from(self.clone()).map(Erc20Htlc::into)
     }
 }
 
-impl From<Params> for Erc20Htlc {
-    fn from(params: Params) -> Self {
+impl TryFrom<Params> for Erc20Htlc {
+    type Error = FromErr;
+
+    fn try_from(params: Params) -> Result<Self, Self::Error> {
         let refund_address = blockchain_contracts::ethereum::Address(params.refund_identity.into());
         let redeem_address = blockchain_contracts::ethereum::Address(params.redeem_identity.into());
         let token_contract_address =
             blockchain_contracts::ethereum::Address(params.asset.token_contract.into());
 
-        Erc20Htlc::new(
+        Ok(Erc20Htlc::new(
             params.expiry.into(),
             refund_address,
             redeem_address,
-            params.secret_hash.into(),
+            params.secret_hash.into_raw()?,
             token_contract_address,
             params.asset.quantity.into(),
-        )
+        ))
     }
 }
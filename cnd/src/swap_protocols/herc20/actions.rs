@@ -0,0 +1,254 @@
+//! Actions funding a herc20 swap hosted by a swap registry in a single
+//! transaction signed by the funder: the registry pulls the tokens with
+//! `transferFrom`, authorised either by a prior `approve` or by an EIP-2612
+//! `permit` signed off-chain.
+
+use crate::{
+    ethereum::{Bytes, Hash, U256},
+    identity,
+    swap_protocols::{
+        actions::ethereum::CallContract,
+        herc20::Params,
+        ledger::ethereum::ChainId,
+        rfc003::FromErr,
+        swap_registry::{self, address_word, call_payload, keccak256, selector, u256_word},
+    },
+    timestamp::Timestamp,
+};
+
+pub const APPROVE_GAS_LIMIT: u64 = 60_000;
+
+lazy_static::lazy_static! {
+    static ref APPROVE_SELECTOR: [u8; 4] = selector("approve(address,uint256)");
+    static ref ALLOWANCE_SELECTOR: [u8; 4] = selector("allowance(address,address)");
+    static ref NONCES_SELECTOR: [u8; 4] = selector("nonces(address)");
+    static ref DOMAIN_SEPARATOR_SELECTOR: [u8; 4] = selector("DOMAIN_SEPARATOR()");
+    static ref PERMIT_SELECTOR: [u8; 4] = selector("permit(address,address,uint256,uint256,uint8,bytes32,bytes32)");
+    static ref PERMIT_TYPEHASH: [u8; 32] = keccak256("Permit(address owner,address spender,uint256 value,uint256 nonce,uint256 deadline)");
+}
+
+/// An EIP-2612 permit allowing `spender` to transfer `value` tokens of
+/// `owner`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Permit {
+    pub domain_separator: Hash,
+    pub owner: identity::Ethereum,
+    pub spender: identity::Ethereum,
+    pub value: U256,
+    pub nonce: U256,
+    pub deadline: Timestamp,
+}
+
+impl Permit {
+    /// The EIP-712 hash `owner` has to sign.
+    pub fn digest(&self) -> Hash {
+        let struct_hash = keccak256(
+            [
+                *PERMIT_TYPEHASH,
+                address_word(self.owner),
+                address_word(self.spender),
+                u256_word(self.value),
+                u256_word(self.nonce),
+                u256_word(U256::from(u32::from(self.deadline))),
+            ]
+            .concat(),
+        );
+
+        Hash::from(keccak256(
+            [
+                &[0x19, 0x01][..],
+                self.domain_separator.as_bytes(),
+                &struct_hash[..],
+            ]
+            .concat(),
+        ))
+    }
+}
+
+/// The signature of a [`Permit`] by its owner.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SignedPermit {
+    pub deadline: Timestamp,
+    pub v: u8,
+    pub r: Hash,
+    pub s: Hash,
+}
+
+/// Approves the swap registry to pull the tokens of the swap, only needed if
+/// the token does not support permits.
+pub fn approve_action(
+    params: &Params,
+    registry: identity::Ethereum,
+    chain_id: ChainId,
+) -> CallContract {
+    let data = call_payload(*APPROVE_SELECTOR, &[
+        address_word(registry),
+        u256_word(params.asset.quantity.to_u256()),
+    ]);
+
+    CallContract {
+        to: params.asset.token_contract,
+        data: Some(data),
+        amount: None,
+        gas_limit: APPROVE_GAS_LIMIT,
+        max_fee_per_gas: None,
+        max_priority_fee_per_gas: None,
+        chain_id,
+        min_block_timestamp: None,
+    }
+}
+
+/// Initiates the swap in the registry which pulls the tokens in the same
+/// transaction, fails if the secret hash of the swap is not 32 bytes long.
+pub fn fund_action(
+    params: Params,
+    registry: identity::Ethereum,
+    chain_id: ChainId,
+    permit: Option<SignedPermit>,
+) -> Result<CallContract, FromErr> {
+    let entry = swap_registry::Entry::from(params);
+    let (data, gas_limit) = match permit {
+        Some(permit) => (
+            entry.initiate_and_fund_with_permit_payload(&permit)?,
+            swap_registry::INITIATE_AND_FUND_WITH_PERMIT_GAS_LIMIT,
+        ),
+        None => (
+            entry.initiate_and_fund_payload()?,
+            swap_registry::INITIATE_AND_FUND_GAS_LIMIT,
+        ),
+    };
+
+    Ok(CallContract {
+        to: registry,
+        data: Some(data),
+        amount: None,
+        gas_limit,
+        max_fee_per_gas: None,
+        max_priority_fee_per_gas: None,
+        chain_id,
+        min_block_timestamp: None,
+    })
+}
+
+pub(crate) fn allowance_payload(owner: identity::Ethereum, spender: identity::Ethereum) -> Bytes {
+    call_payload(*ALLOWANCE_SELECTOR, &[
+        address_word(owner),
+        address_word(spender),
+    ])
+}
+
+pub(crate) fn nonces_payload(owner: identity::Ethereum) -> Bytes {
+    call_payload(*NONCES_SELECTOR, &[address_word(owner)])
+}
+
+pub(crate) fn domain_separator_payload() -> Bytes {
+    call_payload(*DOMAIN_SEPARATOR_SELECTOR, &[])
+}
+
+/// Whether the dispatcher of the token's `code` compares against the selector
+/// of the EIP-2612 `permit`. Tokens with other permits, e.g. DAI's, also
+/// expose `DOMAIN_SEPARATOR` and `nonces`, this tells them apart.
+pub(crate) fn supports_permit(code: &Bytes) -> bool {
+    const PUSH4: u8 = 0x63;
+
+    code.0
+        .windows(5)
+        .any(|window| window[0] == PUSH4 && window[1..] == PERMIT_SELECTOR[..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        asset::{ethereum::FromWei, Erc20, Erc20Quantity},
+        swap_protocols::rfc003::SecretHash,
+    };
+    use chrono::NaiveDateTime;
+    use std::str::FromStr;
+
+    fn owner() -> identity::Ethereum {
+        identity::Ethereum::from_str("d51ecee7414c4445534f74208538683702cbb3e4").unwrap()
+    }
+
+    fn registry() -> identity::Ethereum {
+        identity::Ethereum::from_str("0a81e8be41b21f651a71aab1a85c6813b8bbccf8").unwrap()
+    }
+
+    fn params() -> Params {
+        Params {
+            asset: Erc20::new(
+                identity::Ethereum::from_str("b97048628db6b661d4c2aa833e95dbe1a905b280").unwrap(),
+                Erc20Quantity::from_wei(U256::from(1_000_000_000_000_000_000u64)),
+            ),
+            redeem_identity: registry(),
+            refund_identity: owner(),
+            expiry: Timestamp::from(1_590_000_000),
+            start_of_swap: NaiveDateTime::from_timestamp(1_580_000_000, 0),
+            secret_hash: SecretHash::from_str(
+                "68d627971643a6f97f27c58957826fcba853ec2077fd10ec6b93d8e61deb4cec",
+            )
+            .unwrap(),
+        }
+    }
+
+    #[test]
+    fn permit_digest_follows_eip_712() {
+        let permit = Permit {
+            domain_separator: Hash::from_str(
+                "c1f1bd5a6cdf2a8d1a4e2b5c4a7b9a0d3e6f1c2b5a8d7e0f3a6b9c2d5e8f1a4b",
+            )
+            .unwrap(),
+            owner: owner(),
+            spender: registry(),
+            value: U256::from(1_000_000_000_000_000_000u64),
+            nonce: U256::from(3),
+            deadline: Timestamp::from(1_590_000_000),
+        };
+
+        assert_eq!(hex::encode(*PERMIT_SELECTOR), "d505accf");
+        assert_eq!(
+            hex::encode(*PERMIT_TYPEHASH),
+            "6e71edae12b1b97f4d1f60370fef10105fa2faae0126114a169c64845d6126c9"
+        );
+        assert_eq!(
+            permit.digest(),
+            Hash::from_str("f5dbe7e424ecf8d0f17ee83c3cf1531a73ae8d9ccb11cf0985ea79ab587705af")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn approve_action_approves_the_registry_for_the_swap_amount() {
+        let action = approve_action(&params(), registry(), ChainId::regtest());
+
+        assert_eq!(action.to, params().asset.token_contract);
+        assert_eq!(
+            hex::encode(action.data.unwrap()),
+            "095ea7b3\
+             0000000000000000000000000a81e8be41b21f651a71aab1a85c6813b8bbccf8\
+             0000000000000000000000000000000000000000000000000de0b6b3a7640000"
+        );
+    }
+
+    #[test]
+    fn fund_action_with_permit_appends_the_signature() {
+        let permit = SignedPermit {
+            deadline: Timestamp::from(1_590_000_000),
+            v: 27,
+            r: Hash::from_low_u64_be(1),
+            s: Hash::from_low_u64_be(2),
+        };
+
+        let without_permit = fund_action(params(), registry(), ChainId::regtest(), None).unwrap();
+        let with_permit =
+            fund_action(params(), registry(), ChainId::regtest(), Some(permit)).unwrap();
+
+        let without_permit = without_permit.data.unwrap().0;
+        let with_permit = with_permit.data.unwrap().0;
+        assert_eq!(hex::encode(&without_permit[..4]), "ad94b68c");
+        assert_eq!(hex::encode(&with_permit[..4]), "e79516aa");
+        assert_eq!(with_permit[4..4 + 6 * 32], without_permit[4..]);
+        assert_eq!(with_permit.len(), 4 + 10 * 32);
+        assert_eq!(with_permit[4 + 8 * 32 - 1], 27);
+    }
+}
@@ -1,13 +1,16 @@
 use crate::{
     asset::{ethereum::FromWei, Erc20, Erc20Quantity},
     btsieve::ethereum::{
-        watch_for_contract_creation, watch_for_event, Cache, Event, Topic, Web3Connector,
+        watch_for_contract_creation, watch_for_event, Cache, CallContract, ContractCode, Event,
+        Topic, Web3Connector,
     },
-    ethereum::{Hash, U256},
+    ethereum::{Bytes, Hash, U256},
+    identity,
     swap_protocols::{
         herc20::{
-            Deployed, Funded, Params, Redeemed, Refunded, WaitForDeployed, WaitForFunded,
-            WaitForRedeemed, WaitForRefunded,
+            actions::{self, Permit},
+            Deployed, DetectFunding, Funded, Funding, Params, Redeemed, Refunded, WaitForDeployed,
+            WaitForFunded, WaitForRedeemed, WaitForRefunded,
         },
         rfc003::Secret,
     },
@@ -24,10 +27,10 @@ lazy_static::lazy_static! {
 #[async_trait::async_trait]
 impl WaitForDeployed for Cache<Web3Connector> {
     async fn wait_for_deployed(&self, params: Params) -> anyhow::Result<Deployed> {
-        let expected_bytecode = params.clone().bytecode();
+        let expected_bytecode = params.bytecode()?;
 
         let (transaction, location) =
-            watch_for_contract_creation(self, params.start_of_swap, &expected_bytecode)
+            watch_for_contract_creation(self, params.start_of_swap.into(), &expected_bytecode)
                 .instrument(tracing::trace_span!(
                     "deployed",
                     expected_bytecode = %hex::encode(&expected_bytecode.0)
@@ -53,7 +56,7 @@ impl WaitForFunded for Cache<Web3Connector> {
             ],
         };
 
-        let (transaction, log) = watch_for_event(self, params.start_of_swap, event)
+        let (transaction, log) = watch_for_event(self, params.start_of_swap.into(), event)
             .instrument(tracing::trace_span!("funded"))
             .await?;
 
@@ -83,7 +86,7 @@ impl WaitForRedeemed for Cache<Web3Connector> {
             topics: vec![Some(Topic(*REDEEM_LOG_MSG))],
         };
 
-        let (transaction, log) = watch_for_event(self, params.start_of_swap, event)
+        let (transaction, log) = watch_for_event(self, params.start_of_swap.into(), event)
             .instrument(tracing::info_span!("redeemed"))
             .await?;
 
@@ -110,10 +113,213 @@ impl WaitForRefunded for Cache<Web3Connector> {
             topics: vec![Some(Topic(*REFUND_LOG_MSG))],
         };
 
-        let (transaction, _) = watch_for_event(self, params.start_of_swap, event)
+        let (transaction, _) = watch_for_event(self, params.start_of_swap.into(), event)
             .instrument(tracing::info_span!("refunded"))
             .await?;
 
         Ok(Refunded { transaction })
     }
 }
+
+#[async_trait::async_trait]
+impl DetectFunding for Cache<Web3Connector> {
+    async fn detect_funding(
+        &self,
+        params: Params,
+        registry: identity::Ethereum,
+    ) -> anyhow::Result<Funding> {
+        let token_contract = params.asset.token_contract;
+        let owner = params.refund_identity;
+        let value = params.asset.quantity.to_u256();
+
+        let allowance = self
+            .call_contract(token_contract, actions::allowance_payload(owner, registry))
+            .await?;
+        let allowance = single_word(&allowance)
+            .map(U256::from_big_endian)
+            .ok_or_else(|| {
+                anyhow::anyhow!("token {:x} returned malformed allowance", token_contract)
+            })?;
+
+        if allowance >= value {
+            return Ok(Funding::Approved);
+        }
+
+        let code = self.contract_code(token_contract).await?;
+        if !actions::supports_permit(&code) {
+            tracing::debug!("token {:x} does not support permits", token_contract);
+            return Ok(Funding::Approve);
+        }
+
+        // Fall back to approving if the token does not answer as EIP-2612 specifies.
+        let domain_separator = self
+            .call_contract(token_contract, actions::domain_separator_payload())
+            .await;
+        let nonce = self
+            .call_contract(token_contract, actions::nonces_payload(owner))
+            .await;
+
+        let funding = match (domain_separator, nonce) {
+            (Ok(domain_separator), Ok(nonce)) => {
+                match (single_word(&domain_separator), single_word(&nonce)) {
+                    (Some(domain_separator), Some(nonce)) => Funding::Permit(Permit {
+                        domain_separator: Hash::from_slice(domain_separator),
+                        owner,
+                        spender: registry,
+                        value,
+                        nonce: U256::from_big_endian(nonce),
+                        deadline: params.expiry,
+                    }),
+                    _ => Funding::Approve,
+                }
+            }
+            _ => {
+                tracing::debug!("token {:x} does not support permits", token_contract);
+                Funding::Approve
+            }
+        };
+
+        Ok(funding)
+    }
+}
+
+fn single_word(output: &Bytes) -> Option<&[u8]> {
+    Some(output.0.as_ref()).filter(|word| word.len() == 32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mock_node, swap_protocols::rfc003::SecretHash, timestamp::Timestamp};
+    use chrono::NaiveDateTime;
+    use std::{collections::HashMap, str::FromStr};
+
+    /// Answers `eth_getCode` with `code` and `eth_call`s with the output
+    /// stored for their selector, calls of unknown selectors revert.
+    fn mock_token(
+        code: &'static str,
+        outputs: HashMap<&'static str, &'static str>,
+    ) -> Cache<Web3Connector> {
+        let url = mock_node::json_rpc(move |request| {
+            if request["method"] == "eth_getCode" {
+                return Ok(serde_json::json!(code));
+            }
+
+            let data = request["params"][0]["data"].as_str().unwrap_or_default();
+
+            data.get(2..10)
+                .and_then(|selector| outputs.get(selector))
+                .map(|output| serde_json::json!(output))
+                .ok_or(mock_node::RpcError {
+                    code: -32000,
+                    message: "execution reverted",
+                })
+        });
+
+        Cache::new(Web3Connector::new(url), 10, 10)
+    }
+
+    fn registry() -> identity::Ethereum {
+        identity::Ethereum::from_str("0a81e8be41b21f651a71aab1a85c6813b8bbccf8").unwrap()
+    }
+
+    fn params() -> Params {
+        Params {
+            asset: Erc20::new(
+                identity::Ethereum::from_str("b97048628db6b661d4c2aa833e95dbe1a905b280").unwrap(),
+                Erc20Quantity::from_wei(U256::from(1_000u64)),
+            ),
+            redeem_identity: registry(),
+            refund_identity: identity::Ethereum::from_str(
+                "d51ecee7414c4445534f74208538683702cbb3e4",
+            )
+            .unwrap(),
+            expiry: Timestamp::from(1_590_000_000),
+            start_of_swap: NaiveDateTime::from_timestamp(1_580_000_000, 0),
+            secret_hash: SecretHash::from_str(
+                "68d627971643a6f97f27c58957826fcba853ec2077fd10ec6b93d8e61deb4cec",
+            )
+            .unwrap(),
+        }
+    }
+
+    const ZERO: &str = "0x0000000000000000000000000000000000000000000000000000000000000000";
+    const THOUSAND: &str = "0x00000000000000000000000000000000000000000000000000000000000003e8";
+    const DOMAIN_SEPARATOR: &str =
+        "0xc1f1bd5a6cdf2a8d1a4e2b5c4a7b9a0d3e6f1c2b5a8d7e0f3a6b9c2d5e8f1a4b";
+    /// Dispatcher comparing against the selector of the EIP-2612 `permit`.
+    const PERMIT_CODE: &str = "0x6080604052348015600f57600080fd5b5063d505accf811460325780";
+    /// Dispatcher comparing against the selector of DAI's `permit` which
+    /// takes an `allowed` flag instead of a value.
+    const DAI_PERMIT_CODE: &str = "0x6080604052348015600f57600080fd5b50638fcbaf0c811460325780";
+
+    #[tokio::test]
+    async fn sufficient_allowance_needs_no_approval() {
+        let mut outputs = HashMap::new();
+        outputs.insert("dd62ed3e", THOUSAND);
+        let connector = mock_token(PERMIT_CODE, outputs);
+
+        let funding = connector
+            .detect_funding(params(), registry())
+            .await
+            .unwrap();
+
+        assert_eq!(funding, Funding::Approved);
+    }
+
+    #[tokio::test]
+    async fn permit_is_preferred_over_approval_if_supported() {
+        let mut outputs = HashMap::new();
+        outputs.insert("dd62ed3e", ZERO);
+        outputs.insert("3644e515", DOMAIN_SEPARATOR);
+        outputs.insert("7ecebe00", THOUSAND);
+        let connector = mock_token(PERMIT_CODE, outputs);
+
+        let funding = connector
+            .detect_funding(params(), registry())
+            .await
+            .unwrap();
+
+        assert_eq!(
+            funding,
+            Funding::Permit(Permit {
+                domain_separator: Hash::from_str(&DOMAIN_SEPARATOR[2..]).unwrap(),
+                owner: params().refund_identity,
+                spender: registry(),
+                value: U256::from(1_000u64),
+                nonce: U256::from(1_000u64),
+                deadline: params().expiry,
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn tokens_with_a_different_permit_need_approval() {
+        let mut outputs = HashMap::new();
+        outputs.insert("dd62ed3e", ZERO);
+        outputs.insert("3644e515", DOMAIN_SEPARATOR);
+        outputs.insert("7ecebe00", THOUSAND);
+        let connector = mock_token(DAI_PERMIT_CODE, outputs);
+
+        let funding = connector
+            .detect_funding(params(), registry())
+            .await
+            .unwrap();
+
+        assert_eq!(funding, Funding::Approve);
+    }
+
+    #[tokio::test]
+    async fn tokens_without_permit_need_approval() {
+        let mut outputs = HashMap::new();
+        outputs.insert("dd62ed3e", ZERO);
+        let connector = mock_token("0x6080604052", outputs);
+
+        let funding = connector
+            .detect_funding(params(), registry())
+            .await
+            .unwrap();
+
+        assert_eq!(funding, Funding::Approve);
+    }
+}
@@ -24,7 +24,13 @@ pub use self::{
 
 pub use self::messages::{Accept, Decline, Request};
 
-use crate::seed::SwapSeed;
+use crate::{
+    asset::AssetKind,
+    comit_api::LedgerKind,
+    identity,
+    seed::SwapSeed,
+    swap_protocols::{HashFunction, Role},
+};
 use ::bitcoin::secp256k1::SecretKey;
 
 /// Swap request response as received from peer node acting as Bob.
@@ -43,6 +49,10 @@ pub enum SwapCommunication<AL, BL, AA, BA, AI, BI> {
         request: Request<AL, BL, AA, BA, AI, BI>,
         response: Decline,
     },
+    /// The peer did not respond to the request within the request timeout.
+    TimedOut {
+        request: Request<AL, BL, AA, BA, AI, BI>,
+    },
 }
 
 impl<AL, BL, AA, BA, AI, BI> SwapCommunication<AL, BL, AA, BA, AI, BI> {
@@ -51,10 +61,54 @@ impl<AL, BL, AA, BA, AI, BI> SwapCommunication<AL, BL, AA, BA, AI, BI> {
             SwapCommunication::Accepted { request, .. } => request,
             SwapCommunication::Proposed { request } => request,
             SwapCommunication::Declined { request, .. } => request,
+            SwapCommunication::TimedOut { request } => request,
         }
     }
 }
 
+impl<AL, BL, AA, BA, AI, BI> SwapCommunication<AL, BL, AA, BA, AI, BI>
+where
+    AI: AsEthereumIdentity,
+    BI: AsEthereumIdentity,
+{
+    /// The Ethereum identity of the user, the sender of their Ethereum
+    /// transactions. Bob's identities are only known once he accepted.
+    pub fn ethereum_identity(&self, role: Role) -> Option<identity::Ethereum> {
+        match (role, self) {
+            (Role::Alice, _) => {
+                let request = self.request();
+
+                request
+                    .alpha_ledger_refund_identity
+                    .as_ethereum_identity()
+                    .or_else(|| request.beta_ledger_redeem_identity.as_ethereum_identity())
+            }
+            (Role::Bob, SwapCommunication::Accepted { response, .. }) => response
+                .alpha_ledger_redeem_identity
+                .as_ethereum_identity()
+                .or_else(|| response.beta_ledger_refund_identity.as_ethereum_identity()),
+            (Role::Bob, _) => None,
+        }
+    }
+}
+
+/// Picks the Ethereum identity out of the identities of a swap.
+pub trait AsEthereumIdentity {
+    fn as_ethereum_identity(&self) -> Option<identity::Ethereum>;
+}
+
+impl AsEthereumIdentity for identity::Ethereum {
+    fn as_ethereum_identity(&self) -> Option<identity::Ethereum> {
+        Some(*self)
+    }
+}
+
+impl AsEthereumIdentity for identity::Bitcoin {
+    fn as_ethereum_identity(&self) -> Option<identity::Ethereum> {
+        None
+    }
+}
+
 pub trait DeriveIdentities: Send + Sync + 'static {
     fn derive_redeem_identity(&self) -> SecretKey;
     fn derive_refund_identity(&self) -> SecretKey;
@@ -83,3 +137,81 @@ impl DeriveSecret for SwapSeed {
         self.sha256_with_seed(&[b"SECRET"]).into()
     }
 }
+
+/// An error type for describing that the HTLC of an asset on a ledger cannot
+/// be locked with the hash function of the swap.
+#[derive(Debug, Clone, thiserror::Error)]
+#[error("HTLCs for {asset:?} on {ledger:?} cannot be locked with {hash_function}")]
+pub struct UnsupportedHashFunction {
+    hash_function: HashFunction,
+    ledger: LedgerKind,
+    asset: AssetKind,
+}
+
+/// Bitcoin script has no opcode for Keccak-256 and the ERC20 HTLC of
+/// `blockchain_contracts` only exists for SHA-256.
+///
+/// Combinations of ledgers and assets that have no HTLC at all pass, they are
+/// rejected as unsupported swaps.
+pub fn ensure_htlc_supports(
+    hash_function: HashFunction,
+    ledger: &LedgerKind,
+    asset: &AssetKind,
+) -> Result<(), UnsupportedHashFunction> {
+    let supported = match (ledger, asset) {
+        (LedgerKind::BitcoinMainnet, AssetKind::Bitcoin(_))
+        | (LedgerKind::BitcoinTestnet, AssetKind::Bitcoin(_))
+        | (LedgerKind::BitcoinRegtest, AssetKind::Bitcoin(_)) => {
+            matches!(hash_function, HashFunction::Sha256 | HashFunction::Hash160)
+        }
+        (LedgerKind::Ethereum(_), AssetKind::Erc20(_)) => hash_function == HashFunction::Sha256,
+        _ => true,
+    };
+
+    if !supported {
+        return Err(UnsupportedHashFunction {
+            hash_function,
+            ledger: *ledger,
+            asset: asset.clone(),
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        asset::{self, ethereum::FromWei},
+        ethereum::U256,
+        swap_protocols::ledger::Ethereum,
+    };
+    use strum::IntoEnumIterator;
+
+    #[test]
+    fn bitcoin_htlcs_cannot_be_locked_with_keccak256() {
+        let bitcoin = AssetKind::Bitcoin(asset::Bitcoin::from_sat(100_000_000));
+
+        assert!(
+            ensure_htlc_supports(HashFunction::Hash160, &LedgerKind::BitcoinRegtest, &bitcoin)
+                .is_ok()
+        );
+        assert!(ensure_htlc_supports(
+            HashFunction::Keccak256,
+            &LedgerKind::BitcoinRegtest,
+            &bitcoin
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn ether_htlcs_can_be_locked_with_all_hash_functions() {
+        let ether = AssetKind::Ether(asset::Ether::from_wei(U256::from(1_000)));
+        let ethereum = LedgerKind::Ethereum(Ethereum::default());
+
+        for hash_function in HashFunction::iter() {
+            assert!(ensure_htlc_supports(hash_function, &ethereum, &ether).is_ok());
+        }
+    }
+}
@@ -19,13 +19,22 @@ pub enum Action<Accept, Decline, Deploy, Fund, Redeem, Refund> {
     Fund(Fund),
     Redeem(Redeem),
     Refund(Refund),
+    /// Replaces a redeem transaction stuck in the mempool with one paying a
+    /// higher fee.
+    BumpRedeemFee(Redeem),
+    /// Replaces a refund transaction stuck in the mempool with one paying a
+    /// higher fee.
+    BumpRefundFee(Refund),
+    /// Speeds up a fund transaction stuck in the mempool by spending one of
+    /// its outputs with a high enough fee (child-pays-for-parent).
+    BumpFundFee(Fund),
 }
 
 pub trait MakeFundAction {
     type HtlcParams;
     type Output;
 
-    fn make_fund_action(htlc_params: Self::HtlcParams) -> Self::Output;
+    fn make_fund_action(htlc_params: Self::HtlcParams) -> anyhow::Result<Self::Output>;
 }
 
 pub trait MakeRefundAction {
@@ -39,7 +48,7 @@ pub trait MakeRefundAction {
         htlc_location: Self::HtlcLocation,
         secret_source: &dyn DeriveIdentities,
         fund_transaction: &Self::FundTransaction,
-    ) -> Self::Output;
+    ) -> anyhow::Result<Self::Output>;
 }
 
 pub trait MakeRedeemAction {
@@ -52,7 +61,7 @@ pub trait MakeRedeemAction {
         htlc_location: Self::HtlcLocation,
         secret_source: &dyn DeriveIdentities,
         secret: Secret,
-    ) -> Self::Output;
+    ) -> anyhow::Result<Self::Output>;
 }
 
 #[derive(Clone, Debug, Default)]
@@ -94,5 +103,17 @@ mod tests {
         assert_eq!(ActionKind::Refund.to_string(), "refund".to_string());
         assert_eq!(ActionKind::Redeem.to_string(), "redeem".to_string());
         assert_eq!(ActionKind::Deploy.to_string(), "deploy".to_string());
+        assert_eq!(
+            ActionKind::BumpRedeemFee.to_string(),
+            "bump_redeem_fee".to_string()
+        );
+        assert_eq!(
+            ActionKind::BumpRefundFee.to_string(),
+            "bump_refund_fee".to_string()
+        );
+        assert_eq!(
+            ActionKind::BumpFundFee.to_string(),
+            "bump_fund_fee".to_string()
+        );
     }
 }
@@ -5,13 +5,15 @@ use crate::{
         ledger,
         rfc003::{
             actions::{MakeFundAction, MakeRedeemAction, MakeRefundAction},
+            bitcoin::Htlc,
             create_swap::HtlcParams,
             DeriveIdentities, Secret,
         },
     },
 };
 use ::bitcoin::{Amount, OutPoint, Transaction};
-use blockchain_contracts::bitcoin::{rfc003::bitcoin_htlc::BitcoinHtlc, witness::PrimedInput};
+use blockchain_contracts::bitcoin::witness::PrimedInput;
+use std::convert::TryFrom;
 
 impl<B> MakeFundAction for (B, asset::Bitcoin)
 where
@@ -20,14 +22,14 @@ where
     type HtlcParams = HtlcParams<B, asset::Bitcoin, identity::Bitcoin>;
     type Output = SendToAddress;
 
-    fn make_fund_action(htlc_params: Self::HtlcParams) -> Self::Output {
-        let to = htlc_params.compute_address();
+    fn make_fund_action(htlc_params: Self::HtlcParams) -> anyhow::Result<Self::Output> {
+        let to = htlc_params.compute_address()?;
 
-        SendToAddress {
+        Ok(SendToAddress {
             to,
             amount: htlc_params.asset,
             network: B::network(),
-        }
+        })
     }
 }
 
@@ -45,17 +47,18 @@ where
         htlc_location: Self::HtlcLocation,
         secret_source: &dyn DeriveIdentities,
         fund_transaction: &Self::FundTransaction,
-    ) -> Self::Output {
-        let htlc = BitcoinHtlc::from(htlc_params);
+    ) -> anyhow::Result<Self::Output> {
+        let htlc = Htlc::try_from(htlc_params)?;
 
-        SpendOutput {
+        Ok(SpendOutput {
             output: PrimedInput::new(
                 htlc_location,
                 Amount::from_sat(fund_transaction.output[htlc_location.vout as usize].value),
                 htlc.unlock_after_timeout(&*crate::SECP, secret_source.derive_refund_identity()),
             ),
+            outpoint: htlc_location,
             network: B::network(),
-        }
+        })
     }
 }
 
@@ -72,10 +75,10 @@ where
         htlc_location: Self::HtlcLocation,
         secret_source: &dyn DeriveIdentities,
         secret: Secret,
-    ) -> Self::Output {
-        let htlc = BitcoinHtlc::from(htlc_params);
+    ) -> anyhow::Result<Self::Output> {
+        let htlc = Htlc::try_from(htlc_params)?;
 
-        SpendOutput {
+        Ok(SpendOutput {
             output: PrimedInput::new(
                 htlc_location,
                 htlc_params.asset.clone().into(),
@@ -85,7 +88,8 @@ where
                     secret.into_raw_secret(),
                 ),
             ),
+            outpoint: htlc_location,
             network: B::network(),
-        }
+        })
     }
 }
@@ -5,25 +5,28 @@ use crate::{
     swap_protocols::{
         actions::ethereum::{CallContract, DeployContract},
         ledger::{ethereum::ChainId, Ethereum},
-        rfc003::{create_swap::HtlcParams, Secret},
+        rfc003::{create_swap::HtlcParams, FromErr, Secret},
     },
     timestamp::Timestamp,
 };
 use blockchain_contracts::ethereum::rfc003::erc20_htlc::Erc20Htlc;
+use std::convert::TryFrom;
 
 pub fn deploy_action(
     htlc_params: HtlcParams<Ethereum, asset::Erc20, identity::Ethereum>,
-) -> DeployContract {
+) -> Result<DeployContract, FromErr> {
     let chain_id = htlc_params.ledger.chain_id;
-    let htlc = Erc20Htlc::from(htlc_params);
+    let htlc = Erc20Htlc::try_from(htlc_params)?;
     let gas_limit = Erc20Htlc::deploy_tx_gas_limit();
 
-    DeployContract {
+    Ok(DeployContract {
         data: htlc.into(),
         amount: asset::Ether::zero(),
         gas_limit,
+        max_fee_per_gas: None,
+        max_priority_fee_per_gas: None,
         chain_id,
-    }
+    })
 }
 
 pub fn fund_action(
@@ -41,7 +44,10 @@ pub fn fund_action(
     CallContract {
         to: to_erc20_contract,
         data: Some(Bytes(data)),
+        amount: None,
         gas_limit,
+        max_fee_per_gas: None,
+        max_priority_fee_per_gas: None,
         chain_id,
         min_block_timestamp: None,
     }
@@ -58,7 +64,10 @@ pub fn refund_action(
     CallContract {
         to: beta_htlc_location,
         data: Some(data),
+        amount: None,
         gas_limit,
+        max_fee_per_gas: None,
+        max_priority_fee_per_gas: None,
         chain_id,
         min_block_timestamp: Some(expiry),
     }
@@ -75,7 +84,10 @@ pub fn redeem_action(
     CallContract {
         to: alpha_htlc_location,
         data: Some(data),
+        amount: None,
         gas_limit,
+        max_fee_per_gas: None,
+        max_priority_fee_per_gas: None,
         chain_id,
         min_block_timestamp: None,
     }
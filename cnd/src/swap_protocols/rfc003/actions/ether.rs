@@ -12,22 +12,22 @@ use crate::{
         },
     },
 };
-use blockchain_contracts::ethereum::rfc003::ether_htlc::EtherHtlc;
 
 impl MakeFundAction for (Ethereum, asset::Ether) {
     type HtlcParams = HtlcParams<Ethereum, asset::Ether, identity::Ethereum>;
     type Output = DeployContract;
 
-    fn make_fund_action(htlc_params: Self::HtlcParams) -> Self::Output {
-        let htlc = EtherHtlc::from(htlc_params.clone());
-        let gas_limit = EtherHtlc::deploy_tx_gas_limit();
+    fn make_fund_action(htlc_params: Self::HtlcParams) -> anyhow::Result<Self::Output> {
+        let gas_limit = htlc_params.deploy_tx_gas_limit();
 
-        DeployContract {
-            data: htlc.into(),
+        Ok(DeployContract {
+            data: htlc_params.bytecode()?,
             amount: htlc_params.asset.clone(),
             gas_limit,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
             chain_id: htlc_params.ledger.chain_id,
-        }
+        })
     }
 }
 
@@ -42,16 +42,19 @@ impl MakeRefundAction for (Ethereum, asset::Ether) {
         htlc_location: Self::HtlcLocation,
         _secret_source: &dyn DeriveIdentities,
         _fund_transaction: &Self::FundTransaction,
-    ) -> Self::Output {
-        let gas_limit = EtherHtlc::refund_tx_gas_limit();
+    ) -> anyhow::Result<Self::Output> {
+        let gas_limit = htlc_params.refund_tx_gas_limit();
 
-        CallContract {
+        Ok(CallContract {
             to: htlc_location,
             data: None,
+            amount: None,
             gas_limit,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
             chain_id: htlc_params.ledger.chain_id,
             min_block_timestamp: Some(htlc_params.expiry),
-        }
+        })
     }
 }
 
@@ -65,16 +68,19 @@ impl MakeRedeemAction for (Ethereum, asset::Ether) {
         htlc_location: Self::HtlcLocation,
         _secret_source: &dyn DeriveIdentities,
         secret: Secret,
-    ) -> Self::Output {
+    ) -> anyhow::Result<Self::Output> {
         let data = Bytes::from(secret.as_raw_secret().to_vec());
-        let gas_limit = EtherHtlc::redeem_tx_gas_limit();
+        let gas_limit = htlc_params.redeem_tx_gas_limit();
 
-        CallContract {
+        Ok(CallContract {
             to: htlc_location,
             data: Some(data),
+            amount: None,
             gas_limit,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
             chain_id: htlc_params.ledger.chain_id,
             min_block_timestamp: None,
-        }
+        })
     }
 }
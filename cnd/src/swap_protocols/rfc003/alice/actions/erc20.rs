@@ -48,13 +48,13 @@ where
         ethereum::CallContract,
     >;
 
-    fn actions(&self) -> Vec<Self::ActionKind> {
+    fn actions(&self) -> anyhow::Result<Vec<Self::ActionKind>> {
         let (request, response) = match self.swap_communication {
             SwapCommunication::Accepted {
                 ref request,
                 ref response,
             } => (request, response),
-            _ => return vec![],
+            _ => return Ok(vec![]),
         };
         let alpha_state = &self.alpha_ledger_state;
         let beta_state = &self.beta_ledger_state;
@@ -64,7 +64,7 @@ where
         let mut actions = match alpha_state {
             NotDeployed => vec![Action::Deploy(erc20::deploy_action(
                 HtlcParams::new_alpha_params(request, response),
-            ))],
+            )?)],
             Deployed { htlc_location, .. } => vec![Action::Fund(erc20::fund_action(
                 HtlcParams::new_alpha_params(request, response),
                 request.alpha_asset.token_contract,
@@ -84,9 +84,9 @@ where
                 htlc_location.clone(),
                 &self.secret_source, // Derive identities with this.
                 self.secret_source.derive_secret(), // The secret used by Alice.
-            )));
+            )?));
         }
-        actions
+        Ok(actions)
     }
 }
 
@@ -126,13 +126,13 @@ where
         <(AL, AA) as MakeRefundAction>::Output,
     >;
 
-    fn actions(&self) -> Vec<Self::ActionKind> {
+    fn actions(&self) -> anyhow::Result<Vec<Self::ActionKind>> {
         let (request, response) = match self.swap_communication {
             SwapCommunication::Accepted {
                 ref request,
                 ref response,
             } => (request, response),
-            _ => return vec![],
+            _ => return Ok(vec![]),
         };
         let alpha_state = &self.alpha_ledger_state;
         let beta_state = &self.beta_ledger_state;
@@ -142,7 +142,7 @@ where
         let mut actions = match alpha_state {
             NotDeployed => vec![Action::Fund(<(AL, AA)>::make_fund_action(
                 HtlcParams::new_alpha_params(request, response),
-            ))],
+            )?)],
             Funded {
                 htlc_location,
                 fund_transaction,
@@ -152,7 +152,7 @@ where
                 *htlc_location,
                 &self.secret_source,
                 fund_transaction,
-            ))],
+            )?)],
             _ => vec![],
         };
 
@@ -163,6 +163,6 @@ where
                 request.beta_ledger.chain_id,
             )));
         }
-        actions
+        Ok(actions)
     }
 }
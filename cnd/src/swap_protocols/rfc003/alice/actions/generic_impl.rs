@@ -40,13 +40,13 @@ where
         <(AL, AA) as MakeRefundAction>::Output,
     >;
 
-    fn actions(&self) -> Vec<Self::ActionKind> {
+    fn actions(&self) -> anyhow::Result<Vec<Self::ActionKind>> {
         let (request, response) = match self.swap_communication {
             SwapCommunication::Accepted {
                 ref request,
                 ref response,
             } => (request, response),
-            _ => return vec![],
+            _ => return Ok(vec![]),
         };
         let alpha_state = &self.alpha_ledger_state;
         let beta_state = &self.beta_ledger_state;
@@ -55,7 +55,7 @@ where
         let mut actions = match alpha_state {
             NotDeployed => vec![Action::Fund(<(AL, AA)>::make_fund_action(
                 HtlcParams::new_alpha_params(request, response),
-            ))],
+            )?)],
             Funded {
                 htlc_location,
                 fund_transaction,
@@ -70,7 +70,7 @@ where
                 htlc_location.clone(),
                 &self.secret_source,
                 fund_transaction,
-            ))],
+            )?)],
             _ => vec![],
         };
 
@@ -80,8 +80,8 @@ where
                 htlc_location.clone(),
                 &self.secret_source, // Derive identities with this.
                 self.secret_source.derive_secret(), // The secret used by Alice.
-            )));
+            )?));
         }
-        actions
+        Ok(actions)
     }
 }
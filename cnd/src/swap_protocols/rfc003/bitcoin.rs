@@ -1,35 +1,95 @@
 mod extract_secret;
+mod hash160_htlc;
 mod htlc_events;
 
 use crate::{
     asset, identity,
-    swap_protocols::{ledger, rfc003::create_swap::HtlcParams},
+    swap_protocols::{ledger, rfc003::create_swap::HtlcParams, HashFunction},
 };
 use ::bitcoin::{
     hashes::{hash160, Hash},
-    Address,
+    secp256k1::{Secp256k1, SecretKey, Signing},
+    Address, Network,
 };
-use blockchain_contracts::bitcoin::rfc003::bitcoin_htlc::BitcoinHtlc;
+use blockchain_contracts::bitcoin::{rfc003::bitcoin_htlc::BitcoinHtlc, witness::UnlockParameters};
+use std::convert::TryFrom;
 
-pub use self::htlc_events::*;
+pub use self::{hash160_htlc::Hash160Htlc, htlc_events::*};
 
-impl<B> From<HtlcParams<B, asset::Bitcoin, identity::Bitcoin>> for BitcoinHtlc
+/// The Bitcoin HTLC for the hash function a swap was negotiated with.
+pub enum Htlc {
+    Sha256(BitcoinHtlc),
+    Hash160(Hash160Htlc),
+}
+
+impl Htlc {
+    pub fn compute_address(&self, network: Network) -> Address {
+        match self {
+            Htlc::Sha256(htlc) => htlc.compute_address(network),
+            Htlc::Hash160(htlc) => htlc.compute_address(network),
+        }
+    }
+
+    pub fn unlock_with_secret<C: Signing>(
+        self,
+        secp: &Secp256k1<C>,
+        secret_key: SecretKey,
+        secret: [u8; 32],
+    ) -> UnlockParameters {
+        match self {
+            Htlc::Sha256(htlc) => htlc.unlock_with_secret(secp, secret_key, secret),
+            Htlc::Hash160(htlc) => htlc.unlock_with_secret(secp, secret_key, secret),
+        }
+    }
+
+    pub fn unlock_after_timeout<C: Signing>(
+        self,
+        secp: &Secp256k1<C>,
+        secret_key: SecretKey,
+    ) -> UnlockParameters {
+        match self {
+            Htlc::Sha256(htlc) => htlc.unlock_after_timeout(secp, secret_key),
+            Htlc::Hash160(htlc) => htlc.unlock_after_timeout(secp, secret_key),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, thiserror::Error)]
+#[error("Bitcoin script cannot compute Keccak-256")]
+pub struct Keccak256NotSupported;
+
+impl<B> TryFrom<HtlcParams<B, asset::Bitcoin, identity::Bitcoin>> for Htlc
 where
     B: ledger::Bitcoin,
 {
-    fn from(htlc_params: HtlcParams<B, asset::Bitcoin, identity::Bitcoin>) -> Self {
+    type Error = anyhow::Error;
+
+    fn try_from(
+        htlc_params: HtlcParams<B, asset::Bitcoin, identity::Bitcoin>,
+    ) -> anyhow::Result<Self> {
         let refund_public_key = ::bitcoin::PublicKey::from(htlc_params.refund_identity);
         let redeem_public_key = ::bitcoin::PublicKey::from(htlc_params.redeem_identity);
 
         let refund_identity = hash160::Hash::hash(&refund_public_key.key.serialize());
         let redeem_identity = hash160::Hash::hash(&redeem_public_key.key.serialize());
 
-        BitcoinHtlc::new(
-            htlc_params.expiry.into(),
-            refund_identity,
-            redeem_identity,
-            htlc_params.secret_hash.into_raw(),
-        )
+        let htlc = match htlc_params.hash_function {
+            HashFunction::Sha256 => Htlc::Sha256(BitcoinHtlc::new(
+                htlc_params.expiry.into(),
+                refund_identity,
+                redeem_identity,
+                htlc_params.secret_hash.into_raw()?,
+            )),
+            HashFunction::Hash160 => Htlc::Hash160(Hash160Htlc::new(
+                htlc_params.expiry.into(),
+                refund_identity,
+                redeem_identity,
+                htlc_params.secret_hash,
+            )),
+            HashFunction::Keccak256 => return Err(anyhow::Error::from(Keccak256NotSupported)),
+        };
+
+        Ok(htlc)
     }
 }
 
@@ -37,7 +97,8 @@ impl<B> HtlcParams<B, asset::Bitcoin, identity::Bitcoin>
 where
     B: ledger::Bitcoin + ledger::bitcoin::Network,
 {
-    pub fn compute_address(&self) -> Address {
-        BitcoinHtlc::from(*self).compute_address(B::network())
+    /// Fails for swaps that cannot be locked in a Bitcoin script.
+    pub fn compute_address(&self) -> anyhow::Result<Address> {
+        Ok(Htlc::try_from(*self)?.compute_address(B::network()))
     }
 }
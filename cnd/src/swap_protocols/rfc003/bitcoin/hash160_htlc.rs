@@ -0,0 +1,126 @@
+use crate::swap_protocols::rfc003::SecretHash;
+use ::bitcoin::{
+    blockdata::{
+        opcodes::all::{
+            OP_CHECKSIG, OP_CLTV, OP_DROP, OP_DUP, OP_ELSE, OP_ENDIF, OP_EQUALVERIFY, OP_HASH160,
+            OP_IF,
+        },
+        script::{Builder, Script},
+    },
+    hashes::hash160,
+    secp256k1::{PublicKey, Secp256k1, SecretKey, Signing},
+    Address, Network,
+};
+use blockchain_contracts::bitcoin::witness::{UnlockParameters, Witness};
+
+/// Allows the lock time to be enforced without opting into replace-by-fee.
+const SEQUENCE_ALLOW_NTIMELOCK_NO_RBF: u32 = 0xFFFF_FFFE;
+
+/// The rfc003 Bitcoin HTLC whose secret is checked with `OP_HASH160`.
+///
+/// Apart from the hash opcode and the 20 byte secret hash the script is the
+/// same as the SHA-256 HTLC, so are the witnesses that unlock it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hash160Htlc {
+    script: Script,
+    expiry: u32,
+}
+
+impl Hash160Htlc {
+    pub fn new(
+        expiry: u32,
+        refund_identity: hash160::Hash,
+        redeem_identity: hash160::Hash,
+        secret_hash: SecretHash,
+    ) -> Self {
+        let script = Builder::new()
+            .push_opcode(OP_IF)
+            .push_opcode(OP_HASH160)
+            .push_slice(secret_hash.as_bytes())
+            .push_opcode(OP_EQUALVERIFY)
+            .push_opcode(OP_DUP)
+            .push_opcode(OP_HASH160)
+            .push_slice(&redeem_identity[..])
+            .push_opcode(OP_ELSE)
+            .push_int(i64::from(expiry))
+            .push_opcode(OP_CLTV)
+            .push_opcode(OP_DROP)
+            .push_opcode(OP_DUP)
+            .push_opcode(OP_HASH160)
+            .push_slice(&refund_identity[..])
+            .push_opcode(OP_ENDIF)
+            .push_opcode(OP_EQUALVERIFY)
+            .push_opcode(OP_CHECKSIG)
+            .into_script();
+
+        Hash160Htlc { script, expiry }
+    }
+
+    pub fn compute_address(&self, network: Network) -> Address {
+        Address::p2wsh(&self.script, network)
+    }
+
+    pub fn unlock_with_secret<C: Signing>(
+        self,
+        secp: &Secp256k1<C>,
+        secret_key: SecretKey,
+        secret: [u8; 32],
+    ) -> UnlockParameters {
+        let public_key = PublicKey::from_secret_key(secp, &secret_key);
+
+        UnlockParameters {
+            witness: vec![
+                Witness::Signature(secret_key),
+                Witness::PublicKey(public_key),
+                Witness::Data(secret.to_vec()),
+                Witness::Bool(true),
+                Witness::PrevScript,
+            ],
+            sequence: SEQUENCE_ALLOW_NTIMELOCK_NO_RBF,
+            locktime: 0,
+            prev_script: self.script,
+        }
+    }
+
+    pub fn unlock_after_timeout<C: Signing>(
+        self,
+        secp: &Secp256k1<C>,
+        secret_key: SecretKey,
+    ) -> UnlockParameters {
+        let public_key = PublicKey::from_secret_key(secp, &secret_key);
+
+        UnlockParameters {
+            witness: vec![
+                Witness::Signature(secret_key),
+                Witness::PublicKey(public_key),
+                Witness::Bool(false),
+                Witness::PrevScript,
+            ],
+            sequence: SEQUENCE_ALLOW_NTIMELOCK_NO_RBF,
+            locktime: self.expiry,
+            prev_script: self.script,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_protocols::{rfc003::Secret, HashFunction};
+    use ::bitcoin::hashes::Hash;
+
+    #[test]
+    fn script_checks_secret_with_hash160() {
+        let secret_hash =
+            Secret::from(*b"hello world, you are beautiful!!").hash_with(HashFunction::Hash160);
+        let refund_identity = hash160::Hash::hash(b"refund");
+        let redeem_identity = hash160::Hash::hash(b"redeem");
+
+        let htlc = Hash160Htlc::new(1_590_000_000, refund_identity, redeem_identity, secret_hash);
+
+        assert_eq!(
+            hex::encode(&htlc.script.as_bytes()[..24]),
+            "63a914ce4be827894106ecf7bedfe6c0343a81e1e7814a88"
+        );
+    }
+}
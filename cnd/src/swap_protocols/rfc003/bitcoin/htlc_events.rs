@@ -1,7 +1,8 @@
 use crate::{
     asset,
-    btsieve::bitcoin::{
-        watch_for_created_outpoint, watch_for_spent_outpoint, BitcoindConnector, Cache,
+    btsieve::{
+        bitcoin::{watch_for_created_outpoint, watch_for_spent_outpoint, BitcoindConnector, Cache},
+        StartOfSwap,
     },
     htlc_location, identity,
     swap_protocols::{
@@ -17,7 +18,6 @@ use crate::{
     },
     transaction,
 };
-use chrono::NaiveDateTime;
 use std::cmp::Ordering;
 use tracing_futures::Instrument;
 
@@ -32,7 +32,7 @@ where
         &self,
         htlc_params: &HtlcParams<B, asset::Bitcoin, identity::Bitcoin>,
         htlc_deployment: &Deployed<htlc_location::Bitcoin, transaction::Bitcoin>,
-        _start_of_swap: NaiveDateTime,
+        _start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Funded<asset::Bitcoin, transaction::Bitcoin>> {
         let expected_asset = htlc_params.asset;
 
@@ -65,10 +65,10 @@ where
     async fn htlc_deployed(
         &self,
         htlc_params: &HtlcParams<B, asset::Bitcoin, identity::Bitcoin>,
-        start_of_swap: NaiveDateTime,
+        start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Deployed<htlc_location::Bitcoin, transaction::Bitcoin>> {
         let (transaction, location) =
-            watch_for_created_outpoint(self, start_of_swap, htlc_params.compute_address())
+            watch_for_created_outpoint(self, start_of_swap, htlc_params.compute_address()?)
                 .instrument(tracing::info_span!("htlc_deployed"))
                 .await?;
 
@@ -90,7 +90,7 @@ where
         &self,
         htlc_params: &HtlcParams<B, asset::Bitcoin, identity::Bitcoin>,
         htlc_deployment: &Deployed<htlc_location::Bitcoin, transaction::Bitcoin>,
-        start_of_swap: NaiveDateTime,
+        start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Redeemed<transaction::Bitcoin>> {
         let (transaction, _) = watch_for_spent_outpoint(
             self,
@@ -101,8 +101,12 @@ where
         .instrument(tracing::info_span!("htlc_redeemed"))
         .await?;
 
-        let secret = extract_secret(&transaction, &htlc_params.secret_hash)
-            .expect("Redeem transaction must contain secret");
+        let secret = extract_secret(
+            &transaction,
+            htlc_params.hash_function,
+            &htlc_params.secret_hash,
+        )
+        .expect("Redeem transaction must contain secret");
 
         Ok(Redeemed {
             transaction,
@@ -122,7 +126,7 @@ where
         &self,
         htlc_params: &HtlcParams<B, asset::Bitcoin, identity::Bitcoin>,
         htlc_deployment: &Deployed<htlc_location::Bitcoin, transaction::Bitcoin>,
-        start_of_swap: NaiveDateTime,
+        start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Refunded<transaction::Bitcoin>> {
         let (transaction, _) = watch_for_spent_outpoint(
             self,
@@ -38,7 +38,8 @@ impl<AL, BL, AA, BA, AH, BH, AI, BI, AT, BT> State<AL, BL, AA, BA, AH, BH, AI, B
         match &self.swap_communication {
             SwapCommunication::Accepted { request, .. }
             | SwapCommunication::Proposed { request, .. }
-            | SwapCommunication::Declined { request, .. } => request,
+            | SwapCommunication::Declined { request, .. }
+            | SwapCommunication::TimedOut { request } => request,
         }
     }
 }
@@ -48,19 +48,19 @@ where
         ethereum::CallContract,
     >;
 
-    fn actions(&self) -> Vec<Self::ActionKind> {
+    fn actions(&self) -> anyhow::Result<Vec<Self::ActionKind>> {
         let (request, response) = match &self.swap_communication {
             SwapCommunication::Proposed { .. } => {
-                return vec![
+                return Ok(vec![
                     Action::Accept(Accept::new()),
                     Action::Decline(Decline::new()),
-                ];
+                ]);
             }
             SwapCommunication::Accepted {
                 ref request,
                 ref response,
             } => (request, response),
-            _ => return vec![],
+            _ => return Ok(vec![]),
         };
 
         let alpha_state = &self.alpha_ledger_state;
@@ -76,11 +76,11 @@ where
                     &*self.secret_source, // Derive identities with this.
                     *secret,              /* Bob uses the secret learned from Aliceredeem
                                            * action. */
-                ))]
+                )?)]
             }
             (Funded { .. }, NotDeployed) => vec![Action::Deploy(erc20::deploy_action(
                 HtlcParams::new_beta_params(request, response),
-            ))],
+            )?)],
             (Funded { .. }, Deployed { htlc_location, .. }) => {
                 vec![Action::Fund(erc20::fund_action(
                     HtlcParams::new_beta_params(request, response),
@@ -98,7 +98,7 @@ where
                 *htlc_location,
             )));
         }
-        actions
+        Ok(actions)
     }
 }
 
@@ -138,19 +138,19 @@ where
         <(BL, BA) as MakeRefundAction>::Output,
     >;
 
-    fn actions(&self) -> Vec<Self::ActionKind> {
+    fn actions(&self) -> anyhow::Result<Vec<Self::ActionKind>> {
         let (request, response) = match &self.swap_communication {
             SwapCommunication::Proposed { .. } => {
-                return vec![
+                return Ok(vec![
                     Action::Accept(Accept::new()),
                     Action::Decline(Decline::new()),
-                ];
+                ]);
             }
             SwapCommunication::Accepted {
                 ref request,
                 ref response,
             } => (request, response),
-            _ => return vec![],
+            _ => return Ok(vec![]),
         };
 
         let alpha_state = &self.alpha_ledger_state;
@@ -163,7 +163,7 @@ where
             )],
             (Funded { .. }, NotDeployed) => vec![Action::Fund(<(BL, BA)>::make_fund_action(
                 HtlcParams::new_beta_params(request, response),
-            ))],
+            )?)],
             _ => vec![],
         };
 
@@ -178,8 +178,8 @@ where
                 htlc_location.clone(),
                 &*self.secret_source,
                 fund_transaction,
-            )))
+            )?))
         }
-        actions
+        Ok(actions)
     }
 }
@@ -40,19 +40,19 @@ where
         <(BL, BA) as MakeRefundAction>::Output,
     >;
 
-    fn actions(&self) -> Vec<Self::ActionKind> {
+    fn actions(&self) -> anyhow::Result<Vec<Self::ActionKind>> {
         let (request, response) = match &self.swap_communication {
             SwapCommunication::Proposed { .. } => {
-                return vec![
+                return Ok(vec![
                     Action::Accept(Accept::new()),
                     Action::Decline(Decline::new()),
-                ];
+                ]);
             }
             SwapCommunication::Accepted {
                 ref request,
                 ref response,
             } => (request, response),
-            _ => return vec![],
+            _ => return Ok(vec![]),
         };
 
         let alpha_state = &self.alpha_ledger_state;
@@ -67,11 +67,11 @@ where
                     &*self.secret_source, // Derive identities with this.
                     *secret,              /* Bob uses the secret learned from Alice redeem
                                            * action. */
-                ))]
+                )?)]
             }
             (Funded { .. }, NotDeployed) => vec![Action::Fund(<(BL, BA)>::make_fund_action(
                 HtlcParams::new_beta_params(request, response),
-            ))],
+            )?)],
             _ => vec![],
         };
 
@@ -91,9 +91,9 @@ where
                 htlc_location.clone(),
                 &*self.secret_source,
                 fund_transaction,
-            )))
+            )?))
         }
 
-        actions
+        Ok(actions)
     }
 }
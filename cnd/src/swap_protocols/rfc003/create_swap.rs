@@ -1,4 +1,5 @@
 use crate::{
+    btsieve::StartOfSwap,
     swap_protocols::{
         rfc003::{
             self,
@@ -8,7 +9,7 @@ use crate::{
             },
             state, Accept, LedgerState, Request, SecretHash, SwapId,
         },
-        HashFunction, InsertFailedSwap,
+        HashFunction, InsertFailedSwap, Phase, Side, SwapError,
     },
     timestamp::Timestamp,
 };
@@ -18,6 +19,7 @@ use genawaiter::{
     sync::{Co, Gen},
     GeneratorState,
 };
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
 /// Returns a future that tracks the swap negotiated from the given request and
@@ -31,14 +33,22 @@ use std::sync::Arc;
 ///
 /// It is highly unlikely for Bob to fund the HTLC now, yet the current
 /// implementation is still waiting for that.
+///
+/// The events in `history` were observed by a previous watcher of this side,
+/// they are applied to the ledger state right away and only the remaining
+/// events are watched for, starting from the block of the last one that is
+/// known.
 pub async fn create_watcher<D, S, L, A, H, I, T>(
     dependencies: D,
     ledger_states: Arc<S>,
     id: SwapId,
+    side: Side,
     htlc_params: HtlcParams<L, A, I>,
     accepted_at: NaiveDateTime,
+    history: History<A, H, T>,
 ) where
     D: InsertFailedSwap
+        + LedgerEvents<A, H, T>
         + HtlcFunded<L, A, H, I, T>
         + HtlcDeployed<L, A, H, I, T>
         + HtlcRedeemed<L, A, H, I, T>
@@ -54,19 +64,36 @@ pub async fn create_watcher<D, S, L, A, H, I, T>(
         .insert(id, LedgerState::<A, H, T>::NotDeployed)
         .await;
 
+    let mut phase = Phase::Deploy;
+    for event in history.events.iter().cloned() {
+        tracing::info!("swap {} replayed event {}", id, event);
+        phase = event.next_phase();
+        ledger_states.update(&id, event).await;
+    }
+
+    if history.is_complete() {
+        tracing::info!("swap {} finished before", id);
+        return;
+    }
+
     // construct a generator that watches alpha and beta ledger concurrently
     let mut generator = Gen::new({
         |co| async {
-            watch_ledger::<D, L, A, H, I, T>(&dependencies, co, htlc_params, accepted_at).await
+            watch_ledger::<D, L, A, H, I, T>(&dependencies, co, htlc_params, accepted_at, &history)
+                .await
         }
     });
 
     loop {
         // wait for events to be emitted as the generator executes
         match generator.async_resume().await {
-            // every event that is yielded is passed on
+            // every event that is yielded is persisted and passed on
             GeneratorState::Yielded(event) => {
                 tracing::info!("swap {} yielded event {}", id, event);
+                if let Err(e) = dependencies.save_ledger_event(id, side, &event).await {
+                    tracing::error!("failed to persist event {} of swap {}: {:?}", event, id, e);
+                }
+                phase = event.next_phase();
                 ledger_states.update(&id, event).await;
             }
             // the generator stopped executing, this means there are no more events that can be
@@ -77,7 +104,9 @@ pub async fn create_watcher<D, S, L, A, H, I, T>(
             }
             GeneratorState::Complete(Err(e)) => {
                 tracing::error!("swap {} failed with {:?}", id, e);
-                dependencies.insert_failed_swap(&id).await;
+                dependencies
+                    .insert_failed_swap(id.into(), SwapError::new(side, phase, &e))
+                    .await;
                 return;
             }
         }
@@ -86,12 +115,14 @@ pub async fn create_watcher<D, S, L, A, H, I, T>(
 
 /// Returns a future that waits for events to happen on a ledger.
 ///
-/// Each event is yielded through the controller handle (co) of the coroutine.
+/// Each event is yielded through the controller handle (co) of the coroutine,
+/// events already in `history` are not waited for again.
 async fn watch_ledger<D, L, A, H, I, T>(
     dependencies: &D,
     co: Co<SwapEvent<A, H, T>>,
     htlc_params: HtlcParams<L, A, I>,
-    start_of_swap: NaiveDateTime,
+    accepted_at: NaiveDateTime,
+    history: &History<A, H, T>,
 ) -> anyhow::Result<()>
 where
     D: HtlcFunded<L, A, H, I, T>
@@ -102,15 +133,27 @@ where
     Redeemed<T>: Clone,
     Refunded<T>: Clone,
 {
-    let deployed = dependencies
-        .htlc_deployed(&htlc_params, start_of_swap)
-        .await?;
-    co.yield_(SwapEvent::Deployed(deployed.clone())).await;
+    let start_of_swap = history
+        .resume_from
+        .map_or(StartOfSwap::Time(accepted_at), StartOfSwap::BlockHeight);
 
-    let funded = dependencies
-        .htlc_funded(&htlc_params, &deployed, start_of_swap)
-        .await?;
-    co.yield_(SwapEvent::Funded(funded)).await;
+    let deployed = match history.deployed() {
+        Some(deployed) => deployed.clone(),
+        None => {
+            let deployed = dependencies
+                .htlc_deployed(&htlc_params, start_of_swap)
+                .await?;
+            co.yield_(SwapEvent::Deployed(deployed.clone())).await;
+            deployed
+        }
+    };
+
+    if !history.is_funded() {
+        let funded = dependencies
+            .htlc_funded(&htlc_params, &deployed, start_of_swap)
+            .await?;
+        co.yield_(SwapEvent::Funded(funded)).await;
+    }
 
     let redeemed = dependencies.htlc_redeemed(&htlc_params, &deployed, start_of_swap);
 
@@ -140,6 +183,7 @@ pub struct HtlcParams<L, A, I> {
     pub redeem_identity: I,
     pub refund_identity: I,
     pub expiry: Timestamp,
+    pub hash_function: HashFunction,
     pub secret_hash: SecretHash,
 }
 
@@ -159,6 +203,7 @@ where
             redeem_identity: accept_response.alpha_ledger_redeem_identity.clone(),
             refund_identity: request.alpha_ledger_refund_identity.clone(),
             expiry: request.alpha_expiry,
+            hash_function: request.hash_function,
             secret_hash: request.secret_hash,
         }
     }
@@ -173,6 +218,7 @@ where
             redeem_identity: request.beta_ledger_redeem_identity.clone(),
             refund_identity: accept_response.beta_ledger_refund_identity.clone(),
             expiry: request.beta_expiry,
+            hash_function: request.hash_function,
             secret_hash: request.secret_hash,
         }
     }
@@ -226,6 +272,7 @@ where
             redeem_identity: self.alpha_ledger_redeem_identity.clone(),
             refund_identity: self.alpha_ledger_refund_identity.clone(),
             expiry: self.alpha_expiry,
+            hash_function: self.hash_function,
             secret_hash: self.secret_hash,
         }
     }
@@ -244,12 +291,13 @@ where
             redeem_identity: self.beta_ledger_redeem_identity.clone(),
             refund_identity: self.beta_ledger_refund_identity.clone(),
             expiry: self.beta_expiry,
+            hash_function: self.hash_function,
             secret_hash: self.secret_hash,
         }
     }
 }
 
-#[derive(Debug, Clone, PartialEq, strum_macros::Display)]
+#[derive(Debug, Clone, PartialEq, strum_macros::Display, Serialize, Deserialize)]
 pub enum SwapEvent<A, H, T> {
     Deployed(Deployed<H, T>),
     Funded(Funded<A, T>),
@@ -257,10 +305,346 @@ pub enum SwapEvent<A, H, T> {
     Refunded(Refunded<T>),
 }
 
+impl<A, H, T> SwapEvent<A, H, T> {
+    /// The transaction that caused this event.
+    pub fn transaction(&self) -> &T {
+        match self {
+            SwapEvent::Deployed(Deployed { transaction, .. })
+            | SwapEvent::Funded(Funded::Correctly { transaction, .. })
+            | SwapEvent::Funded(Funded::Incorrectly { transaction, .. })
+            | SwapEvent::Redeemed(Redeemed { transaction, .. })
+            | SwapEvent::Refunded(Refunded { transaction }) => transaction,
+        }
+    }
+
+    /// The phase a watcher is in after this event has been observed.
+    pub fn next_phase(&self) -> Phase {
+        match self {
+            SwapEvent::Deployed(_) => Phase::Fund,
+            SwapEvent::Funded(_) | SwapEvent::Redeemed(_) | SwapEvent::Refunded(_) => {
+                Phase::RedeemOrRefund
+            }
+        }
+    }
+}
+
+/// The events a watcher of one side of a swap observed before, in the order
+/// they happened.
+#[derive(Debug, Clone, PartialEq)]
+pub struct History<A, H, T> {
+    pub events: Vec<SwapEvent<A, H, T>>,
+    /// Height of the block of the last event whose block is known, `None` if
+    /// there is none.
+    pub resume_from: Option<u32>,
+}
+
+impl<A, H, T> Default for History<A, H, T> {
+    fn default() -> Self {
+        History {
+            events: Vec::new(),
+            resume_from: None,
+        }
+    }
+}
+
+impl<A, H, T> History<A, H, T> {
+    fn deployed(&self) -> Option<&Deployed<H, T>> {
+        self.events.iter().find_map(|event| match event {
+            SwapEvent::Deployed(deployed) => Some(deployed),
+            _ => None,
+        })
+    }
+
+    fn is_funded(&self) -> bool {
+        self.events
+            .iter()
+            .any(|event| matches!(event, SwapEvent::Funded(_)))
+    }
+
+    fn is_complete(&self) -> bool {
+        self.events
+            .iter()
+            .any(|event| matches!(event, SwapEvent::Redeemed(_) | SwapEvent::Refunded(_)))
+    }
+}
+
+/// Persists the events observed by the watchers of a swap, so that they can
+/// pick up where they left off when cnd restarts.
+#[async_trait::async_trait]
+pub trait LedgerEvents<A, H, T>: Send + Sync + 'static {
+    async fn save_ledger_event(
+        &self,
+        id: SwapId,
+        side: Side,
+        event: &SwapEvent<A, H, T>,
+    ) -> anyhow::Result<()>;
+    async fn load_history(&self, id: SwapId, side: Side) -> anyhow::Result<History<A, H, T>>;
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{asset, htlc_location, transaction};
+    use crate::{
+        asset::{self, ethereum::FromWei},
+        ethereum::{Hash, U256},
+        htlc_location, identity,
+        swap_protocols::rfc003::{state::Get, LedgerStates, Secret},
+        transaction,
+    };
+    use std::str::FromStr;
+    use tokio::sync::Mutex;
+    use uuid::Uuid;
+
+    type Event = SwapEvent<asset::Ether, htlc_location::Ethereum, transaction::Ethereum>;
+
+    /// Redeems the HTLC right away and remembers what the watcher asked for.
+    #[derive(Clone, Default)]
+    struct MockLedger {
+        history: History<asset::Ether, htlc_location::Ethereum, transaction::Ethereum>,
+        calls: Arc<Mutex<Vec<(&'static str, StartOfSwap)>>>,
+        saved: Arc<Mutex<Vec<Event>>>,
+        failed: Arc<Mutex<Vec<SwapError>>>,
+    }
+
+    impl MockLedger {
+        async fn called(&self, name: &'static str, start_of_swap: StartOfSwap) {
+            self.calls.lock().await.push((name, start_of_swap));
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl InsertFailedSwap for MockLedger {
+        async fn insert_failed_swap(&self, _: Uuid, error: SwapError) {
+            self.failed.lock().await.push(error);
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl LedgerEvents<asset::Ether, htlc_location::Ethereum, transaction::Ethereum> for MockLedger {
+        async fn save_ledger_event(&self, _: SwapId, _: Side, event: &Event) -> anyhow::Result<()> {
+            self.saved.lock().await.push(event.clone());
+            Ok(())
+        }
+
+        async fn load_history(
+            &self,
+            _: SwapId,
+            _: Side,
+        ) -> anyhow::Result<History<asset::Ether, htlc_location::Ethereum, transaction::Ethereum>>
+        {
+            Ok(self.history.clone())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl
+        HtlcDeployed<
+            (),
+            asset::Ether,
+            htlc_location::Ethereum,
+            identity::Ethereum,
+            transaction::Ethereum,
+        > for MockLedger
+    {
+        async fn htlc_deployed(
+            &self,
+            _: &HtlcParams<(), asset::Ether, identity::Ethereum>,
+            start_of_swap: StartOfSwap,
+        ) -> anyhow::Result<Deployed<htlc_location::Ethereum, transaction::Ethereum>> {
+            self.called("deployed", start_of_swap).await;
+            Ok(deployed())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl
+        HtlcFunded<
+            (),
+            asset::Ether,
+            htlc_location::Ethereum,
+            identity::Ethereum,
+            transaction::Ethereum,
+        > for MockLedger
+    {
+        async fn htlc_funded(
+            &self,
+            _: &HtlcParams<(), asset::Ether, identity::Ethereum>,
+            _: &Deployed<htlc_location::Ethereum, transaction::Ethereum>,
+            start_of_swap: StartOfSwap,
+        ) -> anyhow::Result<Funded<asset::Ether, transaction::Ethereum>> {
+            self.called("funded", start_of_swap).await;
+            Ok(funded())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl
+        HtlcRedeemed<
+            (),
+            asset::Ether,
+            htlc_location::Ethereum,
+            identity::Ethereum,
+            transaction::Ethereum,
+        > for MockLedger
+    {
+        async fn htlc_redeemed(
+            &self,
+            _: &HtlcParams<(), asset::Ether, identity::Ethereum>,
+            _: &Deployed<htlc_location::Ethereum, transaction::Ethereum>,
+            start_of_swap: StartOfSwap,
+        ) -> anyhow::Result<Redeemed<transaction::Ethereum>> {
+            self.called("redeemed", start_of_swap).await;
+            Ok(redeemed())
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl
+        HtlcRefunded<
+            (),
+            asset::Ether,
+            htlc_location::Ethereum,
+            identity::Ethereum,
+            transaction::Ethereum,
+        > for MockLedger
+    {
+        async fn htlc_refunded(
+            &self,
+            _: &HtlcParams<(), asset::Ether, identity::Ethereum>,
+            _: &Deployed<htlc_location::Ethereum, transaction::Ethereum>,
+            _: StartOfSwap,
+        ) -> anyhow::Result<Refunded<transaction::Ethereum>> {
+            future::pending().await
+        }
+    }
+
+    fn ethereum_transaction(hash: u64) -> transaction::Ethereum {
+        transaction::Ethereum {
+            hash: Hash::from_low_u64_be(hash),
+            ..transaction::Ethereum::default()
+        }
+    }
+
+    fn deployed() -> Deployed<htlc_location::Ethereum, transaction::Ethereum> {
+        Deployed {
+            location: htlc_location::Ethereum::default(),
+            transaction: ethereum_transaction(1),
+        }
+    }
+
+    fn funded() -> Funded<asset::Ether, transaction::Ethereum> {
+        Funded::Correctly {
+            asset: asset::Ether::from_wei(U256::from(1_000)),
+            transaction: ethereum_transaction(1),
+        }
+    }
+
+    fn redeemed() -> Redeemed<transaction::Ethereum> {
+        Redeemed {
+            transaction: ethereum_transaction(2),
+            secret: Secret::from(*b"hello world, you are beautiful!!"),
+        }
+    }
+
+    fn htlc_params() -> HtlcParams<(), asset::Ether, identity::Ethereum> {
+        HtlcParams {
+            asset: asset::Ether::from_wei(U256::from(1_000)),
+            ledger: (),
+            redeem_identity: identity::Ethereum::default(),
+            refund_identity: identity::Ethereum::default(),
+            expiry: Timestamp::from(2_000_000_000),
+            hash_function: HashFunction::Sha256,
+            secret_hash: SecretHash::from_str(
+                "68d627971643a6f97f27c58957826fcba853ec2077fd10ec6b93d8e61deb4cec",
+            )
+            .unwrap(),
+        }
+    }
+
+    async fn watch(
+        ledger: &MockLedger,
+    ) -> LedgerState<asset::Ether, htlc_location::Ethereum, transaction::Ethereum> {
+        let ledger_states = Arc::new(LedgerStates::default());
+        let id = SwapId::default();
+        let history = ledger.load_history(id, Side::Alpha).await.unwrap();
+
+        create_watcher::<_, _, _, _, htlc_location::Ethereum, _, transaction::Ethereum>(
+            ledger.clone(),
+            ledger_states.clone(),
+            id,
+            Side::Alpha,
+            htlc_params(),
+            NaiveDateTime::from_timestamp(1_500_000_000, 0),
+            history,
+        )
+        .await;
+
+        ledger_states.get(&id).await.unwrap().unwrap()
+    }
+
+    #[tokio::test]
+    async fn watcher_resumes_from_the_block_of_the_last_persisted_event() {
+        let ledger = MockLedger {
+            history: History {
+                events: vec![SwapEvent::Deployed(deployed()), SwapEvent::Funded(funded())],
+                resume_from: Some(650_000),
+            },
+            ..MockLedger::default()
+        };
+
+        let state = watch(&ledger).await;
+
+        assert!(matches!(state, LedgerState::Redeemed { .. }));
+        assert_eq!(*ledger.calls.lock().await, vec![(
+            "redeemed",
+            StartOfSwap::BlockHeight(650_000)
+        )]);
+        assert_eq!(*ledger.saved.lock().await, vec![SwapEvent::Redeemed(
+            redeemed()
+        )]);
+        assert!(ledger.failed.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn watcher_of_finished_swap_only_replays_its_history() {
+        let ledger = MockLedger {
+            history: History {
+                events: vec![
+                    SwapEvent::Deployed(deployed()),
+                    SwapEvent::Funded(funded()),
+                    SwapEvent::Redeemed(redeemed()),
+                ],
+                resume_from: Some(650_000),
+            },
+            ..MockLedger::default()
+        };
+
+        let state = watch(&ledger).await;
+
+        assert!(matches!(state, LedgerState::Redeemed { .. }));
+        assert!(ledger.calls.lock().await.is_empty());
+        assert!(ledger.saved.lock().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn watcher_without_history_persists_every_event() {
+        let ledger = MockLedger::default();
+        let accepted_at = StartOfSwap::Time(NaiveDateTime::from_timestamp(1_500_000_000, 0));
+
+        let state = watch(&ledger).await;
+
+        assert!(matches!(state, LedgerState::Redeemed { .. }));
+        assert_eq!(*ledger.calls.lock().await, vec![
+            ("deployed", accepted_at),
+            ("funded", accepted_at),
+            ("redeemed", accepted_at),
+        ]);
+        assert_eq!(*ledger.saved.lock().await, vec![
+            SwapEvent::Deployed(deployed()),
+            SwapEvent::Funded(funded()),
+            SwapEvent::Redeemed(redeemed()),
+        ]);
+    }
 
     #[test]
     fn swap_event_should_render_to_nice_string() {
@@ -276,4 +660,14 @@ mod tests {
 
         assert_eq!(formatted, "Deployed")
     }
+
+    #[test]
+    fn swap_event_roundtrips_through_json() {
+        let event = Event::Redeemed(redeemed());
+
+        let json = serde_json::to_value(&event).unwrap();
+        let deserialized = serde_json::from_value::<Event>(json).unwrap();
+
+        assert_eq!(deserialized, event);
+    }
 }
@@ -1,37 +1,87 @@
+mod ether_htlc;
 pub mod htlc_events;
 
 use crate::{
     asset,
     ethereum::Bytes,
     identity,
-    swap_protocols::{ledger::Ethereum, rfc003::create_swap::HtlcParams},
+    swap_protocols::{
+        ledger::Ethereum,
+        rfc003::{create_swap::HtlcParams, FromErr},
+    },
 };
 use blockchain_contracts::ethereum::rfc003::{erc20_htlc::Erc20Htlc, ether_htlc::EtherHtlc};
+use std::convert::{TryFrom, TryInto};
 
-impl From<HtlcParams<Ethereum, asset::Ether, identity::Ethereum>> for EtherHtlc {
-    fn from(htlc_params: HtlcParams<Ethereum, asset::Ether, identity::Ethereum>) -> Self {
+pub use self::ether_htlc::AssembledEtherHtlc;
+
+impl TryFrom<HtlcParams<Ethereum, asset::Ether, identity::Ethereum>> for EtherHtlc {
+    type Error = FromErr;
+
+    fn try_from(
+        htlc_params: HtlcParams<Ethereum, asset::Ether, identity::Ethereum>,
+    ) -> Result<Self, Self::Error> {
         let refund_address =
             blockchain_contracts::ethereum::Address(htlc_params.refund_identity.into());
         let redeem_address =
             blockchain_contracts::ethereum::Address(htlc_params.redeem_identity.into());
 
-        EtherHtlc::new(
+        Ok(EtherHtlc::new(
             htlc_params.expiry.into(),
             refund_address,
             redeem_address,
-            htlc_params.secret_hash.into(),
-        )
+            htlc_params.secret_hash.into_raw()?,
+        ))
     }
 }
 
 impl HtlcParams<Ethereum, asset::Ether, identity::Ethereum> {
-    pub fn bytecode(&self) -> Bytes {
-        EtherHtlc::from(self.clone()).into()
+    /// Fails if the secret hash does not fit the hash function of the swap.
+    pub fn bytecode(&self) -> Result<Bytes, FromErr> {
+        match self.assembled_htlc() {
+            Some(htlc) => Ok(htlc.into()),
+            None => self.clone().try_into().map(EtherHtlc::into),
+        }
+    }
+
+    pub fn deploy_tx_gas_limit(&self) -> u64 {
+        match self.assembled_htlc() {
+            Some(_) => AssembledEtherHtlc::DEPLOY_TX_GAS_LIMIT,
+            None => EtherHtlc::deploy_tx_gas_limit(),
+        }
+    }
+
+    pub fn redeem_tx_gas_limit(&self) -> u64 {
+        match self.assembled_htlc() {
+            Some(_) => AssembledEtherHtlc::REDEEM_TX_GAS_LIMIT,
+            None => EtherHtlc::redeem_tx_gas_limit(),
+        }
+    }
+
+    pub fn refund_tx_gas_limit(&self) -> u64 {
+        match self.assembled_htlc() {
+            Some(_) => AssembledEtherHtlc::REFUND_TX_GAS_LIMIT,
+            None => EtherHtlc::refund_tx_gas_limit(),
+        }
+    }
+
+    fn assembled_htlc(&self) -> Option<AssembledEtherHtlc> {
+        AssembledEtherHtlc::new(
+            self.expiry,
+            self.refund_identity,
+            self.redeem_identity,
+            self.hash_function,
+            self.secret_hash,
+        )
     }
 }
 
-impl From<HtlcParams<Ethereum, asset::Erc20, identity::Ethereum>> for Erc20Htlc {
-    fn from(htlc_params: HtlcParams<Ethereum, asset::Erc20, identity::Ethereum>) -> Self {
+impl TryFrom<HtlcParams<Ethereum, asset::Erc20, identity::Ethereum>> for Erc20Htlc {
+    type Error = FromErr;
+
+    fn try_from(
+        htlc_params: HtlcParams<Ethereum, asset::Erc20, identity::Ethereum>,
+    ) -> Result<Self, Self::Error> {
         let refund_address =
             blockchain_contracts::ethereum::Address(htlc_params.refund_identity.into());
         let redeem_address =
@@ -39,19 +89,20 @@ impl From<HtlcParams<Ethereum, asset::Erc20, identity::Ethereum>> for Erc20Htlc
         let token_contract_address =
             blockchain_contracts::ethereum::Address(htlc_params.asset.token_contract.into());
 
-        Erc20Htlc::new(
+        Ok(Erc20Htlc::new(
             htlc_params.expiry.into(),
             refund_address,
             redeem_address,
-            htlc_params.secret_hash.into(),
+            htlc_params.secret_hash.into_raw()?,
             token_contract_address,
             htlc_params.asset.quantity.into(),
-        )
+        ))
     }
 }
 
 impl HtlcParams<Ethereum, asset::Erc20, identity::Ethereum> {
-    pub fn bytecode(&self) -> Bytes {
-        Erc20Htlc::from(self.clone()).into()
+    /// Fails if the secret hash is not 32 bytes long.
+    pub fn bytecode(&self) -> Result<Bytes, FromErr> {
+        Erc20Htlc::try_from(self.clone()).map(Erc20Htlc::into)
     }
 }
@@ -0,0 +1,285 @@
+use crate::{
+    ethereum::Hash,
+    identity,
+    swap_protocols::{rfc003::SecretHash, HashFunction},
+    timestamp::Timestamp,
+};
+use blockchain_contracts::ethereum::rfc003::{REDEEMED_LOG_MSG, REFUNDED_LOG_MSG};
+use std::convert::TryFrom;
+
+const JUMPDEST: u8 = 0x5b;
+const JUMPI: u8 = 0x57;
+const PUSH1: u8 = 0x60;
+const PUSH2: u8 = 0x61;
+const PUSH4: u8 = 0x63;
+const PUSH20: u8 = 0x73;
+const PUSH32: u8 = 0x7f;
+
+/// An rfc003 Ether HTLC for the hash functions `blockchain_contracts` has no
+/// contract for.
+///
+/// The contract behaves like the SHA-256 Ether HTLC: Calling it with the 32
+/// byte secret emits the `Redeemed` log with the secret as data and sends the
+/// Ether to the redeem identity. Calling it without data once the expiry has
+/// passed emits the `Refunded` log and sends the Ether to the refund identity.
+/// Every other call is reverted.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AssembledEtherHtlc {
+    expiry: Timestamp,
+    refund_identity: identity::Ethereum,
+    redeem_identity: identity::Ethereum,
+    hash_function: HashFunction,
+    secret_hash: SecretHash,
+}
+
+impl AssembledEtherHtlc {
+    pub const DEPLOY_TX_GAS_LIMIT: u64 = 150_000;
+    pub const REDEEM_TX_GAS_LIMIT: u64 = 100_000;
+    pub const REFUND_TX_GAS_LIMIT: u64 = 100_000;
+
+    /// Returns `None` for SHA-256, its contract is the one of
+    /// `blockchain_contracts`.
+    pub fn new(
+        expiry: Timestamp,
+        refund_identity: identity::Ethereum,
+        redeem_identity: identity::Ethereum,
+        hash_function: HashFunction,
+        secret_hash: SecretHash,
+    ) -> Option<Self> {
+        match hash_function {
+            HashFunction::Sha256 => None,
+            HashFunction::Hash160 | HashFunction::Keccak256 => Some(AssembledEtherHtlc {
+                expiry,
+                refund_identity,
+                redeem_identity,
+                hash_function,
+                secret_hash,
+            }),
+        }
+    }
+
+    fn runtime_code(&self) -> Vec<u8> {
+        // The sections have the same length whatever their jump destinations
+        // are, hence they are assembled once to find the destinations.
+        let refund_offset = Self::head(0).len() + self.redeem(0).len();
+        let fail_offset = refund_offset + self.refund(0).len();
+
+        let refund_offset = u16::try_from(refund_offset).expect("runtime code to be small");
+        let fail_offset = u16::try_from(fail_offset).expect("runtime code to be small");
+
+        let mut code = Self::head(refund_offset);
+        code.extend(self.redeem(fail_offset));
+        code.extend(self.refund(fail_offset));
+        // JUMPDEST PUSH1 0 DUP1 REVERT
+        code.extend(&[JUMPDEST, PUSH1, 0x00, 0x80, 0xfd]);
+
+        code
+    }
+
+    fn head(refund: u16) -> Vec<u8> {
+        // CALLDATASIZE ISZERO
+        let mut code = vec![0x36, 0x15];
+        jump_if(&mut code, refund);
+
+        code
+    }
+
+    fn redeem(&self, fail: u16) -> Vec<u8> {
+        // CALLDATASIZE PUSH1 32 EQ ISZERO
+        let mut code = vec![0x36, PUSH1, 0x20, 0x14, 0x15];
+        jump_if(&mut code, fail);
+        // PUSH1 0 CALLDATALOAD PUSH1 0 MSTORE
+        code.extend(&[PUSH1, 0x00, 0x35, PUSH1, 0x00, 0x52]);
+
+        match self.hash_function {
+            HashFunction::Keccak256 => {
+                // PUSH1 32 PUSH1 0 SHA3
+                code.extend(&[PUSH1, 0x20, PUSH1, 0x00, 0x20]);
+                code.push(PUSH32);
+            }
+            HashFunction::Hash160 => {
+                // The SHA-256 precompile writes to 32, the RIPEMD-160 precompile
+                // reads from there and writes the right aligned hash to 64.
+                static_call(&mut code, 0x02, 0x00, 0x20, fail);
+                static_call(&mut code, 0x03, 0x20, 0x40, fail);
+                // PUSH1 64 MLOAD
+                code.extend(&[PUSH1, 0x40, 0x51]);
+                code.push(PUSH20);
+            }
+            HashFunction::Sha256 => unreachable!("SHA-256 HTLCs are not assembled"),
+        }
+        code.extend(self.secret_hash.as_bytes());
+        // EQ ISZERO
+        code.extend(&[0x14, 0x15]);
+        jump_if(&mut code, fail);
+
+        // PUSH32 topic PUSH1 32 PUSH1 0 LOG1
+        code.push(PUSH32);
+        code.extend(log_topic(REDEEMED_LOG_MSG).as_bytes());
+        code.extend(&[PUSH1, 0x20, PUSH1, 0x00, 0xa1]);
+        self_destruct(&mut code, self.redeem_identity);
+
+        code
+    }
+
+    fn refund(&self, fail: u16) -> Vec<u8> {
+        // JUMPDEST PUSH4 expiry TIMESTAMP LT
+        let mut code = vec![JUMPDEST, PUSH4];
+        code.extend(&u32::from(self.expiry).to_be_bytes());
+        code.extend(&[0x42, 0x10]);
+        jump_if(&mut code, fail);
+
+        // PUSH32 topic PUSH1 0 DUP1 LOG1
+        code.push(PUSH32);
+        code.extend(log_topic(REFUNDED_LOG_MSG).as_bytes());
+        code.extend(&[PUSH1, 0x00, 0x80, 0xa1]);
+        self_destruct(&mut code, self.refund_identity);
+
+        code
+    }
+}
+
+impl From<AssembledEtherHtlc> for Vec<u8> {
+    fn from(htlc: AssembledEtherHtlc) -> Self {
+        let runtime_code = htlc.runtime_code();
+        let length = u16::try_from(runtime_code.len()).expect("runtime code to be small");
+
+        // PUSH2 length DUP1 PUSH1 12 PUSH1 0 CODECOPY PUSH1 0 RETURN
+        let mut code = vec![PUSH2];
+        code.extend(&length.to_be_bytes());
+        code.extend(&[0x80, PUSH1, 0x0c, PUSH1, 0x00, 0x39, PUSH1, 0x00, 0xf3]);
+        code.extend(runtime_code);
+
+        code
+    }
+}
+
+/// Appends a jump to `destination` that is taken if the top of the stack is
+/// not zero.
+fn jump_if(code: &mut Vec<u8>, destination: u16) {
+    code.push(PUSH2);
+    code.extend(&destination.to_be_bytes());
+    code.push(JUMPI);
+}
+
+/// Appends a call of the precompiled contract at `address` with the 32 bytes
+/// in memory at `input` that stores its 32 bytes of output at `output`.
+fn static_call(code: &mut Vec<u8>, address: u8, input: u8, output: u8, fail: u16) {
+    // PUSH1 32 PUSH1 output PUSH1 32 PUSH1 input PUSH1 address GAS STATICCALL
+    // ISZERO
+    code.extend(&[
+        PUSH1, 0x20, PUSH1, output, PUSH1, 0x20, PUSH1, input, PUSH1, address, 0x5a, 0xfa, 0x15,
+    ]);
+    jump_if(code, fail);
+}
+
+/// Appends `PUSH20 beneficiary SELFDESTRUCT`.
+fn self_destruct(code: &mut Vec<u8>, beneficiary: identity::Ethereum) {
+    let beneficiary: [u8; 20] = beneficiary.into();
+
+    code.push(PUSH20);
+    code.extend(&beneficiary);
+    code.push(0xff);
+}
+
+fn log_topic(log_msg: &str) -> Hash {
+    log_msg
+        .trim_start_matches("0x")
+        .parse()
+        .expect("log message to be valid hex")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::swap_protocols::rfc003::Secret;
+
+    fn htlc(hash_function: HashFunction) -> AssembledEtherHtlc {
+        let secret = Secret::from(*b"hello world, you are beautiful!!");
+
+        AssembledEtherHtlc::new(
+            Timestamp::from(2_000_000_000),
+            identity::Ethereum::from([1u8; 20]),
+            identity::Ethereum::from([2u8; 20]),
+            hash_function,
+            secret.hash_with(hash_function),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn sha256_is_not_assembled() {
+        let secret = Secret::from(*b"hello world, you are beautiful!!");
+
+        let htlc = AssembledEtherHtlc::new(
+            Timestamp::from(2_000_000_000),
+            identity::Ethereum::from([1u8; 20]),
+            identity::Ethereum::from([2u8; 20]),
+            HashFunction::Sha256,
+            secret.hash(),
+        );
+
+        assert!(htlc.is_none());
+    }
+
+    #[test]
+    fn jumps_land_on_jump_destinations() {
+        for hash_function in &[HashFunction::Keccak256, HashFunction::Hash160] {
+            let code = htlc(*hash_function).runtime_code();
+
+            let mut destinations = Vec::new();
+            let mut position = 0;
+            while position < code.len() {
+                let opcode = code[position];
+                if opcode == PUSH2 && code[position + 3] == JUMPI {
+                    destinations.push(usize::from(u16::from_be_bytes([
+                        code[position + 1],
+                        code[position + 2],
+                    ])));
+                }
+                // Skip the data of PUSH1 to PUSH32.
+                if (PUSH1..=PUSH32).contains(&opcode) {
+                    position += usize::from(opcode - PUSH1 + 1);
+                }
+                position += 1;
+            }
+
+            assert_eq!(destinations.len(), match hash_function {
+                HashFunction::Hash160 => 6,
+                _ => 4,
+            });
+            for destination in destinations {
+                assert_eq!(code[destination], JUMPDEST);
+            }
+        }
+    }
+
+    #[test]
+    fn deployment_code_returns_runtime_code() {
+        let htlc = htlc(HashFunction::Keccak256);
+        let runtime_code = htlc.runtime_code();
+
+        let code = Vec::<u8>::from(htlc);
+
+        assert_eq!(&code[..3], &[
+            PUSH2,
+            0x00,
+            u8::try_from(runtime_code.len()).unwrap()
+        ]);
+        assert_eq!(&code[12..], runtime_code.as_slice());
+    }
+
+    #[test]
+    fn keccak256_htlc_compares_with_keccak256_hash() {
+        let code = htlc(HashFunction::Keccak256).runtime_code();
+
+        let secret_hash =
+            hex::decode("e96cb429b7adbbfd242cf8faa243ba5c5ede8ec527d23bc4464efd1e230e3a4f")
+                .unwrap();
+
+        let expected = [&[PUSH1, 0x20, PUSH1, 0x00, 0x20, PUSH32][..], &secret_hash].concat();
+        assert!(code
+            .windows(expected.len())
+            .any(|window| window == expected.as_slice()));
+    }
+}
@@ -1,8 +1,11 @@
 use crate::{
     asset,
     asset::{ethereum::FromWei, Erc20, Erc20Quantity, Ether},
-    btsieve::ethereum::{
-        watch_for_contract_creation, watch_for_event, Cache, Event, Topic, Web3Connector,
+    btsieve::{
+        ethereum::{
+            watch_for_contract_creation, watch_for_event, Cache, Event, Topic, Web3Connector,
+        },
+        StartOfSwap,
     },
     ethereum::{Hash, U256},
     htlc_location, identity,
@@ -19,7 +22,6 @@ use crate::{
     },
     transaction,
 };
-use chrono::NaiveDateTime;
 use std::cmp::Ordering;
 use tracing_futures::Instrument;
 
@@ -44,7 +46,7 @@ impl
         &self,
         htlc_params: &HtlcParams<Ethereum, asset::Ether, identity::Ethereum>,
         deploy_transaction: &Deployed<htlc_location::Ethereum, transaction::Ethereum>,
-        _start_of_swap: NaiveDateTime,
+        _start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Funded<asset::Ether, transaction::Ethereum>> {
         let expected_asset = &htlc_params.asset;
 
@@ -78,9 +80,9 @@ impl
     async fn htlc_deployed(
         &self,
         htlc_params: &HtlcParams<Ethereum, asset::Ether, identity::Ethereum>,
-        start_of_swap: NaiveDateTime,
+        start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Deployed<htlc_location::Ethereum, transaction::Ethereum>> {
-        let expected_bytecode = htlc_params.bytecode();
+        let expected_bytecode = htlc_params.bytecode()?;
 
         let (transaction, location) =
             watch_for_contract_creation(self, start_of_swap, &expected_bytecode)
@@ -111,7 +113,7 @@ impl
         &self,
         _htlc_params: &HtlcParams<Ethereum, asset::Ether, identity::Ethereum>,
         htlc_deployment: &Deployed<htlc_location::Ethereum, transaction::Ethereum>,
-        start_of_swap: NaiveDateTime,
+        start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Redeemed<transaction::Ethereum>> {
         let event = Event {
             address: htlc_deployment.location,
@@ -151,7 +153,7 @@ impl
         &self,
         _htlc_params: &HtlcParams<Ethereum, asset::Ether, identity::Ethereum>,
         htlc_deployment: &Deployed<htlc_location::Ethereum, transaction::Ethereum>,
-        start_of_swap: NaiveDateTime,
+        start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Refunded<transaction::Ethereum>> {
         let event = Event {
             address: htlc_deployment.location,
@@ -184,7 +186,7 @@ impl
         &self,
         htlc_params: &HtlcParams<Ethereum, asset::Erc20, identity::Ethereum>,
         htlc_deployment: &Deployed<htlc_location::Ethereum, transaction::Ethereum>,
-        start_of_swap: NaiveDateTime,
+        start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Funded<asset::Erc20, transaction::Ethereum>> {
         let event = Event {
             address: htlc_params.asset.token_contract,
@@ -226,9 +228,9 @@ impl
     async fn htlc_deployed(
         &self,
         htlc_params: &HtlcParams<Ethereum, asset::Erc20, identity::Ethereum>,
-        start_of_swap: NaiveDateTime,
+        start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Deployed<htlc_location::Ethereum, transaction::Ethereum>> {
-        let expected_bytecode = htlc_params.clone().bytecode();
+        let expected_bytecode = htlc_params.bytecode()?;
 
         let (transaction, location) =
             watch_for_contract_creation(self, start_of_swap, &expected_bytecode)
@@ -259,7 +261,7 @@ impl
         &self,
         _htlc_params: &HtlcParams<Ethereum, Erc20, identity::Ethereum>,
         htlc_deployment: &Deployed<htlc_location::Ethereum, transaction::Ethereum>,
-        start_of_swap: NaiveDateTime,
+        start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Redeemed<transaction::Ethereum>> {
         let event = Event {
             address: htlc_deployment.location,
@@ -295,7 +297,7 @@ impl
         &self,
         _htlc_params: &HtlcParams<Ethereum, Erc20, identity::Ethereum>,
         htlc_deployment: &Deployed<htlc_location::Ethereum, transaction::Ethereum>,
-        start_of_swap: NaiveDateTime,
+        start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Refunded<transaction::Ethereum>> {
         let event = Event {
             address: htlc_deployment.location,
@@ -1,5 +1,7 @@
-use crate::swap_protocols::rfc003::{create_swap::HtlcParams, Secret};
-use chrono::NaiveDateTime;
+use crate::{
+    btsieve::StartOfSwap,
+    swap_protocols::rfc003::{create_swap::HtlcParams, Secret},
+};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -31,7 +33,7 @@ pub trait HtlcFunded<L, A, H, I, T>: Send + Sync + Sized + 'static {
         &self,
         htlc_params: &HtlcParams<L, A, I>,
         htlc_deployment: &Deployed<H, T>,
-        start_of_swap: NaiveDateTime,
+        start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Funded<A, T>>;
 }
 
@@ -40,7 +42,7 @@ pub trait HtlcDeployed<L, A, H, I, T>: Send + Sync + Sized + 'static {
     async fn htlc_deployed(
         &self,
         htlc_params: &HtlcParams<L, A, I>,
-        start_of_swap: NaiveDateTime,
+        start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Deployed<H, T>>;
 }
 
@@ -50,7 +52,7 @@ pub trait HtlcRedeemed<L, A, H, I, T>: Send + Sync + Sized + 'static {
         &self,
         htlc_params: &HtlcParams<L, A, I>,
         htlc_deployment: &Deployed<H, T>,
-        start_of_swap: NaiveDateTime,
+        start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Redeemed<T>>;
 }
 
@@ -60,6 +62,6 @@ pub trait HtlcRefunded<L, A, H, I, T>: Send + Sync + Sized + 'static {
         &self,
         htlc_params: &HtlcParams<L, A, I>,
         htlc_deployment: &Deployed<H, T>,
-        start_of_swap: NaiveDateTime,
+        start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Refunded<T>>;
 }
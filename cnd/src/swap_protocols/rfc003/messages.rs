@@ -83,6 +83,7 @@ pub enum SwapDeclineReason {
     UnsatisfactoryRate,
     UnsupportedProtocol,
     UnsupportedSwap,
+    UnsupportedHashFunction,
     MissingMandatoryHeader,
     BadJsonField,
 }
@@ -133,6 +134,18 @@ mod tests {
         assert_eq!(response, expected_response);
     }
 
+    #[test]
+    fn serialize_decline_body_unsupported_hash_function() {
+        let decline_response_body = DeclineResponseBody {
+            reason: Some(SwapDeclineReason::UnsupportedHashFunction),
+        };
+
+        let response = serde_json::to_string(&decline_response_body).unwrap();
+        let expected_response = r#"{"reason":"unsupported-hash-function"}"#;
+
+        assert_eq!(response, expected_response);
+    }
+
     #[test]
     fn serialize_decline_body_missing_mandatory_header() {
         let decline_response_body = DeclineResponseBody {
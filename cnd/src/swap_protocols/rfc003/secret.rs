@@ -1,9 +1,13 @@
+use crate::swap_protocols::HashFunction;
+use bitcoin::hashes::{hash160, Hash};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use sha2::{Digest, Sha256};
 use std::{
+    convert::{TryFrom, TryInto},
     fmt::{self, Debug},
     str::FromStr,
 };
+use tiny_keccak::{Hasher, Keccak};
 
 #[derive(PartialEq, Clone, Copy, Debug, thiserror::Error)]
 pub enum FromErr {
@@ -13,18 +17,56 @@ pub enum FromErr {
     FromHex(#[from] hex::FromHexError),
 }
 
+/// The hash of a secret, 20 bytes for HASH160 and 32 bytes for all other
+/// hash functions.
 #[derive(Clone, Copy, PartialOrd, Ord, PartialEq, Eq, Hash)]
-pub struct SecretHash([u8; Self::LENGTH]);
+pub struct SecretHash {
+    bytes: [u8; Self::MAX_LENGTH],
+    length: usize,
+}
 
 impl SecretHash {
-    pub const LENGTH: usize = 32;
+    pub const MAX_LENGTH: usize = 32;
 
-    pub fn as_raw(&self) -> &[u8; Self::LENGTH] {
-        &self.0
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.length]
     }
 
-    pub fn into_raw(self) -> [u8; Self::LENGTH] {
-        self.0
+    /// Returns the hash as an array of 32 bytes, fails for 20 byte HASH160
+    /// hashes.
+    pub fn into_raw(self) -> Result<[u8; 32], FromErr> {
+        self.try_into()
+    }
+
+    fn from_slice(slice: &[u8]) -> Result<Self, FromErr> {
+        if slice.len() != 20 && slice.len() != Self::MAX_LENGTH {
+            return Err(FromErr::InvalidLength {
+                expected: Self::MAX_LENGTH,
+                got: slice.len(),
+            });
+        }
+        let mut bytes = [0; Self::MAX_LENGTH];
+        bytes[..slice.len()].copy_from_slice(slice);
+
+        Ok(SecretHash {
+            bytes,
+            length: slice.len(),
+        })
+    }
+}
+
+impl TryFrom<SecretHash> for [u8; 32] {
+    type Error = FromErr;
+
+    fn try_from(secret_hash: SecretHash) -> Result<Self, Self::Error> {
+        if secret_hash.length != 32 {
+            return Err(FromErr::InvalidLength {
+                expected: 32,
+                got: secret_hash.length,
+            });
+        }
+
+        Ok(secret_hash.bytes)
     }
 }
 
@@ -48,7 +90,7 @@ impl fmt::Display for SecretHash {
 
 impl fmt::LowerHex for SecretHash {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
-        f.write_str(hex::encode(&self.0).as_str())
+        f.write_str(hex::encode(self.as_bytes()).as_str())
     }
 }
 
@@ -72,7 +114,7 @@ impl<'de> Deserialize<'de> for SecretHash {
             type Value = SecretHash;
 
             fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> Result<(), fmt::Error> {
-                formatter.write_str("a hex encoded 32 byte value")
+                formatter.write_str("a hex encoded 20 or 32 byte value")
             }
 
             fn visit_str<E>(self, v: &str) -> Result<SecretHash, E>
@@ -94,22 +136,22 @@ impl FromStr for SecretHash {
 
     fn from_str(s: &str) -> Result<Self, <Self as FromStr>::Err> {
         let vec = hex::decode(s)?;
-        if vec.len() != Self::LENGTH {
-            return Err(FromErr::InvalidLength {
-                expected: Self::LENGTH,
-                got: vec.len(),
-            });
+        Self::from_slice(&vec)
+    }
+}
+
+impl From<[u8; 32]> for SecretHash {
+    fn from(hash: [u8; 32]) -> Self {
+        SecretHash {
+            bytes: hash,
+            length: 32,
         }
-        let mut data = [0; Self::LENGTH];
-        let vec = &vec[..Self::LENGTH];
-        data.copy_from_slice(vec);
-        Ok(SecretHash(data))
     }
 }
 
-impl From<[u8; Self::LENGTH]> for SecretHash {
-    fn from(hash: [u8; Self::LENGTH]) -> Self {
-        SecretHash(hash)
+impl From<[u8; 20]> for SecretHash {
+    fn from(hash: [u8; 20]) -> Self {
+        Self::from_slice(&hash).expect("20 bytes is a valid length")
     }
 }
 
@@ -146,12 +188,28 @@ impl Secret {
         Ok(Secret(data))
     }
 
+    /// Hashes the secret with SHA-256, the hash function of lightning
+    /// payments.
     pub fn hash(&self) -> SecretHash {
-        let mut sha = Sha256::new();
-        sha.input(&self.0);
-        let hash: [u8; SecretHash::LENGTH] = sha.result().into();
+        self.hash_with(HashFunction::Sha256)
+    }
 
-        SecretHash::from(hash)
+    pub fn hash_with(&self, hash_function: HashFunction) -> SecretHash {
+        match hash_function {
+            HashFunction::Sha256 => {
+                let hash: [u8; 32] = Sha256::digest(&self.0).into();
+                SecretHash::from(hash)
+            }
+            HashFunction::Hash160 => SecretHash::from(hash160::Hash::hash(&self.0).into_inner()),
+            HashFunction::Keccak256 => {
+                let mut hash = [0u8; 32];
+                let mut keccak = Keccak::v256();
+                keccak.update(&self.0);
+                keccak.finalize(&mut hash);
+
+                SecretHash::from(hash)
+            }
+        }
     }
 
     pub fn as_raw_secret(&self) -> &[u8; Self::LENGTH] {
@@ -178,12 +236,6 @@ impl FromStr for Secret {
     }
 }
 
-impl From<SecretHash> for [u8; 32] {
-    fn from(secret_hash: SecretHash) -> [u8; 32] {
-        secret_hash.0
-    }
-}
-
 impl<'de> Deserialize<'de> for Secret {
     fn deserialize<D>(deserializer: D) -> Result<Self, <D as Deserializer<'de>>::Error>
     where
@@ -235,6 +287,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn secret_hashed_with_hash160_is_20_bytes() {
+        let bytes = b"hello world, you are beautiful!!";
+        let secret = Secret::from(*bytes);
+
+        let hash = secret.hash_with(HashFunction::Hash160);
+
+        assert_eq!(hash.as_bytes().len(), 20);
+        assert_eq!(hash.to_string(), "ce4be827894106ecf7bedfe6c0343a81e1e7814a");
+    }
+
+    #[test]
+    fn secret_hashed_with_keccak256_as_hex() {
+        let bytes = b"hello world, you are beautiful!!";
+        let secret = Secret::from(*bytes);
+
+        assert_eq!(
+            secret.hash_with(HashFunction::Keccak256).to_string(),
+            "e96cb429b7adbbfd242cf8faa243ba5c5ede8ec527d23bc4464efd1e230e3a4f"
+        );
+    }
+
+    #[test]
+    fn secret_hash_from_str_accepts_20_and_32_bytes() {
+        let hash160 = SecretHash::from_str("ce4be827894106ecf7bedfe6c0343a81e1e7814a").unwrap();
+        let sha256 = SecretHash::from_str(
+            "68d627971643a6f97f27c58957826fcba853ec2077fd10ec6b93d8e61deb4cec",
+        )
+        .unwrap();
+
+        assert!(<[u8; 32]>::try_from(hash160).is_err());
+        assert!(<[u8; 32]>::try_from(sha256).is_ok());
+        assert_eq!(
+            SecretHash::from_str("ce4be827894106ecf7bedfe6c0343a81e1e781").unwrap_err(),
+            FromErr::InvalidLength {
+                expected: 32,
+                got: 19
+            }
+        );
+    }
+
     #[test]
     fn secret_hash_should_be_displayed_as_hex() {
         let bytes = b"hello world, you are beautiful!!";
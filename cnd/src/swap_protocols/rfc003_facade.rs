@@ -1,11 +1,18 @@
 use crate::{
     asset,
+    bitcoin::fee_estimation::FeeEstimator,
     btsieve::{
         self,
         bitcoin::BitcoindConnector,
         ethereum::{self, Web3Connector},
+        BlockOfTransaction, LatestBlock, StartOfSwap,
     },
-    db::{AcceptedSwap, DetermineTypes, LoadAcceptedSwap, Retrieve, Save, Sqlite, Swap, SwapTypes},
+    config::{ConfirmationTargets, Expiries},
+    db::{
+        AcceptedSwap, Database, DetermineTypes, LedgerEvent, LedgerEventStore, LoadAcceptedSwap,
+        Retrieve, Save, Swap, SwapTypes,
+    },
+    ethereum::gas_estimation::GasEstimator,
     htlc_location, identity,
     network::{
         ComitPeers, DialInformation, ListenAddresses, LocalPeerId, PendingRequestFor, RequestError,
@@ -13,28 +20,30 @@ use crate::{
     },
     seed::{Rfc003DeriveSwapSeed, RootSeed, SwapSeed},
     swap_protocols::{
+        expiries::LatestBlockTime,
         ledger::{bitcoin, Ethereum},
         rfc003::{
             self,
-            create_swap::HtlcParams,
+            create_swap::{History, HtlcParams, LedgerEvents, SwapEvent},
             events::{
                 Deployed, Funded, HtlcDeployed, HtlcFunded, HtlcRedeemed, HtlcRefunded, Redeemed,
                 Refunded,
             },
             state, LedgerStates, SwapCommunication, SwapCommunicationStates, SwapId,
         },
-        InsertFailedSwap, SwapErrorStates,
+        InsertFailedSwap, Side, SwapError, SwapErrors,
     },
+    timestamp::Timestamp,
     transaction,
 };
 use async_trait::async_trait;
-use chrono::NaiveDateTime;
 use futures::channel::oneshot::Sender;
 use impl_template::impl_template;
 use libp2p::{Multiaddr, PeerId};
 use libp2p_comit::frame::OutboundRequest;
-use serde::de::DeserializeOwned;
+use serde::{de::DeserializeOwned, Serialize};
 use std::{convert::TryInto, fmt::Debug, sync::Arc};
+use uuid::Uuid;
 
 /// This is a facade that implements all the required traits and forwards them
 /// to another implementation. This allows us to keep the number of arguments to
@@ -49,14 +58,18 @@ use std::{convert::TryInto, fmt::Debug, sync::Arc};
 #[delegate(DetermineTypes, target = "db")]
 pub struct Rfc003Facade {
     pub bitcoin_connector: Arc<btsieve::bitcoin::Cache<BitcoindConnector>>,
+    pub bitcoin_fee_estimator: Arc<FeeEstimator>,
     pub ethereum_connector: Arc<ethereum::Cache<Web3Connector>>,
+    pub ethereum_gas_estimator: Arc<GasEstimator>,
+    pub expiries: Expiries,
+    pub confirmation_targets: ConfirmationTargets,
     pub alpha_ledger_states: Arc<LedgerStates>,
     pub beta_ledger_states: Arc<LedgerStates>,
     pub swap_communication_states: Arc<SwapCommunicationStates>,
-    pub swap_error_states: Arc<SwapErrorStates>,
+    pub swap_errors: SwapErrors,
     pub seed: RootSeed,
     pub swarm: Swarm,
-    pub db: Sqlite,
+    pub db: Database,
 }
 
 #[async_trait]
@@ -86,8 +99,60 @@ where
 
 #[async_trait]
 impl InsertFailedSwap for Rfc003Facade {
-    async fn insert_failed_swap(&self, id: &SwapId) {
-        self.swap_error_states.insert_failed_swap(&id).await
+    async fn insert_failed_swap(&self, id: Uuid, error: SwapError) {
+        self.swap_errors.insert_failed_swap(id, error).await
+    }
+}
+
+impl Rfc003Facade {
+    async fn save_ledger_event_of<C, A, H, T>(
+        &self,
+        connector: &C,
+        id: SwapId,
+        side: Side,
+        event: &SwapEvent<A, H, T>,
+        transaction_id: String,
+    ) -> anyhow::Result<()>
+    where
+        C: BlockOfTransaction<T>,
+        SwapEvent<A, H, T>: Serialize,
+    {
+        // Not knowing the block only means we resume watching from further back.
+        let block = connector
+            .block_of_transaction(event.transaction())
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("failed to find block of {}: {:?}", transaction_id, e);
+                None
+            });
+        let ledger_event = LedgerEvent::new(id, side, event, transaction_id, block)?;
+
+        self.db.save(ledger_event).await
+    }
+
+    async fn load_history_of<A, H, T>(
+        &self,
+        id: SwapId,
+        side: Side,
+    ) -> anyhow::Result<History<A, H, T>>
+    where
+        SwapEvent<A, H, T>: DeserializeOwned,
+    {
+        let mut history = History::default();
+
+        for ledger_event in self.db.ledger_events(id).await? {
+            if ledger_event.side != side {
+                continue;
+            }
+            if let Some(block) = ledger_event.block {
+                history.resume_from = Some(block.block_height);
+            }
+            history
+                .events
+                .push(serde_json::from_value(ledger_event.data)?);
+        }
+
+        Ok(history)
     }
 }
 
@@ -110,7 +175,7 @@ impl SendRequest for Rfc003Facade {
 #[async_trait]
 impl<AL, BL, AA, BA, AI, BI> LoadAcceptedSwap<AL, BL, AA, BA, AI, BI> for Rfc003Facade
 where
-    Sqlite: LoadAcceptedSwap<AL, BL, AA, BA, AI, BI>,
+    Database: LoadAcceptedSwap<AL, BL, AA, BA, AI, BI>,
     AcceptedSwap<AL, BL, AA, BA, AI, BI>: Send + 'static,
 {
     async fn load_accepted_swap(
@@ -125,13 +190,32 @@ where
 impl<T> Save<T> for Rfc003Facade
 where
     T: Send + 'static,
-    Sqlite: Save<T>,
+    Database: Save<T>,
 {
     async fn save(&self, data: T) -> anyhow::Result<()> {
         self.db.save(data).await
     }
 }
 
+#[impl_template]
+#[async_trait::async_trait]
+impl LatestBlockTime<((bitcoin::Mainnet, bitcoin::Testnet, bitcoin::Regtest))> for Rfc003Facade {
+    async fn latest_block_time(&self) -> anyhow::Result<Timestamp> {
+        let block = self.bitcoin_connector.latest_block().await?;
+
+        Ok(Timestamp::from(block.header.time))
+    }
+}
+
+#[async_trait::async_trait]
+impl LatestBlockTime<Ethereum> for Rfc003Facade {
+    async fn latest_block_time(&self) -> anyhow::Result<Timestamp> {
+        let block = self.ethereum_connector.latest_block().await?;
+
+        Ok(Timestamp::from(block.timestamp.low_u32()))
+    }
+}
+
 #[impl_template]
 #[async_trait::async_trait]
 impl
@@ -147,7 +231,7 @@ impl
         &self,
         htlc_params: &HtlcParams<__TYPE0__, asset::Bitcoin, identity::Bitcoin>,
         htlc_deployment: &Deployed<htlc_location::Bitcoin, transaction::Bitcoin>,
-        start_of_swap: NaiveDateTime,
+        start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Funded<asset::Bitcoin, transaction::Bitcoin>> {
         self.bitcoin_connector
             .htlc_funded(htlc_params, htlc_deployment, start_of_swap)
@@ -169,7 +253,7 @@ impl
     async fn htlc_deployed(
         &self,
         htlc_params: &HtlcParams<__TYPE0__, asset::Bitcoin, identity::Bitcoin>,
-        start_of_swap: NaiveDateTime,
+        start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Deployed<htlc_location::Bitcoin, transaction::Bitcoin>> {
         self.bitcoin_connector
             .htlc_deployed(htlc_params, start_of_swap)
@@ -192,7 +276,7 @@ impl
         &self,
         htlc_params: &HtlcParams<__TYPE0__, asset::Bitcoin, identity::Bitcoin>,
         htlc_deployment: &Deployed<htlc_location::Bitcoin, transaction::Bitcoin>,
-        start_of_swap: NaiveDateTime,
+        start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Redeemed<transaction::Bitcoin>> {
         self.bitcoin_connector
             .htlc_redeemed(htlc_params, htlc_deployment, start_of_swap)
@@ -215,7 +299,7 @@ impl
         &self,
         htlc_params: &HtlcParams<__TYPE0__, asset::Bitcoin, identity::Bitcoin>,
         htlc_deployment: &Deployed<htlc_location::Bitcoin, transaction::Bitcoin>,
-        start_of_swap: NaiveDateTime,
+        start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Refunded<transaction::Bitcoin>> {
         self.bitcoin_connector
             .htlc_refunded(htlc_params, htlc_deployment, start_of_swap)
@@ -238,7 +322,7 @@ impl
         &self,
         htlc_params: &HtlcParams<Ethereum, __TYPE0__, identity::Ethereum>,
         htlc_deployment: &Deployed<htlc_location::Ethereum, transaction::Ethereum>,
-        start_of_swap: NaiveDateTime,
+        start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Funded<__TYPE0__, transaction::Ethereum>> {
         self.ethereum_connector
             .htlc_funded(htlc_params, htlc_deployment, start_of_swap)
@@ -260,7 +344,7 @@ impl
     async fn htlc_deployed(
         &self,
         htlc_params: &HtlcParams<Ethereum, __TYPE0__, identity::Ethereum>,
-        start_of_swap: NaiveDateTime,
+        start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Deployed<htlc_location::Ethereum, transaction::Ethereum>> {
         self.ethereum_connector
             .htlc_deployed(htlc_params, start_of_swap)
@@ -283,7 +367,7 @@ impl
         &self,
         htlc_params: &HtlcParams<Ethereum, __TYPE0__, identity::Ethereum>,
         htlc_deployment: &Deployed<htlc_location::Ethereum, transaction::Ethereum>,
-        start_of_swap: NaiveDateTime,
+        start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Redeemed<transaction::Ethereum>> {
         self.ethereum_connector
             .htlc_redeemed(htlc_params, htlc_deployment, start_of_swap)
@@ -306,10 +390,59 @@ impl
         &self,
         htlc_params: &HtlcParams<Ethereum, __TYPE0__, identity::Ethereum>,
         htlc_deployment: &Deployed<htlc_location::Ethereum, transaction::Ethereum>,
-        start_of_swap: NaiveDateTime,
+        start_of_swap: StartOfSwap,
     ) -> anyhow::Result<Refunded<transaction::Ethereum>> {
         self.ethereum_connector
             .htlc_refunded(htlc_params, htlc_deployment, start_of_swap)
             .await
     }
 }
+
+#[async_trait::async_trait]
+impl LedgerEvents<asset::Bitcoin, htlc_location::Bitcoin, transaction::Bitcoin> for Rfc003Facade {
+    async fn save_ledger_event(
+        &self,
+        id: SwapId,
+        side: Side,
+        event: &SwapEvent<asset::Bitcoin, htlc_location::Bitcoin, transaction::Bitcoin>,
+    ) -> anyhow::Result<()> {
+        let transaction_id = event.transaction().txid().to_string();
+
+        self.save_ledger_event_of(&*self.bitcoin_connector, id, side, event, transaction_id)
+            .await
+    }
+
+    async fn load_history(
+        &self,
+        id: SwapId,
+        side: Side,
+    ) -> anyhow::Result<History<asset::Bitcoin, htlc_location::Bitcoin, transaction::Bitcoin>> {
+        self.load_history_of(id, side).await
+    }
+}
+
+#[impl_template]
+#[async_trait::async_trait]
+impl LedgerEvents<((asset::Ether, asset::Erc20)), htlc_location::Ethereum, transaction::Ethereum>
+    for Rfc003Facade
+{
+    async fn save_ledger_event(
+        &self,
+        id: SwapId,
+        side: Side,
+        event: &SwapEvent<__TYPE0__, htlc_location::Ethereum, transaction::Ethereum>,
+    ) -> anyhow::Result<()> {
+        let transaction_id = format!("{:#x}", event.transaction().hash);
+
+        self.save_ledger_event_of(&*self.ethereum_connector, id, side, event, transaction_id)
+            .await
+    }
+
+    async fn load_history(
+        &self,
+        id: SwapId,
+        side: Side,
+    ) -> anyhow::Result<History<__TYPE0__, htlc_location::Ethereum, transaction::Ethereum>> {
+        self.load_history_of(id, side).await
+    }
+}
@@ -1,25 +1,321 @@
-use crate::swap_protocols::rfc003::SwapId;
+use crate::{
+    db::{Database, Save, SwapErrorStore},
+    jsonrpc,
+};
 use async_trait::async_trait;
-use std::collections::HashMap;
+use chrono::{NaiveDateTime, Utc};
+use std::{collections::HashMap, sync::Arc};
 use tokio::sync::Mutex;
+use uuid::Uuid;
 
-#[derive(Default, Debug)]
-pub struct SwapErrorStates(Mutex<HashMap<SwapId, bool>>);
+/// The ledger of a swap on which a watcher failed.
+#[derive(
+    Clone, Copy, Debug, PartialEq, Eq, Hash, strum_macros::Display, strum_macros::EnumString,
+)]
+#[strum(serialize_all = "lowercase")]
+pub enum Side {
+    Alpha,
+    Beta,
+}
 
-impl SwapErrorStates {
-    pub async fn has_failed(&self, id: &SwapId) -> bool {
-        *self.0.lock().await.get(id).unwrap_or(&false)
+/// The step of the protocol a watcher was waiting for when it failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, strum_macros::Display, strum_macros::EnumString)]
+#[strum(serialize_all = "snake_case")]
+pub enum Phase {
+    Init,
+    Deploy,
+    Fund,
+    RedeemOrRefund,
+}
+
+/// Describes why a watcher of a swap stopped.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SwapError {
+    pub side: Side,
+    pub phase: Phase,
+    pub retryable: bool,
+    pub message: String,
+    pub at: NaiveDateTime,
+}
+
+impl SwapError {
+    pub fn new(side: Side, phase: Phase, error: &anyhow::Error) -> Self {
+        Self {
+            side,
+            phase,
+            retryable: is_retryable(error),
+            message: format!("{:#}", error),
+            at: Utc::now().naive_local(),
+        }
     }
 }
 
+/// Errors caused by talking to a node (as opposed to unexpected data on the
+/// ledger) are likely to go away if we just try again.
+fn is_retryable(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        cause.is::<reqwest::Error>()
+            || cause.is::<std::io::Error>()
+            || matches!(
+                cause.downcast_ref::<jsonrpc::Error>(),
+                Some(jsonrpc::Error::Connection(_))
+            )
+    })
+}
+
+/// Returns the sides of a swap that can be retried, alpha first: those whose
+/// watchers failed with retryable errors only.
+pub fn retryable_sides(errors: &[SwapError]) -> Vec<Side> {
+    [Side::Alpha, Side::Beta]
+        .iter()
+        .copied()
+        .filter(|side| {
+            let mut side_errors = errors.iter().filter(|error| error.side == *side).peekable();
+
+            side_errors.peek().is_some() && side_errors.all(|error| error.retryable)
+        })
+        .collect()
+}
+
+/// Whether retrying a failed swap may resolve all of its errors.
+pub fn can_retry(errors: &[SwapError]) -> bool {
+    let sides = retryable_sides(errors);
+
+    !errors.is_empty() && errors.iter().all(|error| sides.contains(&error.side))
+}
+
+/// A swap error as it is stored in the database.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FailedSwap {
+    pub swap_id: Uuid,
+    pub error: SwapError,
+}
+
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("swap has not failed, there is nothing to retry")]
+pub struct SwapNotFailed;
+
+#[derive(Debug, Clone, Copy, thiserror::Error)]
+#[error("swap failed for a reason that retrying does not resolve")]
+pub struct SwapNotRetryable;
+
 #[async_trait]
 pub trait InsertFailedSwap {
-    async fn insert_failed_swap(&self, id: &SwapId);
+    async fn insert_failed_swap(&self, id: Uuid, error: SwapError);
+}
+
+#[derive(Default, Debug)]
+pub struct SwapErrorStates(Mutex<HashMap<Uuid, Vec<SwapError>>>);
+
+impl SwapErrorStates {
+    pub async fn has_failed(&self, id: Uuid) -> bool {
+        self.0
+            .lock()
+            .await
+            .get(&id)
+            .map_or(false, |errors| !errors.is_empty())
+    }
+
+    pub async fn get(&self, id: Uuid) -> Vec<SwapError> {
+        self.0.lock().await.get(&id).cloned().unwrap_or_default()
+    }
+
+    /// Removes the errors recorded for the given side of a swap until 'until'
+    /// and returns them.
+    pub async fn clear(&self, id: Uuid, side: Side, until: NaiveDateTime) -> Vec<SwapError> {
+        let mut states = self.0.lock().await;
+        let errors = match states.get_mut(&id) {
+            Some(errors) => errors,
+            None => return Vec::new(),
+        };
+
+        let (cleared, remaining) = errors
+            .drain(..)
+            .partition(|error| error.side == side && error.at <= until);
+        *errors = remaining;
+
+        cleared
+    }
 }
 
 #[async_trait]
 impl InsertFailedSwap for SwapErrorStates {
-    async fn insert_failed_swap(&self, id: &SwapId) {
-        let _ = self.0.lock().await.insert(*id, true);
+    async fn insert_failed_swap(&self, id: Uuid, error: SwapError) {
+        self.0.lock().await.entry(id).or_default().push(error);
+    }
+}
+
+/// Keeps track of swap errors in memory and in the database.
+///
+/// Reading is served from memory, writes go to both.
+#[derive(Clone, Debug)]
+pub struct SwapErrors {
+    states: Arc<SwapErrorStates>,
+    db: Database,
+}
+
+impl SwapErrors {
+    /// Populates the in-memory state with all errors recorded in the database.
+    pub async fn load(db: Database) -> anyhow::Result<Self> {
+        let states = SwapErrorStates::default();
+
+        for FailedSwap { swap_id, error } in db.all_swap_errors().await? {
+            states.insert_failed_swap(swap_id, error).await;
+        }
+
+        Ok(Self {
+            states: Arc::new(states),
+            db,
+        })
+    }
+
+    pub async fn has_failed(&self, id: impl Into<Uuid>) -> bool {
+        self.states.has_failed(id.into()).await
+    }
+
+    pub async fn get(&self, id: impl Into<Uuid>) -> Vec<SwapError> {
+        self.states.get(id.into()).await
+    }
+
+    /// Returns the sides of a swap whose watchers failed, alpha first.
+    pub async fn failed_sides(&self, id: impl Into<Uuid>) -> Vec<Side> {
+        let errors = self.get(id).await;
+
+        [Side::Alpha, Side::Beta]
+            .iter()
+            .copied()
+            .filter(|side| errors.iter().any(|error| error.side == *side))
+            .collect()
+    }
+
+    /// Returns the sides of a swap to retry, alpha first.
+    ///
+    /// Fails if the swap has not failed or if one of its sides failed for a
+    /// reason that retrying does not resolve.
+    pub async fn sides_to_retry(&self, id: impl Into<Uuid>) -> anyhow::Result<Vec<Side>> {
+        let errors = self.get(id).await;
+        if errors.is_empty() {
+            return Err(anyhow::Error::from(SwapNotFailed));
+        }
+
+        if !can_retry(&errors) {
+            return Err(anyhow::Error::from(SwapNotRetryable));
+        }
+
+        Ok(retryable_sides(&errors))
+    }
+
+    /// Forgets about the errors of one side of a swap that were recorded until
+    /// 'until', this is done once the watcher of that side was restarted.
+    ///
+    /// Errors recorded by the restarted watcher are kept.
+    pub async fn clear(
+        &self,
+        id: impl Into<Uuid>,
+        side: Side,
+        until: NaiveDateTime,
+    ) -> anyhow::Result<Vec<SwapError>> {
+        let id = id.into();
+
+        self.db.delete_swap_errors(id, side, until).await?;
+
+        Ok(self.states.clear(id, side, until).await)
+    }
+}
+
+#[async_trait]
+impl InsertFailedSwap for SwapErrors {
+    async fn insert_failed_swap(&self, id: Uuid, error: SwapError) {
+        let failed_swap = FailedSwap {
+            swap_id: id,
+            error: error.clone(),
+        };
+
+        if let Err(e) = self.db.save(failed_swap).await {
+            tracing::error!("failed to persist error of swap {}: {:?}", id, e);
+        }
+
+        self.states.insert_failed_swap(id, error).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn swap_error(side: Side) -> SwapError {
+        let error = std::io::Error::from(std::io::ErrorKind::ConnectionRefused);
+
+        SwapError::new(side, Phase::Fund, &anyhow::Error::from(error))
+    }
+
+    #[tokio::test]
+    async fn clearing_one_side_keeps_the_errors_of_the_other_side() {
+        let states = SwapErrorStates::default();
+        let id = Uuid::new_v4();
+
+        states.insert_failed_swap(id, swap_error(Side::Alpha)).await;
+        states.insert_failed_swap(id, swap_error(Side::Beta)).await;
+
+        let cleared = states
+            .clear(id, Side::Alpha, Utc::now().naive_local())
+            .await;
+
+        assert_eq!(cleared.len(), 1);
+        assert_eq!(cleared[0].side, Side::Alpha);
+        assert!(states.has_failed(id).await);
+        assert_eq!(states.get(id).await[0].side, Side::Beta);
+    }
+
+    #[tokio::test]
+    async fn errors_recorded_after_the_retry_are_kept() {
+        let states = SwapErrorStates::default();
+        let id = Uuid::new_v4();
+        let before = swap_error(Side::Alpha);
+        let retried_at = before.at;
+        let after = SwapError {
+            at: retried_at + chrono::Duration::seconds(1),
+            ..swap_error(Side::Alpha)
+        };
+
+        states.insert_failed_swap(id, before).await;
+        states.insert_failed_swap(id, after.clone()).await;
+
+        let cleared = states.clear(id, Side::Alpha, retried_at).await;
+
+        assert_eq!(cleared.len(), 1);
+        assert_eq!(states.get(id).await, vec![after]);
+    }
+
+    #[test]
+    fn sides_with_a_non_retryable_error_are_not_retried() {
+        let retryable = swap_error(Side::Alpha);
+        let not_retryable = SwapError::new(
+            Side::Beta,
+            Phase::Fund,
+            &anyhow::anyhow!("htlc was incorrectly funded"),
+        );
+
+        assert_eq!(
+            retryable_sides(&[retryable.clone(), not_retryable.clone()]),
+            vec![Side::Alpha]
+        );
+        assert_eq!(
+            retryable_sides(&[retryable, SwapError {
+                side: Side::Alpha,
+                ..not_retryable
+            }]),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn only_errors_talking_to_a_node_are_retryable() {
+        let io_error = anyhow::Error::from(std::io::Error::from(std::io::ErrorKind::TimedOut))
+            .context("failed to fetch block");
+        let other_error = anyhow::anyhow!("htlc was incorrectly funded");
+
+        assert!(is_retryable(&io_error));
+        assert!(!is_retryable(&other_error));
     }
 }
@@ -0,0 +1,643 @@
+//! Ethereum HTLCs hosted by a single swap registry contract instead of a
+//! contract deployed per swap.
+//!
+//! The registry holds any number of swaps keyed by their secret hash:
+//!
+//! ```solidity
+//! function initiate(bytes32 secretHash, address redeemer, address refunder, uint32 expiry, address token, uint256 amount) payable;
+//! function initiateAndFund(bytes32 secretHash, address redeemer, address refunder, uint32 expiry, address token, uint256 amount);
+//! function initiateAndFundWithPermit(bytes32 secretHash, address redeemer, address refunder, uint32 expiry, address token, uint256 amount, uint256 deadline, uint8 v, bytes32 r, bytes32 s);
+//! function fund(bytes32 secretHash);
+//! function redeem(bytes32 secret);
+//! function refund(bytes32 secretHash);
+//!
+//! event Initiated(bytes32 indexed secretHash, address redeemer, address refunder, uint32 expiry, address token, uint256 amount);
+//! event Funded(bytes32 indexed secretHash, uint256 amount);
+//! event Redeemed(bytes32 indexed secretHash, bytes32 secret);
+//! event Refunded(bytes32 indexed secretHash);
+//! ```
+//!
+//! Ether swaps have the zero address as `token` and are funded with the value
+//! sent to `initiate`, which has to be `amount`: `initiate` then emits
+//! `Funded` right after `Initiated`. ERC20 swaps are funded by `fund` which
+//! transfers `amount` tokens from the sender, who has to approve the registry
+//! first. `initiateAndFund` does both in one transaction and emits both
+//! events, `initiateAndFundWithPermit` submits an EIP-2612 permit of the sender
+//! beforehand so that no approval is needed.
+//!
+//! The bytecode of the registry is assembled in [`contract`].
+
+use crate::{
+    asset::{self, ethereum::FromWei, Erc20, Erc20Quantity, Ether},
+    btsieve::{
+        ethereum::{watch_for_event, Event, ReceiptByHash, Topic},
+        BlockByHash, LatestBlock, StartOfSwap,
+    },
+    ethereum::{Block, Bytes, Hash, Log, Transaction, U256},
+    htlc_location, identity,
+    swap_protocols::{
+        herc20::{self, actions::SignedPermit},
+        ledger::Ethereum,
+        rfc003::{
+            create_swap::HtlcParams,
+            events::{
+                Deployed, Funded, HtlcDeployed, HtlcFunded, HtlcRedeemed, HtlcRefunded, Redeemed,
+                Refunded,
+            },
+            FromErr, Secret, SecretHash,
+        },
+    },
+    timestamp::Timestamp,
+    transaction,
+};
+use std::{cmp::Ordering, sync::Arc};
+use tiny_keccak::{Hasher, Keccak};
+use tracing_futures::Instrument;
+
+pub mod contract;
+
+pub const INITIATE_GAS_LIMIT: u64 = 150_000;
+pub const INITIATE_AND_FUND_GAS_LIMIT: u64 = 200_000;
+pub const INITIATE_AND_FUND_WITH_PERMIT_GAS_LIMIT: u64 = 250_000;
+pub const FUND_GAS_LIMIT: u64 = 100_000;
+pub const REDEEM_GAS_LIMIT: u64 = 100_000;
+pub const REFUND_GAS_LIMIT: u64 = 100_000;
+
+const WORD_LENGTH: usize = 32;
+
+lazy_static::lazy_static! {
+    static ref INITIATE_SELECTOR: [u8; 4] = selector("initiate(bytes32,address,address,uint32,address,uint256)");
+    static ref INITIATE_AND_FUND_SELECTOR: [u8; 4] = selector("initiateAndFund(bytes32,address,address,uint32,address,uint256)");
+    static ref INITIATE_AND_FUND_WITH_PERMIT_SELECTOR: [u8; 4] = selector("initiateAndFundWithPermit(bytes32,address,address,uint32,address,uint256,uint256,uint8,bytes32,bytes32)");
+    static ref FUND_SELECTOR: [u8; 4] = selector("fund(bytes32)");
+    static ref REDEEM_SELECTOR: [u8; 4] = selector("redeem(bytes32)");
+    static ref REFUND_SELECTOR: [u8; 4] = selector("refund(bytes32)");
+    static ref INITIATED_LOG_MSG: Hash = Hash::from(keccak256("Initiated(bytes32,address,address,uint32,address,uint256)"));
+    static ref FUNDED_LOG_MSG: Hash = Hash::from(keccak256("Funded(bytes32,uint256)"));
+    static ref REDEEMED_LOG_MSG: Hash = Hash::from(keccak256("Redeemed(bytes32,bytes32)"));
+    static ref REFUNDED_LOG_MSG: Hash = Hash::from(keccak256("Refunded(bytes32)"));
+}
+
+pub(crate) fn keccak256(data: impl AsRef<[u8]>) -> [u8; 32] {
+    let mut keccak = Keccak::v256();
+    let mut hash = [0u8; 32];
+    keccak.update(data.as_ref());
+    keccak.finalize(&mut hash);
+
+    hash
+}
+
+pub(crate) fn selector(signature: &str) -> [u8; 4] {
+    let mut selector = [0u8; 4];
+    selector.copy_from_slice(&keccak256(signature)[..4]);
+
+    selector
+}
+
+pub(crate) fn address_word(address: identity::Ethereum) -> [u8; WORD_LENGTH] {
+    Hash::from(address).into()
+}
+
+pub(crate) fn u256_word(value: U256) -> [u8; WORD_LENGTH] {
+    let mut word = [0u8; WORD_LENGTH];
+    value.to_big_endian(&mut word);
+
+    word
+}
+
+pub(crate) fn call_payload(selector: [u8; 4], words: &[[u8; WORD_LENGTH]]) -> Bytes {
+    let mut payload = selector.to_vec();
+    for word in words {
+        payload.extend_from_slice(word);
+    }
+
+    Bytes::from(payload)
+}
+
+/// A swap as it is registered in the registry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Entry {
+    pub secret_hash: SecretHash,
+    pub redeem_identity: identity::Ethereum,
+    pub refund_identity: identity::Ethereum,
+    pub expiry: Timestamp,
+    /// `None` if the swap is about ether.
+    pub token_contract: Option<identity::Ethereum>,
+    pub amount: U256,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, thiserror::Error)]
+#[error("Initiated event of swap registry contains {got} bytes of data instead of {expected}")]
+pub struct MalformedInitiatedEvent {
+    pub expected: usize,
+    pub got: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, thiserror::Error)]
+#[error("swap {secret_hash} was initiated in the swap registry with other parameters")]
+pub struct EntryMismatch {
+    pub secret_hash: SecretHash,
+}
+
+impl Entry {
+    pub fn initiate_payload(&self) -> Result<Bytes, FromErr> {
+        Ok(call_payload(*INITIATE_SELECTOR, &self.words()?))
+    }
+
+    pub fn initiate_and_fund_payload(&self) -> Result<Bytes, FromErr> {
+        Ok(call_payload(*INITIATE_AND_FUND_SELECTOR, &self.words()?))
+    }
+
+    pub fn initiate_and_fund_with_permit_payload(
+        &self,
+        permit: &SignedPermit,
+    ) -> Result<Bytes, FromErr> {
+        let mut words = self.words()?.to_vec();
+        words.extend_from_slice(&[
+            u256_word(U256::from(u32::from(permit.deadline))),
+            u256_word(U256::from(permit.v)),
+            permit.r.to_fixed_bytes(),
+            permit.s.to_fixed_bytes(),
+        ]);
+
+        Ok(call_payload(
+            *INITIATE_AND_FUND_WITH_PERMIT_SELECTOR,
+            &words,
+        ))
+    }
+
+    /// The parameters shared by all functions initiating a swap, fails if the
+    /// secret hash does not fit into a `bytes32`.
+    fn words(&self) -> Result<[[u8; WORD_LENGTH]; 6], FromErr> {
+        Ok([
+            self.secret_hash.into_raw()?,
+            address_word(self.redeem_identity),
+            address_word(self.refund_identity),
+            u256_word(U256::from(u32::from(self.expiry))),
+            address_word(self.token_contract.unwrap_or_default()),
+            u256_word(self.amount),
+        ])
+    }
+
+    /// Decodes the non-indexed parameters of an `Initiated` event.
+    fn from_initiated_event(
+        secret_hash: SecretHash,
+        data: &[u8],
+    ) -> Result<Self, MalformedInitiatedEvent> {
+        const WORDS: usize = 5;
+
+        if data.len() != WORDS * WORD_LENGTH {
+            return Err(MalformedInitiatedEvent {
+                expected: WORDS * WORD_LENGTH,
+                got: data.len(),
+            });
+        }
+
+        let word = |index: usize| &data[index * WORD_LENGTH..(index + 1) * WORD_LENGTH];
+        let address = |index: usize| identity::Ethereum::from_slice(&word(index)[12..]);
+
+        let mut expiry = [0u8; 4];
+        expiry.copy_from_slice(&word(2)[28..]);
+        let token_contract = Some(address(3)).filter(|token| *token != Default::default());
+
+        Ok(Entry {
+            secret_hash,
+            redeem_identity: address(0),
+            refund_identity: address(1),
+            expiry: Timestamp::from(u32::from_be_bytes(expiry)),
+            token_contract,
+            amount: U256::from_big_endian(word(4)),
+        })
+    }
+}
+
+impl From<HtlcParams<Ethereum, asset::Ether, identity::Ethereum>> for Entry {
+    fn from(htlc_params: HtlcParams<Ethereum, asset::Ether, identity::Ethereum>) -> Self {
+        Entry {
+            secret_hash: htlc_params.secret_hash,
+            redeem_identity: htlc_params.redeem_identity,
+            refund_identity: htlc_params.refund_identity,
+            expiry: htlc_params.expiry,
+            token_contract: None,
+            amount: htlc_params.asset.to_u256(),
+        }
+    }
+}
+
+impl From<herc20::Params> for Entry {
+    fn from(params: herc20::Params) -> Self {
+        Entry {
+            secret_hash: params.secret_hash,
+            redeem_identity: params.redeem_identity,
+            refund_identity: params.refund_identity,
+            expiry: params.expiry,
+            token_contract: Some(params.asset.token_contract),
+            amount: params.asset.quantity.to_u256(),
+        }
+    }
+}
+
+pub fn fund_payload(secret_hash: SecretHash) -> Result<Bytes, FromErr> {
+    Ok(call_payload(*FUND_SELECTOR, &[secret_hash.into_raw()?]))
+}
+
+pub fn redeem_payload(secret: Secret) -> Bytes {
+    call_payload(*REDEEM_SELECTOR, &[secret.into_raw_secret()])
+}
+
+pub fn refund_payload(secret_hash: SecretHash) -> Result<Bytes, FromErr> {
+    Ok(call_payload(*REFUND_SELECTOR, &[secret_hash.into_raw()?]))
+}
+
+/// Watches the swaps of the registry deployed at `address`.
+#[derive(Debug)]
+pub struct SwapRegistry<C> {
+    connector: Arc<C>,
+    address: identity::Ethereum,
+}
+
+impl<C> SwapRegistry<C>
+where
+    C: LatestBlock<Block = Block> + BlockByHash<Block = Block, BlockHash = Hash> + ReceiptByHash,
+{
+    pub fn new(connector: Arc<C>, address: identity::Ethereum) -> Self {
+        Self { connector, address }
+    }
+
+    async fn watch(
+        &self,
+        log_msg: Hash,
+        secret_hash: SecretHash,
+        start_of_swap: StartOfSwap,
+    ) -> anyhow::Result<(Transaction, Log)> {
+        let event = Event {
+            address: self.address,
+            topics: vec![
+                Some(Topic(log_msg)),
+                Some(Topic(Hash::from(secret_hash.into_raw()?))),
+            ],
+        };
+
+        watch_for_event(self.connector.as_ref(), start_of_swap, event)
+            .instrument(tracing::trace_span!(
+                "swap_registry",
+                registry = format_args!("{:x}", self.address),
+                topic = format_args!("{:x}", log_msg)
+            ))
+            .await
+    }
+
+    /// Fails if the swap was initiated with other parameters, e.g. by
+    /// someone front-running the initiation with the same secret hash.
+    async fn initiated(
+        &self,
+        expected: Entry,
+        start_of_swap: StartOfSwap,
+    ) -> anyhow::Result<Deployed<htlc_location::Ethereum, transaction::Ethereum>> {
+        let secret_hash = expected.secret_hash;
+        let (transaction, log) = self
+            .watch(*INITIATED_LOG_MSG, secret_hash, start_of_swap)
+            .await?;
+
+        let entry = Entry::from_initiated_event(secret_hash, log.data.0.as_ref())?;
+        if entry != expected {
+            return Err(anyhow::Error::from(EntryMismatch { secret_hash }));
+        }
+
+        Ok(Deployed {
+            location: self.address,
+            transaction,
+        })
+    }
+
+    async fn funded(
+        &self,
+        secret_hash: SecretHash,
+        start_of_swap: StartOfSwap,
+    ) -> anyhow::Result<(transaction::Ethereum, U256)> {
+        let (transaction, log) = self
+            .watch(*FUNDED_LOG_MSG, secret_hash, start_of_swap)
+            .await?;
+
+        Ok((transaction, U256::from_big_endian(log.data.0.as_ref())))
+    }
+
+    async fn redeemed(
+        &self,
+        secret_hash: SecretHash,
+        start_of_swap: StartOfSwap,
+    ) -> anyhow::Result<Redeemed<transaction::Ethereum>> {
+        let (transaction, log) = self
+            .watch(*REDEEMED_LOG_MSG, secret_hash, start_of_swap)
+            .await?;
+
+        let secret = Secret::from_vec(log.data.0.as_ref())?;
+
+        Ok(Redeemed {
+            transaction,
+            secret,
+        })
+    }
+
+    async fn refunded(
+        &self,
+        secret_hash: SecretHash,
+        start_of_swap: StartOfSwap,
+    ) -> anyhow::Result<Refunded<transaction::Ethereum>> {
+        let (transaction, _) = self
+            .watch(*REFUNDED_LOG_MSG, secret_hash, start_of_swap)
+            .await?;
+
+        Ok(Refunded { transaction })
+    }
+}
+
+#[async_trait::async_trait]
+impl<C>
+    HtlcDeployed<
+        Ethereum,
+        asset::Ether,
+        htlc_location::Ethereum,
+        identity::Ethereum,
+        transaction::Ethereum,
+    > for SwapRegistry<C>
+where
+    C: LatestBlock<Block = Block> + BlockByHash<Block = Block, BlockHash = Hash> + ReceiptByHash,
+{
+    async fn htlc_deployed(
+        &self,
+        htlc_params: &HtlcParams<Ethereum, asset::Ether, identity::Ethereum>,
+        start_of_swap: StartOfSwap,
+    ) -> anyhow::Result<Deployed<htlc_location::Ethereum, transaction::Ethereum>> {
+        self.initiated(Entry::from(htlc_params.clone()), start_of_swap)
+            .await
+    }
+}
+
+#[async_trait::async_trait]
+impl<C>
+    HtlcFunded<
+        Ethereum,
+        asset::Ether,
+        htlc_location::Ethereum,
+        identity::Ethereum,
+        transaction::Ethereum,
+    > for SwapRegistry<C>
+where
+    C: LatestBlock<Block = Block> + BlockByHash<Block = Block, BlockHash = Hash> + ReceiptByHash,
+{
+    async fn htlc_funded(
+        &self,
+        htlc_params: &HtlcParams<Ethereum, asset::Ether, identity::Ethereum>,
+        _htlc_deployment: &Deployed<htlc_location::Ethereum, transaction::Ethereum>,
+        start_of_swap: StartOfSwap,
+    ) -> anyhow::Result<Funded<asset::Ether, transaction::Ethereum>> {
+        let (transaction, amount) = self.funded(htlc_params.secret_hash, start_of_swap).await?;
+        let asset = Ether::from_wei(amount);
+
+        let event = match htlc_params.asset.cmp(&asset) {
+            Ordering::Equal => Funded::Correctly { transaction, asset },
+            _ => Funded::Incorrectly { transaction, asset },
+        };
+
+        Ok(event)
+    }
+}
+
+#[async_trait::async_trait]
+impl<C>
+    HtlcRedeemed<
+        Ethereum,
+        asset::Ether,
+        htlc_location::Ethereum,
+        identity::Ethereum,
+        transaction::Ethereum,
+    > for SwapRegistry<C>
+where
+    C: LatestBlock<Block = Block> + BlockByHash<Block = Block, BlockHash = Hash> + ReceiptByHash,
+{
+    async fn htlc_redeemed(
+        &self,
+        htlc_params: &HtlcParams<Ethereum, asset::Ether, identity::Ethereum>,
+        _htlc_deployment: &Deployed<htlc_location::Ethereum, transaction::Ethereum>,
+        start_of_swap: StartOfSwap,
+    ) -> anyhow::Result<Redeemed<transaction::Ethereum>> {
+        self.redeemed(htlc_params.secret_hash, start_of_swap).await
+    }
+}
+
+#[async_trait::async_trait]
+impl<C>
+    HtlcRefunded<
+        Ethereum,
+        asset::Ether,
+        htlc_location::Ethereum,
+        identity::Ethereum,
+        transaction::Ethereum,
+    > for SwapRegistry<C>
+where
+    C: LatestBlock<Block = Block> + BlockByHash<Block = Block, BlockHash = Hash> + ReceiptByHash,
+{
+    async fn htlc_refunded(
+        &self,
+        htlc_params: &HtlcParams<Ethereum, asset::Ether, identity::Ethereum>,
+        _htlc_deployment: &Deployed<htlc_location::Ethereum, transaction::Ethereum>,
+        start_of_swap: StartOfSwap,
+    ) -> anyhow::Result<Refunded<transaction::Ethereum>> {
+        self.refunded(htlc_params.secret_hash, start_of_swap).await
+    }
+}
+
+#[async_trait::async_trait]
+impl<C> herc20::WaitForDeployed for SwapRegistry<C>
+where
+    C: LatestBlock<Block = Block> + BlockByHash<Block = Block, BlockHash = Hash> + ReceiptByHash,
+{
+    async fn wait_for_deployed(&self, params: herc20::Params) -> anyhow::Result<herc20::Deployed> {
+        let start_of_swap = params.start_of_swap.into();
+        let Deployed {
+            location,
+            transaction,
+        } = self.initiated(Entry::from(params), start_of_swap).await?;
+
+        Ok(herc20::Deployed {
+            transaction,
+            location,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl<C> herc20::WaitForFunded for SwapRegistry<C>
+where
+    C: LatestBlock<Block = Block> + BlockByHash<Block = Block, BlockHash = Hash> + ReceiptByHash,
+{
+    async fn wait_for_funded(
+        &self,
+        params: herc20::Params,
+        _deployed: herc20::Deployed,
+    ) -> anyhow::Result<herc20::Funded> {
+        let (transaction, amount) = self
+            .funded(params.secret_hash, params.start_of_swap.into())
+            .await?;
+        let asset = Erc20::new(params.asset.token_contract, Erc20Quantity::from_wei(amount));
+
+        let event = match params.asset.cmp(&asset) {
+            Ordering::Equal => herc20::Funded::Correctly { transaction, asset },
+            _ => herc20::Funded::Incorrectly { transaction, asset },
+        };
+
+        Ok(event)
+    }
+}
+
+#[async_trait::async_trait]
+impl<C> herc20::WaitForRedeemed for SwapRegistry<C>
+where
+    C: LatestBlock<Block = Block> + BlockByHash<Block = Block, BlockHash = Hash> + ReceiptByHash,
+{
+    async fn wait_for_redeemed(
+        &self,
+        params: herc20::Params,
+        _deployed: herc20::Deployed,
+    ) -> anyhow::Result<herc20::Redeemed> {
+        let Redeemed {
+            transaction,
+            secret,
+        } = self
+            .redeemed(params.secret_hash, params.start_of_swap.into())
+            .await?;
+
+        Ok(herc20::Redeemed {
+            transaction,
+            secret,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl<C> herc20::WaitForRefunded for SwapRegistry<C>
+where
+    C: LatestBlock<Block = Block> + BlockByHash<Block = Block, BlockHash = Hash> + ReceiptByHash,
+{
+    async fn wait_for_refunded(
+        &self,
+        params: herc20::Params,
+        _deployed: herc20::Deployed,
+    ) -> anyhow::Result<herc20::Refunded> {
+        let Refunded { transaction } = self
+            .refunded(params.secret_hash, params.start_of_swap.into())
+            .await?;
+
+        Ok(herc20::Refunded { transaction })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn entry() -> Entry {
+        Entry {
+            secret_hash: SecretHash::from_str(
+                "68d627971643a6f97f27c58957826fcba853ec2077fd10ec6b93d8e61deb4cec",
+            )
+            .unwrap(),
+            redeem_identity: identity::Ethereum::from_str(
+                "0a81e8be41b21f651a71aab1a85c6813b8bbccf8",
+            )
+            .unwrap(),
+            refund_identity: identity::Ethereum::from_str(
+                "d51ecee7414c4445534f74208538683702cbb3e4",
+            )
+            .unwrap(),
+            expiry: Timestamp::from(1_590_000_000),
+            token_contract: None,
+            amount: U256::from(1_000_000_000_000_000_000u64),
+        }
+    }
+
+    #[test]
+    fn selectors_and_topics_are_keccak_of_the_signature() {
+        assert_eq!(hex::encode(*INITIATE_SELECTOR), "90458bc1");
+        assert_eq!(hex::encode(*INITIATE_AND_FUND_SELECTOR), "ad94b68c");
+        assert_eq!(
+            hex::encode(*INITIATE_AND_FUND_WITH_PERMIT_SELECTOR),
+            "e79516aa"
+        );
+        assert_eq!(hex::encode(*REDEEM_SELECTOR), "eda1122c");
+        assert_eq!(
+            *INITIATED_LOG_MSG,
+            Hash::from_str("b9bd462d454648a1a965a437f16083c44a343a3b91f3c6b41c68a61ce3eca171")
+                .unwrap()
+        );
+        assert_eq!(
+            *REDEEMED_LOG_MSG,
+            Hash::from_str("489e9ee921192823d1aa1ef800c9ffc642993538b1e7e43a4d46a91965e894ab")
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn initiate_payload_encodes_the_entry() {
+        let payload = entry().initiate_payload().unwrap();
+
+        assert_eq!(
+            hex::encode(payload.0),
+            "90458bc1\
+             68d627971643a6f97f27c58957826fcba853ec2077fd10ec6b93d8e61deb4cec\
+             0000000000000000000000000a81e8be41b21f651a71aab1a85c6813b8bbccf8\
+             000000000000000000000000d51ecee7414c4445534f74208538683702cbb3e4\
+             000000000000000000000000000000000000000000000000000000005ec57980\
+             0000000000000000000000000000000000000000000000000000000000000000\
+             0000000000000000000000000000000000000000000000000de0b6b3a7640000"
+        );
+    }
+
+    #[test]
+    fn hash160_secret_hash_cannot_be_initiated() {
+        let entry = Entry {
+            secret_hash: SecretHash::from_str("ce4be827894106ecf7bedfe6c0343a81e1e7814a").unwrap(),
+            ..entry()
+        };
+
+        assert_eq!(
+            entry.initiate_payload(),
+            Err(FromErr::InvalidLength {
+                expected: 32,
+                got: 20
+            })
+        );
+    }
+
+    #[test]
+    fn initiated_event_decodes_to_the_initiated_entry() {
+        let token_contract =
+            identity::Ethereum::from_str("b97048628db6b661d4c2aa833e95dbe1a905b280").unwrap();
+        let entry = Entry {
+            token_contract: Some(token_contract),
+            ..entry()
+        };
+        // The event data are the non-indexed parameters of `initiate`.
+        let payload = entry.initiate_payload().unwrap();
+        let data = &payload.0[4 + WORD_LENGTH..];
+
+        let decoded = Entry::from_initiated_event(entry.secret_hash, data);
+
+        assert_eq!(decoded, Ok(entry));
+    }
+
+    #[test]
+    fn initiated_event_with_wrong_length_is_rejected() {
+        let decoded = Entry::from_initiated_event(entry().secret_hash, &[0u8; 4 * WORD_LENGTH]);
+
+        assert_eq!(
+            decoded,
+            Err(MalformedInitiatedEvent {
+                expected: 160,
+                got: 128
+            })
+        );
+    }
+}
@@ -0,0 +1,612 @@
+//! The swap registry contract, assembled from the program below.
+//!
+//! A swap is stored in five slots starting at the keccak256 hash of its secret
+//! hash:
+//!
+//! | slot | content                                           |
+//! |------|---------------------------------------------------|
+//! | +0   | `redeemer` in the low 160 bits, `expiry` above    |
+//! | +1   | `refunder`                                        |
+//! | +2   | `token`, zero for ether                           |
+//! | +3   | `amount`                                          |
+//! | +4   | status: 0 unused, 1 initiated, 2 funded, 3 closed |
+//!
+//! `initiate` stores the swap and emits `Initiated`. For ether the sent value
+//! has to be exactly `amount`, the swap is funded right away and `initiate`
+//! emits `Funded` as well. For ERC20 tokens nothing may be sent, the token
+//! contract has to exist and the swap waits for `fund`, which transfers
+//! `amount` from the sender with `transferFrom` and emits `Funded`.
+//! `initiateAndFund` and `initiateAndFundWithPermit` are only for tokens, the
+//! latter calls `permit(sender, registry, amount, deadline, v, r, s)` on the
+//! token before the transfer.
+//!
+//! `redeem` takes the secret, looks the swap up by its SHA-256 hash, emits
+//! `Redeemed` and pays the redeemer. `refund` emits `Refunded` and pays the
+//! refunder once the block time reached the expiry. Both close the swap
+//! before paying. Token calls are accepted if they succeed and return nothing
+//! or `true`. Calls with unexpected data, value or state are reverted.
+
+use super::{
+    selector, FUNDED_LOG_MSG, FUND_SELECTOR, INITIATED_LOG_MSG, INITIATE_AND_FUND_SELECTOR,
+    INITIATE_AND_FUND_WITH_PERMIT_SELECTOR, INITIATE_SELECTOR, REDEEMED_LOG_MSG, REDEEM_SELECTOR,
+    REFUNDED_LOG_MSG, REFUND_SELECTOR,
+};
+use std::convert::TryFrom;
+
+const STOP: u8 = 0x00;
+const ADD: u8 = 0x01;
+const LT: u8 = 0x10;
+const EQ: u8 = 0x14;
+const ISZERO: u8 = 0x15;
+const OR: u8 = 0x17;
+const SHL: u8 = 0x1b;
+const SHR: u8 = 0x1c;
+const SHA3: u8 = 0x20;
+const ADDRESS: u8 = 0x30;
+const CALLER: u8 = 0x33;
+const CALLVALUE: u8 = 0x34;
+const CALLDATALOAD: u8 = 0x35;
+const CALLDATASIZE: u8 = 0x36;
+const CALLDATACOPY: u8 = 0x37;
+const CODECOPY: u8 = 0x39;
+const EXTCODESIZE: u8 = 0x3b;
+const RETURNDATASIZE: u8 = 0x3d;
+const TIMESTAMP: u8 = 0x42;
+const POP: u8 = 0x50;
+const MLOAD: u8 = 0x51;
+const MSTORE: u8 = 0x52;
+const SLOAD: u8 = 0x54;
+const SSTORE: u8 = 0x55;
+const JUMP: u8 = 0x56;
+const JUMPI: u8 = 0x57;
+const GAS: u8 = 0x5a;
+const JUMPDEST: u8 = 0x5b;
+const PUSH1: u8 = 0x60;
+const PUSH2: u8 = 0x61;
+const PUSH32: u8 = 0x7f;
+const DUP1: u8 = 0x80;
+const DUP2: u8 = 0x81;
+const DUP6: u8 = 0x85;
+const DUP7: u8 = 0x86;
+const LOG2: u8 = 0xa2;
+const CALL: u8 = 0xf1;
+const RETURN: u8 = 0xf3;
+const STATICCALL: u8 = 0xfa;
+const REVERT: u8 = 0xfd;
+
+// Offsets of the arguments in the call data.
+const SECRET_HASH: u8 = 4;
+const REDEEMER: u8 = 36;
+const REFUNDER: u8 = 68;
+const EXPIRY: u8 = 100;
+const TOKEN: u8 = 132;
+const AMOUNT: u8 = 164;
+const DEADLINE: u8 = 196;
+
+const ONE_ARGUMENT: u16 = 36;
+const INITIATE_ARGUMENTS: u16 = 196;
+const PERMIT_ARGUMENTS: u16 = 324;
+
+// Storage slots of a swap, relative to the keccak256 hash of its secret hash.
+const REDEEMER_AND_EXPIRY_SLOT: u8 = 0;
+const REFUNDER_SLOT: u8 = 1;
+const TOKEN_SLOT: u8 = 2;
+const AMOUNT_SLOT: u8 = 3;
+const STATUS_SLOT: u8 = 4;
+
+const INITIATED: u8 = 1;
+const FUNDED: u8 = 2;
+const CLOSED: u8 = 3;
+
+lazy_static::lazy_static! {
+    static ref TRANSFER_SELECTOR: [u8; 4] = selector("transfer(address,uint256)");
+    static ref TRANSFER_FROM_SELECTOR: [u8; 4] = selector("transferFrom(address,address,uint256)");
+    static ref PERMIT_SELECTOR: [u8; 4] = selector("permit(address,address,uint256,uint256,uint8,bytes32,bytes32)");
+}
+
+/// The code of a transaction deploying the swap registry.
+pub fn deploy_code() -> Vec<u8> {
+    let runtime_code = runtime_code();
+    let length = u16::try_from(runtime_code.len()).expect("runtime code to be small");
+
+    // PUSH2 length DUP1 PUSH1 12 PUSH1 0 CODECOPY PUSH1 0 RETURN
+    let mut code = vec![PUSH2];
+    code.extend(&length.to_be_bytes());
+    code.extend(&[
+        DUP1, PUSH1, 0x0c, PUSH1, 0x00, CODECOPY, PUSH1, 0x00, RETURN,
+    ]);
+    code.extend(runtime_code);
+
+    code
+}
+
+fn runtime_code() -> Vec<u8> {
+    let mut program = Program::new();
+    let fail = program.fail;
+
+    let initiate = program.label();
+    let initiate_and_fund = program.label();
+    let initiate_and_fund_with_permit = program.label();
+    let fund = program.label();
+    let redeem = program.label();
+    let refund = program.label();
+
+    // The selector stays on the stack until the function pops it.
+    program
+        .push(&[0])
+        .op(&[CALLDATALOAD])
+        .push(&[224])
+        .op(&[SHR]);
+    for (selector, function) in &[
+        (*INITIATE_SELECTOR, initiate),
+        (*INITIATE_AND_FUND_SELECTOR, initiate_and_fund),
+        (
+            *INITIATE_AND_FUND_WITH_PERMIT_SELECTOR,
+            initiate_and_fund_with_permit,
+        ),
+        (*FUND_SELECTOR, fund),
+        (*REDEEM_SELECTOR, redeem),
+        (*REFUND_SELECTOR, refund),
+    ] {
+        program
+            .op(&[DUP1])
+            .push(selector)
+            .op(&[EQ])
+            .jump_if(*function);
+    }
+    program.jump(fail);
+
+    program.function(initiate, INITIATE_ARGUMENTS);
+    program.register();
+    let ether = program.label();
+    program
+        .push(&[TOKEN])
+        .op(&[CALLDATALOAD, ISZERO])
+        .jump_if(ether);
+    program.require_no_value().op(&[STOP]);
+    program.bind(ether);
+    program
+        .push(&[AMOUNT])
+        .op(&[CALLDATALOAD, CALLVALUE, EQ, ISZERO])
+        .jump_if(fail);
+    program.set_status(FUNDED).emit_funded().op(&[STOP]);
+
+    program.function(initiate_and_fund, INITIATE_ARGUMENTS);
+    program
+        .require_no_value()
+        .register()
+        .require_token()
+        .transfer_from()
+        .op(&[STOP]);
+
+    program.function(initiate_and_fund_with_permit, PERMIT_ARGUMENTS);
+    program
+        .require_no_value()
+        .register()
+        .require_token()
+        .permit()
+        .transfer_from()
+        .op(&[STOP]);
+
+    program.function(fund, ONE_ARGUMENT);
+    program.require_no_value().transfer_from().op(&[STOP]);
+
+    program.redeem(redeem);
+
+    program.function(refund, ONE_ARGUMENT);
+    program.require_no_value().require_status(FUNDED);
+    // The expiry is above the redeemer.
+    program
+        .load(REDEEMER_AND_EXPIRY_SLOT)
+        .push(&[160])
+        .op(&[SHR, TIMESTAMP, LT])
+        .jump_if(fail);
+    program.set_status(CLOSED);
+    program
+        .push(&[SECRET_HASH])
+        .op(&[CALLDATALOAD])
+        .push(REFUNDED_LOG_MSG.as_bytes())
+        .push(&[0])
+        .op(&[DUP1, LOG2]);
+    program.pay(REFUNDER_SLOT).op(&[STOP]);
+
+    program.bind(fail);
+    program.push(&[0]).op(&[DUP1, REVERT]);
+
+    program.assemble()
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Label(usize);
+
+/// Assembles EVM code with jumps to labels whose position is only known
+/// once everything is assembled.
+///
+/// Each step documents the stack it expects and leaves behind, `base` is the
+/// storage slot a swap starts at.
+#[derive(Debug)]
+struct Program {
+    code: Vec<u8>,
+    labels: Vec<Option<u16>>,
+    jumps: Vec<(usize, Label)>,
+    fail: Label,
+}
+
+impl Program {
+    fn new() -> Self {
+        let mut program = Program {
+            code: Vec::new(),
+            labels: Vec::new(),
+            jumps: Vec::new(),
+            fail: Label(0),
+        };
+        program.fail = program.label();
+
+        program
+    }
+
+    fn label(&mut self) -> Label {
+        self.labels.push(None);
+
+        Label(self.labels.len() - 1)
+    }
+
+    fn bind(&mut self, label: Label) -> &mut Self {
+        let position = u16::try_from(self.code.len()).expect("runtime code to be small");
+        self.labels[label.0] = Some(position);
+
+        self.op(&[JUMPDEST])
+    }
+
+    fn op(&mut self, opcodes: &[u8]) -> &mut Self {
+        self.code.extend(opcodes);
+
+        self
+    }
+
+    fn push(&mut self, value: &[u8]) -> &mut Self {
+        let length = u8::try_from(value.len()).expect("at most 32 bytes");
+        self.code.push(PUSH1 + length - 1);
+
+        self.op(value)
+    }
+
+    fn jump(&mut self, label: Label) -> &mut Self {
+        self.jumps.push((self.code.len() + 1, label));
+
+        self.push(&[0, 0]).op(&[JUMP])
+    }
+
+    /// Jumps to `label` if the top of the stack is not zero.
+    fn jump_if(&mut self, label: Label) -> &mut Self {
+        self.jumps.push((self.code.len() + 1, label));
+
+        self.push(&[0, 0]).op(&[JUMPI])
+    }
+
+    fn assemble(mut self) -> Vec<u8> {
+        for (position, label) in self.jumps {
+            let destination = self.labels[label.0].expect("all labels to be bound");
+            self.code[position..position + 2].copy_from_slice(&destination.to_be_bytes());
+        }
+
+        self.code
+    }
+
+    /// `[selector]` -> `[base]`
+    ///
+    /// Starts the function at `label`, taking `size` bytes of call data with
+    /// the secret hash first.
+    fn function(&mut self, label: Label, size: u16) -> &mut Self {
+        let fail = self.fail;
+        self.bind(label).op(&[POP, CALLDATASIZE]);
+        self.push(&size.to_be_bytes())
+            .op(&[EQ, ISZERO])
+            .jump_if(fail);
+
+        // PUSH1 secret_hash CALLDATALOAD PUSH1 0 MSTORE PUSH1 32 PUSH1 0 SHA3
+        self.push(&[SECRET_HASH]).op(&[CALLDATALOAD]);
+        self.push(&[0])
+            .op(&[MSTORE])
+            .push(&[32])
+            .push(&[0])
+            .op(&[SHA3])
+    }
+
+    /// `[selector]` -> exits
+    fn redeem(&mut self, label: Label) -> &mut Self {
+        let fail = self.fail;
+        self.bind(label).op(&[POP, CALLDATASIZE]);
+        self.push(&ONE_ARGUMENT.to_be_bytes())
+            .op(&[EQ, ISZERO])
+            .jump_if(fail);
+        self.require_no_value();
+
+        // The SHA-256 precompile hashes the secret at 0 into 32.
+        self.push(&[SECRET_HASH])
+            .op(&[CALLDATALOAD])
+            .push(&[0])
+            .op(&[MSTORE]);
+        self.push(&[32])
+            .push(&[32])
+            .push(&[32])
+            .push(&[0])
+            .push(&[2]);
+        self.op(&[GAS, STATICCALL, ISZERO]).jump_if(fail);
+        self.push(&[32]).push(&[32]).op(&[SHA3]);
+
+        self.require_status(FUNDED).set_status(CLOSED);
+        // The log goes first, paying overwrites the memory.
+        self.push(&[32]).op(&[MLOAD]);
+        self.push(REDEEMED_LOG_MSG.as_bytes());
+        self.push(&[32]).push(&[0]).op(&[LOG2]);
+
+        self.pay(REDEEMER_AND_EXPIRY_SLOT).op(&[STOP])
+    }
+
+    /// `[base]` -> `[base]`
+    ///
+    /// Stores the swap of the call data as initiated and emits `Initiated`.
+    fn register(&mut self) -> &mut Self {
+        let fail = self.fail;
+        self.load(STATUS_SLOT).jump_if(fail);
+
+        for (argument, bits) in &[(REDEEMER, 160), (REFUNDER, 160), (EXPIRY, 32), (TOKEN, 160)] {
+            self.push(&[*argument]).op(&[CALLDATALOAD]).push(&[*bits]);
+            self.op(&[SHR]).jump_if(fail);
+        }
+        self.push(&[AMOUNT])
+            .op(&[CALLDATALOAD, ISZERO])
+            .jump_if(fail);
+
+        // A token without code would accept every call.
+        let ether = self.label();
+        self.push(&[TOKEN])
+            .op(&[CALLDATALOAD, DUP1, ISZERO])
+            .jump_if(ether);
+        self.op(&[EXTCODESIZE, ISZERO]).jump_if(fail);
+        self.push(&[0]).bind(ether).op(&[POP]);
+
+        self.push(&[REDEEMER]).op(&[CALLDATALOAD]);
+        self.push(&[EXPIRY])
+            .op(&[CALLDATALOAD])
+            .push(&[160])
+            .op(&[SHL, OR]);
+        self.store(REDEEMER_AND_EXPIRY_SLOT);
+        self.push(&[REFUNDER])
+            .op(&[CALLDATALOAD])
+            .store(REFUNDER_SLOT);
+        self.push(&[TOKEN]).op(&[CALLDATALOAD]).store(TOKEN_SLOT);
+        self.push(&[AMOUNT]).op(&[CALLDATALOAD]).store(AMOUNT_SLOT);
+        self.set_status(INITIATED);
+
+        // The log data are the five arguments after the secret hash.
+        self.push(&[160])
+            .push(&[REDEEMER])
+            .push(&[0])
+            .op(&[CALLDATACOPY]);
+        self.push(&[SECRET_HASH]).op(&[CALLDATALOAD]);
+        self.push(INITIATED_LOG_MSG.as_bytes());
+        self.push(&[160]).push(&[0]).op(&[LOG2])
+    }
+
+    /// `[base]` -> `[base]`
+    fn permit(&mut self) -> &mut Self {
+        let fail = self.fail;
+        self.push(&*PERMIT_SELECTOR)
+            .push(&[224])
+            .op(&[SHL])
+            .push(&[0])
+            .op(&[MSTORE]);
+        self.op(&[CALLER]).push(&[4]).op(&[MSTORE]);
+        self.op(&[ADDRESS]).push(&[36]).op(&[MSTORE]);
+        self.push(&[AMOUNT])
+            .op(&[CALLDATALOAD])
+            .push(&[68])
+            .op(&[MSTORE]);
+        // deadline, v, r and s
+        self.push(&[128])
+            .push(&[DEADLINE])
+            .push(&[100])
+            .op(&[CALLDATACOPY]);
+
+        self.push(&[0])
+            .push(&[0])
+            .push(&[228])
+            .push(&[0])
+            .push(&[0]);
+        self.push(&[TOKEN])
+            .op(&[CALLDATALOAD, GAS, CALL, ISZERO])
+            .jump_if(fail)
+    }
+
+    /// `[base]` -> `[base]`
+    ///
+    /// Funds an initiated token swap from the sender and emits `Funded`.
+    fn transfer_from(&mut self) -> &mut Self {
+        self.require_status(INITIATED).set_status(FUNDED);
+
+        self.push(&*TRANSFER_FROM_SELECTOR)
+            .push(&[224])
+            .op(&[SHL])
+            .push(&[0])
+            .op(&[MSTORE]);
+        self.op(&[CALLER]).push(&[4]).op(&[MSTORE]);
+        self.op(&[ADDRESS]).push(&[36]).op(&[MSTORE]);
+        self.load(AMOUNT_SLOT).push(&[68]).op(&[MSTORE]);
+
+        self.push(&[32])
+            .push(&[0])
+            .push(&[100])
+            .push(&[0])
+            .push(&[0]);
+        self.op(&[DUP6])
+            .push(&[TOKEN_SLOT])
+            .op(&[ADD, SLOAD, GAS, CALL]);
+        self.require_token_success().emit_funded()
+    }
+
+    /// `[base]` -> `[base]`
+    ///
+    /// Sends the swapped asset to the identity in `slot`.
+    fn pay(&mut self, slot: u8) -> &mut Self {
+        let fail = self.fail;
+        let ether = self.label();
+        let paid = self.label();
+
+        self.load(TOKEN_SLOT).op(&[DUP1, ISZERO]).jump_if(ether);
+        self.push(&*TRANSFER_SELECTOR)
+            .push(&[224])
+            .op(&[SHL])
+            .push(&[0])
+            .op(&[MSTORE]);
+        self.op(&[DUP2]).identity(slot).push(&[4]).op(&[MSTORE]);
+        self.op(&[DUP2])
+            .push(&[AMOUNT_SLOT])
+            .op(&[ADD, SLOAD])
+            .push(&[36])
+            .op(&[MSTORE]);
+        self.push(&[32])
+            .push(&[0])
+            .push(&[68])
+            .push(&[0])
+            .push(&[0]);
+        self.op(&[DUP6, GAS, CALL])
+            .require_token_success()
+            .jump(paid);
+
+        self.bind(ether);
+        self.push(&[0]).op(&[DUP1, DUP1, DUP1]);
+        self.op(&[DUP6]).push(&[AMOUNT_SLOT]).op(&[ADD, SLOAD]);
+        self.op(&[DUP7]).identity(slot);
+        self.op(&[GAS, CALL, ISZERO]).jump_if(fail);
+
+        self.bind(paid).op(&[POP])
+    }
+
+    /// `[base]` -> `[identity]`
+    fn identity(&mut self, slot: u8) -> &mut Self {
+        match slot {
+            REDEEMER_AND_EXPIRY_SLOT => self
+                .op(&[SLOAD])
+                .push(&[96])
+                .op(&[SHL])
+                .push(&[96])
+                .op(&[SHR]),
+            slot => self.push(&[slot]).op(&[ADD, SLOAD]),
+        }
+    }
+
+    /// `[base, success]` -> `[base]`
+    ///
+    /// Accepts token calls that succeeded and returned nothing or `true`.
+    fn require_token_success(&mut self) -> &mut Self {
+        let fail = self.fail;
+        let accepted = self.label();
+
+        self.op(&[ISZERO]).jump_if(fail);
+        self.op(&[RETURNDATASIZE, ISZERO]).jump_if(accepted);
+        self.push(&[32]).op(&[RETURNDATASIZE, LT]).jump_if(fail);
+        self.push(&[0])
+            .op(&[MLOAD])
+            .push(&[1])
+            .op(&[EQ, ISZERO])
+            .jump_if(fail);
+
+        self.bind(accepted)
+    }
+
+    /// `[base]` -> `[base]`
+    fn emit_funded(&mut self) -> &mut Self {
+        self.load(AMOUNT_SLOT).push(&[0]).op(&[MSTORE]);
+        self.push(&[SECRET_HASH]).op(&[CALLDATALOAD]);
+        self.push(FUNDED_LOG_MSG.as_bytes());
+        self.push(&[32]).push(&[0]).op(&[LOG2])
+    }
+
+    /// `[base]` -> `[base]`
+    fn require_token(&mut self) -> &mut Self {
+        let fail = self.fail;
+        self.push(&[TOKEN])
+            .op(&[CALLDATALOAD, ISZERO])
+            .jump_if(fail)
+    }
+
+    /// `[base]` -> `[base]`
+    fn require_no_value(&mut self) -> &mut Self {
+        let fail = self.fail;
+        self.op(&[CALLVALUE]).jump_if(fail)
+    }
+
+    /// `[base]` -> `[base]`
+    fn require_status(&mut self, status: u8) -> &mut Self {
+        let fail = self.fail;
+        self.load(STATUS_SLOT)
+            .push(&[status])
+            .op(&[EQ, ISZERO])
+            .jump_if(fail)
+    }
+
+    /// `[base]` -> `[base]`
+    fn set_status(&mut self, status: u8) -> &mut Self {
+        self.push(&[status]).store(STATUS_SLOT)
+    }
+
+    /// `[base]` -> `[base, value]`
+    fn load(&mut self, slot: u8) -> &mut Self {
+        self.op(&[DUP1]).push(&[slot]).op(&[ADD, SLOAD])
+    }
+
+    /// `[base, value]` -> `[base]`
+    fn store(&mut self, slot: u8) -> &mut Self {
+        self.op(&[DUP2]).push(&[slot]).op(&[ADD, SSTORE])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn jumps_land_on_jump_destinations() {
+        let code = runtime_code();
+
+        let mut jumps = 0;
+        let mut position = 0;
+        while position < code.len() {
+            let opcode = code[position];
+            if opcode == PUSH2 && [JUMP, JUMPI].contains(&code[position + 3]) {
+                let destination = u16::from_be_bytes([code[position + 1], code[position + 2]]);
+                assert_eq!(code[usize::from(destination)], JUMPDEST);
+                jumps += 1;
+            }
+            // Skip the data of PUSH1 to PUSH32.
+            if (PUSH1..=PUSH32).contains(&opcode) {
+                position += usize::from(opcode - PUSH1 + 1);
+            }
+            position += 1;
+        }
+
+        assert!(jumps > 0);
+    }
+
+    #[test]
+    fn deployment_code_returns_runtime_code() {
+        let runtime_code = runtime_code();
+
+        let code = deploy_code();
+
+        assert_eq!(
+            &code[1..3],
+            &u16::try_from(runtime_code.len()).unwrap().to_be_bytes()
+        );
+        assert_eq!(&code[12..], runtime_code.as_slice());
+    }
+
+    #[test]
+    fn assembles_to_the_published_bytecode() {
+        let published = include_str!("swap_registry.hex");
+
+        assert_eq!(hex::encode(deploy_code()), published.trim());
+    }
+}
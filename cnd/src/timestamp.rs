@@ -21,6 +21,10 @@ impl Timestamp {
         Self(self.0.checked_add(seconds).unwrap_or(std::u32::MAX))
     }
 
+    pub fn minus(self, seconds: u32) -> Self {
+        Self(self.0.saturating_sub(seconds))
+    }
+
     pub fn to_bytes(self) -> [u8; 4] {
         self.0.to_le_bytes()
     }
@@ -32,7 +32,7 @@ async fn find_transaction_missed_previous_latest_block() {
     let start_of_swap = NaiveDateTime::from_timestamp((block1.header.time as i64) + 1, 0);
     let (expected_transaction, _out_point) = watch_for_created_outpoint(
         &connector,
-        start_of_swap,
+        start_of_swap.into(),
         Address::from_str(
             include_str!("test_data/bitcoin/find_transaction_missed_previous_latest_block/address")
                 .trim(),
@@ -79,7 +79,7 @@ async fn find_transaction_missed_previous_latest_block_with_big_gap() {
     let start_of_swap = NaiveDateTime::from_timestamp((block1.header.time as i64) + 1, 0);
     let (expected_transaction, _out_point) = watch_for_created_outpoint(
         &connector,
-        start_of_swap,
+        start_of_swap.into(),
         Address::from_str(
             include_str!(
             "test_data/bitcoin/find_transaction_missed_previous_latest_block_with_big_gap/address"
@@ -117,7 +117,7 @@ async fn find_transaction_if_blockchain_reorganisation() {
     let start_of_swap = Utc::now().naive_local();
     let (expected_transaction, _out_point) = watch_for_created_outpoint(
         &connector,
-        start_of_swap,
+        start_of_swap.into(),
         Address::from_str(
             include_str!("test_data/bitcoin/find_transaction_if_blockchain_reorganisation/address")
                 .trim(),
@@ -154,7 +154,7 @@ async fn find_transaction_if_blockchain_reorganisation_with_long_chain() {
     );
 
     let start_of_swap = Utc::now().naive_local();
-    let (expected_transaction, _out_point) = watch_for_created_outpoint(&connector, start_of_swap, Address::from_str(
+    let (expected_transaction, _out_point) = watch_for_created_outpoint(&connector, start_of_swap.into(), Address::from_str(
         include_str!(
             "test_data/bitcoin/find_transaction_if_blockchain_reorganisation_with_long_chain/address"
         ).trim()
@@ -0,0 +1,158 @@
+use cnd::{
+    ethereum::{Address, Bytes, Hash, TransactionReceipt, U256},
+    identity, jsonrpc,
+    swap_protocols::{
+        rfc003::{ethereum::AssembledEtherHtlc, Secret},
+        HashFunction,
+    },
+    timestamp::Timestamp,
+};
+use reqwest::Url;
+use std::time::Duration;
+use testcontainers::*;
+
+const SECRET: [u8; 32] = *b"hello world, you are beautiful!!";
+const VALUE: u64 = 1_000_000_000;
+
+/// Deploys the HASH160 Ether HTLC on a parity dev chain and verifies that it
+/// only pays out the redeem identity with the right secret and the refund
+/// identity once the expiry has passed.
+#[tokio::test]
+async fn hash160_ether_htlc_can_be_redeemed_with_the_secret() {
+    let cli = clients::Cli::default();
+    let container = cli.run(images::parity_parity::ParityEthereum::default());
+    let client = jsonrpc::Client::new(connection_url(&container).unwrap());
+
+    let redeem_identity = identity::Ethereum::from([2u8; 20]);
+    let htlc = deploy(&client, Timestamp::now().plus(3600), redeem_identity).await;
+
+    let wrong_secret = send(&client, Some(htlc), Bytes(vec![1u8; 32]), 0).await;
+    let early_refund = send(&client, Some(htlc), Bytes::default(), 0).await;
+    let redeem = send(&client, Some(htlc), Bytes(SECRET.to_vec()), 0).await;
+
+    assert!(!wrong_secret.is_status_ok());
+    assert!(!early_refund.is_status_ok());
+    assert!(redeem.is_status_ok());
+    assert_eq!(redeem.logs[0].data, Bytes(SECRET.to_vec()));
+    assert_eq!(balance(&client, redeem_identity).await, U256::from(VALUE));
+    assert_eq!(balance(&client, refund_identity()).await, U256::zero());
+}
+
+#[tokio::test]
+async fn hash160_ether_htlc_can_be_refunded_after_the_expiry() {
+    let cli = clients::Cli::default();
+    let container = cli.run(images::parity_parity::ParityEthereum::default());
+    let client = jsonrpc::Client::new(connection_url(&container).unwrap());
+
+    let redeem_identity = identity::Ethereum::from([2u8; 20]);
+    let htlc = deploy(&client, Timestamp::now().minus(60), redeem_identity).await;
+
+    let refund = send(&client, Some(htlc), Bytes::default(), 0).await;
+
+    assert!(refund.is_status_ok());
+    assert_eq!(balance(&client, refund_identity()).await, U256::from(VALUE));
+    assert_eq!(balance(&client, redeem_identity).await, U256::zero());
+}
+
+fn refund_identity() -> identity::Ethereum {
+    identity::Ethereum::from([1u8; 20])
+}
+
+async fn deploy(
+    client: &jsonrpc::Client,
+    expiry: Timestamp,
+    redeem_identity: identity::Ethereum,
+) -> Address {
+    let htlc = AssembledEtherHtlc::new(
+        expiry,
+        refund_identity(),
+        redeem_identity,
+        HashFunction::Hash160,
+        Secret::from(SECRET).hash_with(HashFunction::Hash160),
+    )
+    .expect("HASH160 HTLCs to be assembled");
+
+    let receipt = send(client, None, Bytes(htlc.into()), VALUE).await;
+    assert!(receipt.is_status_ok());
+
+    receipt
+        .contract_address
+        .expect("deployment to create a contract")
+}
+
+/// Sends a transaction from the parity dev account and waits for its
+/// receipt.
+async fn send(
+    client: &jsonrpc::Client,
+    to: Option<Address>,
+    data: Bytes,
+    value: u64,
+) -> TransactionReceipt {
+    let transaction: Hash = client
+        .send(jsonrpc::Request::new("personal_sendTransaction", vec![
+            jsonrpc::serialize(TransactionRequest {
+                from: "00a329c0648769a73afac7f9381e08fb43dbea72"
+                    .parse()
+                    .expect("failed to parse static string"),
+                to,
+                value: U256::from(value),
+                data,
+                gas: U256::from(AssembledEtherHtlc::DEPLOY_TX_GAS_LIMIT),
+            })
+            .unwrap(),
+            jsonrpc::serialize("").unwrap(),
+        ]))
+        .await
+        .expect("failed to send transaction");
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let receipt: Option<TransactionReceipt> = client
+                .send(jsonrpc::Request::new("eth_getTransactionReceipt", vec![
+                    jsonrpc::serialize(&transaction).unwrap(),
+                ]))
+                .await
+                .expect("failed to get transaction receipt");
+
+            match receipt {
+                Some(receipt) => return receipt,
+                None => tokio::time::delay_for(Duration::from_millis(100)).await,
+            }
+        }
+    })
+    .await
+    .expect("transaction to be mined")
+}
+
+async fn balance(client: &jsonrpc::Client, address: Address) -> U256 {
+    client
+        .send(jsonrpc::Request::new("eth_getBalance", vec![
+            jsonrpc::serialize(address).unwrap(),
+            jsonrpc::serialize("latest").unwrap(),
+        ]))
+        .await
+        .expect("failed to get balance")
+}
+
+fn connection_url<D, E>(container: &Container<'_, D, E>) -> anyhow::Result<reqwest::Url>
+where
+    D: Docker,
+    E: Image,
+{
+    let port = container.get_host_port(8545).unwrap();
+    let endpoint = format!("http://localhost:{}", port);
+
+    let url = Url::parse(&endpoint)?;
+
+    Ok(url)
+}
+
+#[derive(Clone, Debug, PartialEq, serde::Serialize)]
+struct TransactionRequest {
+    pub from: Address,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub to: Option<Address>,
+    pub value: U256,
+    pub data: Bytes,
+    pub gas: U256,
+}
@@ -0,0 +1,43 @@
+pub mod ethereum_helper;
+
+use chrono::NaiveDateTime;
+use cnd::{
+    btsieve::ethereum::watch_for_contract_creation,
+    ethereum::{Block, Transaction, TransactionReceipt, TransactionType},
+};
+use ethereum_helper::EthereumConnectorMock;
+
+#[tokio::test]
+async fn find_contract_deployment_in_dynamic_fee_transaction() {
+    let want_transaction: Transaction =
+        include_json_test_data!("./test_data/ethereum/find_typed_transaction/transaction.json");
+    let want_receipt: TransactionReceipt =
+        include_json_test_data!("./test_data/ethereum/find_typed_transaction/receipt.json");
+    let connector = EthereumConnectorMock::new(
+        vec![include_json_test_data!(
+            "./test_data/ethereum/find_typed_transaction/block2_with_transaction.json"
+        )],
+        vec![
+            include_json_test_data!("./test_data/ethereum/find_typed_transaction/block1.json"),
+            include_json_test_data!(
+                "./test_data/ethereum/find_typed_transaction/block2_with_transaction.json"
+            ),
+        ],
+        vec![(want_transaction.hash, want_receipt.clone())],
+    );
+    let block1: Block =
+        include_json_test_data!("./test_data/ethereum/find_typed_transaction/block1.json");
+    let start_of_swap = NaiveDateTime::from_timestamp(block1.timestamp.as_u32() as i64, 0);
+
+    let (got_transaction, got_address) =
+        watch_for_contract_creation(&connector, start_of_swap.into(), &want_transaction.input)
+            .await
+            .expect("failed to find the contract deployment");
+
+    assert_eq!(
+        got_transaction.transaction_type,
+        TransactionType::DynamicFee
+    );
+    assert_eq!(got_transaction, want_transaction);
+    assert_eq!(Some(got_address), want_receipt.contract_address);
+}
@@ -2,49 +2,50 @@ extern crate proc_macro;
 
 use crate::proc_macro::TokenStream;
 use proc_macro2::{Delimiter, Group, Punct, Spacing};
-use quote::{quote, ToTokens, TokenStreamExt};
-use syn::{Attribute, Data, Fields, Lit, Meta, MetaList, NestedMeta, Type};
+use quote::{format_ident, quote, ToTokens, TokenStreamExt};
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Error, Field, Fields, FieldsNamed, Ident, Lit,
+    LitStr, Meta, NestedMeta, Type,
+};
+
+const HASH_FORMAT: &str = "Expected format: `#[digest(hash = \"MyHashType\")]`";
+const PREFIX_FORMAT: &str = "Expected format: `#[digest(prefix = \"0102..0A\")]`";
+const FIELD_FORMAT: &str =
+    "Expected format: `#[digest(prefix = \"0102..0A\")]` or `#[digest(ignore)]`";
 
 #[proc_macro_derive(Digest, attributes(digest))]
 pub fn digest_macro_fn(input: TokenStream) -> TokenStream {
-    let ast = syn::parse(input).unwrap();
+    let ast = parse_macro_input!(input as DeriveInput);
     impl_digest_macro(&ast)
+        .unwrap_or_else(|error| error.to_compile_error())
+        .into()
 }
 
-fn impl_digest_macro(ast: &syn::DeriveInput) -> TokenStream {
+fn impl_digest_macro(ast: &DeriveInput) -> syn::Result<proc_macro2::TokenStream> {
     let name = &ast.ident;
 
-    let hash_type = extract_hash_type(&ast.attrs);
+    let hash_type = extract_hash_type(ast)?;
 
     match &ast.data {
         Data::Struct(data) => {
-            let (idents, types, bytes) = match &data.fields {
-                Fields::Named(fields) => {
-                    let fields = fields.named.iter().filter(|field| {
-                        let meta_list = extract_meta_list(&field.attrs);
-
-                        let path = &meta_list.nested.first();
-                        if let Some(NestedMeta::Meta(Meta::Path(path))) = path {
-                            if path.is_ident("ignore") {
-                                return false;
-                            }
-                        }
-                        true
-                    });
-
-                    let idents = fields
-                        .clone()
-                        .map(|field| field.ident.as_ref().expect("Named field"));
-
-                    let types = fields.clone().map(|field| &field.ty);
-
-                    let bytes = fields.map(|field| extract_bytes(&field.attrs));
-                    (idents, types, bytes)
+            let fields = match &data.fields {
+                Fields::Named(fields) => digested_fields(fields)?,
+                _ => {
+                    return Err(Error::new_spanned(
+                        name,
+                        "Digest can only be derived for structs with named fields.",
+                    ))
                 }
-                _ => panic!("Only supporting named fields."),
             };
 
-            let gen = quote! {
+            let types = fields.iter().map(|(field, _)| &field.ty);
+            let values = fields.iter().map(|(field, _)| {
+                let ident = &field.ident;
+                quote!(self.#ident)
+            });
+            let digest = fields_digest(values, fields.iter().map(|(_, prefix)| &prefix.bytes));
+
+            Ok(quote! {
                     impl ::digest::Digest for #name
                         where #(#types: ::digest::IntoDigestInput),*
                     {
@@ -52,54 +53,73 @@ fn impl_digest_macro(ast: &syn::DeriveInput) -> TokenStream {
 
                         fn digest(self) -> Self::Hash {
                             use ::digest::{Hash, IntoDigestInput};
-                            let mut digests = vec![];
-                            #(digests.push(::digest::field_digest::<_, Self::Hash>(self.#idents, #bytes.to_vec())););*
+                            #digest
+                        }
+                    }
+            })
+        }
+        Data::Enum(data) => {
+            let mut prefixes = vec![];
+            let mut arms = vec![];
 
-                            digests.sort();
+            for variant in &data.variants {
+                let ident = &variant.ident;
+                let prefix = match extract_attribute(&variant.attrs, ident, PREFIX_FORMAT)? {
+                    DigestAttribute::Prefix(prefix) => prefix,
+                    DigestAttribute::Ignore(path) => {
+                        return Err(Error::new_spanned(
+                            path,
+                            format!("Enum variants cannot be ignored. {}", PREFIX_FORMAT),
+                        ))
+                    }
+                };
+                let bytes = &prefix.bytes;
+
+                arms.push(match &variant.fields {
+                    Fields::Unit => quote! {
+                        Self::#ident => #bytes.to_vec()
+                    },
+                    Fields::Unnamed(fields) if fields.unnamed.len() == 1 => quote! {
+                        Self::#ident(data) => {
+                            let mut bytes = #bytes.to_vec();
+                            bytes.append(&mut data.into_digest_input());
+                            bytes
+                        }
+                    },
+                    Fields::Unnamed(fields) => {
+                        return Err(Error::new_spanned(
+                            fields,
+                            "Tuple variants must hold a single field, use named fields instead.",
+                        ))
+                    }
+                    Fields::Named(fields) => {
+                        let fields = digested_fields(fields)?;
 
-                            let bytes = digests.into_iter().fold(vec![], |mut bytes, digest| {
+                        let idents = fields.iter().map(|(field, _)| &field.ident);
+                        let bindings = fields
+                            .iter()
+                            .map(|(field, _)| binding(field.ident.as_ref().expect("Named field")))
+                            .collect::<Vec<_>>();
+                        let values = bindings.iter().map(|binding| quote!(#binding));
+                        let digest =
+                            fields_digest(values, fields.iter().map(|(_, prefix)| &prefix.bytes));
+
+                        quote! {
+                            Self::#ident { #(#idents: #bindings,)* .. } => {
+                                let digest: Self::Hash = { #digest };
+                                let mut bytes = #bytes.to_vec();
                                 bytes.append(&mut digest.into_digest_input());
                                 bytes
-                            });
-
-                            Self::Hash::hash(&bytes)
+                            }
                         }
                     }
-            };
-            gen.into()
-        }
-        Data::Enum(data) => {
-            let unit_variant_idents = data
-                .variants
-                .iter()
-                .filter(|variant| variant.fields.is_empty())
-                .map(|variant| &variant.ident);
-
-            let unit_variant_bytes = data
-                .variants
-                .iter()
-                .filter(|variant| variant.fields.is_empty())
-                .map(|variant| extract_bytes(&variant.attrs));
-
-            let tuple_variant_idents = data
-                .variants
-                .iter()
-                .filter(|variant| match variant.fields {
-                    Fields::Unnamed(_) => true,
-                    _ => false,
-                })
-                .map(|variant| &variant.ident);
-
-            let tuple_variant_bytes = data
-                .variants
-                .iter()
-                .filter(|variant| match variant.fields {
-                    Fields::Unnamed(_) => true,
-                    _ => false,
-                })
-                .map(|variant| extract_bytes(&variant.attrs));
-
-            let gen = quote! {
+                });
+                prefixes.push(prefix);
+            }
+
+            ensure_unique_prefixes(&prefixes)?;
+
+            Ok(quote! {
                     impl ::digest::Digest for #name
                     {
                         type Hash = #hash_type;
@@ -108,46 +128,114 @@ fn impl_digest_macro(ast: &syn::DeriveInput) -> TokenStream {
                             use ::digest::{Hash, IntoDigestInput};
 
                             let bytes = match self {
-                                #(Self::#unit_variant_idents => #unit_variant_bytes.to_vec()),*,
-                                #(Self::#tuple_variant_idents(data) => {
-                                        let mut bytes = #tuple_variant_bytes.to_vec();
-                                        bytes.append(&mut data.digest().into_digest_input());
-                                        bytes
-                                }),*
+                                #(#arms),*
                             };
 
                             Self::Hash::hash(&bytes)
                         }
                     }
-            };
-            gen.into()
+            })
         }
-        _ => {
-            panic!("Digest derive macro only supports structs & enums.");
+        Data::Union(data) => Err(Error::new_spanned(
+            data.union_token,
+            "Digest derive macro only supports structs & enums.",
+        )),
+    }
+}
+
+/// Returns the fields that are not marked with `#[digest(ignore)]` together
+/// with their prefix.
+fn digested_fields(fields: &FieldsNamed) -> syn::Result<Vec<(&Field, Prefix)>> {
+    let mut digested = vec![];
+
+    for field in &fields.named {
+        match extract_attribute(&field.attrs, &field.ident, FIELD_FORMAT)? {
+            DigestAttribute::Prefix(prefix) => digested.push((field, prefix)),
+            DigestAttribute::Ignore(_) => {}
         }
     }
+
+    let prefixes = digested
+        .iter()
+        .map(|(_, prefix)| prefix.clone())
+        .collect::<Vec<_>>();
+    ensure_unique_prefixes(&prefixes)?;
+
+    Ok(digested)
 }
 
-fn extract_hash_type(attrs: &[Attribute]) -> Type {
-    let meta_list = extract_meta_list(attrs);
+/// Two fields, or two variants, with the same prefix would lose the domain
+/// separation that the prefixes provide.
+fn ensure_unique_prefixes(prefixes: &[Prefix]) -> syn::Result<()> {
+    for (index, prefix) in prefixes.iter().enumerate() {
+        if prefixes[..index]
+            .iter()
+            .any(|other| other.bytes.0 == prefix.bytes.0)
+        {
+            return Err(Error::new_spanned(
+                &prefix.literal,
+                format!("Duplicate prefix \"{}\".", prefix.literal.value()),
+            ));
+        }
+    }
 
-    let path = &meta_list.nested.first();
-    if let Some(NestedMeta::Meta(Meta::NameValue(name_value))) = path {
-        if name_value.path.is_ident("hash") {
-            if let Lit::Str(ref lit_str) = name_value.lit {
-                if let Ok(hash_type) = syn::parse_str::<Type>(&lit_str.value()) {
-                    return hash_type;
-                }
+    Ok(())
+}
+
+/// The binding of a field of a struct variant, prefixed so that it cannot
+/// shadow the variables of the generated code.
+fn binding(ident: &Ident) -> Ident {
+    format_ident!("__digest_{}", ident)
+}
+
+/// Generates the expression computing the digest of the given field values:
+/// the field digests, sorted, concatenated and hashed.
+fn fields_digest<'a>(
+    values: impl Iterator<Item = proc_macro2::TokenStream>,
+    bytes: impl Iterator<Item = &'a Bytes>,
+) -> proc_macro2::TokenStream {
+    quote! {
+        let mut digests = vec![];
+        #(digests.push(::digest::field_digest::<_, Self::Hash>(#values, #bytes.to_vec()));)*
+
+        digests.sort();
+
+        let bytes = digests.into_iter().fold(vec![], |mut bytes, digest| {
+            bytes.append(&mut digest.into_digest_input());
+            bytes
+        });
+
+        Self::Hash::hash(&bytes)
+    }
+}
+
+fn extract_hash_type(ast: &DeriveInput) -> syn::Result<Type> {
+    match extract_nested_meta(&ast.attrs, &ast.ident, HASH_FORMAT)? {
+        NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident("hash") => {
+            match &name_value.lit {
+                Lit::Str(lit_str) => lit_str.parse::<Type>().map_err(|_| {
+                    Error::new_spanned(
+                        lit_str,
+                        format!("Hash type could not be resolved. {}", HASH_FORMAT),
+                    )
+                }),
+                lit => Err(Error::new_spanned(
+                    lit,
+                    format!("Hash type must be a string. {}", HASH_FORMAT),
+                )),
             }
-            panic!("hash type could not be resolved. Expected format: `#[digest(hash = \"MyHashType\")]` ")
-        } else {
-            panic!("Only `hash` identifier is supported for `digest()` outer attribute. Expected format: `#[digest(hash = \"MyHashType\")]`")
         }
-    } else {
-        panic!("Could not find element inside `digest` attribute. Expected format: `#[digest(hash = \"MyHashType\")]`")
+        nested_meta => Err(Error::new_spanned(
+            nested_meta,
+            format!(
+                "Only `hash` is supported for the `digest` attribute of a type. {}",
+                HASH_FORMAT
+            ),
+        )),
     }
 }
 
+#[derive(Clone)]
 struct Bytes(Vec<u8>);
 
 impl ToTokens for Bytes {
@@ -159,45 +247,81 @@ impl ToTokens for Bytes {
     }
 }
 
-fn extract_bytes(attrs: &[Attribute]) -> Bytes {
-    let meta_list = extract_meta_list(attrs);
+/// A decoded prefix and the literal it was decoded from, to point errors at.
+#[derive(Clone)]
+struct Prefix {
+    bytes: Bytes,
+    literal: LitStr,
+}
+
+enum DigestAttribute {
+    Prefix(Prefix),
+    Ignore(syn::Path),
+}
+
+fn extract_attribute<T: ToTokens>(
+    attrs: &[Attribute],
+    item: T,
+    format: &str,
+) -> syn::Result<DigestAttribute> {
+    match extract_nested_meta(attrs, item, format)? {
+        NestedMeta::Meta(Meta::Path(path)) if path.is_ident("ignore") => {
+            Ok(DigestAttribute::Ignore(path))
+        }
+        NestedMeta::Meta(Meta::NameValue(name_value)) if name_value.path.is_ident("prefix") => {
+            match name_value.lit {
+                Lit::Str(literal) => {
+                    let bytes = ::hex::decode(literal.value()).map_err(|e| {
+                        Error::new_spanned(&literal, format!("Prefix is not valid hex: {}.", e))
+                    })?;
+                    if bytes.is_empty() {
+                        return Err(Error::new_spanned(&literal, "Prefix must not be empty."));
+                    }
 
-    let path = &meta_list.nested.first();
-    if let Some(NestedMeta::Meta(Meta::NameValue(name_value))) = path {
-        if name_value.path.is_ident("prefix") {
-            if let Lit::Str(ref lit_str) = name_value.lit {
-                let str = lit_str.value();
-                let bytes = ::hex::decode(&str).expect("prefix value should be in hex format");
-                return Bytes(bytes);
+                    Ok(DigestAttribute::Prefix(Prefix {
+                        bytes: Bytes(bytes),
+                        literal,
+                    }))
+                }
+                lit => Err(Error::new_spanned(
+                    lit,
+                    format!("Prefix must be a string. {}", format),
+                )),
             }
-            panic!(
-                "prefix could not be resolved. Expected format: `#[digest(prefix = \"0102..0A\")]`"
-            )
-        } else {
-            panic!("Only `prefix` identifier is supported for `digest()` field attribute. Expected format: `#[digest(prefix = \"0102..0A\")]`")
         }
-    } else {
-        panic!("Could not find element inside `digest` attribute. Expected format: `#[digest(hash = \"MyHashType\")]`")
+        nested_meta => Err(Error::new_spanned(
+            nested_meta,
+            format!("Unsupported `digest` attribute. {}", format),
+        )),
     }
 }
 
-fn extract_meta_list(attrs: &[Attribute]) -> MetaList {
-    attrs
+/// Returns the single element of the `digest` attribute, `item` is pointed at
+/// if the attribute is missing.
+fn extract_nested_meta<T: ToTokens>(
+    attrs: &[Attribute],
+    item: T,
+    format: &str,
+) -> syn::Result<NestedMeta> {
+    let attr = attrs
         .iter()
-        .find_map(|attr| {
-            attr.parse_meta()
-                .ok()
-                .map(|meta| {
-                    if let Meta::List(meta_list) = meta {
-                        if meta_list.path.is_ident("digest") {
-                            return Some(meta_list);
-                        }
-                    };
-                    None
-                })
-                .unwrap_or(None)
-        })
-        .expect("Could not find `digest` attribute.")
+        .find(|attr| attr.path.is_ident("digest"))
+        .ok_or_else(|| {
+            Error::new_spanned(item, format!("Missing `digest` attribute. {}", format))
+        })?;
+
+    match attr.parse_meta()? {
+        Meta::List(meta_list) if meta_list.nested.len() == 1 => {
+            Ok(meta_list.nested.into_iter().next().expect("one element"))
+        }
+        meta => Err(Error::new_spanned(
+            meta,
+            format!(
+                "The `digest` attribute must hold exactly one element. {}",
+                format
+            ),
+        )),
+    }
 }
 
 #[cfg(test)]
@@ -5,6 +5,8 @@
 /// The attribute `digest_prefix` must be applied on each field. It plays the
 /// role of an identifier to ensure that fields with same data but different
 /// meaning do not result to the same digest.
+/// Hence the derive macro refuses to compile if two fields of a struct, or two
+/// variants of an enum, have the same prefix or if a prefix is not valid hex.
 ///
 /// Data types within the data structure should implement `IntoDigestInput`,
 /// this allows you to control how the data is transformed to a byte array.
@@ -61,8 +63,19 @@
 /// 3. Concatenate the list,
 /// 4. Hash the result.
 ///
-/// For unit variants of enums, only the prefix as input to the hash function.
-/// Note that Nested structures are not supported.
+/// Fields are not limited to types implementing `IntoDigestInput` by hand:
+/// - A field whose type implements `Digest` (e.g. a nested struct deriving
+///   it) is given as input its own digest,
+/// - `Option<T>` is given `00` if absent and `01` + the input of the value
+///   otherwise, hence an absent field never collides with a present one,
+/// - `Vec<T>` is given the input of each element in order, each preceded by
+///   its length as a big-endian `u64`.
+///
+/// For enums, the prefix of the variant is hashed together with its data:
+/// - Unit variants only give the prefix as input to the hash function,
+/// - Tuple variants hold a single field, its input is appended to the prefix,
+/// - Struct variants are digested like a struct, the prefix is prepended to
+///   the resulting digest.
 pub use digest_macro_derive::Digest;
 pub use hex;
 
@@ -79,6 +92,45 @@ pub trait IntoDigestInput {
     fn into_digest_input(self) -> Vec<u8>;
 }
 
+impl<T> IntoDigestInput for T
+where
+    T: Digest,
+{
+    fn into_digest_input(self) -> Vec<u8> {
+        self.digest().into_digest_input()
+    }
+}
+
+impl<T> IntoDigestInput for Option<T>
+where
+    T: IntoDigestInput,
+{
+    fn into_digest_input(self) -> Vec<u8> {
+        match self {
+            None => vec![0x00],
+            Some(value) => {
+                let mut bytes = vec![0x01];
+                bytes.append(&mut value.into_digest_input());
+                bytes
+            }
+        }
+    }
+}
+
+impl<T> IntoDigestInput for Vec<T>
+where
+    T: IntoDigestInput,
+{
+    fn into_digest_input(self) -> Vec<u8> {
+        self.into_iter().fold(vec![], |mut bytes, element| {
+            let mut element = element.into_digest_input();
+            bytes.extend_from_slice(&(element.len() as u64).to_be_bytes());
+            bytes.append(&mut element);
+            bytes
+        })
+    }
+}
+
 pub fn field_digest<T, H>(field: T, prefix: Vec<u8>) -> H
 where
     T: IntoDigestInput,
@@ -195,3 +195,183 @@ fn given_two_enums_with_differnt_variant_return_different_multihash() {
 
     assert_eq!(enum1.digest(), enum2.digest())
 }
+
+#[derive(Digest)]
+#[digest(hash = "MultihashSha256")]
+struct Offer {
+    #[digest(prefix = "0001")]
+    asset: MyString,
+    #[digest(prefix = "0002")]
+    amount: MyString,
+}
+
+#[derive(Digest)]
+#[digest(hash = "MultihashSha256")]
+enum Protocol {
+    #[digest(prefix = "2001")]
+    Rfc003,
+    #[digest(prefix = "2002")]
+    Herc20(Offer),
+    #[digest(prefix = "2003")]
+    Halight {
+        #[digest(prefix = "3001")]
+        cltv_expiry: MyString,
+        #[digest(ignore)]
+        note: String,
+    },
+}
+
+#[derive(Digest)]
+#[digest(hash = "MultihashSha256")]
+struct Swap {
+    #[digest(prefix = "1001")]
+    alpha: Offer,
+    #[digest(prefix = "1002")]
+    memo: Option<MyString>,
+    #[digest(prefix = "1003")]
+    assets: Vec<MyString>,
+    #[digest(prefix = "1004")]
+    protocol: Protocol,
+}
+
+fn bitcoin_offer() -> Offer {
+    Offer {
+        asset: "bitcoin".into(),
+        amount: "100000000".into(),
+    }
+}
+
+fn ether_offer() -> Offer {
+    Offer {
+        asset: "ether".into(),
+        amount: "1000000000000000000".into(),
+    }
+}
+
+fn swap(memo: Option<&str>, assets: &[&str], protocol: Protocol) -> Swap {
+    Swap {
+        alpha: bitcoin_offer(),
+        memo: memo.map(MyString::from),
+        assets: assets.iter().map(|asset| MyString::from(*asset)).collect(),
+        protocol,
+    }
+}
+
+fn hex(hash: MultihashSha256) -> String {
+    digest::hex::encode(hash.0.into_bytes())
+}
+
+#[test]
+fn flat_struct_digest_is_stable() {
+    let digest = DoubleFieldStruct {
+        foo: "foo field".into(),
+        bar: "bar field".into(),
+        ignore: "this field does not matter".into(),
+    }
+    .digest();
+
+    assert_eq!(
+        hex(digest),
+        "162032b58d66f8897b60dcdaee21291330dba26c5b6179cb6c8255c9a97e4b05139e"
+    )
+}
+
+#[test]
+fn unit_variant_digest_is_stable() {
+    assert_eq!(
+        hex(Enum::Foo.digest()),
+        "16209987b33890222491ea263e88f701b2a38cdab8aad9ff4e971a4b730bba63e605"
+    )
+}
+
+#[test]
+fn nested_struct_digest_is_stable() {
+    assert_eq!(
+        hex(bitcoin_offer().digest()),
+        "1620cc58ebdaf103f71cf05d5d3c89b8bfa02b046ae672a2fd3b7a502d47888d69d4"
+    )
+}
+
+#[test]
+fn tuple_variant_digest_is_stable() {
+    assert_eq!(
+        hex(Protocol::Herc20(ether_offer()).digest()),
+        "16200d0978dbb20373a4af13803c93c146384be079248bdc7fe2f938938a7810998e"
+    )
+}
+
+#[test]
+fn struct_variant_digest_is_stable() {
+    let digest = Protocol::Halight {
+        cltv_expiry: "350".into(),
+        note: "Does not matter".into(),
+    }
+    .digest();
+
+    assert_eq!(
+        hex(digest),
+        "162085e8110e6d2a7f117f565e3e47bd8d28409bb37262e76fc2059292d69a0588ea"
+    )
+}
+
+#[test]
+fn swap_digests_are_stable() {
+    let full = swap(
+        Some("first swap"),
+        &["bitcoin", "ether"],
+        Protocol::Herc20(ether_offer()),
+    );
+    let minimal = swap(None, &[], Protocol::Rfc003);
+    let halight = swap(Some(""), &["bitcoin"], Protocol::Halight {
+        cltv_expiry: "350".into(),
+        note: "Does not matter".into(),
+    });
+
+    assert_eq!(
+        hex(full.digest()),
+        "1620f7814f4b976bb4965139fca6a756b8d4a8da82d6b8e827d881c44522d80e4714"
+    );
+    assert_eq!(
+        hex(minimal.digest()),
+        "16202d24656d877c5d3cbe51bfeee0b008e7e2b73741839561279976106a6ecd11ee"
+    );
+    assert_eq!(
+        hex(halight.digest()),
+        "16206cbad5a18b54a99331d2529564c39c82865972705277502b4804b3fb0b2a7a48"
+    );
+}
+
+#[test]
+fn nested_struct_is_hashed_into_its_own_digest() {
+    let expected = {
+        let mut bytes = vec![0x20, 0x02];
+        bytes.append(&mut ether_offer().digest().into_digest_input());
+        MultihashSha256::hash(&bytes)
+    };
+
+    assert_eq!(Protocol::Herc20(ether_offer()).digest(), expected)
+}
+
+#[test]
+fn given_absent_and_empty_option_return_different_multihash() {
+    let absent = swap(None, &[], Protocol::Rfc003);
+    let empty = swap(Some(""), &[], Protocol::Rfc003);
+
+    assert_ne!(absent.digest(), empty.digest())
+}
+
+#[test]
+fn given_same_elements_in_different_order_return_different_multihash() {
+    let swap1 = swap(None, &["bitcoin", "ether"], Protocol::Rfc003);
+    let swap2 = swap(None, &["ether", "bitcoin"], Protocol::Rfc003);
+
+    assert_ne!(swap1.digest(), swap2.digest())
+}
+
+#[test]
+fn given_same_concatenated_elements_return_different_multihash() {
+    let swap1 = swap(None, &["bit", "coin"], Protocol::Rfc003);
+    let swap2 = swap(None, &["bitc", "oin"], Protocol::Rfc003);
+
+    assert_ne!(swap1.digest(), swap2.digest())
+}
@@ -0,0 +1,12 @@
+use digest::Digest;
+
+#[derive(Digest)]
+#[digest(hash = "MyHash")]
+struct Swap {
+    #[digest(prefix = "2001")]
+    alpha_expiry: u32,
+    #[digest(prefix = "2001")]
+    beta_expiry: u32,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use digest::Digest;
+
+#[derive(Digest)]
+#[digest(hash = "MyHash")]
+enum Protocol {
+    #[digest(prefix = "2001")]
+    Halight {
+        #[digest(prefix = "3001")]
+        cltv_expiry: u32,
+        #[digest(prefix = "3001")]
+        amount: u64,
+    },
+}
+
+fn main() {}
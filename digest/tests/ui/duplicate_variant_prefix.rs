@@ -0,0 +1,12 @@
+use digest::Digest;
+
+#[derive(Digest)]
+#[digest(hash = "MyHash")]
+enum Role {
+    #[digest(prefix = "00aa")]
+    Alice,
+    #[digest(prefix = "00AA")]
+    Bob,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use digest::Digest;
+
+#[derive(Digest)]
+#[digest(hash = "MyHash")]
+struct Swap {
+    #[digest(prefix = "")]
+    expiry: u32,
+}
+
+fn main() {}
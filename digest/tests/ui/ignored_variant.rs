@@ -0,0 +1,12 @@
+use digest::Digest;
+
+#[derive(Digest)]
+#[digest(hash = "MyHash")]
+enum Role {
+    #[digest(prefix = "0001")]
+    Alice,
+    #[digest(ignore)]
+    Bob,
+}
+
+fn main() {}
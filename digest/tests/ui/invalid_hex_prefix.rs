@@ -0,0 +1,10 @@
+use digest::Digest;
+
+#[derive(Digest)]
+#[digest(hash = "MyHash")]
+struct Swap {
+    #[digest(prefix = "20g1")]
+    expiry: u32,
+}
+
+fn main() {}
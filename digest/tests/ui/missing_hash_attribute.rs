@@ -0,0 +1,9 @@
+use digest::Digest;
+
+#[derive(Digest)]
+struct Swap {
+    #[digest(prefix = "2001")]
+    expiry: u32,
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use digest::Digest;
+
+#[derive(Digest)]
+#[digest(hash = "MyHash")]
+struct Expiry(u32);
+
+fn main() {}
@@ -0,0 +1,10 @@
+use digest::Digest;
+
+#[derive(Digest)]
+#[digest(hash = "MyHash")]
+enum Expiry {
+    #[digest(prefix = "0001")]
+    Both(u32, u32),
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use digest::Digest;
+
+#[derive(Digest)]
+#[digest(hash = "MyHash")]
+struct Swap {
+    #[digest(prefx = "2001")]
+    expiry: u32,
+}
+
+fn main() {}
@@ -3,12 +3,17 @@ use crate::{
     handler::{
         InboundMessage, OutboundMessage, PendingInboundResponse, ProtocolInEvent, ProtocolOutEvent,
     },
-    ComitHandler, PendingInboundRequest, PendingOutboundRequest,
+    Capabilities, ComitHandler, PendingInboundRequest, PendingOutboundRequest, Version,
 };
 use futures::{
-    channel::mpsc::{self, UnboundedReceiver, UnboundedSender},
-    Future, StreamExt, TryFutureExt,
+    channel::{
+        mpsc::{self, UnboundedReceiver, UnboundedSender},
+        oneshot::Canceled,
+    },
+    future::{self, Either},
+    Future, FutureExt, StreamExt,
 };
+use futures_timer::Delay;
 use libp2p::{
     core::{connection::ConnectionId, ConnectedPoint, Multiaddr, PeerId},
     swarm::{
@@ -18,8 +23,12 @@ use libp2p::{
 use std::{
     collections::{hash_map::Entry, HashMap, HashSet},
     task::{Context, Poll},
+    time::Duration,
 };
 
+/// How long we wait for the response to a request unless told otherwise.
+pub const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(60 * 60);
+
 #[derive(Debug)]
 enum ConnectionState {
     Connected {
@@ -41,33 +50,70 @@ pub enum BehaviourOutEvent {
     },
 }
 
+/// Why a request did not yield a response.
+#[derive(Clone, Copy, Debug, PartialEq, thiserror::Error)]
+pub enum RequestError {
+    #[error("peer did not respond within the request timeout")]
+    Timeout,
+    #[error("connection to the peer failed or closed before it responded")]
+    Connection,
+}
+
 /// Network behaviour that handles the COMIT messaging protocol.
 #[derive(Debug)]
 pub struct Rfc003Comit {
     events_sender: UnboundedSender<NetworkBehaviourAction<ProtocolInEvent, BehaviourOutEvent>>,
     events: UnboundedReceiver<NetworkBehaviourAction<ProtocolInEvent, BehaviourOutEvent>>,
 
-    known_request_headers: HashMap<String, HashSet<String>>,
+    capabilities: Capabilities,
+    request_timeout: Duration,
     connections: HashMap<PeerId, ConnectionState>,
+    peer_capabilities: HashMap<PeerId, Capabilities>,
 }
 
 impl Rfc003Comit {
-    pub fn new(known_request_headers: HashMap<String, HashSet<String>>) -> Self {
+    pub fn new(capabilities: Capabilities) -> Self {
         let (events_sender, events) = mpsc::unbounded();
 
         Self {
             events_sender,
             events,
-            known_request_headers,
+            capabilities,
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
             connections: HashMap::new(),
+            peer_capabilities: HashMap::new(),
         }
     }
 
+    /// Sets for how long requests wait for their response before they fail
+    /// with [`RequestError::Timeout`].
+    pub fn with_request_timeout(self, request_timeout: Duration) -> Self {
+        Self {
+            request_timeout,
+            ..self
+        }
+    }
+
+    /// Returns the capabilities the peer told us once connected, `None` until
+    /// it did. Peers that only speak version 1 never do.
+    pub fn peer_capabilities(&self, peer_id: &PeerId) -> Option<&Capabilities> {
+        self.peer_capabilities.get(peer_id)
+    }
+
+    /// Returns the highest version both we and the peer speak, as far as its
+    /// capabilities are known.
+    pub fn highest_common_version(&self, peer_id: &PeerId) -> Option<Version> {
+        self.peer_capabilities(peer_id)
+            .and_then(|peer_capabilities| {
+                self.capabilities.highest_common_version(peer_capabilities)
+            })
+    }
+
     pub fn send_request(
         &mut self,
         dial_information: (PeerId, Option<Multiaddr>),
         request: OutboundRequest,
-    ) -> impl Future<Output = Result<Response, ()>> + Send + 'static + Unpin {
+    ) -> impl Future<Output = Result<Response, RequestError>> + Send + 'static + Unpin {
         let (peer_id, address_hint) = dial_information;
         let (sender, receiver) = futures::channel::oneshot::channel();
 
@@ -128,10 +174,17 @@ impl Rfc003Comit {
             }
         }
 
-        receiver.map_err(|_| {
-            tracing::warn!(
-                "Sender of response future was unexpectedly dropped before response was received."
-            )
+        // Dropping the receiver on timeout cancels the request, the handler
+        // then stops waiting for the response.
+        future::select(receiver, Delay::new(self.request_timeout)).map(|either| match either {
+            Either::Left((Ok(response), _)) => Ok(response),
+            Either::Left((Err(Canceled), _)) => {
+                tracing::warn!(
+                    "Sender of response future was dropped before response was received."
+                );
+                Err(RequestError::Connection)
+            }
+            Either::Right(((), _)) => Err(RequestError::Timeout),
         })
     }
 
@@ -156,7 +209,7 @@ impl NetworkBehaviour for Rfc003Comit {
     type OutEvent = BehaviourOutEvent;
 
     fn new_handler(&mut self) -> Self::ProtocolsHandler {
-        ComitHandler::new(self.known_request_headers.clone())
+        ComitHandler::new(self.capabilities.clone())
     }
 
     fn addresses_of_peer(&mut self, peer_id: &PeerId) -> Vec<Multiaddr> {
@@ -180,6 +233,21 @@ impl NetworkBehaviour for Rfc003Comit {
 
     fn inject_disconnected(&mut self, _: &PeerId) {}
 
+    fn inject_dial_failure(&mut self, peer_id: &PeerId) {
+        // Dropping the pending requests fails them, leaving the peer
+        // `Connecting` would also prevent us from dialing it ever again.
+        if let Entry::Occupied(entry) = self.connections.entry(peer_id.clone()) {
+            if let ConnectionState::Connecting { pending_events, .. } = entry.get() {
+                tracing::debug!(
+                    "failed to dial {}, dropping {} pending requests",
+                    peer_id,
+                    pending_events.len()
+                );
+                entry.remove();
+            }
+        }
+    }
+
     fn inject_connection_established(
         &mut self,
         peer_id: &PeerId,
@@ -250,7 +318,9 @@ impl NetworkBehaviour for Rfc003Comit {
         {
             addresses.remove(&address);
 
-            if !addresses.is_empty() {
+            if addresses.is_empty() {
+                self.peer_capabilities.remove(peer_id);
+            } else {
                 self.connections
                     .insert(peer_id.clone(), ConnectionState::Connected { addresses });
             }
@@ -275,6 +345,11 @@ impl NetworkBehaviour for Rfc003Comit {
             })) => {
                 let _ = channel.send(response);
             }
+            ProtocolOutEvent::Hello(capabilities) => {
+                tracing::debug!("{} told us its capabilities: {:?}", peer, capabilities);
+
+                self.peer_capabilities.insert(peer, capabilities);
+            }
         }
     }
 
@@ -288,3 +363,41 @@ impl NetworkBehaviour for Rfc003Comit {
             .map(|item| item.expect("unbounded channel never ends"))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    fn request() -> OutboundRequest {
+        OutboundRequest::new("SWAP")
+    }
+
+    #[test]
+    fn request_without_response_times_out() {
+        let mut behaviour = Rfc003Comit::new(Capabilities::default())
+            .with_request_timeout(Duration::from_millis(10));
+
+        let response = behaviour.send_request((PeerId::random(), None), request());
+
+        assert_eq!(block_on(response), Err(RequestError::Timeout));
+    }
+
+    #[test]
+    fn dial_failure_fails_pending_requests() {
+        let mut behaviour = Rfc003Comit::new(Capabilities::default());
+        let peer_id = PeerId::random();
+
+        let address_hint = "/ip4/127.0.0.1/tcp/9939".parse::<Multiaddr>().unwrap();
+
+        let first = behaviour.send_request((peer_id.clone(), Some(address_hint)), request());
+        let second = behaviour.send_request((peer_id.clone(), None), request());
+        assert!(!behaviour.addresses_of_peer(&peer_id).is_empty());
+
+        behaviour.inject_dial_failure(&peer_id);
+
+        assert_eq!(block_on(first), Err(RequestError::Connection));
+        assert_eq!(block_on(second), Err(RequestError::Connection));
+        assert!(behaviour.addresses_of_peer(&peer_id).is_empty());
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{frame::OutboundRequest, protocol::Version, Frame, FrameKind};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+/// What a node supports of the COMIT messaging protocol: its versions and the
+/// request types it understands together with the headers it knows for each
+/// of them.
+///
+/// Peers that speak version 2 or later tell each other their capabilities in
+/// `HELLO` frames once connected.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Capabilities {
+    versions: BTreeSet<Version>,
+    request_types: HashMap<String, HashSet<String>>,
+}
+
+impl Default for Capabilities {
+    fn default() -> Self {
+        Self {
+            versions: Version::ALL.iter().copied().collect(),
+            request_types: HashMap::new(),
+        }
+    }
+}
+
+impl Capabilities {
+    pub fn with_request_type<T, H>(mut self, request_type: T, headers: H) -> Self
+    where
+        T: Into<String>,
+        H: IntoIterator,
+        H::Item: Into<String>,
+    {
+        self.request_types.insert(
+            request_type.into(),
+            headers.into_iter().map(Into::into).collect(),
+        );
+
+        self
+    }
+
+    /// Restricts the node to the given versions.
+    pub fn with_versions<V>(self, versions: V) -> Self
+    where
+        V: IntoIterator<Item = Version>,
+    {
+        Self {
+            versions: versions.into_iter().collect(),
+            ..self
+        }
+    }
+
+    /// Returns the versions from the lowest to the highest.
+    pub fn versions(&self) -> impl Iterator<Item = Version> + '_ {
+        self.versions.iter().copied()
+    }
+
+    /// Returns the headers known for the given request type, `None` if the
+    /// request type is not understood.
+    pub fn known_headers(&self, request_type: &str) -> Option<&HashSet<String>> {
+        self.request_types.get(request_type)
+    }
+
+    /// Returns whether a node with these capabilities understands the type
+    /// and all mandatory headers of 'request'.
+    pub fn understands(&self, request: &OutboundRequest) -> bool {
+        self.known_headers(request.request_type())
+            .map_or(false, |known_headers| {
+                request
+                    .mandatory_headers()
+                    .all(|header| known_headers.contains(header))
+            })
+    }
+
+    pub fn highest_common_version(&self, other: &Capabilities) -> Option<Version> {
+        self.versions.intersection(&other.versions).max().copied()
+    }
+}
+
+impl From<Capabilities> for Frame {
+    fn from(capabilities: Capabilities) -> Frame {
+        // Serializing Capabilities should never fail because its members are
+        // just numbers and Strings.
+        let payload = serde_json::to_value(capabilities).unwrap();
+        Frame::new(FrameKind::Hello, payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::Header;
+
+    #[test]
+    fn highest_common_version_is_picked() {
+        let ours = Capabilities::default();
+        let theirs = Capabilities::default().with_versions(vec![Version::V1, Version::V2]);
+
+        assert_eq!(ours.highest_common_version(&theirs), Some(Version::V2));
+    }
+
+    #[test]
+    fn given_only_version_1_in_common_picks_version_1() {
+        let ours = Capabilities::default();
+        let theirs = Capabilities::default().with_versions(vec![Version::V1]);
+
+        assert_eq!(ours.highest_common_version(&theirs), Some(Version::V1));
+    }
+
+    #[test]
+    fn given_no_common_version_picks_none() {
+        let ours = Capabilities::default().with_versions(vec![Version::V2]);
+        let theirs = Capabilities::default().with_versions(vec![Version::V1]);
+
+        assert_eq!(ours.highest_common_version(&theirs), None);
+    }
+
+    #[test]
+    fn requests_with_unknown_type_or_mandatory_headers_are_not_understood() {
+        let capabilities = Capabilities::default().with_request_type("SWAP", vec!["id"]);
+        let header = Header::with_str_value("value");
+
+        let known = OutboundRequest::new("SWAP")
+            .with_header("id", header.clone())
+            .with_header("_optional", header.clone());
+        let unknown_header = OutboundRequest::new("SWAP").with_header("protocol", header);
+        let unknown_type = OutboundRequest::new("PING");
+
+        assert!(capabilities.understands(&known));
+        assert!(!capabilities.understands(&unknown_header));
+        assert!(!capabilities.understands(&unknown_type));
+    }
+
+    #[test]
+    fn hello_frame_roundtrips_capabilities() {
+        let capabilities =
+            Capabilities::default().with_request_type("SWAP", vec!["alpha_ledger", "beta_ledger"]);
+
+        let frame = Frame::from(capabilities.clone());
+        let deserialized = Capabilities::deserialize(&frame.payload).unwrap();
+
+        assert_eq!(frame.kind, FrameKind::Hello);
+        assert_eq!(deserialized, capabilities);
+        assert_eq!(
+            deserialized.known_headers("SWAP"),
+            Some(
+                &vec!["alpha_ledger".to_owned(), "beta_ledger".to_owned()]
+                    .into_iter()
+                    .collect()
+            )
+        );
+    }
+}
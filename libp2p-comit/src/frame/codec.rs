@@ -1,22 +1,90 @@
-use crate::Frame;
-use bytes::BytesMut;
+use crate::{protocol::Encoding, Frame, Version};
+use bytes::{Buf, BytesMut};
 use futures_codec::{Decoder, Encoder};
-use std::io;
+use std::{convert::TryFrom, io};
 
 #[derive(Debug, thiserror::Error)]
 pub enum CodecError {
     #[error("serde JSON: ")]
     Json(#[from] serde_json::Error),
+    #[error("serde CBOR: ")]
+    Cbor(#[from] serde_cbor::Error),
+    #[error("frame of {0} bytes is too long")]
+    FrameTooLong(usize),
     #[error("io: ")]
     IO(#[from] io::Error),
+    #[error("frame of version {frame:?} on a substream of version {substream:?}")]
+    Version { substream: Version, frame: Version },
 }
 
+/// The codec of a substream, depending on the encoding negotiated for it.
 #[derive(Debug, Clone, Copy)]
-pub struct JsonFrameCodec;
+pub enum FrameCodec {
+    Json(JsonFrameCodec),
+    Cbor(CborFrameCodec),
+}
+
+impl FrameCodec {
+    pub fn new(version: Version, encoding: Encoding) -> Self {
+        match encoding {
+            Encoding::Json => FrameCodec::Json(JsonFrameCodec::new(version)),
+            Encoding::Cbor => FrameCodec::Cbor(CborFrameCodec::new(version)),
+        }
+    }
+}
+
+impl Encoder for FrameCodec {
+    type Item = Frame;
+    type Error = CodecError;
+
+    fn encode(&mut self, item: Frame, dst: &mut BytesMut) -> Result<(), CodecError> {
+        match self {
+            FrameCodec::Json(codec) => codec.encode(item, dst),
+            FrameCodec::Cbor(codec) => codec.encode(item, dst),
+        }
+    }
+}
+
+impl Decoder for FrameCodec {
+    type Item = Frame;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Frame>, CodecError> {
+        match self {
+            FrameCodec::Json(codec) => codec.decode(src),
+            FrameCodec::Cbor(codec) => codec.decode(src),
+        }
+    }
+}
+
+/// Peers only send frames of the version negotiated for the substream.
+fn ensure_version(substream: Version, frame: Frame) -> Result<Frame, CodecError> {
+    if frame.version > substream {
+        return Err(CodecError::Version {
+            substream,
+            frame: frame.version,
+        });
+    }
+
+    Ok(frame)
+}
+
+/// Encodes frames as lines of JSON, stamped with the version negotiated for
+/// the substream.
+#[derive(Debug, Clone, Copy)]
+pub struct JsonFrameCodec {
+    version: Version,
+}
+
+impl JsonFrameCodec {
+    pub fn new(version: Version) -> Self {
+        Self { version }
+    }
+}
 
 impl Default for JsonFrameCodec {
     fn default() -> Self {
-        Self {}
+        Self::new(Version::V1)
     }
 }
 
@@ -24,7 +92,8 @@ impl Encoder for JsonFrameCodec {
     type Item = Frame;
     type Error = CodecError;
 
-    fn encode(&mut self, item: Frame, dst: &mut BytesMut) -> Result<(), CodecError> {
+    fn encode(&mut self, mut item: Frame, dst: &mut BytesMut) -> Result<(), CodecError> {
+        item.version = self.version;
         let mut bytes = serde_json::to_vec(&item)?;
         bytes.push(b'\n');
 
@@ -43,20 +112,120 @@ impl Decoder for JsonFrameCodec {
             Some(position) => {
                 let frame_bytes = src.split_to(position + 1);
                 let frame = serde_json::from_slice(frame_bytes.as_ref())?;
-                Ok(Some(frame))
+
+                Ok(Some(ensure_version(self.version, frame)?))
             }
             None => Ok(None),
         }
     }
 }
 
+/// Encodes frames as CBOR, each preceded by its length as a big-endian `u32`.
+///
+/// Frames longer than [`CborFrameCodec::MAX_FRAME_LENGTH`] are rejected, a
+/// peer cannot make us buffer more than that for a single frame.
+#[derive(Debug, Clone, Copy)]
+pub struct CborFrameCodec {
+    version: Version,
+}
+
+impl CborFrameCodec {
+    const LENGTH_PREFIX: usize = 4;
+    pub const MAX_FRAME_LENGTH: usize = 1024 * 1024;
+
+    pub fn new(version: Version) -> Self {
+        Self { version }
+    }
+}
+
+impl Encoder for CborFrameCodec {
+    type Item = Frame;
+    type Error = CodecError;
+
+    fn encode(&mut self, mut item: Frame, dst: &mut BytesMut) -> Result<(), CodecError> {
+        item.version = self.version;
+        let bytes = serde_cbor::to_vec(&item)?;
+        if bytes.len() > Self::MAX_FRAME_LENGTH {
+            return Err(CodecError::FrameTooLong(bytes.len()));
+        }
+        let length =
+            u32::try_from(bytes.len()).map_err(|_| CodecError::FrameTooLong(bytes.len()))?;
+
+        dst.reserve(Self::LENGTH_PREFIX + bytes.len());
+        dst.extend_from_slice(&length.to_be_bytes());
+        dst.extend(bytes);
+
+        Ok(())
+    }
+}
+
+impl Decoder for CborFrameCodec {
+    type Item = Frame;
+    type Error = CodecError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Frame>, CodecError> {
+        if src.len() < Self::LENGTH_PREFIX {
+            return Ok(None);
+        }
+
+        let mut length = [0u8; Self::LENGTH_PREFIX];
+        length.copy_from_slice(&src[..Self::LENGTH_PREFIX]);
+        let length = u32::from_be_bytes(length) as usize;
+
+        if length > Self::MAX_FRAME_LENGTH {
+            return Err(CodecError::FrameTooLong(length));
+        }
+        if src.len() < Self::LENGTH_PREFIX + length {
+            return Ok(None);
+        }
+
+        src.advance(Self::LENGTH_PREFIX);
+        let frame_bytes = src.split_to(length);
+        let frame = serde_cbor::from_slice(frame_bytes.as_ref())?;
+
+        Ok(Some(ensure_version(self.version, frame)?))
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
     use super::*;
-    use crate::FrameKind;
+    use crate::{
+        frame::{Header, OutboundRequest, UnvalidatedInboundRequest},
+        FrameKind,
+    };
+    use serde::Deserialize;
     use spectral::prelude::*;
 
+    fn swap_request() -> Frame {
+        OutboundRequest::new("SWAP")
+            .with_header("alpha_ledger", Header::with_str_value("bitcoin"))
+            .with_header(
+                "beta_asset",
+                Header::with_str_value("erc20")
+                    .with_parameter("quantity", "100000000000000000000")
+                    .unwrap(),
+            )
+            .with_body(serde_json::json!({
+                "alpha_expiry": u64::MAX,
+                "beta_expiry": -1,
+                "rate": 0.5,
+                "identities": ["0x00a329c0648769a73afac7f9381e08fb43dbea72", null],
+            }))
+            .into()
+    }
+
+    fn roundtrip(mut codec: FrameCodec, frame: Frame) -> Frame {
+        let mut bytes = BytesMut::new();
+        codec.encode(frame, &mut bytes).unwrap();
+
+        let decoded = codec.decode(&mut bytes).unwrap().unwrap();
+        assert!(bytes.is_empty());
+
+        decoded
+    }
+
     #[test]
     fn should_encode_frame_to_bytes() {
         let frame = Frame::new(FrameKind::Request, serde_json::Value::Null);
@@ -136,4 +305,130 @@ mod tests {
             .is_some()
             .is_equal_to(&expected_frame);
     }
+
+    #[test]
+    fn should_encode_version_of_substream() {
+        let frame = Frame::new(FrameKind::Request, serde_json::Value::Null);
+
+        let mut codec = JsonFrameCodec::new(Version::V2);
+
+        let mut bytes = BytesMut::new();
+
+        assert!(codec.encode(frame, &mut bytes).is_ok());
+
+        let frame_bytes = br#"{"type":"REQUEST","version":2,"payload":null}"#.as_ref();
+        let newline = b"\n".as_ref();
+
+        let expected = [frame_bytes, newline].concat();
+
+        assert_eq!(&bytes[..], &expected[..]);
+    }
+
+    #[test]
+    fn given_frame_without_version_should_decode_as_version_1() {
+        let frame_bytes = br#"{"type":"RESPONSE","payload":null}"#.as_ref();
+        let newline = b"\n".as_ref();
+
+        let mut codec = JsonFrameCodec::new(Version::V2);
+
+        let mut bytes = BytesMut::new();
+        bytes.extend([frame_bytes, newline].concat());
+
+        let frame = codec.decode(&mut bytes).unwrap().unwrap();
+
+        assert_eq!(frame.version, Version::V1);
+    }
+
+    #[test]
+    fn given_frame_of_higher_version_than_substream_should_fail() {
+        let frame_bytes = br#"{"type":"HELLO","version":2,"payload":null}"#.as_ref();
+        let newline = b"\n".as_ref();
+
+        let mut codec = JsonFrameCodec::new(Version::V1);
+
+        let mut bytes = BytesMut::new();
+        bytes.extend([frame_bytes, newline].concat());
+
+        assert_that(&codec.decode(&mut bytes)).is_err();
+    }
+
+    #[test]
+    fn should_roundtrip_request_frame_with_json_and_cbor() {
+        for encoding in &[Encoding::Json, Encoding::Cbor] {
+            let codec = FrameCodec::new(Version::V2, *encoding);
+
+            let decoded = roundtrip(codec, swap_request());
+
+            let mut expected = swap_request();
+            expected.version = Version::V2;
+            assert_eq!(decoded, expected);
+            assert_eq!(
+                UnvalidatedInboundRequest::deserialize(&decoded.payload).unwrap(),
+                UnvalidatedInboundRequest::deserialize(&expected.payload).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn cbor_should_be_more_compact_than_json() {
+        let mut json = BytesMut::new();
+        JsonFrameCodec::new(Version::V2)
+            .encode(swap_request(), &mut json)
+            .unwrap();
+        let mut cbor = BytesMut::new();
+        CborFrameCodec::new(Version::V2)
+            .encode(swap_request(), &mut cbor)
+            .unwrap();
+
+        assert!(cbor.len() < json.len());
+    }
+
+    #[test]
+    fn given_incomplete_cbor_frame_should_wait_for_more() {
+        let mut encoded = BytesMut::new();
+        let mut codec = CborFrameCodec::new(Version::V2);
+        codec.encode(swap_request(), &mut encoded).unwrap();
+
+        let mut bytes = BytesMut::new();
+        bytes.extend_from_slice(&encoded[..2]);
+        assert_that(&codec.decode(&mut bytes)).is_ok().is_none();
+
+        bytes.extend_from_slice(&encoded[2..encoded.len() - 1]);
+        assert_that(&codec.decode(&mut bytes)).is_ok().is_none();
+
+        bytes.extend_from_slice(&encoded[encoded.len() - 1..]);
+        assert_that(&codec.decode(&mut bytes)).is_ok().is_some();
+    }
+
+    #[test]
+    fn given_cbor_frame_longer_than_maximum_should_fail_without_waiting_for_it() {
+        let mut codec = CborFrameCodec::new(Version::V2);
+        let length = u32::try_from(CborFrameCodec::MAX_FRAME_LENGTH + 1).unwrap();
+
+        let mut bytes = BytesMut::new();
+        bytes.extend_from_slice(&length.to_be_bytes());
+
+        assert_that(&codec.decode(&mut bytes)).is_err();
+    }
+
+    #[test]
+    fn given_two_cbor_frames_in_a_row_should_decode_both() {
+        let mut codec = CborFrameCodec::new(Version::V2);
+
+        let mut bytes = BytesMut::new();
+        codec
+            .encode(
+                Frame::new(FrameKind::Hello, serde_json::Value::Null),
+                &mut bytes,
+            )
+            .unwrap();
+        codec.encode(swap_request(), &mut bytes).unwrap();
+
+        let first = codec.decode(&mut bytes).unwrap().unwrap();
+        let second = codec.decode(&mut bytes).unwrap().unwrap();
+
+        assert_eq!(first.kind, FrameKind::Hello);
+        assert_eq!(second.kind, FrameKind::Request);
+        assert!(bytes.is_empty());
+    }
 }
@@ -181,6 +181,14 @@ impl Headers {
         self.inner.remove(key)
     }
 
+    /// Returns the keys of the headers the receiver must understand.
+    pub fn mandatory_keys(&self) -> impl Iterator<Item = &str> {
+        self.inner
+            .keys()
+            .filter(|key| !key.starts_with('_'))
+            .map(String::as_str)
+    }
+
     pub fn into_iter(self) -> impl Iterator<Item = (HeaderKey, Header)> {
         self.inner.into_iter().map(|(mut key, value)| {
             let non_mandatory = key.starts_with('_');
@@ -46,6 +46,14 @@ impl ValidatedInboundRequest {
 }
 
 impl OutboundRequest {
+    pub fn request_type(&self) -> &str {
+        self.inner.request_type.as_str()
+    }
+
+    pub fn mandatory_headers(&self) -> impl Iterator<Item = &str> {
+        self.inner.headers.mandatory_keys()
+    }
+
     pub fn new<T>(request_type: T) -> Self
     where
         T: Into<String>,
@@ -1,8 +1,8 @@
 use crate::{
     frame::{self, OutboundRequest, Response, UnknownMandatoryHeaders, ValidatedInboundRequest},
     protocol::Config,
-    substream::{self, Advance, Advanced},
-    ComitHandlerEvent, Frame, Frames,
+    substream::{self, outbound::Awaiting, Advance, Advanced},
+    Capabilities, ComitHandlerEvent, Frame, Frames,
 };
 use futures::{
     channel::oneshot::{self, Canceled},
@@ -11,12 +11,7 @@ use futures::{
 use libp2p::swarm::{
     KeepAlive, ProtocolsHandler, ProtocolsHandlerEvent, ProtocolsHandlerUpgrErr, SubstreamProtocol,
 };
-use std::{
-    collections::{HashMap, HashSet},
-    convert::Infallible,
-    fmt::Display,
-    task::Context,
-};
+use std::{convert::Infallible, fmt::Display, task::Context};
 
 #[derive(derivative::Derivative)]
 #[derivative(Debug)]
@@ -27,11 +22,13 @@ pub struct ComitHandler {
     outbound_substreams: Vec<substream::outbound::State>,
 
     to_send: Vec<PendingOutboundRequest>,
+    /// Whether we still have to tell the peer our capabilities.
+    say_hello: bool,
 
     #[derivative(Debug = "ignore")]
     current_task: Option<Waker>,
 
-    known_headers: HashMap<String, HashSet<String>>,
+    capabilities: Capabilities,
 }
 
 #[derive(Debug, thiserror::Error)]
@@ -55,9 +52,12 @@ pub enum Error {
 }
 
 impl ComitHandler {
-    pub fn new(known_headers: HashMap<String, HashSet<String>>) -> Self {
+    pub fn new(capabilities: Capabilities) -> Self {
         Self {
-            known_headers,
+            say_hello: capabilities
+                .versions()
+                .any(|version| version.supports_hello()),
+            capabilities,
             inbound_substreams: Vec::new(),
             outbound_substreams: Vec::new(),
             to_send: Vec::new(),
@@ -101,6 +101,7 @@ impl Clone for ProtocolInEvent {
 #[derive(Debug)]
 pub enum ProtocolOutboundOpenInfo {
     Message(OutboundMessage),
+    Hello,
 }
 
 /// Events emitted after processing a message from the 'out'side of this node
@@ -108,6 +109,8 @@ pub enum ProtocolOutboundOpenInfo {
 #[derive(Debug)]
 pub enum ProtocolOutEvent {
     Message(InboundMessage),
+    /// The peer told us its capabilities.
+    Hello(Capabilities),
 }
 
 #[derive(Debug)]
@@ -130,7 +133,7 @@ impl ProtocolsHandler for ComitHandler {
     type OutboundOpenInfo = ProtocolOutboundOpenInfo;
 
     fn listen_protocol(&self) -> SubstreamProtocol<Self::InboundProtocol> {
-        SubstreamProtocol::new(Config {})
+        SubstreamProtocol::new(Config::new(&self.capabilities))
     }
 
     fn inject_fully_negotiated_inbound(&mut self, stream: Frames) {
@@ -156,7 +159,15 @@ impl ProtocolsHandler for ComitHandler {
                 self.outbound_substreams
                     .push(substream::outbound::State::WaitingSend {
                         frame: request.into(),
-                        response_sender: channel,
+                        awaiting: Awaiting::Response(channel),
+                        stream: Box::pin(stream),
+                    });
+            }
+            ProtocolOutboundOpenInfo::Hello => {
+                self.outbound_substreams
+                    .push(substream::outbound::State::WaitingSend {
+                        frame: self.capabilities.clone().into(),
+                        awaiting: Awaiting::Hello,
                         stream: Box::pin(stream),
                     });
             }
@@ -181,9 +192,15 @@ impl ProtocolsHandler for ComitHandler {
 
     fn inject_dial_upgrade_error(
         &mut self,
-        _info: Self::OutboundOpenInfo,
-        _error: ProtocolsHandlerUpgrErr<Infallible>,
+        info: Self::OutboundOpenInfo,
+        error: ProtocolsHandlerUpgrErr<Infallible>,
     ) {
+        if let ProtocolOutboundOpenInfo::Hello = info {
+            tracing::debug!(
+                "peer did not accept our hello, it likely only speaks version 1: {}",
+                error
+            );
+        }
     }
 
     fn connection_keep_alive(&self) -> KeepAlive {
@@ -191,20 +208,33 @@ impl ProtocolsHandler for ComitHandler {
     }
 
     fn poll(&mut self, cx: &mut Context<'_>) -> Poll<ComitHandlerEvent> {
+        if self.say_hello {
+            self.say_hello = false;
+
+            return Poll::Ready(ProtocolsHandlerEvent::OutboundSubstreamRequest {
+                protocol: SubstreamProtocol::new(Config::hello(&self.capabilities)),
+                info: ProtocolOutboundOpenInfo::Hello,
+            });
+        }
+
+        // Requests that timed out before we got to open a substream for them
+        // need not be sent at all.
+        self.to_send
+            .retain(|request| !request.channel.is_canceled());
+
         if let Some(request) = self.to_send.pop() {
             return Poll::Ready(ProtocolsHandlerEvent::OutboundSubstreamRequest {
-                protocol: SubstreamProtocol::new(Config {}),
+                protocol: SubstreamProtocol::new(Config::new(&self.capabilities)),
                 info: ProtocolOutboundOpenInfo::Message(OutboundMessage::Request(request)),
             });
         }
 
-        if let Some(result) =
-            poll_substreams(&mut self.outbound_substreams, &self.known_headers, cx)
+        if let Some(result) = poll_substreams(&mut self.outbound_substreams, &self.capabilities, cx)
         {
             return result;
         }
 
-        if let Some(result) = poll_substreams(&mut self.inbound_substreams, &self.known_headers, cx)
+        if let Some(result) = poll_substreams(&mut self.inbound_substreams, &self.capabilities, cx)
         {
             return result;
         }
@@ -217,7 +247,7 @@ impl ProtocolsHandler for ComitHandler {
 
 fn poll_substreams<S>(
     substreams: &mut Vec<S>,
-    known_headers: &HashMap<String, HashSet<String>>,
+    capabilities: &Capabilities,
     cx: &mut Context<'_>,
 ) -> Option<Poll<ComitHandlerEvent>>
 where
@@ -229,7 +259,7 @@ where
 
         let log_message = format!("transition from {}", substream_state);
 
-        let Advanced { new_state, event } = substream_state.advance(known_headers, cx);
+        let Advanced { new_state, event } = substream_state.advance(capabilities, cx);
 
         if let Some(new_state) = new_state {
             tracing::trace!("{} to {}", log_message, new_state);
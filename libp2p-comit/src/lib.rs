@@ -16,6 +16,7 @@
 #[macro_use]
 pub mod frame;
 mod behaviour;
+mod capabilities;
 pub mod handler;
 mod protocol;
 mod substream;
@@ -24,9 +25,10 @@ use serde::{Deserialize, Serialize};
 use serde_json::{self, Value as JsonValue};
 
 pub use self::{
-    behaviour::{BehaviourOutEvent, Rfc003Comit},
+    behaviour::{BehaviourOutEvent, RequestError, Rfc003Comit, DEFAULT_REQUEST_TIMEOUT},
+    capabilities::Capabilities,
     handler::{ComitHandler, PendingInboundRequest, PendingOutboundRequest},
-    protocol::{Config, Frames},
+    protocol::{Config, Encoding, Frames, Protocol, UnknownVersion, Version},
 };
 use crate::handler::{ProtocolOutEvent, ProtocolOutboundOpenInfo};
 use libp2p::swarm::ProtocolsHandlerEvent;
@@ -38,6 +40,11 @@ pub type ComitHandlerEvent =
 pub struct Frame {
     #[serde(rename = "type")]
     pub kind: FrameKind,
+    /// Set by the codec to the version of the substream. Version 1 frames do
+    /// not carry it to stay readable by peers that predate versioning.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Version::is_v1")]
+    pub version: Version,
     pub payload: JsonValue,
 }
 
@@ -46,6 +53,7 @@ pub struct Frame {
 pub enum FrameKind {
     Request,
     Response,
+    Hello,
 
     // This is currently the fallback to not fail on serialisation if the frame type is unknown
     // Unfortunately serde does not support deserialization into a String when using other
@@ -55,6 +63,10 @@ pub enum FrameKind {
 
 impl Frame {
     pub fn new(kind: FrameKind, payload: JsonValue) -> Self {
-        Self { kind, payload }
+        Self {
+            kind,
+            version: Version::default(),
+            payload,
+        }
     }
 }
@@ -1,23 +1,157 @@
-use crate::frame::{self, JsonFrameCodec};
+use crate::{frame::FrameCodec, Capabilities};
 use futures::future;
 use futures_codec::Framed;
 use libp2p::{
-    core::{InboundUpgrade, OutboundUpgrade, UpgradeInfo},
+    core::{upgrade::ProtocolName, InboundUpgrade, OutboundUpgrade, UpgradeInfo},
     swarm::NegotiatedSubstream,
 };
-use std::{convert::Infallible, iter};
+use serde::{Deserialize, Serialize};
+use std::convert::{Infallible, TryFrom};
 
-pub type Frames = Framed<NegotiatedSubstream, JsonFrameCodec>;
+pub type Frames = Framed<NegotiatedSubstream, FrameCodec>;
 
-#[derive(Clone, Copy, Debug)]
-pub struct Config {}
+/// The versions of the COMIT messaging protocol.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "u8", into = "u8")]
+pub enum Version {
+    /// The original frame format, frames do not carry their version.
+    V1,
+    /// Frames carry their version, can be encoded as CBOR and peers say
+    /// `HELLO` once connected.
+    V2,
+}
+
+impl Version {
+    pub const ALL: [Version; 2] = [Version::V1, Version::V2];
+
+    pub fn supports_hello(self) -> bool {
+        self >= Version::V2
+    }
+
+    pub fn supports_cbor(self) -> bool {
+        self >= Version::V2
+    }
+
+    pub(crate) fn is_v1(&self) -> bool {
+        *self == Version::V1
+    }
+}
+
+impl Default for Version {
+    fn default() -> Self {
+        Version::V1
+    }
+}
+
+impl From<Version> for u8 {
+    fn from(version: Version) -> u8 {
+        match version {
+            Version::V1 => 1,
+            Version::V2 => 2,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, thiserror::Error)]
+#[error("unknown version {0}")]
+pub struct UnknownVersion(u8);
+
+impl TryFrom<u8> for Version {
+    type Error = UnknownVersion;
+
+    fn try_from(version: u8) -> Result<Self, UnknownVersion> {
+        match version {
+            1 => Ok(Version::V1),
+            2 => Ok(Version::V2),
+            _ => Err(UnknownVersion(version)),
+        }
+    }
+}
+
+/// How the frames of a substream are encoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    /// Newline-delimited JSON, understood by every version.
+    Json,
+    /// Length-prefixed CBOR, more compact than JSON.
+    Cbor,
+}
+
+/// A version of the protocol together with the encoding of its frames.
+///
+/// Each of them has a protocol name of its own, hence both are negotiated
+/// when a substream is opened: the dialer proposes its protocols in order of
+/// preference and the listener accepts the first one it supports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Protocol {
+    pub version: Version,
+    pub encoding: Encoding,
+}
+
+impl ProtocolName for Protocol {
+    fn protocol_name(&self) -> &[u8] {
+        match (self.version, self.encoding) {
+            (Version::V1, _) => b"/comit/1.0.0",
+            (Version::V2, Encoding::Json) => b"/comit/2.0.0",
+            (Version::V2, Encoding::Cbor) => b"/comit/2.0.0/cbor",
+        }
+    }
+}
+
+/// The protocols a substream can be opened with, in order of preference.
+///
+/// Higher versions are preferred over lower ones, hence the version picked is
+/// the highest both peers support. Within a version, CBOR is preferred and
+/// JSON is the fallback.
+#[derive(Clone, Debug)]
+pub struct Config {
+    protocols: Vec<Protocol>,
+}
+
+impl Config {
+    /// Substreams for requests can be opened with every version the node
+    /// supports.
+    pub fn new(capabilities: &Capabilities) -> Self {
+        let mut versions = capabilities.versions().collect::<Vec<_>>();
+        versions.reverse();
+
+        let mut protocols = Vec::new();
+        for version in versions {
+            if version.supports_cbor() {
+                protocols.push(Protocol {
+                    version,
+                    encoding: Encoding::Cbor,
+                });
+            }
+            protocols.push(Protocol {
+                version,
+                encoding: Encoding::Json,
+            });
+        }
+
+        Self { protocols }
+    }
+
+    /// Substreams for saying `HELLO` can only be opened with the versions that
+    /// have such frames, peers that only speak version 1 refuse them.
+    pub fn hello(capabilities: &Capabilities) -> Self {
+        let Config { protocols } = Config::new(capabilities);
+
+        Self {
+            protocols: protocols
+                .into_iter()
+                .filter(|protocol| protocol.version.supports_hello())
+                .collect(),
+        }
+    }
+}
 
 impl UpgradeInfo for Config {
-    type Info = &'static [u8];
-    type InfoIter = iter::Once<Self::Info>;
+    type Info = Protocol;
+    type InfoIter = std::vec::IntoIter<Protocol>;
 
     fn protocol_info(&self) -> Self::InfoIter {
-        iter::once(b"/comit/1.0.0")
+        self.protocols.clone().into_iter()
     }
 }
 
@@ -27,8 +161,8 @@ impl InboundUpgrade<NegotiatedSubstream> for Config {
     type Future = future::Ready<Result<Self::Output, Infallible>>;
 
     #[inline]
-    fn upgrade_inbound(self, socket: NegotiatedSubstream, _: Self::Info) -> Self::Future {
-        let codec = frame::JsonFrameCodec::default();
+    fn upgrade_inbound(self, socket: NegotiatedSubstream, protocol: Protocol) -> Self::Future {
+        let codec = FrameCodec::new(protocol.version, protocol.encoding);
         let framed = Framed::new(socket, codec);
 
         future::ok(framed)
@@ -41,10 +175,57 @@ impl OutboundUpgrade<NegotiatedSubstream> for Config {
     type Future = future::Ready<Result<Self::Output, Infallible>>;
 
     #[inline]
-    fn upgrade_outbound(self, socket: NegotiatedSubstream, _: Self::Info) -> Self::Future {
-        let codec = frame::JsonFrameCodec::default();
+    fn upgrade_outbound(self, socket: NegotiatedSubstream, protocol: Protocol) -> Self::Future {
+        let codec = FrameCodec::new(protocol.version, protocol.encoding);
         let framed = Framed::new(socket, codec);
 
         future::ok(framed)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn protocol_names(config: Config) -> Vec<String> {
+        config
+            .protocol_info()
+            .map(|protocol| String::from_utf8(protocol.protocol_name().to_vec()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn versions_are_proposed_from_the_highest_with_json_as_fallback() {
+        let config = Config::new(&Capabilities::default());
+
+        assert_eq!(protocol_names(config), vec![
+            "/comit/2.0.0/cbor",
+            "/comit/2.0.0",
+            "/comit/1.0.0"
+        ]);
+    }
+
+    #[test]
+    fn given_only_version_1_proposes_json() {
+        let config = Config::new(&Capabilities::default().with_versions(vec![Version::V1]));
+
+        assert_eq!(protocol_names(config), vec!["/comit/1.0.0"]);
+    }
+
+    #[test]
+    fn hello_is_not_proposed_with_version_1() {
+        let config = Config::hello(&Capabilities::default());
+
+        assert_eq!(protocol_names(config), vec![
+            "/comit/2.0.0/cbor",
+            "/comit/2.0.0"
+        ]);
+    }
+
+    #[test]
+    fn version_serializes_as_number() {
+        assert_eq!(serde_json::to_string(&Version::V2).unwrap(), "2");
+        assert_eq!(serde_json::from_str::<Version>("1").unwrap(), Version::V1);
+        assert!(serde_json::from_str::<Version>("3").is_err());
+    }
+}
@@ -3,16 +3,12 @@ use crate::{
     handler::{self, InboundMessage, PendingInboundRequest, ProtocolOutEvent},
     protocol::Frames,
     substream::{Advance, Advanced, CloseStream},
-    Frame, FrameKind,
+    Capabilities, Frame, FrameKind,
 };
 use futures::{channel::oneshot, task::Poll, Future, Sink, Stream};
 use libp2p::swarm::ProtocolsHandlerEvent;
 use serde::Deserialize;
-use std::{
-    collections::{HashMap, HashSet},
-    pin::Pin,
-    task::Context,
-};
+use std::{pin::Pin, task::Context};
 
 #[derive(strum_macros::Display)]
 #[allow(missing_debug_implementations)]
@@ -43,11 +39,7 @@ impl CloseStream for State {
 }
 
 impl Advance for State {
-    fn advance(
-        self,
-        known_headers: &HashMap<String, HashSet<String>>,
-        cx: &mut Context<'_>,
-    ) -> Advanced<State> {
+    fn advance(self, capabilities: &Capabilities, cx: &mut Context<'_>) -> Advanced<State> {
         use self::State::*;
         match self {
             WaitingMessage { mut stream } => match stream.as_mut().poll_next(cx) {
@@ -56,8 +48,8 @@ impl Advance for State {
                         let request = UnvalidatedInboundRequest::deserialize(&frame.payload)
                             .map_err(handler::Error::MalformedFrame)
                             .and_then(|request| {
-                                known_headers
-                                    .get(request.request_type())
+                                capabilities
+                                    .known_headers(request.request_type())
                                     .ok_or_else(|| {
                                         handler::Error::UnknownRequestType(
                                             request.request_type().to_owned(),
@@ -91,7 +83,23 @@ impl Advance for State {
                             Err(error) => Advanced::error(stream, error),
                         }
                     }
-                    FrameKind::Response => {
+                    FrameKind::Hello if frame.version.supports_hello() => {
+                        match Capabilities::deserialize(&frame.payload) {
+                            Ok(peer_capabilities) => Advanced {
+                                new_state: Some(WaitingSend {
+                                    msg: capabilities.clone().into(),
+                                    stream,
+                                }),
+                                event: Some(ProtocolsHandlerEvent::Custom(
+                                    ProtocolOutEvent::Hello(peer_capabilities),
+                                )),
+                            },
+                            Err(error) => {
+                                Advanced::error(stream, handler::Error::MalformedFrame(error))
+                            }
+                        }
+                    }
+                    FrameKind::Response | FrameKind::Hello => {
                         Advanced::error(stream, handler::Error::UnexpectedFrame(frame))
                     }
                     FrameKind::Unknown => Advanced::error(stream, handler::Error::UnknownFrameKind),
@@ -110,12 +118,12 @@ impl Advance for State {
                     msg: response.into(),
                     stream,
                 }
-                .advance(known_headers, cx),
+                .advance(capabilities, cx),
                 Poll::Pending => Advanced::transition_to(WaitingUser { receiver, stream }),
                 Poll::Ready(Err(error)) => Advanced::error(stream, error),
             },
             WaitingSend { msg, mut stream } => match stream.as_mut().start_send(msg) {
-                Ok(()) => WaitingFlush { stream }.advance(known_headers, cx),
+                Ok(()) => WaitingFlush { stream }.advance(capabilities, cx),
                 Err(error) => Advanced::error(stream, error),
             },
             WaitingFlush { mut stream } => match stream.as_mut().poll_flush(cx) {
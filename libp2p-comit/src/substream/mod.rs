@@ -1,10 +1,6 @@
-use crate::{handler::Error, protocol::Frames, ComitHandlerEvent};
+use crate::{handler::Error, protocol::Frames, Capabilities, ComitHandlerEvent};
 use libp2p::swarm::ProtocolsHandlerEvent;
-use std::{
-    collections::{HashMap, HashSet},
-    pin::Pin,
-    task::Context,
-};
+use std::{pin::Pin, task::Context};
 
 pub mod inbound;
 pub mod outbound;
@@ -18,11 +14,7 @@ pub struct Advanced<S> {
 }
 
 pub trait Advance: Sized {
-    fn advance(
-        self,
-        known_headers: &HashMap<String, HashSet<String>>,
-        cx: &mut Context<'_>,
-    ) -> Advanced<Self>;
+    fn advance(self, capabilities: &Capabilities, cx: &mut Context<'_>) -> Advanced<Self>;
 }
 
 impl<S> Advanced<S> {
@@ -3,17 +3,36 @@ use crate::{
     handler::{self, InboundMessage, PendingInboundResponse, ProtocolOutEvent},
     protocol::Frames,
     substream::{Advance, Advanced, CloseStream},
-    Frame, FrameKind,
+    Capabilities, Frame, FrameKind,
 };
 use futures::{channel::oneshot, Sink, Stream};
 use libp2p::swarm::ProtocolsHandlerEvent;
 use serde::Deserialize;
 use std::{
-    collections::{HashMap, HashSet},
     pin::Pin,
     task::{Context, Poll},
 };
 
+/// What the remote answers the frame we send on an outbound substream with.
+#[derive(Debug)]
+pub enum Awaiting {
+    /// The response to our request, to be handed to the channel.
+    Response(oneshot::Sender<Response>),
+    /// The capabilities of the remote in return for ours.
+    Hello,
+}
+
+impl Awaiting {
+    /// Resolves once the requester no longer waits for the response, e.g.
+    /// because the request timed out. Awaiting a `HELLO` is never canceled.
+    fn poll_canceled(&mut self, cx: &mut Context<'_>) -> Poll<()> {
+        match self {
+            Awaiting::Response(channel) => channel.poll_canceled(cx),
+            Awaiting::Hello => Poll::Pending,
+        }
+    }
+}
+
 #[derive(strum_macros::Display)]
 #[allow(missing_debug_implementations)]
 /// States of an outbound substream i.e. from us to peer node.
@@ -21,17 +40,17 @@ pub enum State {
     /// Waiting to send a message to the remote.
     WaitingSend {
         frame: Frame,
-        response_sender: oneshot::Sender<Response>,
+        awaiting: Awaiting,
         stream: Pin<Box<Frames>>,
     },
     /// Waiting to flush the substream so that the data arrives at the remote.
     WaitingFlush {
-        response_sender: oneshot::Sender<Response>,
+        awaiting: Awaiting,
         stream: Pin<Box<Frames>>,
     },
     /// Waiting for the answer to our message.
     WaitingAnswer {
-        response_sender: oneshot::Sender<Response>,
+        awaiting: Awaiting,
         stream: Pin<Box<Frames>>,
     },
     /// The substream is being closed.
@@ -45,84 +64,77 @@ impl CloseStream for State {
 }
 
 impl Advance for State {
-    fn advance(
-        self,
-        known_headers: &HashMap<String, HashSet<String>>,
-        cx: &mut Context<'_>,
-    ) -> Advanced<State> {
+    fn advance(self, capabilities: &Capabilities, cx: &mut Context<'_>) -> Advanced<State> {
         use self::State::*;
 
         match self {
             WaitingSend {
                 frame,
-                response_sender,
+                awaiting,
                 mut stream,
             } => match stream.as_mut().start_send(frame) {
-                Ok(()) => WaitingFlush {
-                    response_sender,
-                    stream,
-                }
-                .advance(known_headers, cx),
+                Ok(()) => WaitingFlush { awaiting, stream }.advance(capabilities, cx),
                 Err(error) => Advanced::error(stream, error),
             },
             WaitingFlush {
-                response_sender,
+                awaiting,
                 mut stream,
             } => match stream.as_mut().poll_flush(cx) {
-                Poll::Ready(Ok(())) => WaitingAnswer {
-                    response_sender,
-                    stream,
-                }
-                .advance(&known_headers, cx),
-                Poll::Pending => Advanced::transition_to(WaitingFlush {
-                    response_sender,
-                    stream,
-                }),
+                Poll::Ready(Ok(())) => WaitingAnswer { awaiting, stream }.advance(capabilities, cx),
+                Poll::Pending => Advanced::transition_to(WaitingFlush { awaiting, stream }),
                 Poll::Ready(Err(error)) => Advanced::error(stream, error),
             },
             WaitingAnswer {
-                response_sender,
+                mut awaiting,
                 mut stream,
-            } => match stream.as_mut().poll_next(cx) {
-                Poll::Ready(Some(Ok(frame))) => {
-                    let response = match frame.kind {
-                        FrameKind::Response => Response::deserialize(&frame.payload),
-                        FrameKind::Request => {
-                            return Advanced::error(stream, handler::Error::UnexpectedFrame(frame))
-                        }
-                        FrameKind::Unknown => {
-                            return Advanced::error(stream, handler::Error::UnknownFrameKind)
-                        }
-                    };
+            } => {
+                if awaiting.poll_canceled(cx).is_ready() {
+                    tracing::debug!("request was canceled before the response arrived");
+                    return WaitingClose { stream }.advance(capabilities, cx);
+                }
 
-                    match response {
-                        Ok(response) => {
-                            let event = ProtocolOutEvent::Message(InboundMessage::Response(
-                                PendingInboundResponse {
-                                    response,
-                                    channel: response_sender,
-                                },
-                            ));
+                match stream.as_mut().poll_next(cx) {
+                    Poll::Ready(Some(Ok(frame))) => {
+                        let event = match (frame.kind, awaiting) {
+                            (FrameKind::Response, Awaiting::Response(channel)) => {
+                                Response::deserialize(&frame.payload).map(|response| {
+                                    ProtocolOutEvent::Message(InboundMessage::Response(
+                                        PendingInboundResponse { response, channel },
+                                    ))
+                                })
+                            }
+                            (FrameKind::Hello, Awaiting::Hello) => {
+                                Capabilities::deserialize(&frame.payload)
+                                    .map(ProtocolOutEvent::Hello)
+                            }
+                            (FrameKind::Unknown, _) => {
+                                return Advanced::error(stream, handler::Error::UnknownFrameKind)
+                            }
+                            _ => {
+                                return Advanced::error(
+                                    stream,
+                                    handler::Error::UnexpectedFrame(frame),
+                                )
+                            }
+                        };
 
-                            Advanced {
+                        match event {
+                            Ok(event) => Advanced {
                                 new_state: Some(WaitingClose { stream }),
                                 event: Some(ProtocolsHandlerEvent::Custom(event)),
+                            },
+                            Err(error) => {
+                                Advanced::error(stream, handler::Error::MalformedFrame(error))
                             }
                         }
-                        Err(error) => {
-                            Advanced::error(stream, handler::Error::MalformedFrame(error))
-                        }
                     }
+                    Poll::Ready(Some(Err(error))) => {
+                        Advanced::error(stream, handler::Error::MalformedJson(error))
+                    }
+                    Poll::Pending => Advanced::transition_to(WaitingAnswer { awaiting, stream }),
+                    Poll::Ready(None) => Advanced::error(stream, handler::Error::UnexpectedEOF),
                 }
-                Poll::Ready(Some(Err(error))) => {
-                    Advanced::error(stream, handler::Error::MalformedJson(error))
-                }
-                Poll::Pending => Advanced::transition_to(WaitingAnswer {
-                    response_sender,
-                    stream,
-                }),
-                Poll::Ready(None) => Advanced::error(stream, handler::Error::UnexpectedEOF),
-            },
+            }
 
             WaitingClose { mut stream } => match stream.as_mut().poll_close(cx) {
                 Poll::Ready(Ok(())) => Advanced::end(),